@@ -10,10 +10,17 @@ extern crate criterion;
 use ark_ed_on_bls12_381::Fq as Fq381;
 use ark_std::rand::Rng;
 use criterion::Criterion;
-use jf_primitives::merkle_tree::{prelude::RescueMerkleTree, MerkleCommitment, MerkleTreeScheme};
+use jf_primitives::merkle_tree::{
+    prelude::{RescueMerkleTree, RescueSparseMerkleTree},
+    AppendableMerkleTreeScheme, MerkleCommitment, MerkleTreeScheme, UniversalMerkleTreeScheme,
+};
+use num_bigint::BigUint;
 use std::time::Duration;
 
 const BENCH_NAME: &str = "merkle_path_height_20";
+const APPEND_BENCH_NAME: &str = "merkle_append_1000_leaves";
+const BATCH_VERIFY_BENCH_NAME: &str = "merkle_verify_100_proofs_height_20";
+const UPDATE_BATCH_BENCH_NAME: &str = "sparse_merkle_update_500_leaves_height_20";
 
 fn twenty_hashes(c: &mut Criterion) {
     let mut benchmark_group = c.benchmark_group(BENCH_NAME);
@@ -35,8 +42,104 @@ fn twenty_hashes(c: &mut Criterion) {
     benchmark_group.finish();
 }
 
+fn append(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(APPEND_BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    let mut rng = jf_utils::test_rng();
+    let leaves: Vec<Fq381> = (0..1000).map(|_| rng.gen()).collect();
+
+    benchmark_group.bench_function("push", |b| {
+        b.iter(|| {
+            let mut mt = RescueMerkleTree::<Fq381>::from_elems(7, &[]).unwrap();
+            for leaf in leaves.iter() {
+                mt.push(leaf).unwrap();
+            }
+            mt
+        })
+    });
+    benchmark_group.bench_function("extend", |b| {
+        b.iter(|| {
+            let mut mt = RescueMerkleTree::<Fq381>::from_elems(7, &[]).unwrap();
+            mt.extend(&leaves).unwrap();
+            mt
+        })
+    });
+    benchmark_group.finish();
+}
+
+fn batch_verify(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(BATCH_VERIFY_BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    let mut rng = jf_utils::test_rng();
+    let leaves: Vec<Fq381> = (0..1000).map(|_| rng.gen()).collect();
+
+    let mt = RescueMerkleTree::<Fq381>::from_elems(20, &leaves).unwrap();
+    let root = mt.commitment().digest();
+    let proofs: Vec<_> = (0..100u64)
+        .map(|_| {
+            let pos = rng.gen_range(0..1000u64);
+            mt.lookup(pos).expect_ok().unwrap().1
+        })
+        .collect();
+
+    benchmark_group.bench_function("independent", |b| {
+        b.iter(|| {
+            for proof in proofs.iter() {
+                RescueMerkleTree::<Fq381>::verify(&root, proof.pos, proof)
+                    .unwrap()
+                    .unwrap();
+            }
+        })
+    });
+    benchmark_group.bench_function("batch", |b| {
+        b.iter(|| {
+            RescueMerkleTree::<Fq381>::verify_batch(&root, &proofs)
+                .unwrap()
+                .unwrap()
+        })
+    });
+    benchmark_group.finish();
+}
+
+fn update_batch(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(UPDATE_BATCH_BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    let mut rng = jf_utils::test_rng();
+    let updates: Vec<(BigUint, Fq381)> = (0..500)
+        .map(|_| (BigUint::from(rng.gen_range(0..1u64 << 30)), rng.gen()))
+        .collect();
+    let mt = RescueSparseMerkleTree::<BigUint, Fq381>::from_kv_set(20, &updates[..250]).unwrap();
+
+    benchmark_group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let mut mt = mt.clone();
+            for (pos, elem) in updates.iter() {
+                mt.update(pos, elem).unwrap();
+            }
+            mt
+        })
+    });
+    benchmark_group.bench_function("batch", |b| {
+        b.iter(|| {
+            let mut mt = mt.clone();
+            mt.update_batch(&updates).unwrap();
+            mt
+        })
+    });
+    benchmark_group.finish();
+}
+
 fn bench(c: &mut Criterion) {
     twenty_hashes(c);
+    append(c);
+    batch_verify(c);
+    update_batch(c);
 }
 
 criterion_group!(benches, bench);
@@ -0,0 +1,47 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_ed_on_bls12_381::Fq as Fq381;
+use ark_std::rand::Rng;
+use criterion::Criterion;
+use jf_primitives::rescue::Permutation;
+use std::time::Duration;
+
+const BATCH_SPONGE_BENCH_NAME: &str = "rescue_sponge_1000_inputs";
+
+fn batch_sponge(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(BATCH_SPONGE_BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    let mut rng = jf_utils::test_rng();
+    let inputs: Vec<Vec<Fq381>> = (0..1000)
+        .map(|_| (0..3).map(|_| rng.gen()).collect())
+        .collect();
+    let perm = Permutation::default();
+
+    benchmark_group.bench_function("sequential", |b| {
+        b.iter(|| {
+            inputs
+                .iter()
+                .map(|input| perm.sponge_with_padding(input, 1))
+                .collect::<Vec<_>>()
+        })
+    });
+    benchmark_group.bench_function("batch", |b| b.iter(|| perm.batch_sponge(&inputs, 1)));
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    batch_sponge(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
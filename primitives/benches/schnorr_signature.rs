@@ -0,0 +1,55 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_ed_on_bls12_381::{EdwardsConfig as Param381, Fq as Fq381};
+use criterion::Criterion;
+use jf_primitives::{
+    constants::CS_ID_SCHNORR,
+    signatures::schnorr::{KeyPair, SchnorrSignatureScheme},
+};
+use std::time::Duration;
+
+const BATCH_VERIFY_BENCH_NAME: &str = "schnorr_verify_100_signatures";
+
+fn batch_verify(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(BATCH_VERIFY_BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    let mut rng = jf_utils::test_rng();
+    let mut items = vec![];
+    for i in 0..100u64 {
+        let keypair = KeyPair::<Param381>::generate(&mut rng);
+        let msg = vec![Fq381::from(i)];
+        let sig = keypair.sign(&msg, CS_ID_SCHNORR);
+        items.push((keypair.ver_key(), msg, sig));
+    }
+
+    benchmark_group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for (vk, msg, sig) in items.iter() {
+                vk.verify(msg, sig, CS_ID_SCHNORR).unwrap();
+            }
+        })
+    });
+    benchmark_group.bench_function("batch", |b| {
+        b.iter(|| {
+            assert!(SchnorrSignatureScheme::<Param381>::batch_verify(&items, &mut rng).unwrap())
+        })
+    });
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    batch_verify(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
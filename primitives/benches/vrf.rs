@@ -0,0 +1,52 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_ed_on_bls12_381::EdwardsConfig as Param381;
+use criterion::Criterion;
+use jf_primitives::vrf::{ecvrf::ECVRFScheme, Vrf};
+use std::time::Duration;
+
+const BATCH_VERIFY_BENCH_NAME: &str = "ecvrf_verify_100_proofs";
+
+fn batch_verify(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(BATCH_VERIFY_BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    let mut rng = jf_utils::test_rng();
+    let mut vrf = ECVRFScheme::<Param381>::new();
+    let mut items = vec![];
+    let mut proofs = vec![];
+    for i in 0..100u64 {
+        let (sk, pk) = vrf.key_gen(&(), &mut rng).unwrap();
+        let alpha = i.to_le_bytes().to_vec();
+        proofs.push(vrf.prove(&(), &sk, &alpha, &mut rng).unwrap());
+        items.push((pk, alpha, vrf.prove_batchable(&sk, &alpha).unwrap()));
+    }
+
+    benchmark_group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for ((pk, alpha, _), proof) in items.iter().zip(proofs.iter()) {
+                assert!(vrf.verify(&(), proof, pk, alpha).unwrap().0);
+            }
+        })
+    });
+    benchmark_group.bench_function("batch", |b| {
+        b.iter(|| assert!(ECVRFScheme::<Param381>::batch_verify(&items, &mut rng).unwrap()))
+    });
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    batch_verify(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
@@ -401,6 +401,49 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_aead_tampering() -> Result<(), PrimitivesError> {
+        let mut rng = jf_utils::test_rng();
+        let keypair = KeyPair::generate(&mut rng);
+        let msg = b"The quick brown fox jumps over the lazy dog".to_vec();
+        let aad = b"my associated data".to_vec();
+        let ct = keypair.enc_key.encrypt(&mut rng, &msg, &aad)?;
+
+        // a modified ciphertext or tag fails authentication
+        for i in [0, msg.len(), ct.ct.len() - 1] {
+            let mut bad_ct = ct.clone();
+            bad_ct.ct[i] ^= 1;
+            assert!(matches!(
+                keypair.decrypt(&bad_ct, &aad),
+                Err(PrimitivesError::FailedDecryption(_))
+            ));
+        }
+        let mut bad_ct = ct.clone();
+        bad_ct.ct.pop();
+        assert!(keypair.decrypt(&bad_ct, &aad).is_err());
+        let mut bad_ct = ct.clone();
+        bad_ct.nonce[0] ^= 1;
+        assert!(keypair.decrypt(&bad_ct, &aad).is_err());
+        let mut bad_ct = ct.clone();
+        bad_ct.ephemeral_pk = KeyPair::generate(&mut rng).enc_key();
+        assert!(keypair.decrypt(&bad_ct, &aad).is_err());
+        let mut bad_aad = aad.clone();
+        bad_aad[0] ^= 1;
+        assert!(matches!(
+            keypair.decrypt(&ct, &bad_aad),
+            Err(PrimitivesError::FailedDecryption(_))
+        ));
+        assert_eq!(keypair.decrypt(&ct, &aad)?, msg);
+
+        // nonces are never chosen by the caller: encrypting twice uses a fresh
+        // nonce and ephemeral key, so the ciphertexts differ
+        let ct2 = keypair.enc_key.encrypt(&mut rng, &msg, &aad)?;
+        assert_ne!(ct.nonce, ct2.nonce);
+        assert_ne!(ct.ephemeral_pk, ct2.ephemeral_pk);
+        assert_ne!(ct.ct, ct2.ct);
+        Ok(())
+    }
+
     #[test]
     fn test_serde() {
         let mut rng = jf_utils::test_rng();
@@ -12,7 +12,6 @@ use jf_relation::{errors::CircuitError, BoolVar, Circuit, PlonkCircuit, Variable
 
 mod universal_merkle_tree;
 use ark_std::{string::ToString, vec::Vec};
-use typenum::{Unsigned, U3};
 
 use crate::{
     merkle_tree::{
@@ -88,6 +87,17 @@ where
     /// Allocate a variable for the merkle root.
     fn create_root_variable(&mut self, root: M::NodeValue) -> Result<Variable, CircuitError>;
 
+    /// Given variables representing:
+    /// * an element index
+    /// * its merkle proof
+    /// * return the variable of the root obtained by hashing the element up
+    ///   the merkle path.
+    fn compute_root(
+        &mut self,
+        elem_idx_var: Variable,
+        proof_var: &Self::MembershipProofVar,
+    ) -> Result<Variable, CircuitError>;
+
     /// Given variables representing:
     /// * an element index
     /// * its merkle proof
@@ -305,12 +315,11 @@ where
     fn merkle_path(&self) -> &MerklePath<E, I, T>;
 }
 
-impl<E, I, T, Arity> MembershipProof<E, I, T> for MerkleProof<E, I, T, Arity>
+impl<E, I, T, const ARITY: usize> MembershipProof<E, I, T> for MerkleProof<E, I, T, ARITY>
 where
     E: Element,
     I: Index,
     T: NodeValue,
-    Arity: Unsigned,
 {
     fn tree_height(&self) -> usize {
         self.tree_height()
@@ -331,7 +340,7 @@ where
     T: MerkleTreeScheme,
     T::MembershipProof: MembershipProof<T::NodeValue, T::Index, T::NodeValue>,
     T::NodeValue: PrimeField + RescueParameter,
-    T::Index: ToTraversalPath<U3>,
+    T::Index: ToTraversalPath<3>,
 {
     type MembershipProofVar = Merkle3AryMembershipProofVar;
 
@@ -398,33 +407,38 @@ where
         self.create_variable(root)
     }
 
+    fn compute_root(
+        &mut self,
+        elem_idx_var: Variable,
+        proof_var: &Merkle3AryMembershipProofVar,
+    ) -> Result<Variable, CircuitError> {
+        // elem label = H(0, uid, elem)
+        let mut cur_label =
+            Self::DigestGadget::digest_leaf(self, elem_idx_var, proof_var.elem_var)?;
+        for cur_node in proof_var.node_vars.iter() {
+            let input_labels = constrain_sibling_order(
+                self,
+                cur_label,
+                cur_node.sibling1,
+                cur_node.sibling2,
+                cur_node.is_left_child,
+                cur_node.is_right_child,
+            )?;
+            // check that the left child's label is non-zero
+            self.non_zero_gate(input_labels[0])?;
+            cur_label = Self::DigestGadget::digest(self, &input_labels)?;
+        }
+        Ok(cur_label)
+    }
+
     fn is_member(
         &mut self,
         elem_idx_var: Variable,
         proof_var: Merkle3AryMembershipProofVar,
         root_var: Variable,
     ) -> Result<BoolVar, CircuitError> {
-        let computed_root_var = {
-            let proof_var = &proof_var;
-
-            // elem label = H(0, uid, elem)
-            let mut cur_label =
-                Self::DigestGadget::digest_leaf(self, elem_idx_var, proof_var.elem_var)?;
-            for cur_node in proof_var.node_vars.iter() {
-                let input_labels = constrain_sibling_order(
-                    self,
-                    cur_label,
-                    cur_node.sibling1,
-                    cur_node.sibling2,
-                    cur_node.is_left_child,
-                    cur_node.is_right_child,
-                )?;
-                // check that the left child's label is non-zero
-                self.non_zero_gate(input_labels[0])?;
-                cur_label = Self::DigestGadget::digest(self, &input_labels)?;
-            }
-            Ok(cur_label)
-        }?;
+        let computed_root_var =
+            MerkleTreeGadget::<T>::compute_root(self, elem_idx_var, &proof_var)?;
         self.is_equal(root_var, computed_root_var)
     }
 
@@ -456,7 +470,7 @@ mod test {
     use ark_ed_on_bls12_381::Fq as FqEd381;
     use ark_ed_on_bls12_381_bandersnatch::Fq as FqEd381b;
     use ark_ed_on_bn254::Fq as FqEd254;
-    use ark_std::{boxed::Box, vec::Vec};
+    use ark_std::{boxed::Box, rand::RngCore, vec::Vec};
     use jf_relation::{Circuit, PlonkCircuit, Variable};
 
     #[test]
@@ -633,4 +647,53 @@ mod test {
             assert!(circuit.check_circuit_satisfiability(&[]).is_err());
         }
     }
+
+    #[test]
+    fn test_mt_gadget_compute_root() {
+        test_mt_gadget_compute_root_helper::<FqEd254>();
+        test_mt_gadget_compute_root_helper::<FqEd377>();
+        test_mt_gadget_compute_root_helper::<FqEd381>();
+        test_mt_gadget_compute_root_helper::<FqEd381b>();
+        test_mt_gadget_compute_root_helper::<Fq377>();
+    }
+
+    fn test_mt_gadget_compute_root_helper<F: RescueParameter>() {
+        let mut rng = jf_utils::test_rng();
+        for height in 1..4 {
+            let num_leaves = 3u64.pow(height as u32);
+            let elements = (0..num_leaves)
+                .map(|_| F::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let mt = RescueMerkleTree::<F>::from_elems(height, &elements).unwrap();
+            let expected_root = mt.commitment().digest();
+            let uid = rng.next_u64() % num_leaves;
+            let (_, proof) = mt.lookup(uid).expect_ok().unwrap();
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let elem_idx_var = circuit.create_variable(uid.into()).unwrap();
+            let proof_var =
+                MerkleTreeGadget::<RescueMerkleTree<F>>::create_membership_proof_variable(
+                    &mut circuit,
+                    &proof,
+                )
+                .unwrap();
+            let root_var = MerkleTreeGadget::<RescueMerkleTree<F>>::compute_root(
+                &mut circuit,
+                elem_idx_var,
+                &proof_var,
+            )
+            .unwrap();
+            assert_eq!(circuit.witness(root_var).unwrap(), expected_root);
+
+            // The computed root can be bound to a public root.
+            let public_root_var = circuit.create_public_variable(expected_root).unwrap();
+            circuit.enforce_equal(root_var, public_root_var).unwrap();
+            assert!(circuit
+                .check_circuit_satisfiability(&[expected_root])
+                .is_ok());
+            assert!(circuit
+                .check_circuit_satisfiability(&[F::rand(&mut rng)])
+                .is_err());
+        }
+    }
 }
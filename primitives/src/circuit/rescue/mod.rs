@@ -172,6 +172,11 @@ where
 
     /// Define a constraint such that y = x^(1/alpha).
     /// It is implemented by setting q_{H1} y^alpha = q_O x
+    ///
+    /// This costs a single gate for alpha = 5 (three for alpha = 11), in
+    /// TurboPlonk and UltraPlonk circuits alike. There is no lookup-based
+    /// variant: the S-box is a map over the whole field, so its table would
+    /// need one entry per field element.
     /// * `input_var`  - variable id corresponding to x in the equation above
     /// * `returns` - the variable id corresponding to y
     fn pow_alpha_inv(&mut self, input_var: R::Var) -> Result<R::Var, CircuitError>;
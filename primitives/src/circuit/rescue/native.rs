@@ -536,6 +536,7 @@ mod tests {
             Permutation, RescueMatrix, RescueParameter, RescueVector, CRHF_RATE, PRP, STATE_SIZE,
         },
     };
+    use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
     use ark_ed_on_bls12_381::Fq as FqEd381;
     use ark_ed_on_bn254::Fq as FqEd254;
@@ -654,6 +655,30 @@ mod tests {
         check_circuit_satisfiability(&mut circuit, out_value, out_var);
     }
 
+    #[test]
+    fn test_pow_alpha_inv_num_gates() {
+        test_pow_alpha_inv_num_gates_helper::<FqEd254>();
+        test_pow_alpha_inv_num_gates_helper::<FqEd377>();
+        test_pow_alpha_inv_num_gates_helper::<FqEd381>();
+        test_pow_alpha_inv_num_gates_helper::<Fq377>();
+    }
+    fn test_pow_alpha_inv_num_gates_helper<F: RescueParameter>() {
+        let expected_num_gates = if F::A == 5 { 1 } else { 3 };
+        let mut rng = jf_utils::test_rng();
+        for mut circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            let x = F::rand(&mut rng);
+            let x_var = circuit.create_variable(x).unwrap();
+            let num_gates = circuit.num_gates();
+            let y_var = circuit.pow_alpha_inv(x_var).unwrap();
+            assert_eq!(circuit.num_gates() - num_gates, expected_num_gates);
+            assert_eq!(circuit.witness(y_var).unwrap(), x.pow(F::A_INV));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+    }
+
     #[test]
     fn test_affine_transformation() {
         test_affine_transformation_helper::<FqEd254>();
@@ -729,6 +754,32 @@ mod tests {
         check_circuit_satisfiability(&mut circuit, state_out.elems(), out_var);
     }
 
+    #[test]
+    fn test_rescue_perm_random_inputs() {
+        test_rescue_perm_random_inputs_helper::<FqEd254>();
+        test_rescue_perm_random_inputs_helper::<FqEd377>();
+        test_rescue_perm_random_inputs_helper::<FqEd381>();
+    }
+    fn test_rescue_perm_random_inputs_helper<F: RescueParameter>() {
+        let mut rng = jf_utils::test_rng();
+        let perm = Permutation::default();
+        for _ in 0..5 {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let state_in = RescueVector::from(&[
+                F::rand(&mut rng),
+                F::rand(&mut rng),
+                F::rand(&mut rng),
+                F::rand(&mut rng),
+            ]);
+            let state_in_var = circuit.create_rescue_state_variable(&state_in).unwrap();
+            let out_var = circuit.rescue_permutation(state_in_var).unwrap();
+
+            let state_out = perm.eval(&state_in);
+            check_state(&circuit, &out_var, &state_out);
+            check_circuit_satisfiability(&mut circuit, state_out.elems(), out_var);
+        }
+    }
+
     #[test]
     fn test_add_state() {
         test_add_state_helper::<FqEd254>();
@@ -273,6 +273,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_dsa_circuit_matches_native() -> Result<(), CircuitError> {
+        test_dsa_circuit_matches_native_helper::<_, Param377>()?;
+        test_dsa_circuit_matches_native_helper::<_, Param381>()?;
+        test_dsa_circuit_matches_native_helper::<_, Param381b>()?;
+        test_dsa_circuit_matches_native_helper::<_, Param254>()
+    }
+
+    fn test_dsa_circuit_matches_native_helper<F, P>() -> Result<(), CircuitError>
+    where
+        F: RescueParameter,
+        P: Config<BaseField = F>,
+    {
+        let mut rng = jf_utils::test_rng();
+        for msg_len in [1, 2, 7] {
+            let keypair = KeyPair::<P>::generate(&mut rng);
+            let vk = keypair.ver_key_ref();
+            let msg: Vec<F> = (0..msg_len).map(|_| F::rand(&mut rng)).collect();
+            let sig = keypair.sign(&msg, CS_ID_SCHNORR);
+            assert!(vk.verify(&msg, &sig, CS_ID_SCHNORR).is_ok());
+            let circuit = build_verify_sig_circuit(vk, &msg, &sig)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // Tampered signatures are rejected both natively and in the circuit.
+            let mut sig_bad_s = sig.clone();
+            sig_bad_s.s += P::ScalarField::from(1u64);
+            let mut sig_bad_r = sig.clone();
+            sig_bad_r.R += sig_bad_r.R;
+            for sig_bad in [sig_bad_s, sig_bad_r] {
+                assert!(vk.verify(&msg, &sig_bad, CS_ID_SCHNORR).is_err());
+                let bad_circuit = build_verify_sig_circuit(vk, &msg, &sig_bad)?;
+                assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+        Ok(())
+    }
+
     fn build_verify_sig_circuit<F, P>(
         vk: &VerKey<P>,
         msg: &[F],
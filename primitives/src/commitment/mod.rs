@@ -0,0 +1,350 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implements a rescue hash based commitment scheme, and a Pedersen vector
+//! commitment scheme in [`pedersen`].
+
+pub mod pedersen;
+
+use ark_std::marker::PhantomData;
+
+use crate::{
+    crhf::{FixedLengthRescueCRHF, CRHF},
+    errors::PrimitivesError,
+    rescue::{Permutation, RescueParameter},
+};
+use ark_serialize::*;
+use ark_std::{
+    borrow::Borrow,
+    fmt::Debug,
+    hash::Hash,
+    string::{String, ToString},
+    vec::Vec,
+    UniformRand,
+};
+
+/// A trait for cryptographic commitment scheme
+pub trait CommitmentScheme {
+    /// Input to the commitment
+    type Input;
+    /// The type of output commitment value
+    type Output: Clone + Debug + PartialEq + Eq + Hash;
+    /// The type of the hiding/blinding factor
+    type Randomness: Clone + Debug + PartialEq + Eq + UniformRand;
+
+    /// Commit algorithm that takes `input` and blinding randomness `r`
+    /// (optional for hiding commitment schemes), outputs a commitment.
+    fn commit<T: Borrow<Self::Input>>(
+        input: T,
+        r: Option<&Self::Randomness>,
+    ) -> Result<Self::Output, PrimitivesError>;
+
+    /// Verify algorithm that output `Ok` if accepted, or `Err` if rejected.
+    fn verify<T: Borrow<Self::Input>>(
+        input: T,
+        r: Option<&Self::Randomness>,
+        comm: &Self::Output,
+    ) -> Result<(), PrimitivesError>;
+
+    /// Whether `opening` opens `comm`. Output `false` on a mismatched value
+    /// or blinding factor.
+    fn verify_opening(
+        comm: &Self::Output,
+        opening: &Opening<Self::Input, Self::Randomness>,
+    ) -> bool {
+        Self::verify(&opening.value, Some(&opening.blinder), comm).is_ok()
+    }
+}
+
+/// Opening of a commitment, i.e. the committed value and its blinding factor,
+/// to be transmitted along with the commitment.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Opening<V, R> {
+    /// The committed value
+    pub value: V,
+    /// The blinding factor
+    pub blinder: R,
+}
+
+impl<V, R> Opening<V, R> {
+    /// Opening of a commitment to `value` with blinding factor `blinder`
+    pub fn new(value: V, blinder: R) -> Self {
+        Self { value, blinder }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Rescue-based Commitment instance for fixed-length input
+///
+/// ## Note
+/// the current ugly existence of `INPUT_LEN_PLUS_ONE` is due to unstable
+/// feature of using const generic in expression (namely can't use `INPUT_LEN +
+/// 1` in code).
+// FIXME: (alex) when `feature(generic_const_exprs)` is stable, we should remove
+// the third generic param. See more: https://github.com/rust-lang/rust/issues/76560
+pub struct FixedLengthRescueCommitment<
+    F: RescueParameter,
+    const INPUT_LEN: usize,
+    const INPUT_LEN_PLUS_ONE: usize,
+>(PhantomData<F>);
+
+impl<F: RescueParameter, const INPUT_LEN: usize, const INPUT_LEN_PLUS_ONE: usize> CommitmentScheme
+    for FixedLengthRescueCommitment<F, INPUT_LEN, INPUT_LEN_PLUS_ONE>
+{
+    type Input = [F; INPUT_LEN];
+    type Output = F;
+    type Randomness = F;
+
+    fn commit<T: Borrow<Self::Input>>(
+        input: T,
+        r: Option<&Self::Randomness>,
+    ) -> Result<Self::Output, PrimitivesError> {
+        let mut msg = [F::zero(); INPUT_LEN_PLUS_ONE];
+        msg[0] = *r.ok_or_else(|| {
+            PrimitivesError::ParameterError("Expecting a blinding factor".to_string())
+        })?;
+        msg[1..INPUT_LEN_PLUS_ONE].copy_from_slice(&input.borrow()[..(INPUT_LEN)]);
+
+        Ok(FixedLengthRescueCRHF::<F, INPUT_LEN_PLUS_ONE, 1>::evaluate(&msg)?[0])
+    }
+
+    fn verify<T: Borrow<Self::Input>>(
+        input: T,
+        r: Option<&Self::Randomness>,
+        comm: &Self::Output,
+    ) -> Result<(), PrimitivesError> {
+        if <Self as CommitmentScheme>::commit(input, r)? == *comm {
+            Ok(())
+        } else {
+            Err(PrimitivesError::VerificationError(String::from(
+                "Commitment verification failed",
+            )))
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Rescue-based Commitment instance for variable-length input.
+///
+/// The blinding factor is absorbed first, followed by the message, with
+/// [`Permutation::sponge_with_padding`]. The commitment is hiding when the
+/// blinding factor is uniformly random, and binding as long as the padded
+/// Rescue sponge is collision resistant.
+pub struct VariableLengthRescueCommitment<F: RescueParameter>(PhantomData<F>);
+
+impl<F: RescueParameter> CommitmentScheme for VariableLengthRescueCommitment<F> {
+    type Input = Vec<F>;
+    type Output = F;
+    type Randomness = F;
+
+    fn commit<T: Borrow<Self::Input>>(
+        input: T,
+        r: Option<&Self::Randomness>,
+    ) -> Result<Self::Output, PrimitivesError> {
+        let mut msg = Vec::with_capacity(input.borrow().len() + 1);
+        msg.push(*r.ok_or_else(|| {
+            PrimitivesError::ParameterError("Expecting a blinding factor".to_string())
+        })?);
+        msg.extend_from_slice(input.borrow());
+
+        Ok(Permutation::default().sponge_with_padding(&msg, 1)[0])
+    }
+
+    fn verify<T: Borrow<Self::Input>>(
+        input: T,
+        r: Option<&Self::Randomness>,
+        comm: &Self::Output,
+    ) -> Result<(), PrimitivesError> {
+        if <Self as CommitmentScheme>::commit(input, r)? == *comm {
+            Ok(())
+        } else {
+            Err(PrimitivesError::VerificationError(String::from(
+                "Commitment verification failed",
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        commitment::{
+            CommitmentScheme, FixedLengthRescueCommitment, Opening, VariableLengthRescueCommitment,
+        },
+        rescue::{sponge::RescueCRHF, Permutation, RescueParameter, CRHF_RATE},
+    };
+    use ark_bls12_377::Fq as Fq377;
+    use ark_bn254::Fq as Fq254;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bls12_381_bandersnatch::Fq as FqEd381b;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::UniformRand;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{vec, vec::Vec};
+
+    macro_rules! test_commit {
+        ($tr:tt) => {
+            let mut prng = jf_utils::test_rng();
+
+            let input = [$tr::from(1u64), $tr::from(2u64), $tr::from(3u64)];
+            let blind = $tr::rand(&mut prng);
+
+            let c = FixedLengthRescueCommitment::<$tr, 3, 4>::commit(&input, Some(&blind)).unwrap();
+            assert!(
+                FixedLengthRescueCommitment::<$tr, 3, 4>::verify(&input, Some(&blind), &c).is_ok()
+            );
+            // test for correctness
+            let mut msg = vec![blind];
+            msg.extend_from_slice(&input);
+            if (input.len() + 1) % CRHF_RATE == 0 {
+                assert_eq!(c, RescueCRHF::sponge_no_padding(&msg, 1).unwrap()[0])
+            } else {
+                assert_eq!(c, RescueCRHF::sponge_with_zero_padding(&msg, 1)[0])
+            }
+
+            // smaller input size
+            let bad_input = [input[0], input[1]];
+            assert!(
+                FixedLengthRescueCommitment::<$tr, 2, 3>::verify(&bad_input, Some(&blind), &c)
+                    .is_err()
+            );
+            // bad blinding factor
+            let bad_blind = blind + $tr::from(1u8);
+            assert!(
+                FixedLengthRescueCommitment::<$tr, 3, 4>::verify(&input, Some(&bad_blind), &c)
+                    .is_err()
+            );
+            // bad input
+            let bad_input = [$tr::from(2u64), $tr::from(1u64), $tr::from(3u64)];
+            assert!(
+                FixedLengthRescueCommitment::<$tr, 3, 4>::verify(&bad_input, Some(&blind), &c)
+                    .is_err()
+            );
+        };
+    }
+
+    #[test]
+    fn test_commit() {
+        test_commit!(FqEd254);
+        test_commit!(FqEd377);
+        test_commit!(FqEd381);
+        test_commit!(FqEd381b);
+        test_commit!(Fq377);
+        test_commit!(Fq254);
+    }
+
+    macro_rules! test_variable_length_commit {
+        ($tr:tt) => {
+            let mut prng = jf_utils::test_rng();
+
+            let input = vec![$tr::from(1u64), $tr::from(2u64)];
+            let blind = $tr::rand(&mut prng);
+
+            let c = VariableLengthRescueCommitment::<$tr>::commit(&input, Some(&blind)).unwrap();
+            assert!(
+                VariableLengthRescueCommitment::<$tr>::verify(&input, Some(&blind), &c).is_ok()
+            );
+            // test for correctness
+            assert_eq!(
+                c,
+                Permutation::default().sponge_with_padding(&[blind, input[0], input[1]], 1)[0]
+            );
+            // missing blinding factor
+            assert!(VariableLengthRescueCommitment::<$tr>::commit(&input, None).is_err());
+            // bad blinding factor
+            let bad_blind = blind + $tr::from(1u8);
+            assert!(
+                VariableLengthRescueCommitment::<$tr>::verify(&input, Some(&bad_blind), &c)
+                    .is_err()
+            );
+            // longer input, which only differs by padding
+            let bad_input = vec![input[0], input[1], $tr::from(0u8)];
+            assert!(
+                VariableLengthRescueCommitment::<$tr>::verify(&bad_input, Some(&blind), &c)
+                    .is_err()
+            );
+            // distinct random messages give distinct commitments
+            let commitments = (0..10)
+                .map(|_| {
+                    let msg = (0..5).map(|_| $tr::rand(&mut prng)).collect::<Vec<_>>();
+                    VariableLengthRescueCommitment::<$tr>::commit(&msg, Some(&blind)).unwrap()
+                })
+                .collect::<ark_std::collections::BTreeSet<_>>();
+            assert_eq!(commitments.len(), 10);
+        };
+    }
+
+    #[test]
+    fn test_variable_length_commit() {
+        test_variable_length_commit!(FqEd254);
+        test_variable_length_commit!(FqEd377);
+        test_variable_length_commit!(FqEd381);
+        test_variable_length_commit!(FqEd381b);
+        test_variable_length_commit!(Fq377);
+        test_variable_length_commit!(Fq254);
+    }
+
+    #[test]
+    fn test_opening() {
+        test_opening_helper::<FqEd254>();
+        test_opening_helper::<FqEd377>();
+        test_opening_helper::<FqEd381>();
+        test_opening_helper::<FqEd381b>();
+        test_opening_helper::<Fq377>();
+        test_opening_helper::<Fq254>();
+    }
+
+    fn test_opening_helper<F: RescueParameter>() {
+        let mut prng = jf_utils::test_rng();
+        let input = [F::from(1u64), F::from(2u64), F::from(3u64)];
+        let blind = F::rand(&mut prng);
+        let opening = Opening::new(input, blind);
+        let c = FixedLengthRescueCommitment::<F, 3, 4>::commit(&input, Some(&blind)).unwrap();
+        assert!(FixedLengthRescueCommitment::<F, 3, 4>::verify_opening(
+            &c, &opening
+        ));
+
+        // serialization round trip
+        let mut bytes = Vec::new();
+        opening.serialize_compressed(&mut bytes).unwrap();
+        let de = Opening::<[F; 3], F>::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(de, opening);
+        assert!(FixedLengthRescueCommitment::<F, 3, 4>::verify_opening(
+            &c, &de
+        ));
+
+        // tampered value or blinding factor
+        let mut bad_opening = opening.clone();
+        bad_opening.value[2] += F::from(1u8);
+        assert!(!FixedLengthRescueCommitment::<F, 3, 4>::verify_opening(
+            &c,
+            &bad_opening
+        ));
+        let mut bad_opening = opening;
+        bad_opening.blinder += F::from(1u8);
+        assert!(!FixedLengthRescueCommitment::<F, 3, 4>::verify_opening(
+            &c,
+            &bad_opening
+        ));
+
+        // variable length input
+        let input = vec![F::from(1u64), F::from(2u64)];
+        let opening = Opening::new(input.clone(), blind);
+        let c = VariableLengthRescueCommitment::<F>::commit(&input, Some(&blind)).unwrap();
+        let mut bytes = Vec::new();
+        opening.serialize_compressed(&mut bytes).unwrap();
+        let de = Opening::<Vec<F>, F>::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(de, opening);
+        assert!(VariableLengthRescueCommitment::<F>::verify_opening(&c, &de));
+        let mut bad_opening = de;
+        bad_opening.value.push(F::from(0u8));
+        assert!(!VariableLengthRescueCommitment::<F>::verify_opening(
+            &c,
+            &bad_opening
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implements a Pedersen vector commitment scheme over short Weierstrass
+//! curves.
+//!
+//! A vector `(a_1, ..., a_n)` with blinding factor `r` is committed to as
+//! `r * H + a_1 * G_1 + ... + a_n * G_n`. The generators are derived with
+//! [`SWHashToGroup::hash_to_group`], so there is no trusted setup and nobody
+//! knows the discrete logarithms between them. The scheme is perfectly hiding,
+//! computationally binding, and additively homomorphic.
+
+use super::Opening;
+use crate::{constants::CS_ID_PEDERSEN, errors::PrimitivesError, hash_to_group::SWHashToGroup};
+use ark_ec::{
+    short_weierstrass::{Affine, Projective, SWCurveConfig},
+    CurveGroup, VariableBaseMSM,
+};
+use ark_serialize::*;
+use ark_std::{
+    format,
+    hash::{Hash, Hasher},
+    ops::Add,
+    string::ToString,
+    vec::Vec,
+};
+
+/// Commitment key made of the blinding generator `H` and the generators
+/// `G_1, ..., G_n` of the message vector.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = "P: SWCurveConfig"),
+    Clone(bound = "P: SWCurveConfig"),
+    PartialEq(bound = "P: SWCurveConfig"),
+    Eq(bound = "P: SWCurveConfig")
+)]
+pub struct PedersenCommitmentKey<P: SWCurveConfig> {
+    pub(crate) blinding_generator: Affine<P>,
+    pub(crate) generators: Vec<Affine<P>>,
+}
+
+/// Pedersen commitment to a vector of scalars.
+///
+/// Adding the commitments to two vectors gives the commitment to their sum,
+/// blinded by the sum of the blinding factors.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = "P: SWCurveConfig"),
+    Clone(bound = "P: SWCurveConfig"),
+    Copy(bound = "P: SWCurveConfig"),
+    PartialEq(bound = "P: SWCurveConfig"),
+    Eq(bound = "P: SWCurveConfig"),
+    Hash(bound = "P: SWCurveConfig")
+)]
+pub struct PedersenCommitment<P: SWCurveConfig>(pub(crate) Projective<P>);
+
+impl<P: SWCurveConfig> Add for PedersenCommitment<P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl<P: SWHashToGroup> PedersenCommitmentKey<P> {
+    /// Derive a key for vectors of length `len` with
+    /// [`SWHashToGroup::derive_generators`] under [`CS_ID_PEDERSEN`], with
+    /// the first generator used for blinding, so a shorter key is a prefix
+    /// of a longer one.
+    pub fn new(len: usize) -> Result<Self, PrimitivesError> {
+        let points = P::derive_generators(CS_ID_PEDERSEN.as_bytes(), len + 1)?;
+        let mut points = Projective::<P>::normalize_batch(&points);
+        let generators = points.split_off(1);
+        Ok(Self {
+            blinding_generator: points[0],
+            generators,
+        })
+    }
+
+    /// Length of the vectors this key commits to.
+    pub fn len(&self) -> usize {
+        self.generators.len()
+    }
+
+    /// Whether the key commits to empty vectors only.
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+
+    /// Commit to `scalars` with blinding factor `blinder`, with a single
+    /// multi-scalar multiplication. Returns an error if `scalars` does not
+    /// have the length of the key.
+    pub fn commit(
+        &self,
+        scalars: &[P::ScalarField],
+        blinder: &P::ScalarField,
+    ) -> Result<PedersenCommitment<P>, PrimitivesError> {
+        if scalars.len() != self.len() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "Expecting {} scalars, got {}",
+                self.len(),
+                scalars.len()
+            )));
+        }
+        let mut bases = Vec::with_capacity(self.len() + 1);
+        bases.push(self.blinding_generator);
+        bases.extend_from_slice(&self.generators);
+        let mut exponents = Vec::with_capacity(self.len() + 1);
+        exponents.push(*blinder);
+        exponents.extend_from_slice(scalars);
+        Ok(PedersenCommitment(Projective::<P>::msm_unchecked(
+            &bases, &exponents,
+        )))
+    }
+
+    /// Verify the opening `(scalars, blinder)` of `comm`. Output `Ok` if
+    /// accepted, or `Err` if rejected.
+    pub fn verify_open(
+        &self,
+        scalars: &[P::ScalarField],
+        blinder: &P::ScalarField,
+        comm: &PedersenCommitment<P>,
+    ) -> Result<(), PrimitivesError> {
+        if self.commit(scalars, blinder)? == *comm {
+            Ok(())
+        } else {
+            Err(PrimitivesError::VerificationError(
+                "Commitment verification failed".to_string(),
+            ))
+        }
+    }
+
+    /// Whether `opening` opens `comm`. Output `false` on a mismatched vector,
+    /// of any length, or blinding factor.
+    pub fn verify_opening(
+        &self,
+        comm: &PedersenCommitment<P>,
+        opening: &Opening<Vec<P::ScalarField>, P::ScalarField>,
+    ) -> bool {
+        self.verify_open(&opening.value, &opening.blinder, comm)
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::collections::BTreeSet;
+
+    #[test]
+    fn test_pedersen_commitment() {
+        test_pedersen_commitment_helper::<ark_bls12_381::g1::Config>();
+        test_pedersen_commitment_helper::<ark_bls12_377::g1::Config>();
+        test_pedersen_commitment_helper::<ark_bn254::g1::Config>();
+    }
+
+    fn test_pedersen_commitment_helper<P: SWHashToGroup>() {
+        let mut rng = jf_utils::test_rng();
+        let len = 5;
+        let key = PedersenCommitmentKey::<P>::new(len).unwrap();
+        assert_eq!(key.len(), len);
+
+        // generators are deterministic, distinct, and shorter keys are prefixes
+        assert_eq!(key, PedersenCommitmentKey::<P>::new(len).unwrap());
+        let short_key = PedersenCommitmentKey::<P>::new(2).unwrap();
+        assert_eq!(short_key.blinding_generator, key.blinding_generator);
+        assert_eq!(short_key.generators[..], key.generators[..2]);
+        let mut points = key.generators.clone();
+        points.push(key.blinding_generator);
+        assert_eq!(
+            points.iter().map(|g| g.x).collect::<BTreeSet<_>>().len(),
+            len + 1
+        );
+
+        // correctness
+        let a = (0..len)
+            .map(|_| P::ScalarField::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let r = P::ScalarField::rand(&mut rng);
+        let comm = key.commit(&a, &r).unwrap();
+        let expected = key
+            .generators
+            .iter()
+            .zip(a.iter())
+            .fold(key.blinding_generator * r, |acc, (g, a_i)| acc + *g * a_i);
+        assert_eq!(comm.0, expected);
+        assert!(key.verify_open(&a, &r, &comm).is_ok());
+
+        // bad opening
+        let mut bad_a = a.clone();
+        bad_a[len - 1] += P::ScalarField::from(1u8);
+        assert!(key.verify_open(&bad_a, &r, &comm).is_err());
+        assert!(key
+            .verify_open(&a, &(r + P::ScalarField::from(1u8)), &comm)
+            .is_err());
+        assert!(key.commit(&a[..len - 1], &r).is_err());
+        assert!(key.verify_open(&a[..len - 1], &r, &comm).is_err());
+
+        // additive homomorphism
+        let b = (0..len)
+            .map(|_| P::ScalarField::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let s = P::ScalarField::rand(&mut rng);
+        let sum = a
+            .iter()
+            .zip(b.iter())
+            .map(|(x, y)| *x + y)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comm + key.commit(&b, &s).unwrap(),
+            key.commit(&sum, &(r + s)).unwrap()
+        );
+
+        // distinct random vectors give distinct commitments
+        let commitments = (0..10)
+            .map(|_| {
+                let v = (0..len)
+                    .map(|_| P::ScalarField::rand(&mut rng))
+                    .collect::<Vec<_>>();
+                key.commit(&v, &r).unwrap().0.into_affine().x
+            })
+            .collect::<BTreeSet<_>>();
+        assert_eq!(commitments.len(), 10);
+
+        // serialization
+        let mut bytes = Vec::new();
+        comm.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            PedersenCommitment::<P>::deserialize_compressed(&bytes[..]).unwrap(),
+            comm
+        );
+
+        // transmitted openings
+        let opening = Opening::new(a, r);
+        let mut bytes = Vec::new();
+        opening.serialize_compressed(&mut bytes).unwrap();
+        let de = Opening::<Vec<P::ScalarField>, P::ScalarField>::deserialize_compressed(&bytes[..])
+            .unwrap();
+        assert_eq!(de, opening);
+        assert!(key.verify_opening(&comm, &de));
+        let mut bad_opening = opening.clone();
+        bad_opening.value[0] += P::ScalarField::from(1u8);
+        assert!(!key.verify_opening(&comm, &bad_opening));
+        let mut bad_opening = opening.clone();
+        bad_opening.blinder += P::ScalarField::from(1u8);
+        assert!(!key.verify_opening(&comm, &bad_opening));
+        let mut bad_opening = opening;
+        bad_opening.value.pop();
+        assert!(!key.verify_opening(&comm, &bad_opening));
+    }
+}
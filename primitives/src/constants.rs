@@ -9,10 +9,23 @@
 /// ciphersuite identifier for schnorr signature
 pub const CS_ID_SCHNORR: &str = "SCHNORR_WITH_RESCUE_HASH_v01";
 
+/// ciphersuite identifier for ECVRF over twisted Edwards curves, used both as
+/// the domain separation tag of `hash_to_curve` and as the suite string, see:
+/// <https://www.rfc-editor.org/rfc/rfc9381.html#name-ecvrf-ciphersuites>
+pub const CS_ID_ECVRF: &str = "ECVRF_EDWARDS_XMD:SHA-256_ELL2_RO_v01";
+
+/// domain separation tag for deriving the generators of Pedersen commitments
+pub const CS_ID_PEDERSEN: &str = "PEDERSEN_GENERATORS_v01";
+
 /// ciphersuite identifier for BLS signature over BLS12_381, see:
 /// <https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-ciphersuite-format>
 pub const CS_ID_BLS_MIN_SIG: &str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
 
+/// domain separation tag for proofs of possession of BLS verification keys over
+/// BLS12_381, see:
+/// <https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-proof-of-possession>
+pub const CS_ID_BLS_POP: &str = "BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
 /// Size in bytes of a secret key in our BLS signature scheme.
 pub const BLS_SIG_SK_SIZE: usize = 32;
 /// Size in bytes of a signature in our BLS signature scheme.
@@ -5,6 +5,11 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 //! Implements the ElGamal encryption scheme.
+//!
+//! [`Ciphertext`] is the hybrid variant: the Diffie-Hellman shared point keys
+//! a Rescue stream cipher, so messages are arbitrary field element vectors.
+//! [`PointCiphertext`] is the textbook variant where messages are curve
+//! points, which is additively homomorphic.
 
 use crate::{
     elgamal::Direction::{Decrypt, Encrypt},
@@ -15,10 +20,11 @@ use ark_ec::{
     twisted_edwards::{Affine, Projective, TECurveConfig as Config},
     AffineRepr, CurveGroup, Group,
 };
-use ark_ff::UniformRand;
+use ark_ff::{UniformRand, Zero};
 use ark_serialize::*;
 use ark_std::{
     hash::{Hash, Hasher},
+    ops::Add,
     rand::{CryptoRng, Rng, RngCore},
     string::ToString,
     vec,
@@ -153,6 +159,39 @@ where
     }
 }
 
+/// Public encryption cipher text of a curve point `msg`, namely
+/// `(r * G, msg + r * pk)` for a random scalar `r`.
+///
+/// Adding two ciphertexts under the same key gives a ciphertext of the sum of
+/// their plaintexts.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = "P: Config"),
+    Clone(bound = "P: Config"),
+    Copy(bound = "P: Config"),
+    PartialEq(bound = "P: Config"),
+    Eq(bound = "P: Config"),
+    Hash(bound = "P: Config")
+)]
+pub struct PointCiphertext<P>
+where
+    P: Config,
+{
+    pub(crate) ephemeral: Projective<P>,
+    pub(crate) data: Projective<P>,
+}
+
+impl<P: Config> Add for PointCiphertext<P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            ephemeral: self.ephemeral + other.ephemeral,
+            data: self.data + other.data,
+        }
+    }
+}
+
 // =====================================================
 // end of definitions
 // =====================================================
@@ -255,6 +294,43 @@ where
     }
 }
 
+impl<P: Config> EncKey<P> {
+    /// Public key encryption function of a curve point with pre-sampled
+    /// randomness
+    /// * `r` - randomness
+    /// * `msg` - plaintext point
+    /// * `returns` - PointCiphertext
+    pub fn deterministic_encrypt_point(
+        &self,
+        r: P::ScalarField,
+        msg: &Projective<P>,
+    ) -> PointCiphertext<P> {
+        PointCiphertext {
+            ephemeral: Projective::<P>::generator() * r,
+            data: *msg + self.key * r,
+        }
+    }
+
+    /// Public key encryption function of a curve point
+    pub fn encrypt_point<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        msg: &Projective<P>,
+    ) -> PointCiphertext<P> {
+        self.deterministic_encrypt_point(P::ScalarField::rand(prng), msg)
+    }
+}
+
+impl<P: Config> PointCiphertext<P> {
+    /// Rerandomize the cipher text under the public key `enc_key` it was
+    /// encrypted to, by adding a fresh encryption of the identity. The result
+    /// decrypts to the same point but is unlinkable to `self` without the
+    /// decryption key.
+    pub fn rerandomize<R: CryptoRng + RngCore>(&self, enc_key: &EncKey<P>, prng: &mut R) -> Self {
+        *self + enc_key.encrypt_point(prng, &Projective::<P>::zero())
+    }
+}
+
 impl<F, P> DecKey<P>
 where
     F: RescueParameter,
@@ -297,6 +373,14 @@ where
     }
 }
 
+impl<P: Config> KeyPair<P> {
+    /// Decryption function of a curve point. Decrypting with the wrong key
+    /// does not fail but returns an unrelated point.
+    pub fn decrypt_point(&self, ctext: &PointCiphertext<P>) -> Projective<P> {
+        ctext.data - ctext.ephemeral * self.dec.key
+    }
+}
+
 pub(crate) enum Direction {
     Encrypt,
     Decrypt,
@@ -312,7 +396,7 @@ where
     F: RescueParameter,
 {
     let prp = PRP::default();
-    let round_keys = prp.key_schedule(key);
+    let mut round_keys = prp.key_schedule(key);
     // compute stream
     let mut output = data.to_vec();
     // temporarily append dummy padding element
@@ -349,6 +433,7 @@ where
             .enumerate()
             .for_each(round_fn);
     }
+    round_keys.iter_mut().for_each(RescueVector::wipe);
     // remove dummy padding elements
     output.truncate(data.len());
     output
@@ -356,7 +441,8 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::{Ciphertext, DecKey, EncKey, KeyPair, UniformRand};
+    use super::{Ciphertext, DecKey, EncKey, KeyPair, PointCiphertext, UniformRand};
+    use ark_ec::twisted_edwards::Projective;
     use ark_ed_on_bls12_377::{EdwardsConfig as ParamEd377, Fq as FqEd377, Fr as FrEd377};
     use ark_ed_on_bls12_381::{EdwardsConfig as ParamEd381, Fq as FqEd381, Fr as FrEd381};
     use ark_ed_on_bls12_381_bandersnatch::{
@@ -404,6 +490,48 @@ mod test {
         test_enc_and_dec!(ParamEd381b, FqEd381b, FrEd381b);
     }
 
+    macro_rules! test_point_enc_and_dec {
+        ($param: tt, $scalar_field: tt) => {
+            let mut rng = jf_utils::test_rng();
+            let keypair: KeyPair<$param> = KeyPair::generate(&mut rng);
+            let other_keypair: KeyPair<$param> = KeyPair::generate(&mut rng);
+            let pub_key = keypair.enc_key_ref();
+            let msg1 = Projective::<$param>::rand(&mut rng);
+            let msg2 = Projective::<$param>::rand(&mut rng);
+
+            let ctext1 = pub_key.encrypt_point(&mut rng, &msg1);
+            assert_eq!(keypair.decrypt_point(&ctext1), msg1);
+            assert_ne!(other_keypair.decrypt_point(&ctext1), msg1);
+
+            // deterministic variant
+            let r = $scalar_field::rand(&mut rng);
+            let ctext2 = pub_key.deterministic_encrypt_point(r, &msg2);
+            assert_eq!(ctext2, pub_key.deterministic_encrypt_point(r, &msg2));
+            assert_ne!(ctext2, pub_key.encrypt_point(&mut rng, &msg2));
+            assert_eq!(keypair.decrypt_point(&ctext2), msg2);
+
+            // additive homomorphism
+            let sum: PointCiphertext<$param> = ctext1 + ctext2;
+            assert_eq!(keypair.decrypt_point(&sum), msg1 + msg2);
+
+            // rerandomization
+            let rerand = ctext1.rerandomize(pub_key, &mut rng);
+            assert_ne!(rerand, ctext1);
+            assert_ne!(rerand.ephemeral, ctext1.ephemeral);
+            assert_ne!(rerand.data, ctext1.data);
+            assert_eq!(keypair.decrypt_point(&rerand), msg1);
+            assert_ne!(rerand.rerandomize(pub_key, &mut rng), rerand);
+        };
+    }
+
+    #[test]
+    fn test_point_enc_and_dec() {
+        test_point_enc_and_dec!(ParamEd254, FrEd254);
+        test_point_enc_and_dec!(ParamEd377, FrEd377);
+        test_point_enc_and_dec!(ParamEd381, FrEd381);
+        test_point_enc_and_dec!(ParamEd381b, FrEd381b);
+    }
+
     macro_rules! test_serdes {
         ($param: tt, $base_field:tt, $scalar_field: tt) => {
             let mut rng = jf_utils::test_rng();
@@ -431,6 +559,15 @@ mod test {
             let de: Ciphertext<$param> =
                 Ciphertext::deserialize_compressed(&ser_bytes[..]).unwrap();
             assert_eq!(ct, de);
+
+            let point_ct = keypair
+                .enc_key()
+                .encrypt_point(&mut rng, &Projective::<$param>::rand(&mut rng));
+            let mut ser_bytes: Vec<u8> = Vec::new();
+            point_ct.serialize_compressed(&mut ser_bytes).unwrap();
+            let de: PointCiphertext<$param> =
+                PointCiphertext::deserialize_compressed(&ser_bytes[..]).unwrap();
+            assert_eq!(point_ct, de);
         };
     }
 
@@ -29,6 +29,12 @@ pub enum PrimitivesError {
     VerificationError(String),
     /// Bad parameter in function call, {0}
     ParameterError(String),
+    /// Index out of range, {0}
+    IndexOutOfRange(String),
+    /// Invalid proof, {0}
+    InvalidProof(String),
+    /// Invalid byte encoding, {0}
+    InvalidEncoding(String),
     #[rustfmt::skip]
     /// ‼ ️Internal error! Please report to Crypto Team immediately!\nMessage: {0}
     InternalError(String),
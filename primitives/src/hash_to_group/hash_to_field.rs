@@ -0,0 +1,276 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Hashing to finite fields as specified in
+//! [RFC 9380, Section 5](https://www.rfc-editor.org/rfc/rfc9380.html#name-hashing-to-a-finite-field).
+
+use crate::errors::PrimitivesError;
+use ark_ff::{Field, PrimeField};
+use ark_std::{string::ToString, vec, vec::Vec};
+use digest::{core_api::BlockSizeUser, Digest};
+use sha2::Sha256;
+
+/// Target security level `k` of RFC 9380, in bits.
+const SECURITY_PARAMETER: usize = 128;
+
+/// Hash `msg` to `count` field elements with the `hash_to_field` function of
+/// RFC 9380, using `expand_message_xmd` with SHA-256 and the domain separation
+/// tag `dst`.
+///
+/// Each base prime field element is reduced from
+/// `L = ceil((ceil(log2(p)) + 128) / 8)` bytes, so that the output is
+/// statistically close to uniform.
+pub fn hash_to_field<F: Field>(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+) -> Result<Vec<F>, PrimitivesError> {
+    hash_to_field_with_hasher::<F, Sha256>(msg, dst, count)
+}
+
+/// Same as [`hash_to_field`] except that `expand_message_xmd` is built on the
+/// hash function `H`.
+pub fn hash_to_field_with_hasher<F: Field, H: Digest + BlockSizeUser>(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+) -> Result<Vec<F>, PrimitivesError> {
+    let extension_degree = F::extension_degree() as usize;
+    let len_per_base_elem = len_per_base_elem::<F::BasePrimeField>();
+    let len_per_elem = len_per_base_elem * extension_degree;
+    let uniform_bytes = expand_message_xmd::<H>(msg, dst, count * len_per_elem)?;
+    debug_assert_eq!(uniform_bytes.len(), count * len_per_elem);
+
+    uniform_bytes
+        .chunks(len_per_elem)
+        .map(|elem_bytes| {
+            let base_elems = elem_bytes
+                .chunks(len_per_base_elem)
+                .map(F::BasePrimeField::from_be_bytes_mod_order)
+                .collect::<Vec<_>>();
+            F::from_base_prime_field_elems(&base_elems).ok_or_else(|| {
+                PrimitivesError::InternalError("Wrong number of base field elements".to_string())
+            })
+        })
+        .collect()
+}
+
+/// `L = ceil((ceil(log2(p)) + k) / 8)`, the number of bytes reduced to an
+/// element of the prime field of modulus `p`.
+fn len_per_base_elem<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize + SECURITY_PARAMETER + 7) / 8
+}
+
+/// Prefix used to hash domain separation tags longer than 255 bytes, see
+/// [RFC 9380, Section 5.3.3](https://www.rfc-editor.org/rfc/rfc9380.html#name-using-dsts-longer-than-255-).
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// Expand `msg` to `len_in_bytes` uniformly random bytes with the
+/// `expand_message_xmd` function of
+/// [RFC 9380, Section 5.3.1](https://www.rfc-editor.org/rfc/rfc9380.html#name-expand_message_xmd),
+/// built on the hash function `H`.
+///
+/// A domain separation tag `dst` longer than 255 bytes is first hashed to
+/// `H("H2C-OVERSIZE-DST-" || dst)`. Returns an error if `len_in_bytes` is
+/// larger than 65535 or than 255 outputs of `H`.
+pub fn expand_message_xmd<H: Digest + BlockSizeUser>(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, PrimitivesError> {
+    let b_in_bytes = <H as Digest>::output_size();
+    let r_in_bytes = H::block_size();
+    let ell = (len_in_bytes + b_in_bytes - 1) / b_in_bytes;
+    if ell > 255 || len_in_bytes > u16::MAX as usize {
+        return Err(PrimitivesError::IndexOutOfRange(
+            "expand_message_xmd: requested length is too large".to_string(),
+        ));
+    }
+    let dst = if dst.len() > 255 {
+        H::new()
+            .chain_update(OVERSIZE_DST_PREFIX)
+            .chain_update(dst)
+            .finalize()
+            .to_vec()
+    } else {
+        dst.to_vec()
+    };
+    let dst_prime = [&dst[..], &[dst.len() as u8]].concat();
+
+    let b_0 = H::new()
+        .chain_update(vec![0u8; r_in_bytes])
+        .chain_update(msg)
+        .chain_update((len_in_bytes as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut b_i = H::new()
+        .chain_update(&b_0)
+        .chain_update([1u8])
+        .chain_update(&dst_prime)
+        .finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(ell * b_in_bytes);
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let xored = b_0
+            .iter()
+            .zip(b_i.iter())
+            .map(|(a, b)| a ^ b)
+            .collect::<Vec<_>>();
+        b_i = H::new()
+            .chain_update(xored)
+            .chain_update([i as u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    Ok(uniform_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_381::{Fq, Fq2};
+    use ark_ff::MontFp;
+    use sha2::Sha512;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Test vectors from RFC 9380, Appendix K.1.
+    #[test]
+    fn test_expand_message_xmd_rfc_vectors() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        let vectors: [(&[u8], usize, &str); 4] = [
+            (
+                b"",
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"",
+                0x80,
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced",
+            ),
+            (
+                b"abcdef0123456789",
+                0x80,
+                "ef904a29bffc4cf9ee82832451c946ac3c8f8058ae97d8d629831a74c6572bd9ebd0df635cd1f208e2038e760c4994984ce73f0d55ea9f22af83ba4734569d4bc95e18350f740c07eef653cbb9f87910d833751825f0ebefa1abe5420bb52be14cf489b37fe1a72f7de2d10be453b2c9d9eb20c7e3f6edc5a60629178d9478df",
+            ),
+        ];
+        for (msg, len_in_bytes, expected) in vectors {
+            assert_eq!(
+                expand_message_xmd::<Sha256>(msg, dst, len_in_bytes).unwrap(),
+                from_hex(expected)
+            );
+        }
+
+        // A DST longer than 255 bytes is hashed first.
+        let long_dst = [
+            &b"QUUX-V01-CS02-with-expander-SHA256-128-long-DST-"[..],
+            &[b'1'; 208],
+        ]
+        .concat();
+        assert_eq!(
+            expand_message_xmd::<Sha256>(b"", &long_dst, 0x20).unwrap(),
+            from_hex("e8dc0c8b686b7ef2074086fbdd2f30e3f8bfbd3bdf177f73f04b97ce618a3ed3")
+        );
+        let hashed_dst =
+            from_hex("412717974da474d0f8c420f320ff81e8432adb7c927d9bd082b4fb4d16c0a236");
+        assert_eq!(
+            expand_message_xmd::<Sha256>(b"abc", &long_dst, 0x20).unwrap(),
+            expand_message_xmd::<Sha256>(b"abc", &hashed_dst, 0x20).unwrap()
+        );
+
+        // SHA-512, from RFC 9380, Appendix K.3.
+        let dst = b"QUUX-V01-CS02-with-expander-SHA512-256";
+        assert_eq!(
+            expand_message_xmd::<Sha512>(b"", dst, 0x20).unwrap(),
+            from_hex("6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba")
+        );
+        assert_eq!(
+            expand_message_xmd::<Sha512>(b"abc", dst, 0x20).unwrap(),
+            from_hex("0da749f12fbe5483eb066a5f595055679b976e93abe9be6f0f6318bce7aca8dc")
+        );
+
+        // ell is at most 255.
+        assert!(expand_message_xmd::<Sha256>(b"", dst, 255 * 32).is_ok());
+        assert!(matches!(
+            expand_message_xmd::<Sha256>(b"", dst, 255 * 32 + 1),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    // Test vectors from RFC 9380, Appendix J.9.1.
+    #[test]
+    fn test_hash_to_field_rfc_vectors() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let vectors: [(&[u8], [Fq; 2]); 2] = [
+            (
+                b"",
+                [
+                    MontFp!("1790030616568561980207134218344899338736900885118493183248255875682123737756800213955590674957414534085508415116879"),
+                    MontFp!("247470258331762152370823329280394074170045058453263356372873997375166908584899100434893060702108665825589810322121"),
+                ],
+            ),
+            (
+                b"abc",
+                [
+                    MontFp!("2088728490498894818688784437928579501848367107744050576780266498473771518428420173373487118890161663886009635645777"),
+                    MontFp!("32138924938310862093169606408734331410171587925844216752733293543601988453843327878077294514665889481436558332217"),
+                ],
+            ),
+        ];
+        for (msg, expected) in vectors {
+            assert_eq!(hash_to_field::<Fq>(msg, dst, 2).unwrap(), expected);
+        }
+        assert!(hash_to_field::<Fq>(b"", dst, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hash_to_field_bytes_per_elem() {
+        // L = 64 bytes per element of the BLS12-381 base field, as in the
+        // BLS12381G1 suites of RFC 9380, Section 8.8.1, and twice as many for
+        // its quadratic extension.
+        assert_eq!(len_per_base_elem::<Fq>(), 64);
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let count = 3;
+        let uniform_bytes = expand_message_xmd::<Sha256>(b"abc", dst, count * 64).unwrap();
+        let expected = uniform_bytes
+            .chunks(64)
+            .map(Fq::from_be_bytes_mod_order)
+            .collect::<Vec<_>>();
+        assert_eq!(hash_to_field::<Fq>(b"abc", dst, count).unwrap(), expected);
+
+        let uniform_bytes = expand_message_xmd::<Sha256>(b"abc", dst, count * 128).unwrap();
+        let expected = uniform_bytes
+            .chunks(128)
+            .map(|bytes| {
+                Fq2::new(
+                    Fq::from_be_bytes_mod_order(&bytes[..64]),
+                    Fq::from_be_bytes_mod_order(&bytes[64..]),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(hash_to_field::<Fq2>(b"abc", dst, count).unwrap(), expected);
+
+        // The fields of the other curves, with moduli of 254 to 377 bits.
+        assert_eq!(len_per_base_elem::<ark_bn254::Fq>(), 48);
+        assert_eq!(len_per_base_elem::<ark_bls12_377::Fq>(), 64);
+        assert_eq!(len_per_base_elem::<ark_ed_on_bls12_381::Fq>(), 48);
+    }
+}
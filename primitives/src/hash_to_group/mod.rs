@@ -6,6 +6,79 @@
 
 //! Module for hash to various elliptic curve groups
 
+mod hash_to_field;
+mod short_weierstrass;
 mod twisted_edwards;
 
+pub use hash_to_field::{expand_message_xmd, hash_to_field, hash_to_field_with_hasher};
+pub use short_weierstrass::SWHashToGroup;
 pub use twisted_edwards::TEHashToGroup;
+
+use crate::errors::PrimitivesError;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_std::string::ToString;
+
+/// `is_square` of RFC 9380, which counts zero as a square.
+fn is_square<F: Field>(x: &F) -> bool {
+    !x.legendre().is_qnr()
+}
+
+/// `sgn0` of RFC 9380, Section 4.1.
+fn sgn0<F: Field>(x: &F) -> bool {
+    let mut sign = false;
+    let mut zero = true;
+    for x_i in x.to_base_prime_field_elements() {
+        sign |= zero && x_i.into_bigint().is_odd();
+        zero &= x_i.is_zero();
+    }
+    sign
+}
+
+/// The Elligator 2 map of
+/// [RFC 9380, Section 6.7.1](https://www.rfc-editor.org/rfc/rfc9380.html#name-elligator-2-method)
+/// to the Montgomery curve `K * t^2 = s^3 + J * s^2 + s`, returning the
+/// coordinates `(s, t)` of the image of `u`.
+fn elligator2_map_to_montgomery<F: Field>(j: F, k: F, u: F) -> Result<(F, F), PrimitivesError> {
+    let z = find_elligator2_z::<F>();
+
+    let c1 = j / k;
+    let c2 = k.square().inverse().ok_or_else(elligator2_error)?;
+    let rhs = |x: F| ((x + c1) * x + c2) * x;
+
+    let mut x1 = -c1
+        * (F::one() + z * u.square())
+            .inverse()
+            .unwrap_or_else(F::zero);
+    if x1.is_zero() {
+        x1 = -c1;
+    }
+    let gx1 = rhs(x1);
+    let x2 = -x1 - c1;
+    let gx2 = rhs(x2);
+    let (x, y) = if is_square(&gx1) {
+        let y = gx1.sqrt().ok_or_else(elligator2_error)?;
+        (x1, if sgn0(&y) { y } else { -y })
+    } else {
+        let y = gx2.sqrt().ok_or_else(elligator2_error)?;
+        (x2, if sgn0(&y) { -y } else { y })
+    };
+    Ok((x * k, y * k))
+}
+
+/// Find the constant `Z` of the Elligator 2 map, following
+/// `find_z_elligator2` of RFC 9380, Appendix H.3.
+fn find_elligator2_z<F: Field>() -> F {
+    let mut ctr = F::one();
+    loop {
+        for z in [ctr, -ctr] {
+            if !is_square(&z) {
+                return z;
+            }
+        }
+        ctr += F::one();
+    }
+}
+
+fn elligator2_error() -> PrimitivesError {
+    PrimitivesError::InternalError("Elligator 2 map failed".to_string())
+}
@@ -0,0 +1,731 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Hash to Elliptic Curve implementation of <https://datatracker.ietf.org/doc/draft-irtf-cfrg-hash-to-curve/>
+//!
+//! BLS12-381 G1 and G2 use the simplified SWU map of their RFC 9380 suites.
+//! Curves with a Montgomery form, such as the short Weierstrass model of
+//! Jubjub, can select the Elligator 2 map instead, as described in
+//! [`SWHashToGroup::map_to_curve`]. Every other curve, including
+//! BN254 G1 and BLS12-377 G1, uses the Shallue-van de Woestijne map, which
+//! applies to any short Weierstrass curve. None of these paths samples by
+//! rejection, but they are not constant time either: the square roots,
+//! inversions and branches they take may depend on the message.
+
+use super::{
+    elligator2_map_to_montgomery, hash_to_field, hash_to_field_with_hasher, is_square, sgn0,
+};
+use crate::errors::PrimitivesError;
+use ark_ec::{
+    hashing::{
+        curve_maps::wb::{WBConfig, WBMap},
+        map_to_curve_hasher::MapToCurve,
+        HashToCurveError,
+    },
+    short_weierstrass::{Affine, Projective, SWCurveConfig},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{Field, One, Zero};
+use ark_std::{format, string::ToString, vec::Vec};
+use digest::{core_api::BlockSizeUser, Digest};
+use jf_utils::par_utils::parallelizable_slice_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use sha2::Sha256;
+
+/// Trait definition and default implementation for hash to group functions for
+/// Short Weierstrass Curves.
+pub trait SWHashToGroup: SWCurveConfig + Sized {
+    /// Hash to Group point, using sha2-256 function
+    /// hashing to G1 point of `C: ProjectiveCurve`.
+    fn hash_to_group<B: AsRef<[u8]>>(
+        data: B,
+        cs_id: B,
+    ) -> Result<Projective<Self>, PrimitivesError> {
+        Self::hash_to_group_with_hasher::<Sha256, B>(data, cs_id)
+    }
+
+    /// Same as [`Self::hash_to_group`] except that the hash function `H` is
+    /// used instead of sha2-256.
+    // Follows the `hash_to_curve` construction of RFC 9380, using `cs_id` as
+    // the domain separation tag: two field elements are hashed and mapped with
+    // `Self::map_to_curve`, and their sum is cleared of its cofactor.
+    fn hash_to_group_with_hasher<H: Digest + BlockSizeUser, B: AsRef<[u8]>>(
+        data: B,
+        cs_id: B,
+    ) -> Result<Projective<Self>, PrimitivesError> {
+        let u = hash_to_field_with_hasher::<Self::BaseField, H>(data.as_ref(), cs_id.as_ref(), 2)?;
+        let q0 = Self::map_to_curve(u[0])?;
+        let q1 = Self::map_to_curve(u[1])?;
+        let p = <Self as SWHashToGroup>::clear_cofactor(q0 + q1);
+        debug_assert!(Self::is_in_correct_subgroup(&p));
+        Ok(p)
+    }
+
+    /// Map a field element to a curve point, the `map_to_curve` step of
+    /// RFC 9380. The output is on the curve but not necessarily in the prime
+    /// order subgroup.
+    ///
+    /// The default implementation is [`Self::svdw_map_to_curve`], which works
+    /// for any curve but is the slowest map. Implementations may select a
+    /// faster one by overriding this method:
+    /// - [`Self::elligator2_map_to_curve`] for curves with a
+    ///   [`Self::montgomery_form`], i.e. with a point of order 2;
+    /// - the simplified SWU map of ark-ec's `WBMap` for curves with an
+    ///   isogenous curve implementing `WBConfig`, as BLS12-381 G1 and G2 do.
+    fn map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        Self::svdw_map_to_curve(u)
+    }
+
+    /// The Shallue-van de Woestijne map of
+    /// [RFC 9380, Section 6.6.1](https://www.rfc-editor.org/rfc/rfc9380.html#name-shallue-van-de-woestijne-me),
+    /// with the constant [`Self::svdw_z`].
+    fn svdw_map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        svdw_map_to_curve::<Self>(u, Self::svdw_z())
+    }
+
+    /// The constant `Z` of [`Self::svdw_map_to_curve`]. The default
+    /// implementation searches for it as `find_z_svdw` of RFC 9380, Appendix
+    /// H.1 does, on every call, so curves relying on that map should override
+    /// it with the precomputed value.
+    fn svdw_z() -> Self::BaseField {
+        find_svdw_z::<Self>()
+    }
+
+    /// The Elligator 2 map of
+    /// [RFC 9380, Section 6.7.1](https://www.rfc-editor.org/rfc/rfc9380.html#name-elligator-2-method)
+    /// to the Montgomery form `K * t^2 = s^3 + J * s^2 + s` of the curve given
+    /// by [`Self::montgomery_form`], followed by the isomorphism
+    /// `(x, y) = ((s + J / 3) / K, t / K)` to the short Weierstrass model.
+    /// * `returns` - Err() if the curve has no Montgomery form
+    fn elligator2_map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        let (j, k) = Self::montgomery_form().ok_or_else(|| {
+            PrimitivesError::ParameterError("the curve has no Montgomery form".to_string())
+        })?;
+        let (s, t) = elligator2_map_to_montgomery(j, k, u)?;
+        let k_inv = k.inverse().ok_or_else(|| {
+            PrimitivesError::ParameterError("invalid Montgomery coefficient K".to_string())
+        })?;
+        let x = (s + j / Self::BaseField::from(3u64)) * k_inv;
+        let y = t * k_inv;
+        Ok(Affine::new_unchecked(x, y))
+    }
+
+    /// Coefficients `(J, K)` of a Montgomery curve `K * t^2 = s^3 + J * s^2 +
+    /// s` isomorphic to this curve, or `None` if there is none. Only curves
+    /// with a point of order 2 have one. Such curves may select
+    /// [`Self::elligator2_map_to_curve`], which is simpler than the SWU maps,
+    /// as their [`Self::map_to_curve`].
+    fn montgomery_form() -> Option<(Self::BaseField, Self::BaseField)> {
+        None
+    }
+
+    /// Map a curve point into the prime order subgroup, the `clear_cofactor`
+    /// step of RFC 9380.
+    fn clear_cofactor(p: Projective<Self>) -> Projective<Self> {
+        p.into_affine().clear_cofactor().into()
+    }
+
+    /// Check that `p` is on the curve and in the prime order subgroup, as
+    /// every output of [`Self::hash_to_group`] must be. Points returned by
+    /// [`Self::map_to_curve`] generally fail it until their cofactor is
+    /// cleared.
+    ///
+    /// For curves with cofactor 1, such as BN254 G1, every point of the curve
+    /// is in the subgroup, so only the curve equation is checked.
+    fn is_in_correct_subgroup(p: &Projective<Self>) -> bool {
+        let p = p.into_affine();
+        p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()
+    }
+
+    /// Hash each message in `msgs` to a group point with
+    /// [`Self::hash_to_group`], under the same `cs_id`. The messages are
+    /// processed in parallel if the `parallel` feature is on, and the output
+    /// follows the order of `msgs`.
+    fn batch_hash_to_group<B: AsRef<[u8]> + Sync>(
+        msgs: &[B],
+        cs_id: B,
+    ) -> Result<Vec<Projective<Self>>, PrimitivesError> {
+        parallelizable_slice_iter(msgs)
+            .map(|msg| Self::hash_to_group(msg.as_ref(), cs_id.as_ref()))
+            .collect()
+    }
+
+    /// Derive `n` independent generators of the prime order subgroup, for
+    /// which nobody knows the discrete logarithms between them. The `i`-th
+    /// generator is the hash of `i` as a little endian `u64` under `dst`, so
+    /// the output does not depend on the platform, and the generators derived
+    /// for a smaller `n` are a prefix of those for a larger one.
+    fn derive_generators(dst: &[u8], n: usize) -> Result<Vec<Projective<Self>>, PrimitivesError> {
+        let indices = (0..n as u64).map(|i| i.to_le_bytes()).collect::<Vec<_>>();
+        let msgs = indices.iter().map(|i| &i[..]).collect::<Vec<_>>();
+        Self::batch_hash_to_group(&msgs, dst)
+    }
+
+    /// Encode to Group point with the `encode_to_curve` construction of
+    /// [RFC 9380, Section 3](https://www.rfc-editor.org/rfc/rfc9380.html#name-encoding-byte-strings-to-el):
+    /// a single field element is hashed with sha2-256 and mapped to the
+    /// curve, then cleared of its cofactor. `cs_id` is used as the domain
+    /// separation tag.
+    ///
+    /// This is about twice as fast as [`Self::hash_to_group`], but the output
+    /// is not uniformly distributed: only a fraction of the group is reached
+    /// and the result is distinguishable from a random point. It must not be
+    /// used where the protocol needs a random oracle to the group.
+    fn encode_to_group<B: AsRef<[u8]>>(
+        data: B,
+        cs_id: B,
+    ) -> Result<Projective<Self>, PrimitivesError> {
+        let u = hash_to_field::<Self::BaseField>(data.as_ref(), cs_id.as_ref(), 1)?;
+        let q = Self::map_to_curve(u[0])?;
+        let p = <Self as SWHashToGroup>::clear_cofactor(q.into());
+        debug_assert!(Self::is_in_correct_subgroup(&p));
+        Ok(p)
+    }
+}
+
+/// Hashing to G1 follows the `BLS12381G1_XMD:SHA-256_SSWU_RO_` suite, and
+/// encoding the `BLS12381G1_XMD:SHA-256_SSWU_NU_` suite, of
+/// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380.html).
+impl SWHashToGroup for ark_bls12_381::g1::Config {
+    /// The simplified SWU map to an 11-isogenous curve followed by the
+    /// isogeny.
+    fn map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        sswu_map_to_curve::<Self>(u)
+    }
+}
+
+/// Hashing to G2 follows the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite, and
+/// encoding the `BLS12381G2_XMD:SHA-256_SSWU_NU_` suite, of
+/// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380.html). The cofactor is
+/// cleared with the effective cofactor.
+impl SWHashToGroup for ark_bls12_381::g2::Config {
+    /// The simplified SWU map over `Fq2` to a 3-isogenous curve followed by
+    /// the isogeny.
+    fn map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        sswu_map_to_curve::<Self>(u)
+    }
+}
+
+impl SWHashToGroup for ark_bls12_377::g1::Config {
+    fn svdw_z() -> Self::BaseField {
+        Self::BaseField::one()
+    }
+}
+
+/// The short Weierstrass model of Jubjub, which is isomorphic to the
+/// Montgomery curve `-40964 * t^2 = s^3 + 40962 * s^2 + s`, also birationally
+/// equivalent to the twisted Edwards model.
+impl SWHashToGroup for ark_ed_on_bls12_381::JubjubConfig {
+    /// The Elligator 2 map to the Montgomery curve followed by the
+    /// isomorphism.
+    fn map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        Self::elligator2_map_to_curve(u)
+    }
+
+    fn montgomery_form() -> Option<(Self::BaseField, Self::BaseField)> {
+        Some((
+            Self::BaseField::from(40962u64),
+            -Self::BaseField::from(40964u64),
+        ))
+    }
+}
+
+impl SWHashToGroup for ark_bn254::g1::Config {
+    fn svdw_z() -> Self::BaseField {
+        Self::BaseField::one()
+    }
+}
+
+/// The simplified SWU map to an isogenous curve followed by the isogeny, see
+/// [RFC 9380, Section 6.6.3](https://www.rfc-editor.org/rfc/rfc9380.html#name-simplified-swu-for-ab-0).
+fn sswu_map_to_curve<P: WBConfig>(u: P::BaseField) -> Result<Affine<P>, PrimitivesError> {
+    WBMap::<P>::new()
+        .and_then(|map| map.map_to_curve(u))
+        .map_err(hash_to_curve_error)
+}
+
+/// The Shallue-van de Woestijne map of
+/// [RFC 9380, Section 6.6.1](https://www.rfc-editor.org/rfc/rfc9380.html#name-shallue-van-de-woestijne-me),
+/// following the straight-line procedure of Appendix F.1. The three candidate
+/// x-coordinates are always computed and the point is selected among them, so
+/// no input is retried. `z` is the constant found by [`find_svdw_z`].
+fn svdw_map_to_curve<P: SWCurveConfig>(
+    u: P::BaseField,
+    z: P::BaseField,
+) -> Result<Affine<P>, PrimitivesError> {
+    let gz = curve_rhs::<P>(z);
+    let tz = P::BaseField::from(3u64) * z.square() + P::BaseField::from(4u64) * P::COEFF_A;
+
+    let c1 = gz;
+    let c2 = -z / P::BaseField::from(2u64);
+    let mut c3 = (-gz * tz).sqrt().ok_or_else(svdw_error)?;
+    if sgn0(&c3) {
+        c3 = -c3;
+    }
+    let c4 = -P::BaseField::from(4u64) * gz / tz;
+
+    let mut tv1 = u.square() * c1;
+    let tv2 = P::BaseField::one() + tv1;
+    tv1 = P::BaseField::one() - tv1;
+    let tv3 = (tv1 * tv2).inverse().unwrap_or_else(P::BaseField::zero);
+    let tv4 = u * tv1 * tv3 * c3;
+    let x1 = c2 - tv4;
+    let e1 = is_square(&curve_rhs::<P>(x1));
+    let x2 = c2 + tv4;
+    let e2 = is_square(&curve_rhs::<P>(x2)) && !e1;
+    let x3 = (tv2.square() * tv3).square() * c4 + z;
+    let x = if e1 {
+        x1
+    } else if e2 {
+        x2
+    } else {
+        x3
+    };
+    let mut y = curve_rhs::<P>(x).sqrt().ok_or_else(svdw_error)?;
+    if sgn0(&u) != sgn0(&y) {
+        y = -y;
+    }
+    Ok(Affine::new_unchecked(x, y))
+}
+
+/// Find the constant `Z` of the Shallue-van de Woestijne map, following
+/// `find_z_svdw` of RFC 9380, Appendix H.1. The search only depends on the
+/// curve constants.
+fn find_svdw_z<P: SWCurveConfig>() -> P::BaseField {
+    let two = P::BaseField::from(2u64);
+    let three = P::BaseField::from(3u64);
+    let four = P::BaseField::from(4u64);
+    let mut ctr = P::BaseField::one();
+    loop {
+        for z in [ctr, -ctr] {
+            let gz = curve_rhs::<P>(z);
+            if gz.is_zero() {
+                continue;
+            }
+            let hz = -(three * z.square() + four * P::COEFF_A) / (four * gz);
+            if hz.is_zero() || !is_square(&hz) {
+                continue;
+            }
+            if is_square(&gz) || is_square(&curve_rhs::<P>(-z / two)) {
+                return z;
+            }
+        }
+        ctr += P::BaseField::one();
+    }
+}
+
+/// `x^3 + A * x + B`
+fn curve_rhs<P: SWCurveConfig>(x: P::BaseField) -> P::BaseField {
+    (x.square() + P::COEFF_A) * x + P::COEFF_B
+}
+
+fn svdw_error() -> PrimitivesError {
+    PrimitivesError::InternalError("Shallue-van de Woestijne map failed".to_string())
+}
+
+fn hash_to_curve_error(e: HashToCurveError) -> PrimitivesError {
+    PrimitivesError::InternalError(format!("Hash to curve failed: {e:?}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_vectors::{self, HashToCurveSuite};
+    use ark_bls12_381::{g1::Config as G1Config, g2::Config as G2Config, Fq2, G1Affine, G2Affine};
+    use ark_ff::MontFp;
+    use ark_std::{vec, UniformRand};
+    use sha2::Sha512;
+
+    #[test]
+    fn test_hash_to_group() {
+        test_hash_to_group_helper::<ark_bls12_381::g1::Config>();
+        test_hash_to_group_helper::<ark_bls12_381::g2::Config>();
+        test_hash_to_group_helper::<ark_bls12_377::g1::Config>();
+        test_hash_to_group_helper::<ark_bn254::g1::Config>();
+        test_hash_to_group_helper::<ark_ed_on_bls12_381::JubjubConfig>();
+    }
+
+    fn test_hash_to_group_helper<P: SWHashToGroup>() {
+        let data = vec![1u8, 2, 3, 4, 5];
+        let g1 =
+            <P as SWHashToGroup>::hash_to_group::<&[u8]>(data.as_ref(), "BLS Signature".as_ref())
+                .unwrap()
+                .into_affine();
+        assert!(g1.is_on_curve());
+        assert!(g1.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(
+            g1,
+            <P as SWHashToGroup>::hash_to_group::<&[u8]>(data.as_ref(), "BLS Signature".as_ref())
+                .unwrap()
+                .into_affine()
+        );
+    }
+
+    #[test]
+    fn test_hash_to_group_with_hasher() {
+        test_hash_to_group_with_hasher_helper::<ark_bls12_381::g1::Config>();
+        test_hash_to_group_with_hasher_helper::<ark_bls12_381::g2::Config>();
+        test_hash_to_group_with_hasher_helper::<ark_bls12_377::g1::Config>();
+        test_hash_to_group_with_hasher_helper::<ark_bn254::g1::Config>();
+    }
+
+    fn test_hash_to_group_with_hasher_helper<P: SWHashToGroup>() {
+        let data: &[u8] = b"message";
+        let cs_id: &[u8] = b"BLS Signature";
+        let p = P::hash_to_group_with_hasher::<Sha512, _>(data, cs_id)
+            .unwrap()
+            .into_affine();
+        assert!(p.is_on_curve());
+        assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        assert_ne!(p, P::hash_to_group(data, cs_id).unwrap().into_affine());
+        assert_eq!(
+            P::hash_to_group(data, cs_id).unwrap(),
+            P::hash_to_group_with_hasher::<Sha256, _>(data, cs_id).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_batch_hash_to_group() {
+        test_batch_hash_to_group_helper::<ark_bls12_381::g1::Config>();
+        test_batch_hash_to_group_helper::<ark_bls12_381::g2::Config>();
+        test_batch_hash_to_group_helper::<ark_bls12_377::g1::Config>();
+        test_batch_hash_to_group_helper::<ark_bn254::g1::Config>();
+    }
+
+    fn test_batch_hash_to_group_helper<P: SWHashToGroup>() {
+        let cs_id: &[u8] = b"BLS Signature";
+        let msgs: Vec<&[u8]> = vec![b"", b"abc", b"message", b"abc"];
+        let points = P::batch_hash_to_group(&msgs, cs_id).unwrap();
+        assert_eq!(points.len(), msgs.len());
+        for (msg, p) in msgs.iter().zip(points.iter()) {
+            assert_eq!(*p, P::hash_to_group(*msg, cs_id).unwrap());
+        }
+        assert!(P::batch_hash_to_group(&[], cs_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_derive_generators() {
+        test_derive_generators_helper::<ark_bls12_381::g1::Config>();
+        test_derive_generators_helper::<ark_bls12_381::g2::Config>();
+        test_derive_generators_helper::<ark_bls12_377::g1::Config>();
+        test_derive_generators_helper::<ark_bn254::g1::Config>();
+    }
+
+    fn test_derive_generators_helper<P: SWHashToGroup>() {
+        let dst: &[u8] = b"Generators";
+        let n = 16;
+        let generators = P::derive_generators(dst, n).unwrap();
+        assert_eq!(generators.len(), n);
+        assert_eq!(generators, P::derive_generators(dst, n).unwrap());
+        assert_eq!(generators[..4], P::derive_generators(dst, 4).unwrap()[..]);
+        assert!(P::derive_generators(dst, 0).unwrap().is_empty());
+
+        // The derivation is pinned to the little endian encoding of the index.
+        assert_eq!(
+            generators[1],
+            P::hash_to_group(&[1u8, 0, 0, 0, 0, 0, 0, 0][..], dst).unwrap()
+        );
+
+        let generators = Projective::<P>::normalize_batch(&generators);
+        for (i, g) in generators.iter().enumerate() {
+            assert!(g.is_on_curve());
+            assert!(g.is_in_correct_subgroup_assuming_on_curve());
+            assert!(!g.is_zero());
+            assert!(generators[..i].iter().all(|h| h != g));
+        }
+
+        // Another tag gives other generators.
+        let other = P::derive_generators(b"Other generators", n).unwrap();
+        assert!(other.iter().all(|g| !generators.contains(&g.into_affine())));
+    }
+
+    #[test]
+    fn test_elligator2_map_to_curve() {
+        type P = ark_ed_on_bls12_381::JubjubConfig;
+        type F = ark_ed_on_bls12_381::Fq;
+
+        // The Montgomery form is isomorphic to the curve: A = (3 - J^2) /
+        // (3 * K^2) and B = (2 * J^3 - 9 * J) / (27 * K^3).
+        let (j, k) = P::montgomery_form().unwrap();
+        assert_eq!(
+            <P as SWCurveConfig>::COEFF_A,
+            (F::from(3u64) - j.square()) / (F::from(3u64) * k.square())
+        );
+        assert_eq!(
+            <P as SWCurveConfig>::COEFF_B,
+            (F::from(2u64) * j.square() * j - F::from(9u64) * j)
+                / (F::from(27u64) * k.square() * k)
+        );
+
+        let mut rng = jf_utils::test_rng();
+        let mut inputs = vec![F::zero(), F::one(), -F::one()];
+        inputs.extend((0..20).map(|_| F::rand(&mut rng)));
+        for u in inputs {
+            let p = <P as SWHashToGroup>::map_to_curve(u).unwrap();
+            assert_eq!(p, <P as SWHashToGroup>::elligator2_map_to_curve(u).unwrap());
+            assert!(p.is_on_curve());
+            let p = <P as SWHashToGroup>::clear_cofactor(p.into());
+            assert!(<P as SWHashToGroup>::is_in_correct_subgroup(&p));
+        }
+
+        // Curves without a Montgomery form cannot select it.
+        assert!(ark_bn254::g1::Config::montgomery_form().is_none());
+        assert!(matches!(
+            <ark_bn254::g1::Config as SWHashToGroup>::elligator2_map_to_curve(ark_bn254::Fq::one()),
+            Err(PrimitivesError::ParameterError(_))
+        ));
+    }
+
+    #[test]
+    fn test_svdw_map_to_curve() {
+        test_svdw_map_to_curve_helper::<ark_bn254::g1::Config>();
+        test_svdw_map_to_curve_helper::<ark_bls12_377::g1::Config>();
+        test_svdw_map_to_curve_helper::<ark_bls12_381::g1::Config>();
+        // The precomputed Z = 1 for both BN254 and BLS12-377, where A = 0.
+        assert_eq!(
+            find_svdw_z::<ark_bn254::g1::Config>(),
+            <ark_bn254::g1::Config as SWHashToGroup>::svdw_z()
+        );
+        assert_eq!(
+            find_svdw_z::<ark_bls12_377::g1::Config>(),
+            <ark_bls12_377::g1::Config as SWHashToGroup>::svdw_z()
+        );
+    }
+
+    fn test_svdw_map_to_curve_helper<P: SWCurveConfig>() {
+        let mut rng = jf_utils::test_rng();
+        let mut inputs = vec![
+            P::BaseField::zero(),
+            P::BaseField::one(),
+            -P::BaseField::one(),
+        ];
+        inputs.extend((0..20).map(|_| P::BaseField::rand(&mut rng)));
+        let z = find_svdw_z::<P>();
+        for u in inputs {
+            let p = svdw_map_to_curve::<P>(u, z).unwrap();
+            assert!(p.is_on_curve());
+            assert_eq!(sgn0(&u), sgn0(&p.y));
+            let p = p.clear_cofactor();
+            assert!(p.is_on_curve());
+            assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        }
+    }
+
+    #[test]
+    fn test_is_in_correct_subgroup() {
+        test_is_in_correct_subgroup_helper::<ark_bls12_381::g1::Config>(false);
+        test_is_in_correct_subgroup_helper::<ark_bls12_381::g2::Config>(false);
+        test_is_in_correct_subgroup_helper::<ark_bls12_377::g1::Config>(false);
+        // With cofactor 1, every point of the curve is in the subgroup.
+        test_is_in_correct_subgroup_helper::<ark_bn254::g1::Config>(true);
+    }
+
+    fn test_is_in_correct_subgroup_helper<P: SWHashToGroup>(cofactor_is_one: bool) {
+        let data: &[u8] = b"message";
+        let cs_id: &[u8] = b"BLS Signature";
+        let p = P::hash_to_group(data, cs_id).unwrap();
+        assert!(P::is_in_correct_subgroup(&p));
+
+        // The output of the map before its cofactor is cleared.
+        let u = hash_to_field::<P::BaseField>(data, cs_id, 1).unwrap();
+        let q = P::map_to_curve(u[0]).unwrap();
+        assert!(q.is_on_curve());
+        assert_eq!(P::is_in_correct_subgroup(&q.into()), cofactor_is_one);
+        assert!(P::is_in_correct_subgroup(
+            &<P as SWHashToGroup>::clear_cofactor(q.into())
+        ));
+
+        // Off the curve.
+        let p = p.into_affine();
+        let off_curve = Affine::<P>::new_unchecked(p.x, p.y + P::BaseField::one());
+        assert!(!P::is_in_correct_subgroup(&off_curve.into()));
+    }
+
+    #[test]
+    fn test_encode_to_group() {
+        test_encode_to_group_helper::<ark_bls12_381::g1::Config>();
+        test_encode_to_group_helper::<ark_bls12_381::g2::Config>();
+        test_encode_to_group_helper::<ark_bls12_377::g1::Config>();
+        test_encode_to_group_helper::<ark_bn254::g1::Config>();
+    }
+
+    fn test_encode_to_group_helper<P: SWHashToGroup>() {
+        let data: &[u8] = b"message";
+        let cs_id: &[u8] = b"BLS Signature";
+        let p = P::encode_to_group(data, cs_id).unwrap().into_affine();
+        assert!(p.is_on_curve());
+        assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(p, P::encode_to_group(data, cs_id).unwrap().into_affine());
+        assert_ne!(p, P::hash_to_group(data, cs_id).unwrap().into_affine());
+    }
+
+    // Intermediate values of the test vector for the empty message in
+    // RFC 9380, Appendix J.9.1.
+    #[test]
+    fn test_bls12_381_g1_rfc_stages() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let u = hash_to_field::<ark_bls12_381::Fq>(b"", dst, 2).unwrap();
+        let q0 = G1Config::map_to_curve(u[0]).unwrap();
+        let q1 = G1Config::map_to_curve(u[1]).unwrap();
+        assert_eq!(
+            q0,
+            G1Affine::new_unchecked(
+                MontFp!("2715020688846377217335438104604904715810987801342572961948795328294467090945535855005782938414453837381763209848574"),
+                MontFp!("2296064027963677462102732806416784924633946674250659271331705244237479823406569751054138764013769902705787390693815"),
+            )
+        );
+        assert_eq!(
+            q1,
+            G1Affine::new_unchecked(
+                MontFp!("3386118521359795452491592112474903013403563705976979413906657246155086738547458719845510093968336733362278563786652"),
+                MontFp!("2084873627170452397466071646774067343162430120589753601649788355161907382097748603047706952090839756741323773801310"),
+            )
+        );
+        assert!(q0.is_on_curve());
+        assert!(!q0.is_in_correct_subgroup_assuming_on_curve());
+
+        let p = <G1Config as SWHashToGroup>::clear_cofactor(q0 + q1).into_affine();
+        assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(
+            p,
+            G1Config::hash_to_group::<&[u8]>(b"", dst)
+                .unwrap()
+                .into_affine()
+        );
+        assert_eq!(
+            p,
+            G1Affine::new(
+                MontFp!("794311575721400831362957049303781044852006323422624111893352859557450008308620925451441746926395141598720928151969"),
+                MontFp!("1343412193624222137939591894701031123123641958980729764240763391191550653712890272928110356903136085217047453540965"),
+            )
+        );
+    }
+
+    // Test vectors from RFC 9380, Appendix J.9.2.
+    #[test]
+    fn test_bls12_381_g1_encode_rfc_vectors() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_NU_";
+        let vectors: [(&[u8], G1Affine); 2] = [
+            (
+                b"",
+                G1Affine::new(
+                    MontFp!("3739458466830661468953341342129567279875926523016247087039101662181447483287198459699466062199208589244562592909498"),
+                    MontFp!("654425034689574044304586010629415640496365028285092416770785394355619662021746513529290942525870066619776789679091"),
+                ),
+            ),
+            (
+                b"abc",
+                G1Affine::new(
+                    MontFp!("91034094472056383692706137559754790750936786811267714512454747683448064940685964827936886876674608837412761677421"),
+                    MontFp!("3262708204940680298593486727902884375314071044036767031306544098760616289716019290723937653785127388882209803980076"),
+                ),
+            ),
+        ];
+        for (msg, expected) in vectors {
+            let p = G1Config::encode_to_group::<&[u8]>(msg, dst).unwrap();
+            assert_eq!(p.into_affine(), expected);
+        }
+    }
+
+    // Test vectors from RFC 9380, Appendix J.9.1.
+    #[test]
+    fn test_bls12_381_g1_rfc_vectors() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let vectors: [(&[u8], G1Affine); 2] = [
+            (
+                b"",
+                G1Affine::new(
+                    MontFp!("794311575721400831362957049303781044852006323422624111893352859557450008308620925451441746926395141598720928151969"),
+                    MontFp!("1343412193624222137939591894701031123123641958980729764240763391191550653712890272928110356903136085217047453540965"),
+                ),
+            ),
+            (
+                b"abc",
+                G1Affine::new(
+                    MontFp!("513738460217615943921285247703448567647875874745567372796164155472383127756567780059136521508428662765965997467907"),
+                    MontFp!("1786897908129645780825838873875416513994655004408749907941296449131605892957529391590865627492442562626458913769565"),
+                ),
+            ),
+        ];
+        for (msg, expected) in vectors {
+            let p = G1Config::hash_to_group::<&[u8]>(msg, dst).unwrap();
+            assert_eq!(p.into_affine(), expected);
+        }
+    }
+
+    // The vector files of the BLS12-381 G1 suites of RFC 9380, Appendix J.9.
+    #[test]
+    fn test_bls12_381_g1_rfc_vector_files() {
+        for file in [
+            "BLS12381G1_XMD_SHA-256_SSWU_RO_.json",
+            "BLS12381G1_XMD_SHA-256_SSWU_NU_.json",
+        ] {
+            let suite: HashToCurveSuite = test_vectors::load(file);
+            assert!(!suite.vectors.is_empty());
+            let dst = suite.dst.as_bytes();
+            for vector in &suite.vectors {
+                let msg = vector.msg.as_bytes();
+                let p = if suite.random_oracle {
+                    G1Config::hash_to_group::<&[u8]>(msg, dst).unwrap()
+                } else {
+                    G1Config::encode_to_group::<&[u8]>(msg, dst).unwrap()
+                };
+                assert_eq!(p.into_affine(), vector.output::<G1Config>());
+
+                let u = vector.field_elems::<ark_bls12_381::Fq>();
+                if !u.is_empty() {
+                    assert_eq!(
+                        hash_to_field::<ark_bls12_381::Fq>(msg, dst, u.len()).unwrap(),
+                        u
+                    );
+                }
+                if let Some((q0, q1)) = vector.mapped_points::<G1Config>() {
+                    assert_eq!(G1Config::map_to_curve(u[0]).unwrap(), q0);
+                    assert_eq!(G1Config::map_to_curve(u[1]).unwrap(), q1);
+                }
+            }
+        }
+    }
+
+    // Test vectors from RFC 9380, Appendix J.10.1.
+    #[test]
+    fn test_bls12_381_g2_rfc_vectors() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let vectors: [(&[u8], G2Affine); 2] = [
+            (
+                b"",
+                G2Affine::new(
+                    Fq2::new(
+                        MontFp!("193548053368451749411421515628510806626565736652086807419354395577367693778571452628423727082668900187036482254730"),
+                        MontFp!("891930009643099423308102777951250899694559203647724988361022851024990473423938537113948850338098230396747396259901"),
+                    ),
+                    Fq2::new(
+                        MontFp!("771717272055834152378281705972671257005357145478800908373659404991537354153455452961747174765859335819766715637138"),
+                        MontFp!("2810310118582126634041133454180705304393079139103252956502404531123692847658283858246402311867775854528543237781718"),
+                    ),
+                ),
+            ),
+            (
+                b"abc",
+                G2Affine::new(
+                    Fq2::new(
+                        MontFp!("424958340463073975547762735517193206833255107941790909009827635556634414746056077714431786321247871628515967727334"),
+                        MontFp!("3018679803970127877262826393814472528557413504329194740495363852840690589001358162447917674089074634504498585239512"),
+                    ),
+                    Fq2::new(
+                        MontFp!("3621308185128395459888995526527127556614768604472132176060423302734876099689739385100475320409412954617897892887112"),
+                        MontFp!("102447784096837908713257069727879782642075240724579670654226801345708452018676587771714457671432122751958633012502"),
+                    ),
+                ),
+            ),
+        ];
+        for (msg, expected) in vectors {
+            let p = G2Config::hash_to_group::<&[u8]>(msg, dst).unwrap();
+            assert_eq!(p.into_affine(), expected);
+        }
+    }
+}
@@ -6,65 +6,103 @@
 
 //! Hash to Elliptic Curve implementation of <https://datatracker.ietf.org/doc/draft-irtf-cfrg-hash-to-curve/>
 
+use super::{elligator2_map_to_montgomery, hash_to_field_with_hasher};
 use crate::errors::PrimitivesError;
 use ark_ec::{
     twisted_edwards::{Affine, Projective, TECurveConfig},
-    AffineRepr,
+    AffineRepr, CurveGroup,
 };
-use ark_std::{
-    rand::{Rng, SeedableRng},
-    UniformRand,
-};
-use digest::Digest;
-use rand_chacha::ChaCha20Rng;
+use ark_ff::{Field, One, Zero};
+use digest::{core_api::BlockSizeUser, Digest};
 use sha2::Sha256;
 
 /// Trait definition and default implementation for hash to group functions for
 /// Twisted Edwards Curves.
 pub trait TEHashToGroup: TECurveConfig + Sized {
-    /// Hash to Group point, using sha2-512 function
+    /// Hash to Group point, using sha2-256 function
     /// hashing to G1 point of `C: ProjectiveCurve`.
-    // Default implementation implements a naive solution via rejection sampling.
-    // Slow, and non-constant time.
-    //
-    // For specific curves we may want to overload it with a more efficient
-    // algorithm, such as IETF BLS draft.
     fn hash_to_group<B: AsRef<[u8]>>(
         data: B,
         cs_id: B,
     ) -> Result<Projective<Self>, PrimitivesError> {
-        let mut hasher = Sha256::new();
-        hasher.update([cs_id.as_ref(), data.as_ref()].concat());
-        let mut seed = [0u8; 32];
-        seed.copy_from_slice(hasher.finalize().as_ref());
-        let mut rng = ChaCha20Rng::from_seed(seed);
-        loop {
-            let y = Self::BaseField::rand(&mut rng);
-            // a boolean flag to decide if y is positive or not
-            let x_flag = rng.gen();
-            if let Some(p) = Affine::<Self>::get_point_from_y_unchecked(y, x_flag) {
-                return Ok(p.mul_by_cofactor_to_group());
-            }
-        }
+        Self::hash_to_group_with_hasher::<Sha256, B>(data, cs_id)
+    }
+
+    /// Same as [`Self::hash_to_group`] except that the hash function `H` is
+    /// used instead of sha2-256.
+    // Follows the `hash_to_curve` construction of RFC 9380, using `cs_id` as
+    // the domain separation tag: two field elements are hashed and mapped with
+    // `Self::map_to_curve`, and their sum is cleared of its cofactor.
+    fn hash_to_group_with_hasher<H: Digest + BlockSizeUser, B: AsRef<[u8]>>(
+        data: B,
+        cs_id: B,
+    ) -> Result<Projective<Self>, PrimitivesError> {
+        let u = hash_to_field_with_hasher::<Self::BaseField, H>(data.as_ref(), cs_id.as_ref(), 2)?;
+        let q0 = Self::map_to_curve(u[0])?;
+        let q1 = Self::map_to_curve(u[1])?;
+        let p = <Self as TEHashToGroup>::clear_cofactor(q0 + q1);
+        debug_assert!(Self::is_in_correct_subgroup(&p));
+        Ok(p)
     }
-}
 
-impl TEHashToGroup for ark_ed_on_bls12_377::EdwardsConfig {
-    // TODO:
-    // overload hash to group with the method in
-    // <https://github.com/algorand/pairing-plus/blob/7ec2ae03aae4ba2fc5210810211478171ccededf/src/bls12_381/osswu_map/g1.rs#L47>
+    /// Map a field element to a curve point, the `map_to_curve` step of
+    /// RFC 9380. The output is on the curve but not necessarily in the prime
+    /// order subgroup.
+    // Default implementation uses the Elligator 2 map to the birationally
+    // equivalent Montgomery curve, followed by the rational map back to the
+    // twisted Edwards curve.
+    fn map_to_curve(u: Self::BaseField) -> Result<Affine<Self>, PrimitivesError> {
+        elligator2_map_to_curve::<Self>(u)
+    }
+
+    /// Map a curve point into the prime order subgroup, the `clear_cofactor`
+    /// step of RFC 9380.
+    fn clear_cofactor(p: Projective<Self>) -> Projective<Self> {
+        p.into_affine().clear_cofactor().into()
+    }
+
+    /// Check that `p` is on the curve and in the prime order subgroup, as
+    /// every output of [`Self::hash_to_group`] must be.
+    ///
+    /// Twisted Edwards curves have a cofactor of at least 4, so points
+    /// returned by [`Self::map_to_curve`] generally fail it until
+    /// [`Self::clear_cofactor`] has multiplied them by the cofactor, which
+    /// removes their small order component.
+    fn is_in_correct_subgroup(p: &Projective<Self>) -> bool {
+        let p = p.into_affine();
+        p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()
+    }
 }
 
-impl TEHashToGroup for ark_ed_on_bls12_381::EdwardsConfig {
-    // TODO:
-    // overload hash to group with the method in
-    // <https://github.com/algorand/pairing-plus/blob/7ec2ae03aae4ba2fc5210810211478171ccededf/src/bls12_381/osswu_map/g1.rs#L47>
+impl TEHashToGroup for ark_ed_on_bls12_377::EdwardsConfig {}
+
+impl TEHashToGroup for ark_ed_on_bls12_381::EdwardsConfig {}
+
+/// The Elligator 2 map to the Montgomery curve `K * t^2 = s^3 + J * s^2 + s`
+/// with `J = 2 * (a + d) / (a - d)` and `K = 4 / (a - d)`, followed by the
+/// rational map `(v, w) = (s / t, (s - 1) / (s + 1))` to the twisted Edwards
+/// curve `a * v^2 + w^2 = 1 + d * v^2 * w^2`.
+fn elligator2_map_to_curve<P: TECurveConfig>(
+    u: P::BaseField,
+) -> Result<Affine<P>, PrimitivesError> {
+    let j = P::BaseField::from(2u64) * (P::COEFF_A + P::COEFF_D) / (P::COEFF_A - P::COEFF_D);
+    let k = P::BaseField::from(4u64) / (P::COEFF_A - P::COEFF_D);
+    let (s, t) = elligator2_map_to_montgomery(j, k, u)?;
+
+    let (v, w) = match (t.inverse(), (s + P::BaseField::one()).inverse()) {
+        (Some(t_inv), Some(s_plus_one_inv)) => {
+            (s * t_inv, (s - P::BaseField::one()) * s_plus_one_inv)
+        },
+        _ => (P::BaseField::zero(), P::BaseField::one()),
+    };
+    Ok(Affine::new_unchecked(v, w))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use ark_std::vec;
+    use crate::hash_to_group::find_elligator2_z;
+    use ark_std::{vec, UniformRand};
 
     #[test]
     fn test_hash_to_group() {
@@ -74,8 +112,70 @@ mod test {
 
     fn test_hash_to_group_helper<P: TEHashToGroup>() {
         let data = vec![1u8, 2, 3, 4, 5];
-        let _g1 =
+        let g1 =
+            <P as TEHashToGroup>::hash_to_group::<&[u8]>(data.as_ref(), "Jubjub curves".as_ref())
+                .unwrap()
+                .into_affine();
+        assert!(g1.is_on_curve());
+        assert!(g1.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(
+            g1,
             <P as TEHashToGroup>::hash_to_group::<&[u8]>(data.as_ref(), "Jubjub curves".as_ref())
-                .unwrap();
+                .unwrap()
+                .into_affine()
+        );
+    }
+
+    #[test]
+    fn test_is_in_correct_subgroup() {
+        test_is_in_correct_subgroup_helper::<ark_ed_on_bls12_377::EdwardsConfig>();
+        test_is_in_correct_subgroup_helper::<ark_ed_on_bls12_381::EdwardsConfig>();
+    }
+
+    fn test_is_in_correct_subgroup_helper<P: TEHashToGroup>() {
+        let p = P::hash_to_group::<&[u8]>(b"message", b"Jubjub curves").unwrap();
+        assert!(P::is_in_correct_subgroup(&p));
+
+        // Adding the point of order 2 leaves the prime order subgroup, as if
+        // the cofactor had not been cleared.
+        let order_two = Affine::<P>::new_unchecked(P::BaseField::zero(), -P::BaseField::one());
+        assert!(order_two.is_on_curve());
+        let not_cleared = p + order_two;
+        assert!(!P::is_in_correct_subgroup(&not_cleared));
+        assert!(P::is_in_correct_subgroup(
+            &<P as TEHashToGroup>::clear_cofactor(not_cleared)
+        ));
+
+        // Off the curve.
+        let p = p.into_affine();
+        let off_curve = Affine::<P>::new_unchecked(p.x, p.y + P::BaseField::one());
+        assert!(!P::is_in_correct_subgroup(&off_curve.into()));
+    }
+
+    #[test]
+    fn test_elligator2_map_to_curve() {
+        test_elligator2_map_to_curve_helper::<ark_ed_on_bls12_377::EdwardsConfig>();
+        test_elligator2_map_to_curve_helper::<ark_ed_on_bls12_381::EdwardsConfig>();
+        assert_eq!(
+            find_elligator2_z::<ark_ed_on_bls12_381::Fq>(),
+            ark_ed_on_bls12_381::Fq::from(5u64)
+        );
+    }
+
+    fn test_elligator2_map_to_curve_helper<P: TEHashToGroup>() {
+        let mut rng = jf_utils::test_rng();
+        let mut inputs = vec![
+            P::BaseField::zero(),
+            P::BaseField::one(),
+            -P::BaseField::one(),
+        ];
+        inputs.extend((0..20).map(|_| P::BaseField::rand(&mut rng)));
+        for u in inputs {
+            let p = P::map_to_curve(u).unwrap();
+            assert!(p.is_on_curve());
+            let p = <P as TEHashToGroup>::clear_cofactor(p.into()).into_affine();
+            assert!(p.is_on_curve());
+            assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        }
     }
 }
@@ -39,4 +39,15 @@ pub mod vdf;
 pub mod vid;
 pub mod vrf;
 
+#[cfg(test)]
+pub(crate) mod test_vectors;
 pub(crate) mod utils;
+
+/// Dependencies referred to by the exported macros, so that they expand in
+/// crates which do not depend on them.
+#[doc(hidden)]
+pub mod __reexports {
+    pub use ark_std;
+    pub use num_bigint;
+    pub use num_traits;
+}
@@ -8,38 +8,46 @@
 
 use core::ops::AddAssign;
 
+#[cfg(feature = "parallel")]
+use super::internal::build_tree_internal_parallel;
 use super::{
-    internal::{build_tree_internal, MerkleNode, MerkleProof, MerkleTreeCommitment},
+    internal::{
+        batch_traversal_paths, build_tree_internal, check_range, MerkleBatchProof,
+        MerkleConsistencyProof, MerkleNode, MerkleProof, MerkleRangeProof, MerkleTreeCommitment,
+    },
+    store::{NodeId, NodeStore},
     AppendableMerkleTreeScheme, DigestAlgorithm, Element, ForgetableMerkleTreeScheme, Index,
-    LookupResult, MerkleCommitment, MerkleTreeScheme, NodeValue, ToTraversalPath,
+    LookupResult, MerkleCommitment, MerkleTreeIter, MerkleTreeScheme, NodeValue, ToTraversalPath,
 };
 use crate::{
     errors::{PrimitivesError, VerificationResult},
     impl_forgetable_merkle_tree_scheme, impl_merkle_tree_scheme,
 };
 use ark_std::{
-    borrow::Borrow, boxed::Box, fmt::Debug, marker::PhantomData, string::ToString, vec, vec::Vec,
+    borrow::Borrow, boxed::Box, collections::VecDeque, fmt::Debug, marker::PhantomData,
+    string::ToString, vec, vec::Vec,
 };
 use num_bigint::BigUint;
 use num_traits::pow::pow;
 use serde::{Deserialize, Serialize};
-use typenum::Unsigned;
 
 impl_merkle_tree_scheme!(MerkleTree, build_tree_internal);
 impl_forgetable_merkle_tree_scheme!(MerkleTree);
 
-impl<E, H, I, Arity, T> AppendableMerkleTreeScheme for MerkleTree<E, H, I, Arity, T>
+impl<E, H, I, const ARITY: usize, T> AppendableMerkleTreeScheme for MerkleTree<E, H, I, ARITY, T>
 where
     E: Element,
     H: DigestAlgorithm<E, I, T>,
-    I: Index + From<u64> + AddAssign + ToTraversalPath<Arity>,
-    Arity: Unsigned,
+    I: Index + From<u64> + AddAssign + ToTraversalPath<ARITY>,
     T: NodeValue,
 {
     fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), PrimitivesError> {
         <Self as AppendableMerkleTreeScheme>::extend(self, [elem])
     }
 
+    /// Insert all `elems` in a single pass over the frontier. Every internal
+    /// node touched by the new leaves is only rehashed once, which is much
+    /// cheaper than pushing the elements one by one.
     fn extend(
         &mut self,
         elems: impl IntoIterator<Item = impl Borrow<Self::Element>>,
@@ -47,7 +55,7 @@ where
         let mut iter = elems.into_iter().peekable();
 
         let traversal_path = I::from(self.num_leaves).to_traversal_path(self.height);
-        self.num_leaves += self.root.extend_internal::<H, Arity>(
+        self.num_leaves += self.root.extend_internal::<H, ARITY>(
             self.height,
             &I::from(self.num_leaves),
             &traversal_path,
@@ -55,7 +63,7 @@ where
             &mut iter,
         )?;
         if iter.peek().is_some() {
-            return Err(PrimitivesError::ParameterError(
+            return Err(PrimitivesError::IndexOutOfRange(
                 "Exceed merkle tree capacity".to_string(),
             ));
         }
@@ -63,15 +71,264 @@ where
     }
 }
 
-// TODO(Chengyu): extract a merkle frontier
+impl<E, H, I, const ARITY: usize, T> MerkleTree<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Update the leaf value at a given position, only the nodes along the
+    /// path of this leaf are rehashed.
+    /// * `pos` - zero-based index of the leaf in the tree, must be smaller than
+    ///   `num_leaves()` since the tree is append only
+    /// * `elem` - newly updated element
+    /// * `returns` - Ok(LookupResult::Ok(elem, ())) if the update is success,
+    ///   and `elem` is the original element at the given `pos`.
+    ///   Ok(LookupResult::NotInMemory) if the leaf has been forgotten. Err() if
+    ///   `pos` is not occupied.
+    pub fn update(
+        &mut self,
+        pos: impl Borrow<I>,
+        elem: impl Borrow<E>,
+    ) -> Result<LookupResult<E, (), ()>, PrimitivesError> {
+        let pos = pos.borrow();
+        if *pos >= I::from(self.num_leaves) {
+            return Err(PrimitivesError::IndexOutOfRange(
+                "Cannot update an unoccupied position of an append only merkle tree".to_string(),
+            ));
+        }
+        let traversal_path = pos.to_traversal_path(self.height);
+        match self
+            .root
+            .update_internal::<H, ARITY>(self.height, pos, &traversal_path, elem)?
+        {
+            LookupResult::NotFound(_) => Err(PrimitivesError::InternalError(
+                "Occupied position is empty".to_string(),
+            )),
+            ret => Ok(ret),
+        }
+    }
+
+    /// Returns a proof that this tree truncated to its first `new_size`
+    /// leaves is an append-only extension of the same tree truncated to its
+    /// first `old_size` leaves.
+    /// * `returns` - Err() if `old_size > new_size`, if `new_size` is larger
+    ///   than `num_leaves()`, or if the proof needs forgotten leaves.
+    pub fn consistency_proof(
+        &self,
+        old_size: u64,
+        new_size: u64,
+    ) -> Result<MerkleConsistencyProof<T, ARITY>, PrimitivesError> {
+        if old_size > new_size || new_size > self.num_leaves {
+            return Err(PrimitivesError::ParameterError(
+                "Invalid sizes for a consistency proof".to_string(),
+            ));
+        }
+        let mut proof = vec![];
+        self.root.consistency_proof_internal::<H, ARITY>(
+            self.height,
+            0,
+            old_size,
+            new_size,
+            &mut proof,
+        )?;
+        Ok(MerkleConsistencyProof {
+            old_size,
+            new_size,
+            height: self.height,
+            proof,
+        })
+    }
+
+    /// Refresh a membership proof after new leaves were appended, without
+    /// access to the tree. This has to be called once per appended leaf, in
+    /// the order in which they were appended.
+    /// * `proof` - a membership proof against the root before the append
+    /// * `appended` - membership proof of the appended leaf, against the root
+    ///   right after the append
+    /// * `returns` - Err() if the proofs are not for the same tree.
+    pub fn update_proof(
+        proof: &mut MerkleProof<E, I, T, ARITY>,
+        appended: impl Borrow<MerkleProof<E, I, T, ARITY>>,
+    ) -> Result<(), PrimitivesError> {
+        proof.update_with::<H>(appended.borrow())
+    }
+
+    /// Verify that the tree committed by `new_root` is an append-only
+    /// extension of the tree committed by `old_root`.
+    /// * `proof` - a consistency proof from [`Self::consistency_proof`]
+    /// * `returns` - Ok(Ok(())) if the proof is accepted, Ok(Err(())) if not.
+    ///   Err() if the proof is not well structured, or not for these
+    ///   commitments.
+    pub fn verify_consistency_proof(
+        old_root: impl Borrow<MerkleTreeCommitment<T>>,
+        new_root: impl Borrow<MerkleTreeCommitment<T>>,
+        proof: impl Borrow<MerkleConsistencyProof<T, ARITY>>,
+    ) -> Result<VerificationResult, PrimitivesError> {
+        proof
+            .borrow()
+            .verify_consistency_proof::<E, I, H>(old_root.borrow(), new_root.borrow())
+    }
+}
+
+impl<E, H, I, const ARITY: usize, T> MerkleTree<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + AddAssign + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Keep track of the last `capacity` roots of this tree, starting with
+    /// the current one, so that proofs can be produced against any of them.
+    /// At least the current root is always kept.
+    pub fn with_history(self, capacity: usize) -> MerkleTreeWithHistory<E, H, I, ARITY, T> {
+        let mut history = VecDeque::with_capacity(capacity.max(1));
+        history.push_back(self.commitment());
+        MerkleTreeWithHistory {
+            tree: self,
+            capacity: capacity.max(1),
+            history,
+        }
+    }
+}
+
+/// An append only merkle tree which also retains its last few roots. A client
+/// holding a stale root can still get membership proofs valid against it, as
+/// long as it has not been evicted from the history.
+#[derive(Debug, Clone)]
+pub struct MerkleTreeWithHistory<E, H, I, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index,
+    T: NodeValue,
+{
+    tree: MerkleTree<E, H, I, ARITY, T>,
+    capacity: usize,
+    // Oldest root first.
+    history: VecDeque<MerkleTreeCommitment<T>>,
+}
+
+impl<E, H, I, const ARITY: usize, T> MerkleTreeWithHistory<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + AddAssign + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// The underlying merkle tree, at its latest root
+    pub fn tree(&self) -> &MerkleTree<E, H, I, ARITY, T> {
+        &self.tree
+    }
+
+    /// The retained roots, from the oldest to the latest one
+    pub fn history(&self) -> impl Iterator<Item = &MerkleTreeCommitment<T>> {
+        self.history.iter()
+    }
+
+    /// Insert a new value at the leftmost available slot, and record the new
+    /// root.
+    pub fn push(&mut self, elem: impl Borrow<E>) -> Result<(), PrimitivesError> {
+        self.extend([elem])
+    }
+
+    /// Insert a list of new values at the leftmost available slots, and record
+    /// the root after the whole insertion.
+    pub fn extend(
+        &mut self,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<(), PrimitivesError> {
+        let res = self.tree.extend(elems);
+        // Even a failed insertion may have filled the remaining slots.
+        if self.history.back().map(|com| com.size()) != Some(self.tree.num_leaves()) {
+            if self.history.len() == self.capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.tree.commitment());
+        }
+        res
+    }
+
+    /// Returns the leaf value given a position, with a membership proof
+    /// against a root from the history, i.e. against the tree as it was when
+    /// this root was the latest.
+    /// * `pos` - zero-based index of the leaf in the tree
+    /// * `root` - a root retained in the history
+    /// * `returns` - Err() if `root` is not in the history, e.g. if it has
+    ///   been evicted. Otherwise the same as [`MerkleTreeScheme::lookup`] on
+    ///   the past tree.
+    pub fn lookup_historical(
+        &self,
+        pos: impl Borrow<I>,
+        root: impl Borrow<T>,
+    ) -> Result<LookupResult<&E, MerkleProof<E, I, T, ARITY>, ()>, PrimitivesError> {
+        let (pos, root) = (pos.borrow(), root.borrow());
+        let com = self
+            .history
+            .iter()
+            .rev()
+            .find(|com| com.digest() == *root)
+            .ok_or_else(|| {
+                PrimitivesError::ParameterError(
+                    "Root is not in the history, it may have been evicted".to_string(),
+                )
+            })?;
+        if *pos >= I::from(com.size()) {
+            return Ok(LookupResult::NotFound(()));
+        }
+        let traversal_path = pos.to_traversal_path(self.tree.height);
+        Ok(
+            match self.tree.root.lookup_prefix_internal::<H, ARITY>(
+                self.tree.height,
+                0,
+                com.size(),
+                &traversal_path,
+            )? {
+                LookupResult::Ok(elem, proof) => {
+                    LookupResult::Ok(elem, MerkleProof::new(pos.clone(), proof))
+                },
+                LookupResult::NotInMemory => LookupResult::NotInMemory,
+                LookupResult::NotFound(_) => LookupResult::NotFound(()),
+            },
+        )
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<E, H, I, const ARITY: usize, T> MerkleTree<E, H, I, ARITY, T>
+where
+    E: Element + Send + Sync,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + ToTraversalPath<ARITY> + Send,
+    T: NodeValue + Send,
+{
+    /// Construct a new merkle tree with given height from a data slice. Same
+    /// as [`MerkleTreeScheme::from_elems`], except that the nodes of each level
+    /// are hashed in parallel.
+    pub fn from_elems_parallel(
+        height: usize,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<Self, PrimitivesError> {
+        let (root, num_leaves) = build_tree_internal_parallel::<E, H, I, ARITY, T>(height, elems)?;
+        Ok(MerkleTree {
+            root,
+            height,
+            num_leaves,
+            _phantom_h: PhantomData,
+        })
+    }
+}
 
 // TODO(Chengyu): unit tests
 #[cfg(test)]
 mod mt_tests {
     use crate::{
+        errors::PrimitivesError,
         merkle_tree::{
-            internal::{MerkleNode, MerkleProof},
-            prelude::RescueMerkleTree,
+            examples::{Sha3Digest, Sha3Node},
+            internal::{MerkleNode, MerkleProof, MerkleTreeCommitment},
+            prelude::{MerkleTree, RescueHash, RescueMerkleTree},
             *,
         },
         rescue::RescueParameter,
@@ -79,6 +336,9 @@ mod mt_tests {
     use ark_ed_on_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_381::Fq as Fq381;
     use ark_ed_on_bn254::Fq as Fq254;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::rand::Rng;
+    use num_bigint::BigUint;
 
     #[test]
     fn test_mt_builder() {
@@ -89,7 +349,40 @@ mod mt_tests {
 
     fn test_mt_builder_helper<F: RescueParameter>() {
         assert!(RescueMerkleTree::<F>::from_elems(1, &[F::from(0u64); 3]).is_ok());
-        assert!(RescueMerkleTree::<F>::from_elems(1, &[F::from(0u64); 4]).is_err());
+        assert!(matches!(
+            RescueMerkleTree::<F>::from_elems(1, &[F::from(0u64); 4]),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_mt_getters() {
+        test_mt_getters_helper::<Fq254>();
+        test_mt_getters_helper::<Fq377>();
+        test_mt_getters_helper::<Fq381>();
+    }
+
+    fn test_mt_getters_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(3, [] as [&F; 0]).unwrap();
+        assert_eq!(mt.height(), 3);
+        assert_eq!(mt.capacity(), BigUint::from(27u64));
+        assert_eq!(mt.num_leaves(), 0);
+
+        mt.push(F::from(1u64)).unwrap();
+        assert_eq!(mt.num_leaves(), 1);
+        mt.extend(&[F::from(2u64), F::from(3u64), F::from(4u64)])
+            .unwrap();
+        assert_eq!(mt.num_leaves(), 4);
+        // Updating or forgetting a leaf does not change the occupancy.
+        mt.update(0, F::from(5u64)).unwrap();
+        mt.forget(1).expect_ok().unwrap();
+        assert_eq!(mt.num_leaves(), 4);
+        assert_eq!(mt.height(), 3);
+        assert_eq!(mt.capacity(), BigUint::from(27u64));
+
+        let mt =
+            MerkleTree::<F, Sha3Digest, u64, 2, Sha3Node>::from_elems(8, [F::from(0u64)]).unwrap();
+        assert_eq!(mt.capacity(), BigUint::from(256u64));
     }
 
     #[test]
@@ -108,11 +401,112 @@ mod mt_tests {
         assert_eq!(mt.num_leaves(), 9u64); // full merkle tree
 
         // Now unable to insert more data
-        assert!(mt.push(F::from(0u64)).is_err());
+        assert!(matches!(
+            mt.push(F::from(0u64)),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
         assert!(mt.extend(&[]).is_ok());
         assert!(mt.extend(&[F::from(1u64)]).is_err());
     }
 
+    #[test]
+    fn test_mt_extend_matches_push() {
+        test_mt_extend_matches_push_helper::<Fq254>();
+        test_mt_extend_matches_push_helper::<Fq377>();
+        test_mt_extend_matches_push_helper::<Fq381>();
+    }
+
+    fn test_mt_extend_matches_push_helper<F: RescueParameter>() {
+        let elems = (0..1000u64).map(F::from).collect::<Vec<_>>();
+
+        let mut seq_mt = RescueMerkleTree::<F>::from_elems(7, &[]).unwrap();
+        for elem in elems.iter() {
+            seq_mt.push(elem).unwrap();
+        }
+
+        let mut batch_mt = RescueMerkleTree::<F>::from_elems(7, &[]).unwrap();
+        batch_mt.extend(&elems).unwrap();
+        assert_eq!(batch_mt.commitment(), seq_mt.commitment());
+
+        // Extending from a partially filled frontier should agree as well.
+        let mut split_mt = RescueMerkleTree::<F>::from_elems(7, &elems[..333]).unwrap();
+        split_mt.extend(&elems[333..]).unwrap();
+        assert_eq!(split_mt.commitment(), seq_mt.commitment());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_mt_parallel_builder() {
+        test_mt_parallel_builder_helper::<Fq254>();
+        test_mt_parallel_builder_helper::<Fq377>();
+        test_mt_parallel_builder_helper::<Fq381>();
+    }
+
+    #[cfg(feature = "parallel")]
+    fn test_mt_parallel_builder_helper<F: RescueParameter>() {
+        let mut rng = jf_utils::test_rng();
+        for num_leaves in [1, 2, 3, 10, 81] {
+            let elems = (0..num_leaves)
+                .map(|_| F::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let mt = RescueMerkleTree::<F>::from_elems(4, &elems).unwrap();
+            let par_mt = RescueMerkleTree::<F>::from_elems_parallel(4, &elems).unwrap();
+            assert_eq!(mt, par_mt);
+            assert_eq!(
+                mt.lookup(num_leaves - 1).expect_ok().unwrap().1,
+                par_mt.lookup(num_leaves - 1).expect_ok().unwrap().1
+            );
+        }
+        assert!(RescueMerkleTree::<F>::from_elems_parallel(1, &[F::from(0u64); 4]).is_err());
+    }
+
+    #[test]
+    fn test_mt_update() {
+        test_mt_update_helper::<Fq254>();
+        test_mt_update_helper::<Fq377>();
+        test_mt_update_helper::<Fq381>();
+    }
+
+    fn test_mt_update_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(2, &[F::from(3u64), F::from(1u64)]).unwrap();
+        let old_root = mt.commitment().digest();
+
+        let ret = mt.update(0, F::from(5u64)).unwrap();
+        assert_eq!(ret, LookupResult::Ok(F::from(3u64), ()));
+        assert_eq!(mt.num_leaves(), 2);
+        let new_root = mt.commitment().digest();
+        assert_ne!(old_root, new_root);
+        assert_eq!(
+            new_root,
+            RescueMerkleTree::<F>::from_elems(2, &[F::from(5u64), F::from(1u64)])
+                .unwrap()
+                .commitment()
+                .digest()
+        );
+
+        let (elem, proof) = mt.lookup(0).expect_ok().unwrap();
+        assert_eq!(elem, &F::from(5u64));
+        assert!(RescueMerkleTree::<F>::verify(&new_root, 0, &proof)
+            .unwrap()
+            .is_ok());
+        assert!(RescueMerkleTree::<F>::verify(&old_root, 0, &proof)
+            .unwrap()
+            .is_err());
+
+        // Empty or out of capacity positions cannot be updated.
+        assert!(mt.update(2, F::from(5u64)).is_err());
+        assert!(matches!(
+            mt.update(9, F::from(5u64)),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+
+        mt.forget(1).expect_ok().unwrap();
+        assert_eq!(
+            mt.update(1, F::from(5u64)).unwrap(),
+            LookupResult::NotInMemory
+        );
+    }
+
     #[test]
     fn test_mt_lookup() {
         test_mt_lookup_helper::<Fq254>();
@@ -161,6 +555,775 @@ mod mt_tests {
         assert!(result.unwrap().is_err());
     }
 
+    #[test]
+    fn test_mt_lookup_result() {
+        test_mt_lookup_result_helper::<Fq254>();
+        test_mt_lookup_result_helper::<Fq377>();
+        test_mt_lookup_result_helper::<Fq381>();
+    }
+
+    fn test_mt_lookup_result_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(2, &[F::from(3u64), F::from(1u64)]).unwrap();
+        assert!(matches!(mt.lookup(0), LookupResult::Ok(..)));
+        // An empty slot inside the tree
+        assert!(matches!(mt.lookup(5), LookupResult::NotFound(())));
+        // A position beyond the capacity, whose traversal path wraps around to
+        // the occupied position 0
+        assert!(matches!(mt.lookup(9), LookupResult::NotFound(())));
+        // A forgotten leaf
+        mt.forget(1).expect_ok().unwrap();
+        assert!(matches!(mt.lookup(1), LookupResult::NotInMemory));
+        assert!(matches!(mt.lookup(0), LookupResult::Ok(..)));
+    }
+
+    #[test]
+    fn test_mt_compute_root_from_proof() {
+        test_mt_compute_root_from_proof_helper::<Fq254>();
+        test_mt_compute_root_from_proof_helper::<Fq377>();
+        test_mt_compute_root_from_proof_helper::<Fq381>();
+    }
+
+    fn test_mt_compute_root_from_proof_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(2, &[F::from(3u64), F::from(1u64)]).unwrap();
+        let old_root = mt.commitment().digest();
+        let proof = mt.lookup(1).expect_ok().unwrap().1;
+        assert_eq!(
+            RescueMerkleTree::<F>::compute_root_from_proof(&proof).unwrap(),
+            old_root
+        );
+
+        // Pick the matching root among several candidates.
+        mt.push(F::from(4u64)).unwrap();
+        let new_root = mt.commitment().digest();
+        let new_proof = mt.lookup(1).expect_ok().unwrap().1;
+        let candidates = [old_root, new_root];
+        let matching = |proof: &MerkleProof<F, u64, F, 3>| {
+            let root = RescueMerkleTree::<F>::compute_root_from_proof(proof).unwrap();
+            candidates.iter().position(|candidate| *candidate == root)
+        };
+        assert_eq!(matching(&proof), Some(0));
+        assert_eq!(matching(&new_proof), Some(1));
+
+        let mut bad_proof = proof.clone();
+        if let MerkleNode::Leaf { elem, .. } = &mut bad_proof.proof[0] {
+            *elem = F::from(2u64);
+        }
+        assert_eq!(matching(&bad_proof), None);
+
+        let mut wrong_index_proof = proof;
+        wrong_index_proof.pos = 0;
+        assert!(RescueMerkleTree::<F>::compute_root_from_proof(&wrong_index_proof).is_err());
+    }
+
+    #[test]
+    fn test_mt_standalone_verify() {
+        test_mt_standalone_verify_helper::<Fq254>();
+        test_mt_standalone_verify_helper::<Fq377>();
+        test_mt_standalone_verify_helper::<Fq381>();
+    }
+
+    fn test_mt_standalone_verify_helper<F: RescueParameter>() {
+        let (root, proof) = {
+            let mt = RescueMerkleTree::<F>::from_elems(2, &[F::from(3u64), F::from(1u64)]).unwrap();
+            (
+                mt.commitment().digest(),
+                mt.lookup(1).expect_ok().unwrap().1,
+            )
+        };
+        // The tree is gone, only the root is needed. Index 1 has an empty sibling
+        // at index 2.
+        assert!(proof
+            .verify_membership_proof::<RescueHash<F>>(&root)
+            .unwrap()
+            .is_ok());
+
+        let mut bad_proof = proof.clone();
+        if let MerkleNode::Leaf { elem, .. } = &mut bad_proof.proof[0] {
+            *elem = F::from(4u64);
+        } else {
+            unreachable!()
+        }
+        assert!(bad_proof
+            .verify_membership_proof::<RescueHash<F>>(&root)
+            .unwrap()
+            .is_err());
+
+        let mut wrong_index_proof = proof.clone();
+        wrong_index_proof.pos = 0;
+        assert!(wrong_index_proof
+            .verify_membership_proof::<RescueHash<F>>(&root)
+            .unwrap()
+            .is_err());
+
+        let mut malformed_proof = proof;
+        malformed_proof.proof[1] = MerkleNode::Empty;
+        assert!(malformed_proof
+            .verify_membership_proof::<RescueHash<F>>(&root)
+            .is_err());
+    }
+
+    #[test]
+    fn test_mt_batch_verify() {
+        test_mt_batch_verify_helper::<Fq254>();
+        test_mt_batch_verify_helper::<Fq377>();
+        test_mt_batch_verify_helper::<Fq381>();
+    }
+
+    fn test_mt_batch_verify_helper<F: RescueParameter>() {
+        let elems = (0..50u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(4, &elems).unwrap();
+        let root = mt.commitment().digest();
+        let mut proofs = [0u64, 1, 2, 17, 49, 17]
+            .iter()
+            .map(|pos| mt.lookup(pos).expect_ok().unwrap().1)
+            .collect::<Vec<_>>();
+        assert!(RescueMerkleTree::<F>::verify_batch(root, &proofs)
+            .unwrap()
+            .is_ok());
+        assert!(RescueMerkleTree::<F>::verify_batch(root, &[])
+            .unwrap()
+            .is_ok());
+
+        let other_root = RescueMerkleTree::<F>::from_elems(4, &elems[1..])
+            .unwrap()
+            .commitment()
+            .digest();
+        assert!(RescueMerkleTree::<F>::verify_batch(other_root, &proofs)
+            .unwrap()
+            .is_err());
+
+        // A wrong element for a position that is also proven correctly.
+        let mut bad_proof = proofs[3].clone();
+        if let MerkleNode::Leaf { elem, .. } = &mut bad_proof.proof[0] {
+            *elem = F::from(18u64);
+        } else {
+            unreachable!()
+        }
+        proofs.push(bad_proof.clone());
+        assert!(RescueMerkleTree::<F>::verify_batch(root, &proofs)
+            .unwrap()
+            .is_err());
+        // The same bad proof on its own, and placed first.
+        assert!(
+            RescueMerkleTree::<F>::verify_batch(root, &[bad_proof.clone()])
+                .unwrap()
+                .is_err()
+        );
+        proofs.rotate_right(1);
+        assert!(RescueMerkleTree::<F>::verify_batch(root, &proofs)
+            .unwrap()
+            .is_err());
+
+        // A tampered sibling deep in the tree.
+        let mut bad_proof = proofs[2].clone();
+        if let MerkleNode::Branch { children, .. } = &mut bad_proof.proof[1] {
+            children[2] = Box::new(MerkleNode::ForgettenSubtree {
+                value: F::from(0u64),
+            });
+        } else {
+            unreachable!()
+        }
+        assert!(
+            RescueMerkleTree::<F>::verify_batch(root, &[proofs[1].clone(), bad_proof])
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_mt_subtree_commitment() {
+        test_mt_subtree_commitment_helper::<Fq254>();
+        test_mt_subtree_commitment_helper::<Fq377>();
+        test_mt_subtree_commitment_helper::<Fq381>();
+    }
+
+    fn test_mt_subtree_commitment_helper<F: RescueParameter>() {
+        let elems = (0..50u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(4, &elems).unwrap();
+        let root = mt.commitment().digest();
+        assert_eq!(mt.subtree_commitment(&[]).unwrap(), root);
+
+        // The commitment of the subtree holding leaf 40 at every level, plus
+        // the siblings above it, gives back the global root.
+        let (_, proof) = mt.lookup(40).expect_ok().unwrap();
+        let path = 40u64.to_traversal_path(4);
+        for level in 0..=4 {
+            let prefix = path[level..].iter().rev().copied().collect::<Vec<_>>();
+            let subtree_root = mt.subtree_commitment(&prefix).unwrap();
+            let computed_root =
+                (level..4).fold(subtree_root, |value, level| match &proof.proof[level + 1] {
+                    MerkleNode::Branch { children, .. } => {
+                        let mut data = children.iter().map(|node| node.value()).collect::<Vec<_>>();
+                        data[path[level]] = value;
+                        <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest(&data).unwrap()
+                    },
+                    _ => unreachable!(),
+                });
+            assert_eq!(computed_root, root);
+        }
+        // An empty subtree.
+        assert_eq!(mt.subtree_commitment(&[2, 0]).unwrap(), F::default());
+
+        assert!(matches!(
+            mt.subtree_commitment(&[0, 0, 0, 0, 0]),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+        assert!(matches!(
+            mt.subtree_commitment(&[3]),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_mt_proof_bytes() {
+        test_mt_proof_bytes_helper::<Fq254>();
+        test_mt_proof_bytes_helper::<Fq377>();
+        test_mt_proof_bytes_helper::<Fq381>();
+    }
+
+    fn test_mt_proof_bytes_helper<F: RescueParameter>() {
+        let mt = RescueMerkleTree::<F>::from_elems(3, [F::from(3u64), F::from(1u64)]).unwrap();
+        let root = mt.commitment().digest();
+        let (_, proof) = mt.lookup(1).expect_ok().unwrap();
+        let bytes = proof.to_bytes().unwrap();
+        let decoded = MerkleProof::<F, u64, F, 3>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(RescueMerkleTree::<F>::verify(root, 1, &decoded)
+            .unwrap()
+            .is_ok());
+
+        // Truncated and oversized buffers, and buffers for another arity.
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                MerkleProof::<F, u64, F, 3>::from_bytes(&bytes[..len]),
+                Err(PrimitivesError::InvalidEncoding(_))
+            ));
+        }
+        let mut oversized = bytes.clone();
+        oversized.push(0);
+        assert!(matches!(
+            MerkleProof::<F, u64, F, 3>::from_bytes(&oversized),
+            Err(PrimitivesError::InvalidEncoding(_))
+        ));
+        assert!(matches!(
+            MerkleProof::<F, u64, F, 2>::from_bytes(&bytes),
+            Err(PrimitivesError::InvalidEncoding(_))
+        ));
+        // An empty proof cannot be encoded.
+        assert!(matches!(
+            MerkleProof::<F, u64, F, 3>::new(1, vec![]).to_bytes(),
+            Err(PrimitivesError::InvalidProof(_))
+        ));
+
+        // A corrupted buffer is either rejected or decodes to another proof.
+        let mut rng = jf_utils::test_rng();
+        for _ in 0..200 {
+            let mut corrupted = bytes.clone();
+            corrupted[rng.gen_range(0..bytes.len())] ^= rng.gen_range(1..=u8::MAX);
+            if let Ok(corrupted_proof) = MerkleProof::<F, u64, F, 3>::from_bytes(&corrupted) {
+                assert_ne!(corrupted_proof, proof);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mt_range_proof() {
+        test_mt_range_proof_helper::<Fq254>();
+        test_mt_range_proof_helper::<Fq377>();
+        test_mt_range_proof_helper::<Fq381>();
+    }
+
+    fn test_mt_range_proof_helper<F: RescueParameter>() {
+        let elems = (0..70u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(4, &elems).unwrap();
+        let root = mt.commitment().digest();
+
+        // [27, 54) is exactly the middle subtree below the root, [5, 40) crosses
+        // several subtree boundaries on both ends.
+        for (start, end) in [(27u64, 54u64), (5, 40), (0, 70), (69, 70)] {
+            let proof = mt.range_proof(start, end).unwrap();
+            assert_eq!(proof.elems, elems[start as usize..end as usize]);
+            assert!(RescueMerkleTree::<F>::verify_range_proof(root, &proof)
+                .unwrap()
+                .is_ok());
+
+            let independent_size: usize = (start..end)
+                .map(|pos| {
+                    let proof = mt.lookup(pos).expect_ok().unwrap().1;
+                    bincode::serialize(&proof).unwrap().len()
+                })
+                .sum();
+            if end - start > 1 {
+                assert!(bincode::serialize(&proof).unwrap().len() * 2 < independent_size);
+            }
+
+            let mut bad_proof = proof.clone();
+            bad_proof.elems[0] = F::from(100u64);
+            assert!(RescueMerkleTree::<F>::verify_range_proof(root, &bad_proof)
+                .unwrap()
+                .is_err());
+
+            let mut bad_proof = proof.clone();
+            bad_proof.siblings[0] = F::from(100u64);
+            assert!(RescueMerkleTree::<F>::verify_range_proof(root, &bad_proof)
+                .unwrap()
+                .is_err());
+
+            // Shifting the range moves the elements to other positions.
+            let mut bad_proof = proof.clone();
+            bad_proof.start += 1;
+            assert!(RescueMerkleTree::<F>::verify_range_proof(root, &bad_proof).is_err());
+
+            let mut bad_proof = proof;
+            bad_proof.elems.pop();
+            assert!(matches!(
+                RescueMerkleTree::<F>::verify_range_proof(root, &bad_proof),
+                Err(PrimitivesError::InvalidProof(_))
+            ));
+        }
+        assert_eq!(mt.range_proof(27u64, 54u64).unwrap().siblings.len(), 2);
+
+        assert!(mt.range_proof(5u64, 5u64).is_err());
+        assert!(mt.range_proof(60u64, 71u64).is_err());
+        assert!(matches!(
+            mt.range_proof(60u64, 82u64),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_mt_batch_proof() {
+        test_mt_batch_proof_helper::<Fq254>();
+        test_mt_batch_proof_helper::<Fq377>();
+        test_mt_batch_proof_helper::<Fq381>();
+    }
+
+    fn test_mt_batch_proof_helper<F: RescueParameter>() {
+        let elems = (0..70u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(4, &elems).unwrap();
+        let root = mt.commitment();
+
+        // 3, 4 and 5 share their whole path but the leaves, and 0 joins them
+        // one level above.
+        let positions = [5u64, 3, 30, 4, 69, 0, 5];
+        let proof = mt.batch_proof(&positions).unwrap();
+        assert_eq!(proof.positions, [0, 3, 4, 5, 30, 69]);
+        assert_eq!(
+            proof.elems,
+            [0u64, 3, 4, 5, 30, 69].map(|pos| elems[pos as usize])
+        );
+        assert_eq!(proof.siblings.len(), 17);
+        assert!(RescueMerkleTree::<F>::verify_batch_proof(&root, &proof)
+            .unwrap()
+            .is_ok());
+
+        let independent_size: usize = proof
+            .positions
+            .iter()
+            .map(|pos| {
+                let proof = mt.lookup(pos).expect_ok().unwrap().1;
+                bincode::serialize(&proof).unwrap().len()
+            })
+            .sum();
+        assert!(bincode::serialize(&proof).unwrap().len() * 2 < independent_size);
+
+        let mut bad_proof = proof.clone();
+        bad_proof.elems[1] = F::from(100u64);
+        assert!(RescueMerkleTree::<F>::verify_batch_proof(&root, &bad_proof)
+            .unwrap()
+            .is_err());
+
+        let mut bad_proof = proof.clone();
+        bad_proof.siblings.swap(0, 1);
+        assert!(RescueMerkleTree::<F>::verify_batch_proof(&root, &bad_proof)
+            .unwrap()
+            .is_err());
+
+        let mut bad_proof = proof.clone();
+        bad_proof.positions.swap(0, 1);
+        assert!(matches!(
+            RescueMerkleTree::<F>::verify_batch_proof(&root, &bad_proof),
+            Err(PrimitivesError::InvalidProof(_))
+        ));
+
+        let mut bad_proof = proof.clone();
+        bad_proof.siblings.pop();
+        assert!(matches!(
+            RescueMerkleTree::<F>::verify_batch_proof(&root, &bad_proof),
+            Err(PrimitivesError::InvalidProof(_))
+        ));
+
+        // The height is bounded by the commitment, not by the proof.
+        let mut bad_proof = proof;
+        bad_proof.height = usize::MAX;
+        assert!(matches!(
+            RescueMerkleTree::<F>::verify_batch_proof(&root, &bad_proof),
+            Err(PrimitivesError::InvalidProof(_))
+        ));
+
+        assert!(mt.batch_proof(&[]).is_err());
+        assert!(mt.batch_proof(&[3, 70]).is_err());
+        assert!(matches!(
+            mt.batch_proof(&[3, 81]),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_mt_authentication_path() {
+        test_mt_authentication_path_helper::<Fq254>();
+        test_mt_authentication_path_helper::<Fq377>();
+        test_mt_authentication_path_helper::<Fq381>();
+    }
+
+    fn test_mt_authentication_path_helper<F: RescueParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(3, &elems).unwrap();
+        for pos in [0u64, 4, 9] {
+            let path = mt.authentication_path(pos).unwrap().collect::<Vec<_>>();
+            assert_eq!(path.len(), 3);
+            let root = path.into_iter().fold(
+                RescueHash::<F>::digest_leaf(&pos, &elems[pos as usize]).unwrap(),
+                |val, (branch, mut siblings)| {
+                    assert_eq!(siblings.len(), 2);
+                    siblings.insert(branch, val);
+                    <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest(&siblings).unwrap()
+                },
+            );
+            assert_eq!(root, mt.commitment().digest());
+        }
+        assert!(mt.authentication_path(10u64).is_err());
+    }
+
+    #[test]
+    fn test_mt_evm_encoding() {
+        // Hand-built proof for position 5 = 0b12 in base 3, in a tree of height 2.
+        let node = |values: [u64; 3]| MerkleNode::<Fq254, u64, Fq254>::Branch {
+            value: Fq254::from(0u64),
+            children: values
+                .iter()
+                .map(|value| {
+                    Box::new(MerkleNode::ForgettenSubtree {
+                        value: Fq254::from(*value),
+                    })
+                })
+                .collect(),
+        };
+        let proof = MerkleProof::<Fq254, u64, Fq254, 3>::new(
+            5,
+            vec![
+                MerkleNode::Leaf {
+                    value: Fq254::from(0u64),
+                    pos: 5,
+                    elem: -Fq254::from(1u64),
+                },
+                node([1, 2, 0]),
+                node([3, 0, 4]),
+            ],
+        );
+        let mut expected = vec![0u8; 6 * 32];
+        expected[31] = 5;
+        // The largest element, r - 1 for the BN254 scalar field.
+        expected[32..64].copy_from_slice(&[
+            0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81,
+            0x58, 0x5d, 0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93,
+            0xf0, 0x00, 0x00, 0x00,
+        ]);
+        expected[95] = 1;
+        expected[127] = 2;
+        expected[159] = 3;
+        expected[191] = 4;
+        assert_eq!(proof.encode_for_evm().unwrap(), expected);
+        assert!(matches!(
+            MerkleProof::<Fq254, u64, Fq254, 3>::new(5, vec![]).encode_for_evm(),
+            Err(PrimitivesError::InvalidProof(_))
+        ));
+
+        let elems = (0..20u64).map(Fq254::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<Fq254>::from_elems(3, &elems).unwrap();
+        let root = mt.commitment().digest();
+        for pos in [0u64, 13, 19] {
+            let (_, proof) = mt.lookup(pos).expect_ok().unwrap();
+            let bytes = proof.encode_for_evm().unwrap();
+            assert_eq!(bytes.len(), (2 + 3 * 2) * 32);
+            assert!(
+                MerkleProof::<Fq254, u64, Fq254, 3>::verify_evm_encoding::<RescueHash<Fq254>>(
+                    &bytes, 3, &root
+                )
+                .unwrap()
+                .is_ok()
+            );
+
+            let mut bad_bytes = bytes.clone();
+            bad_bytes[127] ^= 1;
+            assert!(
+                MerkleProof::<Fq254, u64, Fq254, 3>::verify_evm_encoding::<RescueHash<Fq254>>(
+                    &bad_bytes, 3, &root
+                )
+                .unwrap()
+                .is_err()
+            );
+            // Words are not reduced modulo the field size.
+            let mut bad_bytes = bytes.clone();
+            bad_bytes[32] = 0xff;
+            assert!(matches!(
+                MerkleProof::<Fq254, u64, Fq254, 3>::verify_evm_encoding::<RescueHash<Fq254>>(
+                    &bad_bytes, 3, &root
+                ),
+                Err(PrimitivesError::InvalidEncoding(_))
+            ));
+            assert!(matches!(
+                MerkleProof::<Fq254, u64, Fq254, 3>::verify_evm_encoding::<RescueHash<Fq254>>(
+                    &bytes[32..],
+                    3,
+                    &root
+                ),
+                Err(PrimitivesError::InvalidEncoding(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_mt_serialize_tree() {
+        test_mt_serialize_tree_helper::<Fq254>();
+        test_mt_serialize_tree_helper::<Fq377>();
+        test_mt_serialize_tree_helper::<Fq381>();
+    }
+
+    fn test_mt_serialize_tree_helper<F: RescueParameter>() {
+        let elems = (0..300u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(6, &elems).unwrap();
+        mt.forget(42).expect_ok().unwrap();
+        let bytes = mt.serialize_tree().unwrap();
+        let restored = RescueMerkleTree::<F>::deserialize_tree(&bytes).unwrap();
+        assert_eq!(restored, mt);
+        assert_eq!(restored.commitment(), mt.commitment());
+        for pos in [0u64, 41, 150, 299] {
+            assert_eq!(
+                restored.lookup(pos).expect_ok().unwrap().1,
+                mt.lookup(pos).expect_ok().unwrap().1
+            );
+        }
+        assert!(matches!(restored.lookup(42), LookupResult::NotInMemory));
+
+        // Flipping any byte, in the header or in any node, is detected.
+        for i in (0..bytes.len())
+            .step_by(53)
+            .chain([0, 4, 8, 15, bytes.len() - 1])
+        {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 1;
+            assert!(RescueMerkleTree::<F>::deserialize_tree(&corrupted).is_err());
+        }
+        assert!(RescueMerkleTree::<F>::deserialize_tree(&bytes[..bytes.len() - 1]).is_err());
+        assert!(
+            RescueMerkleTree::<F>::deserialize_tree(&[bytes.as_slice(), &[0]].concat()).is_err()
+        );
+        assert!(RescueMerkleTree::<F>::deserialize_tree(&bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn test_mt_consistency_proof() {
+        test_mt_consistency_proof_helper::<Fq254>();
+        test_mt_consistency_proof_helper::<Fq377>();
+        test_mt_consistency_proof_helper::<Fq381>();
+    }
+
+    fn test_mt_consistency_proof_helper<F: RescueParameter>() {
+        let elems = (0..70u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(4, &elems[..25]).unwrap();
+        let old_root = mt.commitment();
+        mt.extend(&elems[25..]).unwrap();
+        let new_root = mt.commitment();
+
+        let proof = mt.consistency_proof(25, 70).unwrap();
+        assert!(
+            RescueMerkleTree::<F>::verify_consistency_proof(&old_root, &new_root, &proof)
+                .unwrap()
+                .is_ok()
+        );
+
+        // Any pair of past sizes, checked against freshly built trees.
+        for (old_size, new_size) in [(0u64, 70u64), (27, 54), (25, 26), (1, 69)] {
+            let proof = mt.consistency_proof(old_size, new_size).unwrap();
+            let old_root = RescueMerkleTree::<F>::from_elems(4, &elems[..old_size as usize])
+                .unwrap()
+                .commitment();
+            let new_root = RescueMerkleTree::<F>::from_elems(4, &elems[..new_size as usize])
+                .unwrap()
+                .commitment();
+            assert!(
+                RescueMerkleTree::<F>::verify_consistency_proof(&old_root, &new_root, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+        }
+
+        // Nothing appended.
+        let proof = mt.consistency_proof(70, 70).unwrap();
+        assert!(
+            RescueMerkleTree::<F>::verify_consistency_proof(&new_root, &new_root, &proof)
+                .unwrap()
+                .is_ok()
+        );
+
+        // An old leaf modified instead of only appending.
+        let mut tampered = RescueMerkleTree::<F>::from_elems(4, &elems[..25]).unwrap();
+        tampered.update(3, F::from(100u64)).unwrap();
+        tampered.extend(&elems[25..]).unwrap();
+        let proof = tampered.consistency_proof(25, 70).unwrap();
+        assert!(RescueMerkleTree::<F>::verify_consistency_proof(
+            &old_root,
+            tampered.commitment(),
+            &proof
+        )
+        .unwrap()
+        .is_err());
+        // An honest proof does not vouch for the tampered tree either.
+        let proof = mt.consistency_proof(25, 70).unwrap();
+        assert!(RescueMerkleTree::<F>::verify_consistency_proof(
+            &old_root,
+            tampered.commitment(),
+            &proof
+        )
+        .unwrap()
+        .is_err());
+
+        // Proofs for other sizes are rejected.
+        assert!(
+            RescueMerkleTree::<F>::verify_consistency_proof(&new_root, &new_root, &proof).is_err()
+        );
+        assert!(mt.consistency_proof(30, 25).is_err());
+        assert!(mt.consistency_proof(25, 71).is_err());
+    }
+
+    #[test]
+    fn test_mt_update_proof() {
+        test_mt_update_proof_helper::<Fq254>();
+        test_mt_update_proof_helper::<Fq377>();
+        test_mt_update_proof_helper::<Fq381>();
+    }
+
+    fn test_mt_update_proof_helper<F: RescueParameter>() {
+        let elems = (0..30u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(3, &elems[..3]).unwrap();
+        let (_, mut proof) = mt.lookup(1).expect_ok().unwrap();
+
+        // Leaves appended in the same bottom node, then in other subtrees.
+        for (pos, elem) in elems.iter().enumerate().skip(3) {
+            mt.push(elem).unwrap();
+            let (_, appended) = mt.lookup(pos as u64).expect_ok().unwrap();
+            RescueMerkleTree::<F>::update_proof(&mut proof, &appended).unwrap();
+            assert_eq!(proof, mt.lookup(1).expect_ok().unwrap().1);
+            assert!(
+                RescueMerkleTree::<F>::verify(mt.commitment().digest(), 1, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+        }
+        let (_, mut proof) = mt.lookup(29).expect_ok().unwrap();
+        let fresh = proof.clone();
+        RescueMerkleTree::<F>::update_proof(&mut proof, &fresh).unwrap();
+        assert_eq!(proof, fresh);
+
+        // A proof from a tree of another height is rejected.
+        let other = RescueMerkleTree::<F>::from_elems(4, &elems).unwrap();
+        let (_, other_proof) = other.lookup(3).expect_ok().unwrap();
+        assert!(RescueMerkleTree::<F>::update_proof(&mut proof, &other_proof).is_err());
+
+        // So are empty proofs, on either side.
+        let mut empty = MerkleProof::new(1, vec![]);
+        assert!(RescueMerkleTree::<F>::update_proof(&mut empty, &fresh).is_err());
+        let empty = MerkleProof::new(29, vec![]);
+        assert!(RescueMerkleTree::<F>::update_proof(&mut proof, &empty).is_err());
+    }
+
+    #[test]
+    fn test_mt_history() {
+        test_mt_history_helper::<Fq254>();
+        test_mt_history_helper::<Fq377>();
+        test_mt_history_helper::<Fq381>();
+    }
+
+    fn test_mt_history_helper<F: RescueParameter>() {
+        let elems = (0..20u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(3, &elems[..5])
+            .unwrap()
+            .with_history(3);
+        let first_root = mt.tree().commitment().digest();
+        mt.push(elems[5]).unwrap();
+        let intermediate_root = mt.tree().commitment().digest();
+        mt.extend(&elems[6..12]).unwrap();
+        mt.push(elems[12]).unwrap();
+        assert_eq!(mt.history().count(), 3);
+        assert_eq!(
+            mt.history().last().unwrap().digest(),
+            mt.tree().commitment().digest()
+        );
+
+        // A proof against an intermediate root, for a leaf which was already
+        // there and for the leaf appended at that time.
+        for pos in [2u64, 5] {
+            let (elem, proof) = mt
+                .lookup_historical(pos, intermediate_root)
+                .unwrap()
+                .expect_ok()
+                .unwrap();
+            assert_eq!(elem, &elems[pos as usize]);
+            assert!(
+                RescueMerkleTree::<F>::verify(intermediate_root, pos, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+            assert!(
+                RescueMerkleTree::<F>::verify(mt.tree().commitment().digest(), pos, &proof)
+                    .unwrap()
+                    .is_err()
+            );
+        }
+        // Leaves appended later are not in the intermediate tree.
+        assert!(mt
+            .lookup_historical(6, intermediate_root)
+            .unwrap()
+            .expect_not_found()
+            .is_ok());
+
+        // The first root has been evicted.
+        assert!(mt.lookup_historical(2, first_root).is_err());
+    }
+
+    #[test]
+    fn test_mt_arity() {
+        test_mt_arity_helper::<Fq254, 2>();
+        test_mt_arity_helper::<Fq377, 3>();
+        test_mt_arity_helper::<Fq381, 4>();
+    }
+
+    fn test_mt_arity_helper<F: RescueParameter, const ARITY: usize>() {
+        let height = 3;
+        let capacity = ARITY.pow(height as u32);
+        let elems = (0..capacity as u64).map(F::from).collect::<Vec<_>>();
+        let mut mt =
+            MerkleTree::<F, Sha3Digest, u64, ARITY, Sha3Node>::from_elems(height, &elems).unwrap();
+        assert_eq!(
+            MerkleTree::<F, Sha3Digest, u64, ARITY, Sha3Node>::ARITY,
+            ARITY
+        );
+        assert_eq!(mt.capacity(), BigUint::from(capacity));
+        let root = mt.commitment().digest();
+        for (i, elem) in elems.iter().enumerate() {
+            let (val, proof) = mt.lookup(i as u64).expect_ok().unwrap();
+            assert_eq!(val, *elem);
+            assert_eq!(proof.tree_height(), height + 1);
+            assert!(MerkleTree::<F, Sha3Digest, u64, ARITY, Sha3Node>::verify(
+                &root, i as u64, &proof
+            )
+            .unwrap()
+            .is_ok());
+        }
+        assert!(mt.push(F::from(0u64)).is_err());
+    }
+
     #[test]
     fn test_mt_forget_remember() {
         test_mt_forget_remember_helper::<Fq254>();
@@ -201,7 +1364,7 @@ mod mt_tests {
         }
 
         let result = mt.remember(0u64, elem, &bad_proof);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(PrimitivesError::InvalidProof(_))));
 
         let mut forge_proof = MerkleProof::new(2, proof.proof.clone());
         if let MerkleNode::Leaf {
@@ -216,12 +1379,133 @@ mod mt_tests {
             unreachable!()
         }
         let result = mt.remember(2u64, elem, &forge_proof);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(PrimitivesError::InvalidProof(_))));
 
         assert!(mt.remember(0, elem, &proof).is_ok());
         assert!(mt.lookup(0).expect_ok().is_ok());
     }
 
+    #[test]
+    fn test_mt_from_commitment() {
+        test_mt_from_commitment_helper::<Fq254>();
+        test_mt_from_commitment_helper::<Fq377>();
+        test_mt_from_commitment_helper::<Fq381>();
+    }
+
+    fn test_mt_from_commitment_helper<F: RescueParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(3, &elems).unwrap();
+        let mut verifier = RescueMerkleTree::<F>::from_commitment(mt.commitment());
+        assert_eq!(verifier.commitment(), mt.commitment());
+
+        // Proofs generated by the full tree are checked against the verifier's
+        // commitment, while it cannot look anything up by itself.
+        for pos in 0..10u64 {
+            let (_, proof) = mt.lookup(pos).expect_ok().unwrap();
+            assert!(
+                RescueMerkleTree::<F>::verify(verifier.commitment().digest(), pos, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+            assert!(matches!(verifier.lookup(pos), LookupResult::NotInMemory));
+        }
+
+        // Appending needs the frontier, which the verifier can remember from a
+        // proof of the last leaf.
+        assert!(verifier.push(F::from(10u64)).is_err());
+        let (_, proof) = mt.lookup(9).expect_ok().unwrap();
+        verifier.remember(9, elems[9], &proof).unwrap();
+        verifier.push(F::from(10u64)).unwrap();
+        mt.push(F::from(10u64)).unwrap();
+        assert_eq!(verifier.commitment(), mt.commitment());
+
+        // An empty tree has nothing to remember.
+        let empty = RescueMerkleTree::<F>::from_elems(3, [] as [&F; 0]).unwrap();
+        let mut verifier = RescueMerkleTree::<F>::from_commitment(empty.commitment());
+        verifier.extend(&elems).unwrap();
+        assert_eq!(
+            verifier.commitment(),
+            RescueMerkleTree::<F>::from_elems(3, &elems)
+                .unwrap()
+                .commitment()
+        );
+
+        // A commitment of size 0 to another root is not an empty tree, and
+        // cannot be appended to.
+        let forged = MerkleTreeCommitment::new(mt.commitment().digest(), 3, 0);
+        let mut verifier = RescueMerkleTree::<F>::from_commitment(forged);
+        assert_eq!(verifier.commitment(), forged);
+        assert!(verifier.push(F::from(0u64)).is_err());
+        assert_eq!(verifier.commitment(), forged);
+    }
+
+    #[test]
+    fn test_mt_forget_remember_many() {
+        test_mt_forget_remember_many_helper::<Fq254>();
+        test_mt_forget_remember_many_helper::<Fq377>();
+        test_mt_forget_remember_many_helper::<Fq381>();
+    }
+
+    fn test_mt_forget_remember_many_helper<F: RescueParameter>() {
+        let elems = (0..27u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(3, &elems).unwrap();
+        let root = mt.commitment().digest();
+
+        // Forget the first half of the leaves.
+        let proofs = (0..13u64)
+            .map(|i| mt.forget(i).expect_ok().unwrap().1)
+            .collect::<Vec<_>>();
+        for i in 0..27u64 {
+            if i < 13 {
+                assert!(matches!(mt.lookup(i), LookupResult::NotInMemory));
+            } else {
+                assert!(mt.lookup(i).expect_ok().is_ok());
+            }
+        }
+        assert_eq!(mt.commitment().digest(), root);
+
+        // A proof against another root is rejected.
+        let other_mt = RescueMerkleTree::<F>::from_elems(3, elems.iter().rev()).unwrap();
+        let (_, other_proof) = other_mt.lookup(0).expect_ok().unwrap();
+        assert!(mt.remember(0, &elems[26], &other_proof).is_err());
+        assert!(matches!(mt.lookup(0), LookupResult::NotInMemory));
+
+        // A proof for another position is rejected.
+        assert!(mt.remember(1, &elems[0], &proofs[0]).is_err());
+
+        // Remember a few of them.
+        for i in [0u64, 4, 12] {
+            mt.remember(i, &elems[i as usize], &proofs[i as usize])
+                .unwrap();
+            let (elem, proof) = mt.lookup(i).expect_ok().unwrap();
+            assert_eq!(elem, &elems[i as usize]);
+            assert!(RescueMerkleTree::<F>::verify(&root, i, &proof)
+                .unwrap()
+                .is_ok());
+        }
+        assert!(matches!(mt.lookup(1), LookupResult::NotInMemory));
+        assert_eq!(mt.commitment().digest(), root);
+    }
+
+    #[test]
+    fn test_mt_iter() {
+        test_mt_iter_helper::<Fq254>();
+        test_mt_iter_helper::<Fq377>();
+        test_mt_iter_helper::<Fq381>();
+    }
+
+    fn test_mt_iter_helper<F: RescueParameter>() {
+        let elems = (0..100u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(5, &elems).unwrap();
+        assert_eq!(mt.iter().count(), elems.len());
+        for ((pos, elem), (expected_pos, expected_elem)) in
+            (&mt).into_iter().zip(elems.iter().enumerate())
+        {
+            assert_eq!(*pos, expected_pos as u64);
+            assert_eq!(elem, expected_elem);
+        }
+    }
+
     #[test]
     fn test_mt_serde() {
         test_mt_serde_helper::<Fq254>();
@@ -247,4 +1531,69 @@ mod mt_tests {
             bincode::deserialize(&bincode::serialize(node).unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn test_mt_canonical_serde() {
+        test_mt_canonical_serde_helper::<Fq254>();
+        test_mt_canonical_serde_helper::<Fq377>();
+        test_mt_canonical_serde_helper::<Fq381>();
+    }
+
+    fn test_mt_canonical_serde_helper<F: RescueParameter>() {
+        let mt = RescueMerkleTree::<F>::from_elems(2, &[F::from(3u64), F::from(1u64)]).unwrap();
+        let commitment = mt.commitment();
+        let proof = mt.lookup(1).expect_ok().unwrap().1;
+
+        let mut bytes = Vec::new();
+        commitment.serialize_compressed(&mut bytes).unwrap();
+        let de_commitment =
+            <RescueMerkleTree<F> as MerkleTreeScheme>::Commitment::deserialize_compressed(
+                &bytes[..],
+            )
+            .unwrap();
+        assert_eq!(commitment, de_commitment);
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), proof.compressed_size());
+        let de_proof =
+            <RescueMerkleTree<F> as MerkleTreeScheme>::MembershipProof::deserialize_compressed(
+                &bytes[..],
+            )
+            .unwrap();
+        assert_eq!(proof, de_proof);
+        assert!(
+            RescueMerkleTree::<F>::verify(de_commitment.digest(), 1, &de_proof)
+                .unwrap()
+                .is_ok()
+        );
+
+        // Serialization is deterministic
+        let mut bytes2 = Vec::new();
+        de_proof.serialize_uncompressed(&mut bytes2).unwrap();
+        let mut bytes1 = Vec::new();
+        proof.serialize_uncompressed(&mut bytes1).unwrap();
+        assert_eq!(bytes1, bytes2);
+
+        // Nodes nested deeper than their height are rejected before
+        // recursing, as on a proof whose leaf is a branch.
+        let mut bytes = Vec::new();
+        1u64.serialize_compressed(&mut bytes).unwrap();
+        1u64.serialize_compressed(&mut bytes).unwrap();
+        proof.proof[1].serialize_compressed(&mut bytes).unwrap();
+        assert!(
+            <RescueMerkleTree<F> as MerkleTreeScheme>::MembershipProof::deserialize_compressed(
+                &bytes[..],
+            )
+            .is_err()
+        );
+        let mut bytes = Vec::new();
+        for _ in 0..100_000 {
+            1u8.serialize_compressed(&mut bytes).unwrap();
+            F::zero().serialize_compressed(&mut bytes).unwrap();
+            1u64.serialize_compressed(&mut bytes).unwrap();
+        }
+        0u8.serialize_compressed(&mut bytes).unwrap();
+        assert!(MerkleNode::<F, u64, F>::deserialize_compressed(&bytes[..]).is_err());
+    }
 }
@@ -7,7 +7,10 @@
 //! Provides sample instantiations of merkle tree.
 //! E.g. Sparse merkle tree with BigUInt index.
 
-use super::{append_only::MerkleTree, prelude::RescueHash, DigestAlgorithm, Element, Index};
+use super::{
+    append_only::MerkleTree, internal::MerkleProof, prelude::RescueHash, DigestAlgorithm, Element,
+    Index, MerkleTreeScheme,
+};
 use crate::{
     errors::PrimitivesError,
     rescue::{sponge::RescueCRHF, RescueParameter},
@@ -17,32 +20,139 @@ use ark_serialize::{
     CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
     Write,
 };
-use ark_std::vec::Vec;
-use sha3::{Digest, Sha3_256};
-use typenum::U3;
+use ark_std::{string::ToString, vec::Vec};
+use sha3::{
+    digest::{self, ExtendableOutput, XofReader},
+    Digest, Keccak256, Sha3_256, Shake256,
+};
+use tagged_base64::tagged;
 
-/// Element type for interval merkle tree
-#[derive(PartialEq, Eq, Copy, Clone, Hash)]
+/// Element type for interval merkle tree: the closed interval `[low, high]`
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
 pub struct Interval<F: Field>(pub F, pub F);
 // impl<F: Field> Element for Interval<F> {}
 
-impl<F: RescueParameter> DigestAlgorithm<Interval<F>, u64, F> for RescueHash<F> {
-    fn digest(data: &[F]) -> Result<F, PrimitivesError> {
-        Ok(RescueCRHF::<F>::sponge_no_padding(data, 1)?[0])
+/// Node value of an interval merkle tree. Besides the digest, every node
+/// carries the smallest lower bound and the largest upper bound of the
+/// intervals below it. The digest of a node commits to both bounds, so that
+/// the aggregate range of every subtree is authenticated by the root digest
+/// alone, and range queries can soundly prune subtrees.
+#[derive(
+    Default,
+    Eq,
+    PartialEq,
+    Clone,
+    Copy,
+    Debug,
+    Ord,
+    PartialOrd,
+    Hash,
+    CanonicalSerialize,
+    CanonicalDeserialize,
+)]
+pub struct IntervalNode<F: Field> {
+    /// Digest of the subtree
+    pub digest: F,
+    /// Smallest lower bound of the intervals in the subtree
+    pub low: F,
+    /// Largest upper bound of the intervals in the subtree
+    pub high: F,
+}
+
+impl<F: Field> IntervalNode<F> {
+    /// The aggregate range `(min_low, max_high)` of the intervals in the
+    /// subtree, None for an empty subtree.
+    pub fn range(&self) -> Option<(F, F)> {
+        if *self == Self::default() {
+            None
+        } else {
+            Some((self.low, self.high))
+        }
+    }
+}
+
+impl<F: RescueParameter> DigestAlgorithm<Interval<F>, u64, IntervalNode<F>> for RescueHash<F> {
+    // The digest of an internal node is the hash of the values of its
+    // children, bounds included. Its own aggregate range is a function of
+    // them and needs no hashing: any forged range of a child changes the
+    // digest, and a forged range of the node itself differs from the one
+    // recomputed from its children.
+    fn digest(data: &[IntervalNode<F>]) -> Result<IntervalNode<F>, PrimitivesError> {
+        // Empty subtrees do not contribute to the bounds.
+        let (low, high) = data
+            .iter()
+            .filter_map(IntervalNode::range)
+            .reduce(|(low, high), (l, h)| (low.min(l), high.max(h)))
+            .unwrap_or((F::zero(), F::zero()));
+        let input = data
+            .iter()
+            .flat_map(|node| [node.digest, node.low, node.high])
+            .collect::<Vec<_>>();
+        let digest = RescueCRHF::<F>::sponge_no_padding(&input, 1)?[0];
+        Ok(IntervalNode { digest, low, high })
     }
 
-    fn digest_leaf(pos: &u64, elem: &Interval<F>) -> Result<F, PrimitivesError> {
+    fn digest_leaf(pos: &u64, elem: &Interval<F>) -> Result<IntervalNode<F>, PrimitivesError> {
         let data = [F::from(*pos), elem.0, elem.1];
-        Ok(RescueCRHF::<F>::sponge_no_padding(&data, 1)?[0])
+        Ok(IntervalNode {
+            digest: RescueCRHF::<F>::sponge_no_padding(&data, 1)?[0],
+            low: elem.0,
+            high: elem.1,
+        })
+    }
+}
+
+impl<F, const ARITY: usize> MerkleTree<Interval<F>, RescueHash<F>, u64, ARITY, IntervalNode<F>>
+where
+    F: RescueParameter,
+{
+    /// Returns the leaves whose interval contains `x`, with their membership
+    /// proofs, in index order.
+    /// * `returns` - Err() if some leaves which may contain `x` have been
+    ///   forgotten.
+    #[allow(clippy::type_complexity)]
+    pub fn query_point(
+        &self,
+        x: F,
+    ) -> Result<Vec<(u64, MerkleProof<Interval<F>, u64, IntervalNode<F>, ARITY>)>, PrimitivesError>
+    {
+        self.query_overlap(x, x)
+    }
+
+    /// Returns the leaves whose interval overlaps `[low, high]`, with their
+    /// membership proofs, in index order. Subtrees are pruned using the bounds
+    /// aggregated in their node value.
+    /// * `returns` - Err() if `low > high`, or if some leaves which may
+    ///   overlap have been forgotten.
+    #[allow(clippy::type_complexity)]
+    pub fn query_overlap(
+        &self,
+        low: F,
+        high: F,
+    ) -> Result<Vec<(u64, MerkleProof<Interval<F>, u64, IntervalNode<F>, ARITY>)>, PrimitivesError>
+    {
+        if low > high {
+            return Err(PrimitivesError::ParameterError(
+                "Empty query interval".to_string(),
+            ));
+        }
+        self.search(|node| node.low <= high && node.high >= low)?
+            .into_iter()
+            .map(|pos| {
+                let (_, proof) = self.lookup(pos).expect_ok()?;
+                Ok((pos, proof))
+            })
+            .collect()
     }
 }
 
 /// Interval merkle tree instantiation for interval merkle tree using Rescue
 /// hash function.
-pub type IntervalMerkleTree<F> = MerkleTree<Interval<F>, RescueHash<F>, u64, U3, F>;
+pub type IntervalMerkleTree<F> = MerkleTree<Interval<F>, RescueHash<F>, u64, 3, IntervalNode<F>>;
 
 /// Update the array length here
 #[derive(Default, Eq, PartialEq, Clone, Copy, Debug, Ord, PartialOrd, Hash)]
+#[tagged("SHA3_NODE")]
 pub struct Sha3Node(pub(crate) [u8; 32]);
 
 impl AsRef<[u8]> for Sha3Node {
@@ -83,27 +193,608 @@ impl Valid for Sha3Node {
     }
 }
 
+/// Domain separation tag for leaf hashes in [`Sha3Digest`], [`Blake3Digest`]
+/// and [`Shake256Digest`]
+const LEAF_HASH_DOM_SEP: [u8; 1] = [0u8];
+/// Domain separation tag for internal node hashes in [`Sha3Digest`],
+/// [`Blake3Digest`] and [`Shake256Digest`]
+const INTERNAL_HASH_DOM_SEP: [u8; 1] = [1u8];
+
+/// Input of a byte oriented hash function for a leaf: the leaf domain
+/// separation byte `0` followed by the compressed serialization of the
+/// position and of the element. It makes any element and index implementing
+/// [`CanonicalSerialize`] usable with such hash functions, e.g. in
+/// [`Sha3Digest`].
+pub fn leaf_hash_input<E, I>(pos: &I, elem: &E) -> Result<Vec<u8>, PrimitivesError>
+where
+    E: CanonicalSerialize,
+    I: CanonicalSerialize,
+{
+    let mut input = LEAF_HASH_DOM_SEP.to_vec();
+    pos.serialize_compressed(&mut input)?;
+    elem.serialize_compressed(&mut input)?;
+    Ok(input)
+}
+
 /// Wrapper for SHA3_512 hash function
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct Sha3Digest();
 
-impl<E: Element + CanonicalSerialize, I: Index> DigestAlgorithm<E, I, Sha3Node> for Sha3Digest {
+impl<E: Element + CanonicalSerialize, I: Index + CanonicalSerialize> DigestAlgorithm<E, I, Sha3Node>
+    for Sha3Digest
+{
     fn digest(data: &[Sha3Node]) -> Result<Sha3Node, PrimitivesError> {
         let mut hasher = Sha3_256::new();
+        hasher.update(INTERNAL_HASH_DOM_SEP);
         for value in data {
             hasher.update(value);
         }
         Ok(Sha3Node(hasher.finalize().into()))
     }
 
-    fn digest_leaf(_pos: &I, elem: &E) -> Result<Sha3Node, PrimitivesError> {
-        let mut writer = Vec::new();
-        elem.serialize_compressed(&mut writer).unwrap();
-        let mut hasher = Sha3_256::new();
-        hasher.update(writer);
-        Ok(Sha3Node(hasher.finalize().into()))
+    fn digest_leaf(pos: &I, elem: &E) -> Result<Sha3Node, PrimitivesError> {
+        let input = leaf_hash_input(pos, elem)?;
+        Ok(Sha3Node(Sha3_256::digest(input).into()))
     }
 }
 
 /// Merkle tree using SHA3 hash
-pub type SHA3MerkleTree<E> = MerkleTree<E, Sha3Digest, u64, U3, Sha3Node>;
+pub type SHA3MerkleTree<E> = MerkleTree<E, Sha3Digest, u64, 3, Sha3Node>;
+
+/// Internal and root node value of a [`Blake3MerkleTree`]
+#[derive(Default, Eq, PartialEq, Clone, Copy, Debug, Ord, PartialOrd, Hash)]
+#[tagged("BLAKE3_NODE")]
+pub struct Blake3Node(pub(crate) [u8; 32]);
+
+impl AsRef<[u8]> for Blake3Node {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl CanonicalSerialize for Blake3Node {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        _compress: Compress,
+    ) -> Result<(), SerializationError> {
+        writer.write_all(&self.0)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        32
+    }
+}
+impl CanonicalDeserialize for Blake3Node {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        _compress: Compress,
+        _validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut ret = [0u8; 32];
+        reader.read_exact(&mut ret)?;
+        Ok(Blake3Node(ret))
+    }
+}
+
+impl Valid for Blake3Node {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+/// Wrapper for BLAKE3 hash function
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Blake3Digest();
+
+impl<E: Element + CanonicalSerialize, I: Index + CanonicalSerialize>
+    DigestAlgorithm<E, I, Blake3Node> for Blake3Digest
+{
+    fn digest(data: &[Blake3Node]) -> Result<Blake3Node, PrimitivesError> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&INTERNAL_HASH_DOM_SEP);
+        for value in data {
+            hasher.update(value.as_ref());
+        }
+        Ok(Blake3Node(hasher.finalize().into()))
+    }
+
+    fn digest_leaf(pos: &I, elem: &E) -> Result<Blake3Node, PrimitivesError> {
+        let input = leaf_hash_input(pos, elem)?;
+        Ok(Blake3Node(blake3::hash(&input).into()))
+    }
+}
+
+/// Merkle tree using BLAKE3 hash
+pub type Blake3MerkleTree<E> = MerkleTree<E, Blake3Digest, u64, 3, Blake3Node>;
+
+/// Node value made of `N` bytes, for hash functions with a configurable
+/// output length, e.g. 20 bytes for address sized nodes or 64 bytes for wide
+/// ones.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Ord, PartialOrd, Hash)]
+#[tagged("BYTE_NODE")]
+pub struct ByteNode<const N: usize>(pub(crate) [u8; N]);
+
+// `Default` is only derived for arrays of up to 32 elements.
+impl<const N: usize> Default for ByteNode<N> {
+    fn default() -> Self {
+        ByteNode([0u8; N])
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ByteNode<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> CanonicalSerialize for ByteNode<N> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        _compress: Compress,
+    ) -> Result<(), SerializationError> {
+        writer.write_all(&self.0)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        N
+    }
+}
+impl<const N: usize> CanonicalDeserialize for ByteNode<N> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        _compress: Compress,
+        _validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut ret = [0u8; N];
+        reader.read_exact(&mut ret)?;
+        Ok(ByteNode(ret))
+    }
+}
+
+impl<const N: usize> Valid for ByteNode<N> {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+/// Wrapper for SHAKE256 hash function, whose output length is the size of
+/// the [`ByteNode`]
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Shake256Digest();
+
+impl<E: Element + CanonicalSerialize, I: Index + CanonicalSerialize, const N: usize>
+    DigestAlgorithm<E, I, ByteNode<N>> for Shake256Digest
+{
+    fn digest(data: &[ByteNode<N>]) -> Result<ByteNode<N>, PrimitivesError> {
+        let mut input = INTERNAL_HASH_DOM_SEP.to_vec();
+        for value in data {
+            input.extend_from_slice(value.as_ref());
+        }
+        Ok(shake256(&input))
+    }
+
+    fn digest_leaf(pos: &I, elem: &E) -> Result<ByteNode<N>, PrimitivesError> {
+        Ok(shake256(&leaf_hash_input(pos, elem)?))
+    }
+}
+
+fn shake256<const N: usize>(input: &[u8]) -> ByteNode<N> {
+    let mut hasher = Shake256::default();
+    // `digest::Update` is not imported, as its `update` would be ambiguous
+    // with the one of `Digest` for the fixed output hashes.
+    digest::Update::update(&mut hasher, input);
+    let mut ret = ByteNode::default();
+    hasher.finalize_xof().read(&mut ret.0);
+    ret
+}
+
+/// Merkle tree using SHAKE256 hash with `N` bytes node values
+pub type ShakeMerkleTree<E, const N: usize> = MerkleTree<E, Shake256Digest, u64, 3, ByteNode<N>>;
+
+/// Internal and root node value of a [`KeccakMerkleTree`]
+#[derive(Default, Eq, PartialEq, Clone, Copy, Debug, Ord, PartialOrd, Hash)]
+#[tagged("KECCAK256_NODE")]
+pub struct Keccak256Node(pub(crate) [u8; 32]);
+
+impl AsRef<[u8]> for Keccak256Node {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl CanonicalSerialize for Keccak256Node {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        _compress: Compress,
+    ) -> Result<(), SerializationError> {
+        writer.write_all(&self.0)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        32
+    }
+}
+impl CanonicalDeserialize for Keccak256Node {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        _compress: Compress,
+        _validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut ret = [0u8; 32];
+        reader.read_exact(&mut ret)?;
+        Ok(Keccak256Node(ret))
+    }
+}
+
+impl Valid for Keccak256Node {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+/// Wrapper for the Keccak-256 hash function used by the EVM, so that roots
+/// and proofs can be checked by a Solidity contract.
+///
+/// The encoding matches `abi.encodePacked`:
+/// - a leaf is `keccak256(abi.encodePacked(uint64(pos), elem))`, i.e. the
+///   8-byte big-endian position followed by the raw bytes of the element;
+/// - an internal node is `keccak256(abi.encodePacked(child_0, ..., child_n))`,
+///   its children values concatenated from left to right;
+/// - an empty subtree is `bytes32(0)`.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Keccak256Digest();
+
+impl<E: Element + AsRef<[u8]>> DigestAlgorithm<E, u64, Keccak256Node> for Keccak256Digest {
+    fn digest(data: &[Keccak256Node]) -> Result<Keccak256Node, PrimitivesError> {
+        let mut hasher = Keccak256::new();
+        for value in data {
+            hasher.update(value);
+        }
+        Ok(Keccak256Node(hasher.finalize().into()))
+    }
+
+    fn digest_leaf(pos: &u64, elem: &E) -> Result<Keccak256Node, PrimitivesError> {
+        let mut hasher = Keccak256::new();
+        hasher.update(pos.to_be_bytes());
+        hasher.update(elem);
+        Ok(Keccak256Node(hasher.finalize().into()))
+    }
+}
+
+/// Binary merkle tree using Keccak-256 hash, see [`Keccak256Digest`] for the
+/// encoding.
+pub type KeccakMerkleTree<E> = MerkleTree<E, Keccak256Digest, u64, 2, Keccak256Node>;
+
+#[cfg(test)]
+mod mt_tests {
+    use super::*;
+    use crate::{
+        merkle_tree::{
+            internal::{MerkleNode, MerkleProof},
+            MerkleCommitment, MerkleTreeScheme,
+        },
+        test_vectors::{self, MerkleRootVectors},
+    };
+    use ark_ed_on_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as Fq254;
+    use ark_std::{boxed::Box, vec};
+
+    #[test]
+    fn test_interval_mt_query() {
+        test_interval_mt_query_helper::<Fq254>();
+        test_interval_mt_query_helper::<Fq377>();
+        test_interval_mt_query_helper::<Fq381>();
+    }
+
+    fn test_interval_mt_query_helper<F: RescueParameter>() {
+        let intervals = [
+            (10u64, 20u64),
+            (0, 5),
+            (15, 30),
+            (40, 50),
+            (18, 18),
+            (60, 70),
+        ]
+        .map(|(low, high)| Interval(F::from(low), F::from(high)));
+        let mt = IntervalMerkleTree::<F>::from_elems(2, intervals).unwrap();
+        let root = mt.commitment().digest();
+        let positions = |hits: Vec<(u64, MerkleProof<_, _, _, 3>)>| {
+            hits.into_iter()
+                .map(|(pos, proof)| {
+                    assert!(IntervalMerkleTree::<F>::verify(root, pos, &proof)
+                        .unwrap()
+                        .is_ok());
+                    assert_eq!(proof.elem(), Some(&intervals[pos as usize]));
+                    pos
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Point hits, including on the bounds, and misses.
+        assert_eq!(
+            positions(mt.query_point(F::from(18u64)).unwrap()),
+            [0, 2, 4]
+        );
+        assert_eq!(positions(mt.query_point(F::from(5u64)).unwrap()), [1]);
+        assert_eq!(positions(mt.query_point(F::from(70u64)).unwrap()), [5]);
+        assert!(mt.query_point(F::from(35u64)).unwrap().is_empty());
+        assert!(mt.query_point(F::from(100u64)).unwrap().is_empty());
+
+        // Overlaps.
+        assert_eq!(
+            positions(mt.query_overlap(F::from(25u64), F::from(45u64)).unwrap()),
+            [2, 3]
+        );
+        assert_eq!(
+            positions(mt.query_overlap(F::from(0u64), F::from(100u64)).unwrap()),
+            [0, 1, 2, 3, 4, 5]
+        );
+        assert!(mt
+            .query_overlap(F::from(51u64), F::from(59u64))
+            .unwrap()
+            .is_empty());
+        assert!(mt.query_overlap(F::from(2u64), F::from(1u64)).is_err());
+    }
+
+    #[test]
+    fn test_interval_mt_aggregate_range() {
+        test_interval_mt_aggregate_range_helper::<Fq254>();
+        test_interval_mt_aggregate_range_helper::<Fq377>();
+        test_interval_mt_aggregate_range_helper::<Fq381>();
+    }
+
+    fn test_interval_mt_aggregate_range_helper<F: RescueParameter>() {
+        let intervals = [(10u64, 20u64), (0, 5), (15, 30), (40, 50)]
+            .map(|(low, high)| Interval(F::from(low), F::from(high)));
+        let mt = IntervalMerkleTree::<F>::from_elems(2, intervals).unwrap();
+        let root = mt.commitment().digest();
+        assert_eq!(root.range(), Some((F::from(0u64), F::from(50u64))));
+        assert_eq!(IntervalNode::<F>::default().range(), None);
+
+        let (_, proof) = mt.lookup(0).expect_ok().unwrap();
+        assert!(IntervalMerkleTree::<F>::verify(root, 0, &proof)
+            .unwrap()
+            .is_ok());
+
+        // A wrong aggregate range for the root, even with the right digest.
+        let mut bad_root = root;
+        bad_root.low = F::from(1u64);
+        assert!(IntervalMerkleTree::<F>::verify(bad_root, 0, &proof)
+            .unwrap()
+            .is_err());
+        let mut bad_root = root;
+        bad_root.high = F::from(100u64);
+        assert!(IntervalMerkleTree::<F>::verify(bad_root, 0, &proof)
+            .unwrap()
+            .is_err());
+
+        // A wrong aggregate range for a sibling, including one which does not
+        // change the range of the parent.
+        for (low, high) in [(0u64, 100u64), (12, 30)] {
+            let mut bad_proof = proof.clone();
+            if let MerkleNode::Branch { children, .. } = &mut bad_proof.proof[1] {
+                let mut value = children[2].value();
+                value.low = F::from(low);
+                value.high = F::from(high);
+                children[2] = Box::new(MerkleNode::ForgettenSubtree { value });
+            }
+            assert!(IntervalMerkleTree::<F>::verify(root, 0, &bad_proof)
+                .unwrap()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_sha3_mt_lookup_and_verify() {
+        test_sha3_mt_lookup_and_verify_helper::<Fq254>();
+        test_sha3_mt_lookup_and_verify_helper::<Fq377>();
+        test_sha3_mt_lookup_and_verify_helper::<Fq381>();
+    }
+
+    fn test_sha3_mt_lookup_and_verify_helper<F: RescueParameter>() {
+        let elems = [F::from(3u64), F::from(1u64), F::from(4u64)];
+        let mt = SHA3MerkleTree::<F>::from_elems(2, elems).unwrap();
+        let root = mt.commitment().digest();
+        for (i, expected) in elems.iter().enumerate() {
+            let (elem, proof) = mt.lookup(i as u64).expect_ok().unwrap();
+            assert_eq!(elem, expected);
+            assert!(SHA3MerkleTree::<F>::verify(root, i as u64, &proof)
+                .unwrap()
+                .is_ok());
+        }
+
+        // A proof for one index should not verify at another.
+        let (_, proof) = mt.lookup(0).expect_ok().unwrap();
+        assert!(SHA3MerkleTree::<F>::verify(root, 1u64, proof)
+            .unwrap()
+            .is_err());
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq, Hash, CanonicalSerialize)]
+    struct Account<F: Field> {
+        id: u64,
+        name: Vec<u8>,
+        balance: F,
+    }
+
+    #[test]
+    fn test_sha3_mt_root_vector_file() {
+        let vectors: MerkleRootVectors = test_vectors::load("sha3_merkle_tree_ed_on_bn254.json");
+        assert_eq!(vectors.arity, 3);
+        assert!(!vectors.vectors.is_empty());
+        for vector in &vectors.vectors {
+            let mt = SHA3MerkleTree::<Fq254>::from_elems(vector.height, vector.elems::<Fq254>())
+                .unwrap();
+            assert_eq!(mt.commitment().digest().0[..], vector.root_bytes()[..]);
+        }
+    }
+
+    #[test]
+    fn test_sha3_mt_custom_element() {
+        test_sha3_mt_custom_element_helper::<Fq254>();
+        test_sha3_mt_custom_element_helper::<Fq377>();
+        test_sha3_mt_custom_element_helper::<Fq381>();
+    }
+
+    fn test_sha3_mt_custom_element_helper<F: RescueParameter>() {
+        let accounts =
+            [(7u64, "alice", 100u64), (3, "bob", 5), (9, "carol", 0)].map(|(id, name, balance)| {
+                Account {
+                    id,
+                    name: name.as_bytes().to_vec(),
+                    balance: F::from(balance),
+                }
+            });
+        let mt = SHA3MerkleTree::<Account<F>>::from_elems(2, &accounts).unwrap();
+        let root = mt.commitment().digest();
+        for (i, expected) in accounts.iter().enumerate() {
+            let (elem, proof) = mt.lookup(i as u64).expect_ok().unwrap();
+            assert_eq!(elem, expected);
+            assert!(SHA3MerkleTree::<Account<F>>::verify(root, i as u64, &proof)
+                .unwrap()
+                .is_ok());
+        }
+
+        // The leaf digest is the one of the serialized position and element.
+        let leaf = <Sha3Digest as DigestAlgorithm<Account<F>, u64, Sha3Node>>::digest_leaf(
+            &1,
+            &accounts[1],
+        )
+        .unwrap();
+        let mut input = vec![0u8];
+        input.extend_from_slice(&1u64.to_le_bytes());
+        accounts[1].serialize_compressed(&mut input).unwrap();
+        assert_eq!(leaf_hash_input(&1u64, &accounts[1]).unwrap(), input);
+        assert_eq!(leaf.as_ref(), Sha3_256::digest(&input).as_slice());
+
+        // A tampered element does not verify.
+        let (_, mut proof) = mt.lookup(0).expect_ok().unwrap();
+        if let MerkleNode::Leaf { elem, .. } = &mut proof.proof[0] {
+            elem.balance = F::from(1000u64);
+        }
+        assert!(SHA3MerkleTree::<Account<F>>::verify(root, 0u64, &proof)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_blake3_mt_lookup_and_verify() {
+        test_blake3_mt_lookup_and_verify_helper::<Fq254>();
+        test_blake3_mt_lookup_and_verify_helper::<Fq377>();
+        test_blake3_mt_lookup_and_verify_helper::<Fq381>();
+    }
+
+    fn test_blake3_mt_lookup_and_verify_helper<F: RescueParameter>() {
+        let elems = [F::from(3u64), F::from(1u64), F::from(4u64), F::from(1u64)];
+        let mt = Blake3MerkleTree::<F>::from_elems(2, elems).unwrap();
+        let root = mt.commitment().digest();
+        for (i, expected) in elems.iter().enumerate() {
+            let (elem, proof) = mt.lookup(i as u64).expect_ok().unwrap();
+            assert_eq!(elem, expected);
+            assert!(Blake3MerkleTree::<F>::verify(root, i as u64, &proof)
+                .unwrap()
+                .is_ok());
+        }
+
+        // A proof should not verify at another index holding an equal element.
+        let (_, proof) = mt.lookup(1).expect_ok().unwrap();
+        assert!(Blake3MerkleTree::<F>::verify(root, 3u64, proof)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_shake_mt_lookup_and_verify() {
+        test_shake_mt_lookup_and_verify_helper::<Fq254, 20>();
+        test_shake_mt_lookup_and_verify_helper::<Fq254, 64>();
+        test_shake_mt_lookup_and_verify_helper::<Fq377, 20>();
+        test_shake_mt_lookup_and_verify_helper::<Fq381, 64>();
+    }
+
+    fn test_shake_mt_lookup_and_verify_helper<F: RescueParameter, const N: usize>() {
+        let elems = [F::from(3u64), F::from(1u64), F::from(4u64), F::from(1u64)];
+        let mt = ShakeMerkleTree::<F, N>::from_elems(2, elems).unwrap();
+        let root = mt.commitment().digest();
+        assert_eq!(root.as_ref().len(), N);
+        for (i, expected) in elems.iter().enumerate() {
+            let (elem, proof) = mt.lookup(i as u64).expect_ok().unwrap();
+            assert_eq!(elem, expected);
+            assert!(ShakeMerkleTree::<F, N>::verify(root, i as u64, &proof)
+                .unwrap()
+                .is_ok());
+        }
+
+        // A proof should not verify at another index holding an equal element.
+        let (_, proof) = mt.lookup(1).expect_ok().unwrap();
+        assert!(ShakeMerkleTree::<F, N>::verify(root, 3u64, &proof)
+            .unwrap()
+            .is_err());
+
+        // The node values are serialized as their `N` bytes.
+        let bytes = bincode::serialize(&mt).unwrap();
+        let de: ShakeMerkleTree<F, N> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(de.commitment(), mt.commitment());
+        assert_eq!(root.serialized_size(Compress::Yes), N);
+        let json = serde_json::to_string(&root).unwrap();
+        assert!(json.starts_with("\"BYTE_NODE~"));
+        assert_eq!(serde_json::from_str::<ByteNode<N>>(&json).unwrap(), root);
+    }
+
+    #[test]
+    fn test_keccak_mt_test_vector() {
+        let elems = [b"alice".to_vec(), b"bob".to_vec(), b"carol".to_vec()];
+        let mt = KeccakMerkleTree::<Vec<u8>>::from_elems(2, &elems).unwrap();
+        let root = mt.commitment().digest();
+        // Computed with an independent Keccak-256 implementation as
+        // keccak256(keccak256(l0 || l1) || keccak256(l2 || bytes32(0))), where
+        // li = keccak256(uint64(i) || elems[i]).
+        let expected = [
+            0xb9, 0x1f, 0xe9, 0xaf, 0x4d, 0x5e, 0xf6, 0x50, 0xac, 0x5c, 0xec, 0xd8, 0x2b, 0xe0,
+            0xf4, 0x4c, 0xac, 0x45, 0x56, 0x34, 0x3a, 0xdf, 0xf5, 0xfb, 0xe0, 0xa2, 0x84, 0x05,
+            0xcf, 0xdd, 0xa7, 0x03,
+        ];
+        assert_eq!(root.as_ref(), expected);
+
+        for (i, expected) in elems.iter().enumerate() {
+            let (elem, proof) = mt.lookup(i as u64).expect_ok().unwrap();
+            assert_eq!(elem, expected);
+            assert!(KeccakMerkleTree::<Vec<u8>>::verify(root, i as u64, &proof)
+                .unwrap()
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_sha3_mt_json_serde() {
+        test_sha3_mt_json_serde_helper::<Fq254>();
+        test_sha3_mt_json_serde_helper::<Fq377>();
+        test_sha3_mt_json_serde_helper::<Fq381>();
+    }
+
+    fn test_sha3_mt_json_serde_helper<F: RescueParameter>() {
+        let mt = SHA3MerkleTree::<F>::from_elems(2, [F::from(3u64), F::from(1u64)]).unwrap();
+        let commitment = mt.commitment();
+        let proof = mt.lookup(1).expect_ok().unwrap().1;
+
+        let de_commitment: <SHA3MerkleTree<F> as MerkleTreeScheme>::Commitment =
+            serde_json::from_str(&serde_json::to_string(&commitment).unwrap()).unwrap();
+        assert_eq!(commitment, de_commitment);
+        let root = commitment.digest();
+        let de_root: Sha3Node =
+            serde_json::from_str(&serde_json::to_string(&root).unwrap()).unwrap();
+        assert_eq!(root, de_root);
+        let de_proof: <SHA3MerkleTree<F> as MerkleTreeScheme>::MembershipProof =
+            serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+        assert_eq!(proof, de_proof);
+        assert!(SHA3MerkleTree::<F>::verify(de_root, 1, de_proof)
+            .unwrap()
+            .is_ok());
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! The frontier of an append only merkle tree, for light clients following an
+//! append only log. It only keeps the values of the full subtrees on the left
+//! of the next position, which is enough to append leaves and to compute the
+//! root, without the rest of the tree.
+
+use super::{internal::MerkleTreeCommitment, DigestAlgorithm, Element, Index, NodeValue};
+use crate::errors::{PrimitivesError, VerificationResult};
+use ark_std::{borrow::Borrow, marker::PhantomData, string::ToString, vec, vec::Vec};
+use derivative::Derivative;
+use jf_utils::canonical;
+use serde::{Deserialize, Serialize};
+
+/// Frontier of an append only merkle tree, i.e. at most `ARITY - 1` values of
+/// full subtrees per level. The root after appending some leaves is the same
+/// as the one of a [`MerkleTree`](super::append_only::MerkleTree) with the
+/// same leaves.
+#[derive(Derivative, Serialize, Deserialize)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    Hash(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct MerkleFrontier<E, H, I, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index,
+    T: NodeValue,
+{
+    height: usize,
+    num_leaves: u64,
+    /// Values of the full subtrees of height `i` on the left of the next
+    /// position, for each level `i` from the leaves upward
+    #[serde(with = "canonical")]
+    levels: Vec<Vec<T>>,
+    #[serde(with = "canonical")]
+    root: T,
+
+    _phantom: PhantomData<(E, H, I)>,
+}
+
+impl<E, H, I, const ARITY: usize, T> MerkleFrontier<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64>,
+    T: NodeValue,
+{
+    /// Frontier of an empty merkle tree of the given height
+    pub fn new(height: usize) -> Self {
+        Self {
+            height,
+            num_leaves: 0,
+            levels: vec![vec![]; height],
+            root: T::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Height of the merkle tree
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of leaves appended so far
+    pub fn num_leaves(&self) -> u64 {
+        self.num_leaves
+    }
+
+    /// Current root of the merkle tree
+    pub fn root(&self) -> T {
+        self.root
+    }
+
+    /// Commitment of the merkle tree, equal to the one of the full tree
+    pub fn commitment(&self) -> MerkleTreeCommitment<T> {
+        MerkleTreeCommitment::new(self.root, self.height, self.num_leaves)
+    }
+
+    /// Append a new leaf, given its digest.
+    /// * `leaf_digest` - [`DigestAlgorithm::digest_leaf`] of the new element
+    ///   at position [`Self::num_leaves`]
+    /// * `returns` - the new root, Err() if the tree is full or if the
+    ///   frontier is malformed, as a deserialized one may be
+    pub fn append(&mut self, leaf_digest: impl Borrow<T>) -> Result<T, PrimitivesError> {
+        let capacity = u32::try_from(self.height)
+            .ok()
+            .and_then(|height| (ARITY as u64).checked_pow(height));
+        self.check_levels(capacity)?;
+        if matches!(capacity, Some(capacity) if self.num_leaves >= capacity) {
+            return Err(PrimitivesError::IndexOutOfRange(
+                "Merkle tree is full".to_string(),
+            ));
+        }
+        // Merge the subtrees which become full, from the leaves upward.
+        let mut value = *leaf_digest.borrow();
+        let mut level = 0;
+        while level < self.height {
+            self.levels[level].push(value);
+            if self.levels[level].len() < ARITY {
+                break;
+            }
+            value = H::digest(&self.levels[level])?;
+            self.levels[level].clear();
+            level += 1;
+        }
+        self.num_leaves += 1;
+        // A tree where every subtree got merged is full, `value` being its root.
+        self.root = if level == self.height {
+            value
+        } else {
+            self.compute_root()?
+        };
+        Ok(self.root)
+    }
+
+    /// Append a new element, at position [`Self::num_leaves`].
+    /// * `returns` - the new root, Err() if the tree is full
+    pub fn push(&mut self, elem: impl Borrow<E>) -> Result<T, PrimitivesError> {
+        let leaf_digest = H::digest_leaf(&I::from(self.num_leaves), elem.borrow())?;
+        self.append(leaf_digest)
+    }
+
+    /// Verify that appending leaves to this frontier gives the expected root.
+    /// * `leaf_digests` - digests of the appended leaves, in order
+    /// * `returns` - Ok(Ok(())) if the new root is the expected one,
+    ///   Ok(Err(())) if not. Err() if the leaves do not fit in the tree.
+    pub fn verify_append(
+        &self,
+        leaf_digests: &[T],
+        expected_root: impl Borrow<T>,
+    ) -> Result<VerificationResult, PrimitivesError> {
+        let mut frontier = self.clone();
+        for leaf_digest in leaf_digests {
+            frontier.append(leaf_digest)?;
+        }
+        if frontier.root == *expected_root.borrow() {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(()))
+        }
+    }
+
+    // There is one level per height, holding as many full subtrees as the
+    // digit of `num_leaves` in base `ARITY` at this level.
+    fn check_levels(&self, capacity: Option<u64>) -> Result<(), PrimitivesError> {
+        let mut num_leaves = self.num_leaves;
+        let well_formed = self.levels.len() == self.height
+            && capacity.map_or(true, |capacity| self.num_leaves <= capacity)
+            && self.levels.iter().all(|level| {
+                let digit = num_leaves % ARITY as u64;
+                num_leaves /= ARITY as u64;
+                level.len() as u64 == digit
+            });
+        if well_formed {
+            Ok(())
+        } else {
+            Err(PrimitivesError::ParameterError(
+                "Malformed merkle frontier".to_string(),
+            ))
+        }
+    }
+
+    // The node at each level on the path to the next position has the full
+    // subtrees of the frontier as its leftmost children, followed by the node
+    // below if it is not empty. The other children are empty.
+    fn compute_root(&self) -> Result<T, PrimitivesError> {
+        let mut value: Option<T> = None;
+        for level in &self.levels {
+            let mut children = level.clone();
+            children.extend(value);
+            if children.is_empty() {
+                continue;
+            }
+            children.resize(ARITY, T::default());
+            value = Some(H::digest(&children)?);
+        }
+        Ok(value.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MerkleFrontier;
+    use crate::{
+        errors::PrimitivesError,
+        merkle_tree::{
+            prelude::{RescueHash, RescueMerkleTree},
+            DigestAlgorithm, MerkleCommitment, MerkleTreeScheme,
+        },
+        rescue::RescueParameter,
+    };
+    use ark_ed_on_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as Fq254;
+    use ark_std::vec::Vec;
+
+    type RescueFrontier<F> = MerkleFrontier<F, RescueHash<F>, u64, 3, F>;
+
+    #[test]
+    fn test_frontier_matches_tree() {
+        test_frontier_matches_tree_helper::<Fq254>();
+        test_frontier_matches_tree_helper::<Fq377>();
+        test_frontier_matches_tree_helper::<Fq381>();
+    }
+
+    fn test_frontier_matches_tree_helper<F: RescueParameter>() {
+        let elems = (0..27u64).map(|i| F::from(i + 100)).collect::<Vec<_>>();
+        let mut frontier = RescueFrontier::<F>::new(3);
+        let empty = RescueMerkleTree::<F>::from_elems(3, [] as [F; 0]).unwrap();
+        assert_eq!(frontier.commitment(), empty.commitment());
+
+        // Up to a full tree, going through every partially filled level.
+        for n in 1..=elems.len() {
+            let root = frontier.push(elems[n - 1]).unwrap();
+            let mt = RescueMerkleTree::<F>::from_elems(3, &elems[..n]).unwrap();
+            assert_eq!(root, mt.commitment().digest());
+            assert_eq!(frontier.commitment(), mt.commitment());
+        }
+        assert!(matches!(
+            frontier.push(F::from(0u64)),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_frontier_verify_append() {
+        test_frontier_verify_append_helper::<Fq254>();
+        test_frontier_verify_append_helper::<Fq377>();
+        test_frontier_verify_append_helper::<Fq381>();
+    }
+
+    fn test_frontier_verify_append_helper<F: RescueParameter>() {
+        let elems = (0..20u64).map(F::from).collect::<Vec<_>>();
+        let mut frontier = RescueFrontier::<F>::new(3);
+        for elem in &elems[..7] {
+            frontier.push(elem).unwrap();
+        }
+        let leaf_digests = (7..20u64)
+            .map(|pos| {
+                <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest_leaf(
+                    &pos,
+                    &elems[pos as usize],
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let root = RescueMerkleTree::<F>::from_elems(3, &elems)
+            .unwrap()
+            .commitment()
+            .digest();
+        assert!(frontier.verify_append(&leaf_digests, root).unwrap().is_ok());
+        // The verification does not change the prior frontier.
+        assert_eq!(frontier.num_leaves(), 7);
+
+        // Missing, reordered or too many leaves
+        assert!(frontier
+            .verify_append(&leaf_digests[1..], root)
+            .unwrap()
+            .is_err());
+        let mut swapped = leaf_digests.clone();
+        swapped.swap(0, 1);
+        assert!(frontier.verify_append(&swapped, root).unwrap().is_err());
+        let too_many = [leaf_digests.as_slice(), &leaf_digests[..8]].concat();
+        assert!(frontier.verify_append(&too_many, root).is_err());
+    }
+
+    #[test]
+    fn test_frontier_malformed() {
+        test_frontier_malformed_helper::<Fq254>();
+        test_frontier_malformed_helper::<Fq377>();
+        test_frontier_malformed_helper::<Fq381>();
+    }
+
+    fn test_frontier_malformed_helper<F: RescueParameter>() {
+        let mut frontier = RescueFrontier::<F>::new(3);
+        for i in 0..7u64 {
+            frontier.push(F::from(i)).unwrap();
+        }
+        let bytes = bincode::serialize(&frontier).unwrap();
+        let mut frontier: RescueFrontier<F> = bincode::deserialize(&bytes).unwrap();
+        frontier.push(F::from(7u64)).unwrap();
+
+        // Missing levels, as in a frontier deserialized from untrusted bytes
+        let mut bad_frontier = frontier.clone();
+        bad_frontier.levels.truncate(1);
+        assert!(matches!(
+            bad_frontier.push(F::from(8u64)),
+            Err(PrimitivesError::ParameterError(_))
+        ));
+        // Levels not matching the number of leaves
+        let mut bad_frontier = frontier.clone();
+        bad_frontier.levels[1].push(F::from(0u64));
+        assert!(matches!(
+            bad_frontier.push(F::from(8u64)),
+            Err(PrimitivesError::ParameterError(_))
+        ));
+        let mut bad_frontier = frontier;
+        bad_frontier.num_leaves = 100;
+        assert!(bad_frontier
+            .verify_append(&[F::from(0u64)], F::from(0u64))
+            .is_err());
+    }
+}
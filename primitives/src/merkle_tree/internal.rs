@@ -4,22 +4,27 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-use core::{marker::PhantomData, ops::AddAssign};
+use core::ops::AddAssign;
 
 use super::{
+    store::{NodeId, NodeStore},
     DigestAlgorithm, Element, Index, LookupResult, MerkleCommitment, NodeValue, ToTraversalPath,
 };
 use crate::errors::{PrimitivesError, VerificationResult};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{
     borrow::Borrow, boxed::Box, format, iter::Peekable, string::ToString, vec, vec::Vec,
 };
+use hashbrown::HashMap;
 use itertools::Itertools;
 use jf_utils::canonical;
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 use tagged_base64::tagged;
-use typenum::Unsigned;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(bound = "E: CanonicalSerialize + CanonicalDeserialize,
@@ -72,6 +77,164 @@ where
     }
 }
 
+// Serialization tags of the [`MerkleNode`] variants.
+const EMPTY_NODE_TAG: u8 = 0;
+const BRANCH_NODE_TAG: u8 = 1;
+const LEAF_NODE_TAG: u8 = 2;
+const FORGOTTEN_NODE_TAG: u8 = 3;
+
+/// Largest height of a [`MerkleNode`] deserialized on its own, where the
+/// height of the tree is unknown. It bounds the recursion on untrusted input.
+const MAX_DESERIALIZED_NODE_HEIGHT: usize = 256;
+
+impl<E, I, T> MerkleNode<E, I, T>
+where
+    E: Element + CanonicalSerialize,
+    I: Index + CanonicalSerialize,
+    T: NodeValue,
+{
+    // Works on a `&mut W` so that recursing into the children does not
+    // instantiate an infinite chain of writer types.
+    fn serialize_node<W: Write>(
+        &self,
+        writer: &mut W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match self {
+            Self::Empty => EMPTY_NODE_TAG.serialize_with_mode(&mut *writer, compress),
+            Self::Branch { value, children } => {
+                BRANCH_NODE_TAG.serialize_with_mode(&mut *writer, compress)?;
+                value.serialize_with_mode(&mut *writer, compress)?;
+                (children.len() as u64).serialize_with_mode(&mut *writer, compress)?;
+                for child in children.iter() {
+                    child.serialize_node(writer, compress)?;
+                }
+                Ok(())
+            },
+            Self::Leaf { value, pos, elem } => {
+                LEAF_NODE_TAG.serialize_with_mode(&mut *writer, compress)?;
+                value.serialize_with_mode(&mut *writer, compress)?;
+                pos.serialize_with_mode(&mut *writer, compress)?;
+                elem.serialize_with_mode(&mut *writer, compress)
+            },
+            Self::ForgettenSubtree { value } => {
+                FORGOTTEN_NODE_TAG.serialize_with_mode(&mut *writer, compress)?;
+                value.serialize_with_mode(&mut *writer, compress)
+            },
+        }
+    }
+}
+
+impl<E, I, T> CanonicalSerialize for MerkleNode<E, I, T>
+where
+    E: Element + CanonicalSerialize,
+    I: Index + CanonicalSerialize,
+    T: NodeValue,
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.serialize_node(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        1 + match self {
+            Self::Empty => 0,
+            Self::Branch { value, children } => {
+                value.serialized_size(compress)
+                    + 8
+                    + children
+                        .iter()
+                        .map(|child| child.serialized_size(compress))
+                        .sum::<usize>()
+            },
+            Self::Leaf { value, pos, elem } => {
+                value.serialized_size(compress)
+                    + pos.serialized_size(compress)
+                    + elem.serialized_size(compress)
+            },
+            Self::ForgettenSubtree { value } => value.serialized_size(compress),
+        }
+    }
+}
+
+impl<E, I, T> MerkleNode<E, I, T>
+where
+    E: Element + CanonicalDeserialize,
+    I: Index + CanonicalDeserialize,
+    T: NodeValue,
+{
+    // See `serialize_node`. A node of height `height` has no branch below
+    // `height` levels, so that the recursion depth is bounded by the height of
+    // the tree rather than by the input.
+    fn deserialize_node<R: Read>(
+        reader: &mut R,
+        compress: Compress,
+        validate: Validate,
+        height: usize,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(&mut *reader, compress, validate)? {
+            EMPTY_NODE_TAG => Ok(Self::Empty),
+            BRANCH_NODE_TAG => {
+                if height == 0 {
+                    return Err(SerializationError::InvalidData);
+                }
+                let value = T::deserialize_with_mode(&mut *reader, compress, validate)?;
+                let len = u64::deserialize_with_mode(&mut *reader, compress, validate)?;
+                let children = (0..len)
+                    .map(|_| {
+                        Self::deserialize_node(&mut *reader, compress, validate, height - 1)
+                            .map(Box::new)
+                    })
+                    .collect::<Result<Vec<_>, SerializationError>>()?;
+                Ok(Self::Branch { value, children })
+            },
+            LEAF_NODE_TAG => Ok(Self::Leaf {
+                value: T::deserialize_with_mode(&mut *reader, compress, validate)?,
+                pos: I::deserialize_with_mode(&mut *reader, compress, validate)?,
+                elem: E::deserialize_with_mode(&mut *reader, compress, validate)?,
+            }),
+            FORGOTTEN_NODE_TAG => Ok(Self::ForgettenSubtree {
+                value: T::deserialize_with_mode(&mut *reader, compress, validate)?,
+            }),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<E, I, T> CanonicalDeserialize for MerkleNode<E, I, T>
+where
+    E: Element + CanonicalDeserialize,
+    I: Index + CanonicalDeserialize,
+    T: NodeValue,
+{
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_node(
+            &mut reader,
+            compress,
+            validate,
+            MAX_DESERIALIZED_NODE_HEIGHT,
+        )
+    }
+}
+
+impl<E, I, T> Valid for MerkleNode<E, I, T>
+where
+    E: Element + CanonicalDeserialize,
+    I: Index + CanonicalDeserialize,
+    T: NodeValue,
+{
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
 pub type MerklePath<E, I, T> = Vec<MerkleNode<E, I, T>>;
 
 /// A merkle commitment consists a root hash value, a tree height and number of
@@ -126,40 +289,31 @@ impl<T: NodeValue> MerkleCommitment<T> for MerkleTreeCommitment<T> {
 #[derivative(Eq, Hash, PartialEq)]
 #[serde(bound = "E: CanonicalSerialize + CanonicalDeserialize,
              I: CanonicalSerialize + CanonicalDeserialize,")]
-pub struct MerkleProof<E, I, T, Arity>
+pub struct MerkleProof<E, I, T, const ARITY: usize>
 where
     E: Element,
     I: Index,
     T: NodeValue,
-    Arity: Unsigned,
 {
     /// Proof of inclusion for element at index `pos`
     #[serde(with = "canonical")]
     pub pos: I,
     /// Nodes of proof path, from root to leaf
     pub proof: MerklePath<E, I, T>,
-
-    /// Place holder for Arity
-    _phantom_arity: PhantomData<Arity>,
 }
 
-impl<E, I, T, Arity> MerkleProof<E, I, T, Arity>
+impl<E, I, T, const ARITY: usize> MerkleProof<E, I, T, ARITY>
 where
     E: Element,
     I: Index,
     T: NodeValue,
-    Arity: Unsigned,
 {
     pub fn tree_height(&self) -> usize {
         self.proof.len()
     }
 
     pub fn new(pos: I, proof: MerklePath<E, I, T>) -> Self {
-        MerkleProof {
-            pos,
-            proof,
-            _phantom_arity: PhantomData,
-        }
+        MerkleProof { pos, proof }
     }
 
     pub fn index(&self) -> &I {
@@ -174,8 +328,446 @@ where
     }
 }
 
+impl<E, I, T, const ARITY: usize> CanonicalSerialize for MerkleProof<E, I, T, ARITY>
+where
+    E: Element + CanonicalSerialize,
+    I: Index + CanonicalSerialize,
+    T: NodeValue,
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.pos.serialize_with_mode(&mut writer, compress)?;
+        self.proof.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.pos.serialized_size(compress) + self.proof.serialized_size(compress)
+    }
+}
+
+impl<E, I, T, const ARITY: usize> CanonicalDeserialize for MerkleProof<E, I, T, ARITY>
+where
+    E: Element + CanonicalDeserialize,
+    I: Index + CanonicalDeserialize,
+    T: NodeValue,
+{
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let pos = I::deserialize_with_mode(&mut reader, compress, validate)?;
+        // Same encoding as a `Vec`, where the `i`-th node of the path, from
+        // the leaf up, has height `i`.
+        let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+        let proof = (0..len)
+            .map(|height| {
+                MerkleNode::<E, I, T>::deserialize_node(
+                    &mut reader,
+                    compress,
+                    validate,
+                    height as usize,
+                )
+            })
+            .collect::<Result<MerklePath<E, I, T>, SerializationError>>()?;
+        Ok(MerkleProof::new(pos, proof))
+    }
+}
+
+impl<E, I, T, const ARITY: usize> Valid for MerkleProof<E, I, T, ARITY>
+where
+    E: Element + CanonicalDeserialize,
+    I: Index + CanonicalDeserialize,
+    T: NodeValue,
+{
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<E, I, T, const ARITY: usize> MerkleProof<E, I, T, ARITY>
+where
+    E: Element + CanonicalSerialize + CanonicalDeserialize,
+    I: Index + CanonicalSerialize + CanonicalDeserialize,
+    T: NodeValue,
+{
+    /// Encode this proof in a flat, self-describing byte buffer. All integers
+    /// are little-endian, and the index, elements and node values use their
+    /// compressed canonical serialization. The layout is:
+    /// * `arity: u32`, `height: u32`
+    /// * `index_len: u32`, followed by `index_len` bytes of the index
+    /// * `occupied: u8`, 1 for a membership proof and 0 for a non-membership
+    ///   one. If 1, it is followed by `elem_len: u32`, `elem_len` bytes of the
+    ///   element and the value of the leaf.
+    /// * `height * arity` node values, the children of each node on the path
+    ///   from the leaf up to the root. An empty child is encoded as the
+    ///   default node value.
+    ///
+    /// Every node value takes the same number of bytes, so the total length
+    /// is fully determined by the header.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PrimitivesError> {
+        let height = self
+            .proof
+            .len()
+            .checked_sub(1)
+            .ok_or_else(|| PrimitivesError::InvalidProof("Empty merkle proof".to_string()))?;
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&(ARITY as u32).to_le_bytes());
+        bytes.extend_from_slice(&(height as u32).to_le_bytes());
+        write_with_len(&mut bytes, &self.pos)?;
+        match &self.proof[0] {
+            MerkleNode::Leaf { value, elem, .. } => {
+                bytes.push(1);
+                write_with_len(&mut bytes, elem)?;
+                value.serialize_compressed(&mut bytes)?;
+            },
+            MerkleNode::Empty => bytes.push(0),
+            _ => {
+                return Err(PrimitivesError::InvalidProof(
+                    "Malformed merkle proof".to_string(),
+                ))
+            },
+        }
+        for node in self.proof.iter().skip(1) {
+            match node {
+                MerkleNode::Branch { children, .. } if children.len() == ARITY => {
+                    for child in children.iter() {
+                        child.value().serialize_compressed(&mut bytes)?;
+                    }
+                },
+                _ => {
+                    return Err(PrimitivesError::InvalidProof(
+                        "Malformed merkle proof".to_string(),
+                    ))
+                },
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Decode a proof encoded by [`Self::to_bytes`].
+    /// * `returns` - Err() if the buffer is truncated or oversized, if it is
+    ///   for another arity, or if some field cannot be deserialized.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PrimitivesError> {
+        let mut reader = bytes;
+        if read_u32(&mut reader)? as usize != ARITY {
+            return Err(PrimitivesError::InvalidEncoding(
+                "Merkle proof is for another arity".to_string(),
+            ));
+        }
+        let height = read_u32(&mut reader)? as usize;
+        let pos: I = read_with_len(&mut reader)?;
+        let leaf = match read_bytes(&mut reader, 1)?[0] {
+            0 => MerkleNode::Empty,
+            1 => {
+                let elem: E = read_with_len(&mut reader)?;
+                let value = read_node_value(&mut reader)?;
+                MerkleNode::Leaf {
+                    value,
+                    pos: pos.clone(),
+                    elem,
+                }
+            },
+            _ => {
+                return Err(PrimitivesError::InvalidEncoding(
+                    "Invalid leaf tag in merkle proof".to_string(),
+                ))
+            },
+        };
+        // Check the length before allocating anything for the path.
+        let value_size = T::default().compressed_size();
+        if Some(reader.len())
+            != height
+                .checked_mul(ARITY)
+                .and_then(|n| n.checked_mul(value_size))
+        {
+            return Err(PrimitivesError::InvalidEncoding(
+                "Wrong length for a merkle proof".to_string(),
+            ));
+        }
+        let mut proof = Vec::with_capacity(height + 1);
+        proof.push(leaf);
+        for _ in 0..height {
+            let children = (0..ARITY)
+                .map(|_| {
+                    let value: T = read_node_value(&mut reader)?;
+                    Ok(Box::new(if value == T::default() {
+                        MerkleNode::Empty
+                    } else {
+                        MerkleNode::ForgettenSubtree { value }
+                    }))
+                })
+                .collect::<Result<Vec<_>, PrimitivesError>>()?;
+            proof.push(MerkleNode::Branch {
+                value: T::default(),
+                children,
+            });
+        }
+        Ok(MerkleProof::new(pos, proof))
+    }
+}
+
+fn write_with_len<S: CanonicalSerialize>(
+    bytes: &mut Vec<u8>,
+    value: &S,
+) -> Result<(), PrimitivesError> {
+    let len = u32::try_from(value.compressed_size()).map_err(|_| {
+        PrimitivesError::ParameterError("Value too large for a merkle proof".to_string())
+    })?;
+    bytes.extend_from_slice(&len.to_le_bytes());
+    Ok(value.serialize_compressed(bytes)?)
+}
+
+fn read_bytes<'a>(reader: &mut &'a [u8], len: usize) -> Result<&'a [u8], PrimitivesError> {
+    if reader.len() < len {
+        return Err(PrimitivesError::InvalidEncoding(
+            "Truncated merkle proof".to_string(),
+        ));
+    }
+    let (head, tail) = reader.split_at(len);
+    *reader = tail;
+    Ok(head)
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, PrimitivesError> {
+    let bytes = read_bytes(reader, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// Deserialize a value from exactly `len` bytes.
+fn read_exact<D: CanonicalDeserialize>(
+    reader: &mut &[u8],
+    len: usize,
+) -> Result<D, PrimitivesError> {
+    let mut bytes = read_bytes(reader, len)?;
+    let value = D::deserialize_compressed(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(PrimitivesError::InvalidEncoding(
+            "Trailing bytes in a merkle proof field".to_string(),
+        ));
+    }
+    Ok(value)
+}
+
+fn read_with_len<D: CanonicalDeserialize>(reader: &mut &[u8]) -> Result<D, PrimitivesError> {
+    let len = read_u32(reader)? as usize;
+    read_exact(reader, len)
+}
+
+fn read_node_value<T: NodeValue>(reader: &mut &[u8]) -> Result<T, PrimitivesError> {
+    read_exact(reader, T::default().compressed_size())
+}
+
+/// A proof for all the leaves in a contiguous range `[start, end)` of a merkle
+/// tree. Instead of one path per leaf, it only carries the values of the
+/// subtrees adjacent to the range, which are shared by all the leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(bound = "E: CanonicalSerialize + CanonicalDeserialize,
+             I: CanonicalSerialize + CanonicalDeserialize,")]
+pub struct MerkleRangeProof<E, I, T, const ARITY: usize>
+where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+{
+    /// Position of the first leaf in the range
+    #[serde(with = "canonical")]
+    pub start: I,
+    /// Position right after the last leaf in the range
+    #[serde(with = "canonical")]
+    pub end: I,
+    /// Height of the merkle tree
+    pub height: usize,
+    /// Elements in the range, in index order
+    #[serde(with = "canonical")]
+    pub elems: Vec<E>,
+    /// Values of the subtrees adjacent to the range, in depth-first order
+    #[serde(with = "canonical")]
+    pub siblings: Vec<T>,
+}
+
+/// Size of an EVM word, in which field elements are packed.
+const EVM_WORD_SIZE: usize = 32;
+
+impl<F, const ARITY: usize> MerkleProof<F, u64, F, ARITY>
+where
+    F: PrimeField,
+{
+    /// Encode this membership proof as tightly packed 32-byte big-endian
+    /// words, i.e. `uint256` in Solidity, for an on-chain verifier. The layout
+    /// is:
+    /// * the position of the leaf, then the element
+    /// * for each node on the path from the leaf up to the root, its
+    ///   `ARITY - 1` children off the path, in order
+    ///
+    /// The branch taken in each node is not encoded: it is the base-`ARITY`
+    /// digit of the position at that level, least significant first. The
+    /// verifier computes the leaf digest with [`DigestAlgorithm::digest_leaf`]
+    /// and each node digest with [`DigestAlgorithm::digest`] over its `ARITY`
+    /// children, the computed value being inserted at the branch. For
+    /// [`RescueHash`](super::prelude::RescueHash) these are the Rescue sponge
+    /// without padding over `[0, pos, elem]` and over the children. See
+    /// [`Self::verify_evm_encoding`] for a reference implementation.
+    /// * `returns` - Err() if the proof is empty or not a membership proof, or
+    ///   if the field elements do not fit in a word.
+    pub fn encode_for_evm(&self) -> Result<Vec<u8>, PrimitivesError> {
+        check_evm_word_size::<F>()?;
+        let height = self
+            .tree_height()
+            .checked_sub(1)
+            .ok_or_else(|| PrimitivesError::InvalidProof("Empty merkle proof".to_string()))?;
+        let elem = self
+            .elem()
+            .ok_or_else(|| PrimitivesError::InvalidProof("Not a membership proof".to_string()))?;
+        let traversal_path = self.pos.to_traversal_path(height);
+        let mut bytes =
+            Vec::with_capacity(EVM_WORD_SIZE * (2 + traversal_path.len() * (ARITY - 1)));
+        push_evm_word(&mut bytes, &F::from(self.pos));
+        push_evm_word(&mut bytes, elem);
+        for (branch, node) in traversal_path.iter().zip(self.proof.iter().skip(1)) {
+            match node {
+                MerkleNode::Branch { value: _, children } if children.len() == ARITY => {
+                    for (i, child) in children.iter().enumerate() {
+                        if i != *branch {
+                            push_evm_word(&mut bytes, &child.value());
+                        }
+                    }
+                },
+                _ => {
+                    return Err(PrimitivesError::InvalidProof(
+                        "Incompatible proof for this merkle tree".to_string(),
+                    ))
+                },
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Reference implementation of the on-chain verifier of proofs encoded
+    /// with [`Self::encode_for_evm`].
+    /// * `bytes` - encoded proof for a tree of the given height
+    /// * `returns` - Ok(Ok(())) if the proof is accepted, Ok(Err(())) if not.
+    ///   Err() if the encoding is malformed.
+    pub fn verify_evm_encoding<H>(
+        bytes: &[u8],
+        height: usize,
+        expected_root: &F,
+    ) -> Result<VerificationResult, PrimitivesError>
+    where
+        H: DigestAlgorithm<F, u64, F>,
+    {
+        check_evm_word_size::<F>()?;
+        if Some(bytes.len())
+            != height
+                .checked_mul(ARITY - 1)
+                .and_then(|n| n.checked_add(2))
+                .and_then(|n| n.checked_mul(EVM_WORD_SIZE))
+        {
+            return Err(PrimitivesError::InvalidEncoding(
+                "Wrong length for an EVM encoded merkle proof".to_string(),
+            ));
+        }
+        let mut words = bytes.chunks_exact(EVM_WORD_SIZE);
+        let mut next_word = || -> Result<F, PrimitivesError> {
+            let word = words.next().ok_or_else(|| {
+                PrimitivesError::InvalidEncoding("Truncated EVM word".to_string())
+            })?;
+            let value = F::from_be_bytes_mod_order(word);
+            let mut canonical = Vec::with_capacity(EVM_WORD_SIZE);
+            push_evm_word(&mut canonical, &value);
+            if canonical != word {
+                return Err(PrimitivesError::InvalidEncoding(
+                    "Non canonical field element in EVM word".to_string(),
+                ));
+            }
+            Ok(value)
+        };
+        let pos = next_word()?;
+        let pos = match u64::try_from(BigUint::from(pos.into_bigint())) {
+            Ok(pos) => pos,
+            Err(_) => return Ok(Err(())),
+        };
+        let elem = next_word()?;
+        let mut val = H::digest_leaf(&pos, &elem)?;
+        for branch in pos.to_traversal_path(height) {
+            let mut data = (0..ARITY - 1)
+                .map(|_| next_word())
+                .collect::<Result<Vec<_>, PrimitivesError>>()?;
+            data.insert(branch, val);
+            val = H::digest(&data)?;
+        }
+        if val == *expected_root {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(()))
+        }
+    }
+}
+
+fn check_evm_word_size<F: PrimeField>() -> Result<(), PrimitivesError> {
+    if F::MODULUS_BIT_SIZE as usize > 8 * EVM_WORD_SIZE {
+        return Err(PrimitivesError::ParameterError(
+            "Field elements do not fit in an EVM word".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn push_evm_word<F: PrimeField>(bytes: &mut Vec<u8>, value: &F) {
+    let value = value.into_bigint().to_bytes_be();
+    bytes.resize(bytes.len() + EVM_WORD_SIZE - value.len(), 0);
+    bytes.extend_from_slice(&value);
+}
+
+/// A proof for the leaves at arbitrary positions of a merkle tree. The paths
+/// of all the leaves are merged, so that every node needed to recompute the
+/// root is only included once, however many leaves share it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(bound = "E: CanonicalSerialize + CanonicalDeserialize,
+             I: CanonicalSerialize + CanonicalDeserialize,")]
+pub struct MerkleBatchProof<E, I, T, const ARITY: usize>
+where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+{
+    /// Distinct positions of the proven leaves, in index order
+    #[serde(with = "canonical")]
+    pub positions: Vec<I>,
+    /// Height of the merkle tree
+    pub height: usize,
+    /// Elements at the proven positions, in the same order
+    #[serde(with = "canonical")]
+    pub elems: Vec<E>,
+    /// Values of the children off the merged paths, in depth-first order
+    #[serde(with = "canonical")]
+    pub siblings: Vec<T>,
+}
+
+/// A proof that a merkle tree with `new_size` leaves is an append-only
+/// extension of the same tree when it had `old_size` leaves, in the spirit of
+/// RFC 6962 consistency proofs. It carries the values of the subtrees that
+/// are either full or empty in the old tree; the full ones are shared by both
+/// trees, so that both roots can be recomputed from the proof.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MerkleConsistencyProof<T: NodeValue, const ARITY: usize> {
+    /// Number of leaves in the old tree
+    pub old_size: u64,
+    /// Number of leaves in the new tree
+    pub new_size: u64,
+    /// Height of the merkle tree
+    pub height: usize,
+    /// Values of the subtrees full or empty in the old tree, in depth-first
+    /// order. Empty subtrees carry their value in the new tree.
+    #[serde(with = "canonical")]
+    pub proof: Vec<T>,
+}
+
 #[allow(clippy::type_complexity)]
-pub(crate) fn build_tree_internal<E, H, I, Arity, T>(
+pub(crate) fn build_tree_internal<E, H, I, const ARITY: usize, T>(
     height: usize,
     elems: impl IntoIterator<Item = impl Borrow<E>>,
 ) -> Result<(Box<MerkleNode<E, I, T>>, u64), PrimitivesError>
@@ -183,22 +775,21 @@ where
     E: Element,
     H: DigestAlgorithm<E, I, T>,
     I: Index + From<u64>,
-    Arity: Unsigned,
     T: NodeValue,
 {
     let leaves: Vec<_> = elems.into_iter().collect();
     let num_leaves = leaves.len() as u64;
-    let capacity = BigUint::from(Arity::to_u64()).pow(height as u32);
+    let capacity = BigUint::from(ARITY as u64).pow(height as u32);
 
     if BigUint::from(num_leaves) > capacity {
-        Err(PrimitivesError::ParameterError(
+        Err(PrimitivesError::IndexOutOfRange(
             "Too many data for merkle tree".to_string(),
         ))
     } else if num_leaves > 0 {
         let mut cur_nodes = leaves
             .into_iter()
             .enumerate()
-            .chunks(Arity::to_usize())
+            .chunks(ARITY)
             .into_iter()
             .map(|chunk| {
                 let children = chunk
@@ -210,7 +801,7 @@ where
                             elem: elem.borrow().clone(),
                         }))
                     })
-                    .pad_using(Arity::to_usize(), |_| Ok(Box::new(MerkleNode::Empty)))
+                    .pad_using(ARITY, |_| Ok(Box::new(MerkleNode::Empty)))
                     .collect::<Result<Vec<_>, PrimitivesError>>()?;
                 Ok(Box::new(MerkleNode::<E, I, T>::Branch {
                     value: digest_branch::<E, H, I, T>(&children)?,
@@ -221,13 +812,84 @@ where
         for _ in 1..height {
             cur_nodes = cur_nodes
                 .into_iter()
-                .chunks(Arity::to_usize())
+                .chunks(ARITY)
                 .into_iter()
                 .map(|chunk| {
                     let children = chunk
-                        .pad_using(Arity::to_usize(), |_| {
-                            Box::new(MerkleNode::<E, I, T>::Empty)
-                        })
+                        .pad_using(ARITY, |_| Box::new(MerkleNode::<E, I, T>::Empty))
+                        .collect::<Vec<_>>();
+                    Ok(Box::new(MerkleNode::<E, I, T>::Branch {
+                        value: digest_branch::<E, H, I, T>(&children)?,
+                        children,
+                    }))
+                })
+                .collect::<Result<Vec<_>, PrimitivesError>>()?;
+        }
+        Ok((cur_nodes[0].clone(), num_leaves))
+    } else {
+        Ok((Box::new(MerkleNode::<E, I, T>::Empty), 0))
+    }
+}
+
+/// Similar to [`build_tree_internal`], but the digests of every level of the
+/// tree are computed in parallel.
+#[cfg(feature = "parallel")]
+#[allow(clippy::type_complexity)]
+pub(crate) fn build_tree_internal_parallel<E, H, I, const ARITY: usize, T>(
+    height: usize,
+    elems: impl IntoIterator<Item = impl Borrow<E>>,
+) -> Result<(Box<MerkleNode<E, I, T>>, u64), PrimitivesError>
+where
+    E: Element + Send + Sync,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + Send,
+    T: NodeValue + Send,
+{
+    use rayon::prelude::*;
+
+    let leaves: Vec<E> = elems
+        .into_iter()
+        .map(|elem| elem.borrow().clone())
+        .collect();
+    let num_leaves = leaves.len() as u64;
+    let capacity = BigUint::from(ARITY as u64).pow(height as u32);
+
+    if BigUint::from(num_leaves) > capacity {
+        Err(PrimitivesError::IndexOutOfRange(
+            "Too many data for merkle tree".to_string(),
+        ))
+    } else if num_leaves > 0 {
+        let mut cur_nodes = leaves
+            .par_chunks(ARITY)
+            .enumerate()
+            .map(|(chunk_idx, chunk)| {
+                let children = chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, elem)| {
+                        let pos = I::from((chunk_idx * ARITY + idx) as u64);
+                        Ok(Box::new(MerkleNode::Leaf {
+                            value: H::digest_leaf(&pos, elem)?,
+                            pos,
+                            elem: elem.clone(),
+                        }))
+                    })
+                    .pad_using(ARITY, |_| Ok(Box::new(MerkleNode::Empty)))
+                    .collect::<Result<Vec<_>, PrimitivesError>>()?;
+                Ok(Box::new(MerkleNode::<E, I, T>::Branch {
+                    value: digest_branch::<E, H, I, T>(&children)?,
+                    children,
+                }))
+            })
+            .collect::<Result<Vec<_>, PrimitivesError>>()?;
+        for _ in 1..height {
+            cur_nodes = cur_nodes
+                .into_par_iter()
+                .chunks(ARITY)
+                .map(|chunk| {
+                    let children = chunk
+                        .into_iter()
+                        .pad_using(ARITY, |_| Box::new(MerkleNode::<E, I, T>::Empty))
                         .collect::<Vec<_>>();
                     Ok(Box::new(MerkleNode::<E, I, T>::Branch {
                         value: digest_branch::<E, H, I, T>(&children)?,
@@ -243,7 +905,7 @@ where
 }
 
 #[allow(clippy::type_complexity)]
-pub(crate) fn build_light_weight_tree_internal<E, H, I, Arity, T>(
+pub(crate) fn build_light_weight_tree_internal<E, H, I, const ARITY: usize, T>(
     height: usize,
     elems: impl IntoIterator<Item = impl Borrow<E>>,
 ) -> Result<(Box<MerkleNode<E, I, T>>, u64), PrimitivesError>
@@ -251,24 +913,23 @@ where
     E: Element,
     H: DigestAlgorithm<E, I, T>,
     I: Index + From<u64>,
-    Arity: Unsigned,
     T: NodeValue,
 {
     let leaves: Vec<_> = elems.into_iter().collect();
     let num_leaves = leaves.len() as u64;
-    let capacity = num_traits::checked_pow(Arity::to_u64(), height).ok_or_else(|| {
+    let capacity = num_traits::checked_pow(ARITY as u64, height).ok_or_else(|| {
         PrimitivesError::ParameterError("Merkle tree size too large.".to_string())
     })?;
 
     if num_leaves > capacity {
-        Err(PrimitivesError::ParameterError(
+        Err(PrimitivesError::IndexOutOfRange(
             "Too many data for merkle tree".to_string(),
         ))
     } else if num_leaves > 0 {
         let mut cur_nodes = leaves
             .into_iter()
             .enumerate()
-            .chunks(Arity::to_usize())
+            .chunks(ARITY)
             .into_iter()
             .map(|chunk| {
                 let children = chunk
@@ -286,7 +947,7 @@ where
                             })
                         })
                     })
-                    .pad_using(Arity::to_usize(), |_| Ok(Box::new(MerkleNode::Empty)))
+                    .pad_using(ARITY, |_| Ok(Box::new(MerkleNode::Empty)))
                     .collect::<Result<Vec<_>, PrimitivesError>>()?;
                 Ok(Box::new(MerkleNode::<E, I, T>::Branch {
                     value: digest_branch::<E, H, I, T>(&children)?,
@@ -302,13 +963,11 @@ where
         for _ in 1..height {
             cur_nodes = cur_nodes
                 .into_iter()
-                .chunks(Arity::to_usize())
+                .chunks(ARITY)
                 .into_iter()
                 .map(|chunk| {
                     let children = chunk
-                        .pad_using(Arity::to_usize(), |_| {
-                            Box::new(MerkleNode::<E, I, T>::Empty)
-                        })
+                        .pad_using(ARITY, |_| Box::new(MerkleNode::<E, I, T>::Empty))
                         .collect::<Vec<_>>();
                     Ok(Box::new(MerkleNode::<E, I, T>::Branch {
                         value: digest_branch::<E, H, I, T>(&children)?,
@@ -423,7 +1082,7 @@ where
     }
 
     /// Re-insert a forgotten leaf to the Merkle tree if the proof is valid.
-    pub(crate) fn remember_internal<H, Arity>(
+    pub(crate) fn remember_internal<H, const ARITY: usize>(
         &mut self,
         height: usize,
         traversal_path: &[usize],
@@ -432,10 +1091,9 @@ where
     ) -> Result<(), PrimitivesError>
     where
         H: DigestAlgorithm<E, I, T>,
-        Arity: Unsigned,
     {
         if self.value() != path_values[height] {
-            return Err(PrimitivesError::ParameterError(format!(
+            return Err(PrimitivesError::InvalidProof(format!(
                 "Invalid proof. Hash differs at height {}: (expected: {:?}, received: {:?})",
                 height,
                 self.value(),
@@ -447,7 +1105,7 @@ where
             (Self::ForgettenSubtree { value }, Self::Branch { children, .. }) => {
                 // Recurse into the appropriate sub-tree to remember the rest of the path.
                 let mut children = children.clone();
-                children[traversal_path[height - 1]].remember_internal::<H, Arity>(
+                children[traversal_path[height - 1]].remember_internal::<H, ARITY>(
                     height - 1,
                     traversal_path,
                     path_values,
@@ -468,13 +1126,13 @@ where
             },
             (Self::Branch { children, .. }, Self::Branch { .. }) => children
                 [traversal_path[height - 1]]
-                .remember_internal::<H, Arity>(height - 1, traversal_path, path_values, proof),
+                .remember_internal::<H, ARITY>(height - 1, traversal_path, path_values, proof),
             (Self::Leaf { .. }, Self::Leaf { .. }) | (Self::Empty, Self::Empty) => {
                 // This node is already a complete sub-tree, so there's nothing to remember. The
                 // proof matches, so just return success.
                 Ok(())
             },
-            (..) => Err(PrimitivesError::ParameterError("Invalid proof".into())),
+            (..) => Err(PrimitivesError::InvalidProof("Invalid proof".into())),
         }
     }
 
@@ -544,7 +1202,7 @@ where
     }
 
     /// Update the element at the given index.
-    pub(crate) fn update_internal<H, Arity>(
+    pub(crate) fn update_internal<H, const ARITY: usize>(
         &mut self,
         height: usize,
         pos: impl Borrow<I>,
@@ -553,7 +1211,6 @@ where
     ) -> Result<LookupResult<E, (), ()>, PrimitivesError>
     where
         H: DigestAlgorithm<E, I, T>,
-        Arity: Unsigned,
     {
         let pos = pos.borrow();
         let elem = elem.borrow();
@@ -568,7 +1225,7 @@ where
                 Ok(LookupResult::Ok(ret, ()))
             },
             MerkleNode::Branch { value, children } => {
-                let res = (*children[traversal_path[height - 1]]).update_internal::<H, Arity>(
+                let res = (*children[traversal_path[height - 1]]).update_internal::<H, ARITY>(
                     height - 1,
                     pos,
                     traversal_path,
@@ -593,8 +1250,8 @@ where
                         elem: elem.clone(),
                     }
                 } else {
-                    let mut children = vec![Box::new(MerkleNode::Empty); Arity::to_usize()];
-                    (*children[traversal_path[height - 1]]).update_internal::<H, Arity>(
+                    let mut children = vec![Box::new(MerkleNode::Empty); ARITY];
+                    (*children[traversal_path[height - 1]]).update_internal::<H, ARITY>(
                         height - 1,
                         pos,
                         traversal_path,
@@ -611,36 +1268,628 @@ where
         }
     }
 
-    /// Batch insertion for the given Merkle node.
-    pub(crate) fn extend_internal<H, Arity>(
+    /// Apply several updates at once, with the same outcome as applying them
+    /// one after the other with [`Self::update_internal`], but computing the
+    /// value of every touched node only once.
+    /// * `updates` - the index in `results` of each update, its position,
+    ///   traversal path and new element, in the order they are applied
+    /// * `results` - where the result of each update is written
+    pub(crate) fn update_batch_internal<H, const ARITY: usize>(
         &mut self,
         height: usize,
-        pos: &I,
-        traversal_path: &[usize],
-        at_frontier: bool,
-        data: &mut Peekable<impl Iterator<Item = impl Borrow<E>>>,
-    ) -> Result<u64, PrimitivesError>
+        updates: &[(usize, &I, &[usize], &E)],
+        results: &mut [LookupResult<E, (), ()>],
+    ) -> Result<(), PrimitivesError>
     where
         H: DigestAlgorithm<E, I, T>,
-        Arity: Unsigned,
-        I: AddAssign,
     {
-        if data.peek().is_none() {
-            return Ok(0);
+        if updates.is_empty() {
+            return Ok(());
         }
-        let mut cur_pos = pos.clone();
         match self {
+            MerkleNode::Leaf {
+                elem: node_elem,
+                value,
+                pos,
+            } => {
+                for (i, _, _, elem) in updates {
+                    let ret = ark_std::mem::replace(node_elem, (*elem).clone());
+                    results[*i] = LookupResult::Ok(ret, ());
+                }
+                *value = H::digest_leaf(pos, node_elem)?;
+                Ok(())
+            },
             MerkleNode::Branch { value, children } => {
-                let mut cnt = 0u64;
-                let mut frontier = if at_frontier {
-                    traversal_path[height - 1]
-                } else {
-                    0
-                };
-                let cap = Arity::to_usize();
-                while data.peek().is_some() && frontier < cap {
-                    let increment = children[frontier].extend_internal::<H, Arity>(
-                        height - 1,
+                let mut branches = vec![vec![]; ARITY];
+                for update in updates {
+                    branches[update.2[height - 1]].push(*update);
+                }
+                for (child, branch) in children.iter_mut().zip(branches.iter()) {
+                    child.update_batch_internal::<H, ARITY>(height - 1, branch, results)?;
+                }
+                // As for a single update, the value of this node only changes if some update
+                // reached a subtree in memory.
+                if updates
+                    .iter()
+                    .any(|(i, ..)| results[*i] != LookupResult::NotInMemory)
+                {
+                    *value = digest_branch::<E, H, I, T>(children)?;
+                }
+                Ok(())
+            },
+            MerkleNode::Empty => {
+                if height == 0 {
+                    let (i, pos, _, elem) = updates[0];
+                    *self = MerkleNode::Leaf {
+                        value: H::digest_leaf(pos, elem)?,
+                        pos: pos.clone(),
+                        elem: elem.clone(),
+                    };
+                    results[i] = LookupResult::NotFound(());
+                    self.update_batch_internal::<H, ARITY>(height, &updates[1..], results)
+                } else {
+                    // The value is computed by the recursive call on the new branch.
+                    *self = MerkleNode::Branch {
+                        value: T::default(),
+                        children: vec![Box::new(MerkleNode::Empty); ARITY],
+                    };
+                    self.update_batch_internal::<H, ARITY>(height, updates, results)
+                }
+            },
+            MerkleNode::ForgettenSubtree { .. } => {
+                for (i, ..) in updates {
+                    results[*i] = LookupResult::NotInMemory;
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Remove the element at the given index. Branches whose children are all
+    /// empty after the removal collapse into an empty node, so that the tree
+    /// is the same as if the element had never been inserted.
+    /// A leaf at the end of the traversal path is only removed if it is at
+    /// position `pos`, so that a position beyond the capacity of the tree,
+    /// whose path wraps around to another leaf, is not found.
+    pub(crate) fn remove_internal<H, const ARITY: usize>(
+        &mut self,
+        height: usize,
+        pos: &I,
+        traversal_path: &[usize],
+    ) -> Result<LookupResult<E, (), ()>, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        match self {
+            MerkleNode::Leaf {
+                elem,
+                pos: leaf_pos,
+                ..
+            } => {
+                if leaf_pos != pos {
+                    return Ok(LookupResult::NotFound(()));
+                }
+                let elem = elem.clone();
+                *self = MerkleNode::Empty;
+                Ok(LookupResult::Ok(elem, ()))
+            },
+            MerkleNode::Branch { value, children } => {
+                let res = (*children[traversal_path[height - 1]]).remove_internal::<H, ARITY>(
+                    height - 1,
+                    pos,
+                    traversal_path,
+                )?;
+                // Nothing changed if the element was not there.
+                if !matches!(res, LookupResult::Ok(..)) {
+                    return Ok(res);
+                }
+                if children
+                    .iter()
+                    .all(|child| matches!(**child, MerkleNode::Empty))
+                {
+                    *self = MerkleNode::Empty;
+                } else {
+                    *value = digest_branch::<E, H, I, T>(children)?;
+                }
+                Ok(res)
+            },
+            MerkleNode::Empty => Ok(LookupResult::NotFound(())),
+            MerkleNode::ForgettenSubtree { .. } => Ok(LookupResult::NotInMemory),
+        }
+    }
+
+    /// Position of the rightmost occupied leaf under this node.
+    pub(crate) fn max_index_internal(&self) -> LookupResult<&I, (), ()> {
+        match self {
+            MerkleNode::Leaf { pos, .. } => LookupResult::Ok(pos, ()),
+            MerkleNode::Branch { children, .. } => children
+                .iter()
+                .rev()
+                .find(|child| !matches!(***child, MerkleNode::Empty))
+                .map_or(LookupResult::NotFound(()), |child| {
+                    child.max_index_internal()
+                }),
+            MerkleNode::ForgettenSubtree { .. } => LookupResult::NotInMemory,
+            MerkleNode::Empty => LookupResult::NotFound(()),
+        }
+    }
+
+    /// Collect the positions of the leaves that differ between this node and
+    /// `other`, which is at the same place in another tree. Subtrees with the
+    /// same value are skipped without being visited.
+    pub(crate) fn diff_internal(
+        &self,
+        other: &Self,
+        positions: &mut Vec<I>,
+    ) -> Result<(), PrimitivesError> {
+        if self.value() == other.value() {
+            return Ok(());
+        }
+        match (self, other) {
+            (
+                MerkleNode::Branch { children, .. },
+                MerkleNode::Branch {
+                    children: other_children,
+                    ..
+                },
+            ) => {
+                for (child, other_child) in children.iter().zip(other_children.iter()) {
+                    child.diff_internal(other_child, positions)?;
+                }
+            },
+            (MerkleNode::ForgettenSubtree { .. }, _) | (_, MerkleNode::ForgettenSubtree { .. }) => {
+                return Err(PrimitivesError::ParameterError(
+                    "Cannot diff forgotten leaves".to_string(),
+                ))
+            },
+            (MerkleNode::Leaf { pos, .. }, MerkleNode::Leaf { pos: other_pos, .. }) => {
+                positions.push(pos.clone());
+                if pos != other_pos {
+                    positions.push(other_pos.clone());
+                }
+            },
+            (MerkleNode::Leaf { pos, .. }, node) | (node, MerkleNode::Leaf { pos, .. }) => {
+                positions.push(pos.clone());
+                node.diff_internal(&MerkleNode::Empty, positions)?;
+            },
+            (MerkleNode::Branch { children, .. }, MerkleNode::Empty)
+            | (MerkleNode::Empty, MerkleNode::Branch { children, .. }) => {
+                for child in children.iter() {
+                    child.diff_internal(&MerkleNode::Empty, positions)?;
+                }
+            },
+            (MerkleNode::Empty, MerkleNode::Empty) => {},
+        }
+        Ok(())
+    }
+
+    /// Collect the elements in `[start, end)` under this node, and the values
+    /// of the subtrees adjacent to the range, in depth-first order. `lo` is
+    /// the position of the leftmost leaf under this node.
+    pub(crate) fn range_proof_internal<const ARITY: usize>(
+        &self,
+        height: usize,
+        lo: u64,
+        start: &I,
+        end: &I,
+        elems: &mut Vec<E>,
+        siblings: &mut Vec<T>,
+    ) -> Result<(), PrimitivesError> {
+        if !subtree_in_range::<I, ARITY>(lo, height, start, end) {
+            siblings.push(self.value());
+            return Ok(());
+        }
+        match self {
+            MerkleNode::Leaf { elem, .. } => {
+                elems.push(elem.clone());
+                Ok(())
+            },
+            MerkleNode::Branch { value: _, children } => {
+                for (i, child) in children.iter().enumerate() {
+                    match child_lo::<ARITY>(lo, i, height - 1) {
+                        Some(child_lo) => child.range_proof_internal::<ARITY>(
+                            height - 1,
+                            child_lo,
+                            start,
+                            end,
+                            elems,
+                            siblings,
+                        )?,
+                        None => siblings.push(child.value()),
+                    }
+                }
+                Ok(())
+            },
+            MerkleNode::Empty => Err(PrimitivesError::ParameterError(
+                "Range contains empty leaves".to_string(),
+            )),
+            MerkleNode::ForgettenSubtree { .. } => Err(PrimitivesError::ParameterError(
+                "Range contains forgotten leaves".to_string(),
+            )),
+        }
+    }
+
+    /// Collect the elements at the given positions under this node, and the
+    /// values of the children off their paths, in depth-first order. `paths`
+    /// come from [`batch_traversal_paths`] and all go through this node.
+    pub(crate) fn batch_proof_internal(
+        &self,
+        height: usize,
+        paths: &[(I, Vec<usize>)],
+        elems: &mut Vec<E>,
+        siblings: &mut Vec<T>,
+    ) -> Result<(), PrimitivesError> {
+        match self {
+            MerkleNode::Leaf { pos, elem, .. } if *pos == paths[0].0 => {
+                elems.push(elem.clone());
+                Ok(())
+            },
+            MerkleNode::Branch { value: _, children } => {
+                let mut paths = paths;
+                for (i, child) in children.iter().enumerate() {
+                    let group = split_by_branch(&mut paths, height - 1, i);
+                    if group.is_empty() {
+                        siblings.push(child.value());
+                    } else {
+                        child.batch_proof_internal(height - 1, group, elems, siblings)?;
+                    }
+                }
+                Ok(())
+            },
+            MerkleNode::ForgettenSubtree { .. } => Err(PrimitivesError::ParameterError(
+                "Batch contains forgotten leaves".to_string(),
+            )),
+            _ => Err(PrimitivesError::ParameterError(
+                "Batch contains empty leaves".to_string(),
+            )),
+        }
+    }
+
+    /// Value of this subtree in the same tree truncated to its first `size`
+    /// leaves. `lo` is the position of the leftmost leaf under this node.
+    pub(crate) fn prefix_value_internal<H, const ARITY: usize>(
+        &self,
+        height: usize,
+        lo: u64,
+        size: u64,
+    ) -> Result<T, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        match prefix_coverage::<ARITY>(lo, height, size) {
+            PrefixCoverage::Full => Ok(self.value()),
+            PrefixCoverage::Empty => Ok(T::default()),
+            PrefixCoverage::Partial => match self {
+                MerkleNode::Branch { value: _, children } => {
+                    let children = children
+                        .iter()
+                        .enumerate()
+                        .map(|(i, child)| {
+                            child.prefix_value_internal::<H, ARITY>(
+                                height - 1,
+                                child_lo::<ARITY>(lo, i, height - 1).unwrap_or(u64::MAX),
+                                size,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, PrimitivesError>>()?;
+                    H::digest(&children)
+                },
+                MerkleNode::ForgettenSubtree { .. } => Err(PrimitivesError::ParameterError(
+                    "Consistency proof needs forgotten leaves".to_string(),
+                )),
+                _ => Err(PrimitivesError::InternalError(
+                    "Occupied positions are missing from the tree".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// Same as [`Self::lookup_internal`], but in the tree truncated to its
+    /// first `size` leaves. `lo` is the position of the leftmost leaf under
+    /// this node, and the position looked up must be smaller than `size`.
+    pub(crate) fn lookup_prefix_internal<H, const ARITY: usize>(
+        &self,
+        height: usize,
+        lo: u64,
+        size: u64,
+        traversal_path: &[usize],
+    ) -> Result<LookupResult<&E, MerklePath<E, I, T>, ()>, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        match self {
+            MerkleNode::Branch { value: _, children } => {
+                let branch = traversal_path[height - 1];
+                let mut siblings = Vec::with_capacity(ARITY);
+                for (i, child) in children.iter().enumerate() {
+                    let child_lo = child_lo::<ARITY>(lo, i, height - 1).unwrap_or(u64::MAX);
+                    siblings.push(Box::new(
+                        match prefix_coverage::<ARITY>(child_lo, height - 1, size) {
+                            PrefixCoverage::Empty => MerkleNode::Empty,
+                            _ => MerkleNode::ForgettenSubtree {
+                                value: child.prefix_value_internal::<H, ARITY>(
+                                    height - 1,
+                                    child_lo,
+                                    size,
+                                )?,
+                            },
+                        },
+                    ));
+                }
+                let child_lo = child_lo::<ARITY>(lo, branch, height - 1).unwrap_or(u64::MAX);
+                match children[branch].lookup_prefix_internal::<H, ARITY>(
+                    height - 1,
+                    child_lo,
+                    size,
+                    traversal_path,
+                )? {
+                    LookupResult::Ok(elem, mut proof) => {
+                        proof.push(MerkleNode::Branch {
+                            value: T::default(),
+                            children: siblings,
+                        });
+                        Ok(LookupResult::Ok(elem, proof))
+                    },
+                    res => Ok(res),
+                }
+            },
+            MerkleNode::Leaf { elem, .. } => Ok(LookupResult::Ok(elem, vec![self.clone()])),
+            MerkleNode::ForgettenSubtree { .. } => Ok(LookupResult::NotInMemory),
+            MerkleNode::Empty => Err(PrimitivesError::InternalError(
+                "Occupied position is empty".to_string(),
+            )),
+        }
+    }
+
+    /// Collect the values of the subtrees under this node that are full or
+    /// empty in the tree truncated to `old_size` leaves, in depth-first order.
+    /// Empty ones are given their value in the tree truncated to `new_size`
+    /// leaves. `lo` is the position of the leftmost leaf under this node.
+    pub(crate) fn consistency_proof_internal<H, const ARITY: usize>(
+        &self,
+        height: usize,
+        lo: u64,
+        old_size: u64,
+        new_size: u64,
+        proof: &mut Vec<T>,
+    ) -> Result<(), PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        match prefix_coverage::<ARITY>(lo, height, old_size) {
+            PrefixCoverage::Full => proof.push(self.value()),
+            PrefixCoverage::Empty => {
+                proof.push(self.prefix_value_internal::<H, ARITY>(height, lo, new_size)?)
+            },
+            PrefixCoverage::Partial => match self {
+                MerkleNode::Branch { value: _, children } => {
+                    for (i, child) in children.iter().enumerate() {
+                        child.consistency_proof_internal::<H, ARITY>(
+                            height - 1,
+                            child_lo::<ARITY>(lo, i, height - 1).unwrap_or(u64::MAX),
+                            old_size,
+                            new_size,
+                            proof,
+                        )?;
+                    }
+                },
+                MerkleNode::ForgettenSubtree { .. } => {
+                    return Err(PrimitivesError::ParameterError(
+                        "Consistency proof needs forgotten leaves".to_string(),
+                    ))
+                },
+                _ => {
+                    return Err(PrimitivesError::InternalError(
+                        "Occupied positions are missing from the tree".to_string(),
+                    ))
+                },
+            },
+        }
+        Ok(())
+    }
+
+    /// Write this node and all its descendants to `store`, under their
+    /// traversal path from the root. Branches are stored without their
+    /// children, and empty subtrees are not stored at all.
+    pub(crate) fn store_internal<S>(
+        &self,
+        path: &mut Vec<usize>,
+        store: &mut S,
+    ) -> Result<(), PrimitivesError>
+    where
+        S: NodeStore<Self>,
+    {
+        match self {
+            MerkleNode::Empty => Ok(()),
+            MerkleNode::Branch { value, children } => {
+                store.put(
+                    NodeId(path.clone()),
+                    MerkleNode::Branch {
+                        value: *value,
+                        children: vec![],
+                    },
+                )?;
+                for (i, child) in children.iter().enumerate() {
+                    path.push(i);
+                    child.store_internal(path, store)?;
+                    path.pop();
+                }
+                Ok(())
+            },
+            _ => store.put(NodeId(path.clone()), self.clone()),
+        }
+    }
+
+    /// Check that this subtree, at the end of `path` from the root, is well
+    /// formed: branches have `ARITY` children, the value of every node is the
+    /// digest of its children, and every leaf is at its own position. The
+    /// values of forgotten subtrees cannot be checked.
+    /// * `returns` - the number of leaves in memory, and whether some
+    ///   subtree has been forgotten
+    pub(crate) fn check_internal<H, const ARITY: usize>(
+        &self,
+        height: usize,
+        path: &mut Vec<usize>,
+    ) -> Result<(u64, bool), PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+        I: ToTraversalPath<ARITY>,
+    {
+        let corrupted = || PrimitivesError::InvalidEncoding("Corrupted merkle tree".to_string());
+        match self {
+            MerkleNode::Empty => Ok((0, false)),
+            MerkleNode::ForgettenSubtree { .. } => Ok((0, true)),
+            MerkleNode::Leaf { value, pos, elem } => {
+                let mut leaf_path = pos.to_traversal_path(path.len());
+                leaf_path.reverse();
+                if height != 0 || leaf_path != *path || *value != H::digest_leaf(pos, elem)? {
+                    return Err(corrupted());
+                }
+                Ok((1, false))
+            },
+            MerkleNode::Branch { value, children } => {
+                if height == 0 || children.len() != ARITY {
+                    return Err(corrupted());
+                }
+                let (mut num_leaves, mut forgotten) = (0, false);
+                for (i, child) in children.iter().enumerate() {
+                    path.push(i);
+                    let (n, f) = child.check_internal::<H, ARITY>(height - 1, path)?;
+                    path.pop();
+                    num_leaves += n;
+                    forgotten |= f;
+                }
+                let data = children
+                    .iter()
+                    .map(|child| child.value())
+                    .collect::<Vec<_>>();
+                if *value != H::digest(&data)? {
+                    return Err(corrupted());
+                }
+                Ok((num_leaves, forgotten))
+            },
+        }
+    }
+
+    /// Bring the end of `traversal_path` back in memory from the nodes
+    /// written to `store` by [`Self::store_internal`]. Forgotten subtrees met
+    /// on the way are read from the store: the children of a branch are only
+    /// kept if their values digest to the value of the branch, and a leaf only
+    /// if its value is the digest of its element and it is at its own
+    /// position.
+    /// * `path` - traversal path from the root to this node
+    /// * `returns` - Err() if the store is corrupted or cannot be read
+    pub(crate) fn fetch_internal<H, S, const ARITY: usize>(
+        &mut self,
+        height: usize,
+        traversal_path: &[usize],
+        path: &mut Vec<usize>,
+        store: &S,
+    ) -> Result<(), PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+        I: ToTraversalPath<ARITY>,
+        S: NodeStore<Self>,
+    {
+        let corrupted = || PrimitivesError::InvalidEncoding("Corrupted node store".to_string());
+        if let MerkleNode::ForgettenSubtree { value } = self {
+            let value = *value;
+            match store.get(&NodeId(path.clone()))? {
+                None if value == T::default() => *self = MerkleNode::Empty,
+                Some(MerkleNode::Leaf {
+                    value: leaf_value,
+                    pos,
+                    elem,
+                }) => {
+                    let mut leaf_path = pos.to_traversal_path(path.len());
+                    leaf_path.reverse();
+                    if height != 0
+                        || leaf_value != value
+                        || leaf_path != *path
+                        || leaf_value != H::digest_leaf(&pos, &elem)?
+                    {
+                        return Err(corrupted());
+                    }
+                    *self = MerkleNode::Leaf {
+                        value: leaf_value,
+                        pos,
+                        elem,
+                    };
+                },
+                Some(MerkleNode::Branch {
+                    value: branch_value,
+                    ..
+                }) if height != 0 && branch_value == value => {
+                    let mut children = Vec::with_capacity(ARITY);
+                    for i in 0..ARITY {
+                        path.push(i);
+                        let child = store.get(&NodeId(path.clone()))?;
+                        path.pop();
+                        children.push(Box::new(match child {
+                            None => MerkleNode::Empty,
+                            Some(child) => MerkleNode::ForgettenSubtree {
+                                value: child.value(),
+                            },
+                        }));
+                    }
+                    if branch_value != digest_branch::<E, H, I, T>(&children)? {
+                        return Err(corrupted());
+                    }
+                    *self = MerkleNode::Branch {
+                        value: branch_value,
+                        children,
+                    };
+                },
+                // The subtree was forgotten when it was stored.
+                Some(MerkleNode::ForgettenSubtree { value: stored }) if stored == value => {
+                    return Ok(())
+                },
+                _ => return Err(corrupted()),
+            }
+        }
+        if let MerkleNode::Branch { children, .. } = self {
+            let branch = traversal_path[height - 1];
+            path.push(branch);
+            children[branch].fetch_internal::<H, S, ARITY>(
+                height - 1,
+                traversal_path,
+                path,
+                store,
+            )?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// Batch insertion for the given Merkle node.
+    pub(crate) fn extend_internal<H, const ARITY: usize>(
+        &mut self,
+        height: usize,
+        pos: &I,
+        traversal_path: &[usize],
+        at_frontier: bool,
+        data: &mut Peekable<impl Iterator<Item = impl Borrow<E>>>,
+    ) -> Result<u64, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+        I: AddAssign,
+    {
+        if data.peek().is_none() {
+            return Ok(0);
+        }
+        let mut cur_pos = pos.clone();
+        match self {
+            MerkleNode::Branch { value, children } => {
+                let mut cnt = 0u64;
+                let mut frontier = if at_frontier {
+                    traversal_path[height - 1]
+                } else {
+                    0
+                };
+                let cap = ARITY;
+                while data.peek().is_some() && frontier < cap {
+                    let increment = children[frontier].extend_internal::<H, ARITY>(
+                        height - 1,
                         &cur_pos,
                         traversal_path,
                         at_frontier && frontier == traversal_path[height - 1],
@@ -670,10 +1919,10 @@ where
                     } else {
                         0
                     };
-                    let cap = Arity::to_usize();
+                    let cap = ARITY;
                     let mut children = vec![Box::new(MerkleNode::Empty); cap];
                     while data.peek().is_some() && frontier < cap {
-                        let increment = children[frontier].extend_internal::<H, Arity>(
+                        let increment = children[frontier].extend_internal::<H, ARITY>(
                             height - 1,
                             &cur_pos,
                             traversal_path,
@@ -702,7 +1951,7 @@ where
 
     /// Similar to [`extend_internal`], but this function will automatically
     /// forget every leaf except for the Merkle tree frontier.
-    pub(crate) fn extend_and_forget_internal<H, Arity>(
+    pub(crate) fn extend_and_forget_internal<H, const ARITY: usize>(
         &mut self,
         height: usize,
         pos: &I,
@@ -712,7 +1961,6 @@ where
     ) -> Result<u64, PrimitivesError>
     where
         H: DigestAlgorithm<E, I, T>,
-        Arity: Unsigned,
         I: AddAssign,
     {
         if data.peek().is_none() {
@@ -727,7 +1975,7 @@ where
                 } else {
                     0
                 };
-                let cap = Arity::to_usize();
+                let cap = ARITY;
                 while data.peek().is_some() && frontier < cap {
                     if frontier > 0 && !children[frontier - 1].is_forgotten() {
                         children[frontier - 1] =
@@ -735,7 +1983,7 @@ where
                                 value: children[frontier - 1].value(),
                             });
                     }
-                    let increment = children[frontier].extend_internal::<H, Arity>(
+                    let increment = children[frontier].extend_internal::<H, ARITY>(
                         height - 1,
                         &cur_pos,
                         traversal_path,
@@ -766,7 +2014,7 @@ where
                     } else {
                         0
                     };
-                    let cap = Arity::to_usize();
+                    let cap = ARITY;
                     let mut children = vec![Box::new(MerkleNode::Empty); cap];
                     while data.peek().is_some() && frontier < cap {
                         if frontier > 0 && !children[frontier - 1].is_forgotten() {
@@ -775,7 +2023,7 @@ where
                                     value: children[frontier - 1].value(),
                                 });
                         }
-                        let increment = children[frontier].extend_internal::<H, Arity>(
+                        let increment = children[frontier].extend_internal::<H, ARITY>(
                             height - 1,
                             &cur_pos,
                             traversal_path,
@@ -803,70 +2051,237 @@ where
     }
 }
 
-impl<E, I, T, Arity> MerkleProof<E, I, T, Arity>
+impl<E, I, T, const ARITY: usize> MerkleProof<E, I, T, ARITY>
 where
     E: Element,
-    I: Index + From<u64> + ToTraversalPath<Arity>,
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
     T: NodeValue,
-    Arity: Unsigned,
 {
-    /// Verify a membership proof by comparing the computed root value to the
-    /// expected one.
-    pub(crate) fn verify_membership_proof<H>(
-        &self,
-        expected_root: &T,
-    ) -> Result<VerificationResult, PrimitivesError>
+    /// Refresh this membership proof after another leaf of the tree changed,
+    /// e.g. after a new leaf was appended, without access to the tree.
+    /// * `other` - membership proof of the changed leaf in the new tree
+    ///
+    /// Only the nodes from the lowest common ancestor of both leaves up to the
+    /// root are patched, and the result is the same proof as a fresh lookup
+    /// in the new tree.
+    pub fn update_with<H>(&mut self, other: &Self) -> Result<(), PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        let height = self
+            .tree_height()
+            .checked_sub(1)
+            .ok_or_else(|| PrimitivesError::InvalidProof("Empty merkle proof".to_string()))?;
+        let other_elem = match other.proof.first() {
+            Some(MerkleNode::Leaf { pos, elem, .. }) if *pos == other.pos => elem,
+            _ => {
+                return Err(PrimitivesError::InvalidProof(
+                    "Expected a membership proof of the changed leaf".to_string(),
+                ))
+            },
+        };
+        if other.tree_height() != self.tree_height() {
+            return Err(PrimitivesError::InvalidProof(
+                "Incompatible proof for this merkle tree".to_string(),
+            ));
+        }
+        let path = self.pos.to_traversal_path(height);
+        let other_path = other.pos.to_traversal_path(height);
+        // The lowest common ancestor is the node at level `lca + 1`.
+        let lca = match (0..height)
+            .rev()
+            .find(|&level| path[level] != other_path[level])
+        {
+            Some(lca) => lca,
+            None => {
+                *self = other.clone();
+                return Ok(());
+            },
+        };
+
+        // Value of the subtree holding the changed leaf right below the common
+        // ancestor, in the new tree.
+        let mut value = H::digest_leaf(&other.pos, other_elem)?;
+        for level in 0..lca {
+            match &other.proof[level + 1] {
+                MerkleNode::Branch { children, .. } if children.len() == ARITY => {
+                    let mut data = children.iter().map(|node| node.value()).collect::<Vec<_>>();
+                    data[other_path[level]] = value;
+                    value = H::digest(&data)?;
+                },
+                _ => {
+                    return Err(PrimitivesError::InvalidProof(
+                        "Incompatible proof for this merkle tree".to_string(),
+                    ))
+                },
+            }
+        }
+        match &mut self.proof[lca + 1] {
+            MerkleNode::Branch { children, .. } if children.len() == ARITY => {
+                children[other_path[lca]] = Box::new(MerkleNode::ForgettenSubtree { value });
+            },
+            _ => {
+                return Err(PrimitivesError::InvalidProof(
+                    "Incompatible proof for this merkle tree".to_string(),
+                ))
+            },
+        }
+        // Above the common ancestor, both leaves share the same path.
+        self.proof[lca + 2..].clone_from_slice(&other.proof[lca + 2..]);
+        Ok(())
+    }
+
+    /// Compute the root value from the proven leaf and the siblings of this
+    /// proof, e.g. to compare it with several candidate roots.
+    ///
+    /// Returns Err() if the position of the proven leaf does not match the
+    /// index of this proof, or if the proof is not well structured.
+    pub fn compute_root<H>(&self) -> Result<T, PrimitivesError>
     where
         H: DigestAlgorithm<E, I, T>,
-        Arity: Unsigned,
     {
-        if let MerkleNode::<E, I, T>::Leaf {
+        if let Some(MerkleNode::<E, I, T>::Leaf {
             value: _,
             pos,
             elem,
-        } = &self.proof[0]
+        }) = self.proof.first()
         {
+            if *pos != self.pos {
+                return Err(PrimitivesError::InvalidProof(
+                    "Leaf position does not match the proof index".to_string(),
+                ));
+            }
             let init = H::digest_leaf(pos, elem)?;
-            let computed_root = self
-                .pos
+            self.pos
                 .to_traversal_path(self.tree_height() - 1)
                 .iter()
                 .zip(self.proof.iter().skip(1))
                 .try_fold(init, |val, (branch, node)| -> Result<T, PrimitivesError> {
                     match node {
-                        MerkleNode::Branch { value: _, children } => {
+                        MerkleNode::Branch { value: _, children } if children.len() == ARITY => {
                             let mut data =
                                 children.iter().map(|node| node.value()).collect::<Vec<_>>();
                             data[*branch] = val;
                             H::digest(&data)
                         },
-                        _ => Err(PrimitivesError::ParameterError(
+                        _ => Err(PrimitivesError::InvalidProof(
                             "Incompatible proof for this merkle tree".to_string(),
                         )),
                     }
-                })?;
-            if computed_root == *expected_root {
-                Ok(Ok(()))
-            } else {
-                Ok(Err(()))
-            }
+                })
         } else {
-            Err(PrimitivesError::ParameterError(
+            Err(PrimitivesError::InvalidProof(
                 "Invalid proof type".to_string(),
             ))
         }
     }
 
-    /// Verify a non membership proof by comparing the computed root value
-    /// to the expected one.
-    pub(crate) fn verify_non_membership_proof<H>(
+    /// Verify a membership proof by comparing the computed root value to the
+    /// expected one.
+    ///
+    /// This only needs the root, so it can be used by light clients who do not
+    /// hold a copy of the tree. Returns `Ok(Err(()))` if the position of the
+    /// proven leaf does not match the index of this proof.
+    pub fn verify_membership_proof<H>(
         &self,
         expected_root: &T,
-    ) -> Result<bool, PrimitivesError>
+    ) -> Result<VerificationResult, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        if let Some(MerkleNode::<E, I, T>::Leaf { pos, .. }) = self.proof.first() {
+            if *pos != self.pos {
+                return Ok(Err(()));
+            }
+        }
+        if self.compute_root::<H>()? == *expected_root {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(()))
+        }
+    }
+
+    /// Verify a batch of membership proofs against the same root.
+    ///
+    /// Nodes shared by several proofs are only hashed once: the values
+    /// computed for the earlier proofs are remembered, and a later proof stops
+    /// as soon as it joins a path that is already authenticated. Returns
+    /// `Ok(Err(()))` if any of the proofs is invalid.
+    pub fn verify_batch<H>(
+        expected_root: &T,
+        proofs: &[Self],
+    ) -> Result<VerificationResult, PrimitivesError>
     where
         H: DigestAlgorithm<E, I, T>,
     {
-        if let MerkleNode::<E, I, T>::Empty = &self.proof[0] {
+        // Authenticated node values, keyed by their level (0 for leaves) and
+        // their traversal path from the root.
+        let mut verified: HashMap<(usize, Vec<usize>), T> = HashMap::new();
+        for proof in proofs {
+            let (pos, elem) = match proof.proof.first() {
+                Some(MerkleNode::Leaf {
+                    value: _,
+                    pos,
+                    elem,
+                }) => (pos, elem),
+                _ => {
+                    return Err(PrimitivesError::InvalidProof(
+                        "Invalid proof type".to_string(),
+                    ))
+                },
+            };
+            if *pos != proof.pos {
+                return Ok(Err(()));
+            }
+            let traversal_path = proof.pos.to_traversal_path(proof.tree_height() - 1);
+            let mut val = H::digest_leaf(pos, elem)?;
+            let mut joined = false;
+            for (level, node) in proof.proof.iter().enumerate() {
+                if level > 0 {
+                    val = match node {
+                        MerkleNode::Branch { value: _, children } if children.len() == ARITY => {
+                            let mut data =
+                                children.iter().map(|node| node.value()).collect::<Vec<_>>();
+                            data[traversal_path[level - 1]] = val;
+                            H::digest(&data)?
+                        },
+                        _ => {
+                            return Err(PrimitivesError::InvalidProof(
+                                "Incompatible proof for this merkle tree".to_string(),
+                            ))
+                        },
+                    };
+                }
+                let key = (level, traversal_path[level..].to_vec());
+                if let Some(known) = verified.get(&key) {
+                    // Two valid proofs always agree on the nodes they share.
+                    if *known != val {
+                        return Ok(Err(()));
+                    }
+                    joined = true;
+                    break;
+                }
+                verified.insert(key, val);
+            }
+            if !joined && val != *expected_root {
+                return Ok(Err(()));
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    /// Verify a non membership proof by comparing the computed root value
+    /// to the expected one.
+    ///
+    /// Like [`Self::verify_membership_proof`], only the root is needed. Empty
+    /// subtrees have the value `T::default()` at any height, so the empty
+    /// levels of the path are not hashed: there is no chain of padding
+    /// digests to recompute, however deep the empty region is.
+    pub fn verify_non_membership_proof<H>(&self, expected_root: &T) -> Result<bool, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        if let Some(MerkleNode::<E, I, T>::Empty) = self.proof.first() {
             let init = T::default();
             let computed_root = self
                 .pos
@@ -875,23 +2290,447 @@ where
                 .zip(self.proof.iter().skip(1))
                 .try_fold(init, |val, (branch, node)| -> Result<T, PrimitivesError> {
                     match node {
-                        MerkleNode::Branch { value: _, children } => {
+                        MerkleNode::Branch { value: _, children } if children.len() == ARITY => {
                             let mut data =
                                 children.iter().map(|node| node.value()).collect::<Vec<_>>();
                             data[*branch] = val;
                             H::digest(&data)
                         },
                         MerkleNode::Empty => Ok(init),
-                        _ => Err(PrimitivesError::ParameterError(
+                        _ => Err(PrimitivesError::InvalidProof(
                             "Incompatible proof for this merkle tree".to_string(),
                         )),
                     }
                 })?;
             Ok(computed_root == *expected_root)
         } else {
-            Err(PrimitivesError::ParameterError(
+            Err(PrimitivesError::InvalidProof(
                 "Invalid proof type".to_string(),
             ))
         }
     }
 }
+
+/// Position of the leftmost leaf of the `child`-th subtree of a node whose
+/// leftmost leaf is `lo`. `None` if it does not fit in a `u64`.
+fn child_lo<const ARITY: usize>(lo: u64, child: usize, child_height: usize) -> Option<u64> {
+    if child == 0 {
+        return Some(lo);
+    }
+    (ARITY as u64)
+        .checked_pow(child_height as u32)?
+        .checked_mul(child as u64)?
+        .checked_add(lo)
+}
+
+/// Whether the subtree of the given height whose leftmost leaf is `lo` has a
+/// leaf in `[start, end)`.
+fn subtree_in_range<I, const ARITY: usize>(lo: u64, height: usize, start: &I, end: &I) -> bool
+where
+    I: Index + From<u64>,
+{
+    if I::from(lo) >= *end {
+        return false;
+    }
+    match (ARITY as u64)
+        .checked_pow(height as u32)
+        .and_then(|size| size.checked_add(lo))
+    {
+        Some(next) => I::from(next) > *start,
+        None => true,
+    }
+}
+
+/// How the subtree of a given height is covered by the first leaves of a tree.
+enum PrefixCoverage {
+    /// All the leaves of the subtree are in the prefix
+    Full,
+    /// No leaf of the subtree is in the prefix
+    Empty,
+    /// Only some of the leaves of the subtree are in the prefix
+    Partial,
+}
+
+/// Whether the subtree of the given height whose leftmost leaf is `lo` is
+/// covered by the first `size` leaves.
+fn prefix_coverage<const ARITY: usize>(lo: u64, height: usize, size: u64) -> PrefixCoverage {
+    if lo >= size {
+        return PrefixCoverage::Empty;
+    }
+    match (ARITY as u64)
+        .checked_pow(height as u32)
+        .and_then(|len| len.checked_add(lo))
+    {
+        Some(next) if next <= size => PrefixCoverage::Full,
+        _ => PrefixCoverage::Partial,
+    }
+}
+
+/// Check that `[start, end)` is a non-empty range of positions in a tree of
+/// the given height.
+pub(crate) fn check_range<I, const ARITY: usize>(
+    start: &I,
+    end: &I,
+    height: usize,
+) -> Result<(), PrimitivesError>
+where
+    I: Index + From<u64>,
+{
+    if start >= end {
+        return Err(PrimitivesError::ParameterError(
+            "Empty range of leaves".to_string(),
+        ));
+    }
+    let capacity = (ARITY as u64).checked_pow(height as u32);
+    if *end > I::from(capacity.unwrap_or(u64::MAX)) {
+        return Err(PrimitivesError::IndexOutOfRange(
+            "Range of leaves exceeds the tree capacity".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl<E, I, T, const ARITY: usize> MerkleRangeProof<E, I, T, ARITY>
+where
+    E: Element,
+    I: Index + From<u64>,
+    T: NodeValue,
+{
+    /// Verify a range proof by recomputing the root from the elements in the
+    /// range and the adjacent subtrees, then comparing it to the expected one.
+    pub fn verify_range_proof<H>(
+        &self,
+        expected_root: &T,
+    ) -> Result<VerificationResult, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        check_range::<I, ARITY>(&self.start, &self.end, self.height)?;
+        let mut elems = self.elems.iter();
+        let mut siblings = self.siblings.iter();
+        let computed_root = self.compute_root::<H>(self.height, 0, &mut elems, &mut siblings)?;
+        if elems.next().is_some() || siblings.next().is_some() {
+            return Err(PrimitivesError::InvalidProof(
+                "Malformed range proof".to_string(),
+            ));
+        }
+        if computed_root == *expected_root {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(()))
+        }
+    }
+
+    // Mirrors `MerkleNode::range_proof_internal`, consuming the proof in the
+    // same depth-first order.
+    fn compute_root<H>(
+        &self,
+        height: usize,
+        lo: u64,
+        elems: &mut core::slice::Iter<E>,
+        siblings: &mut core::slice::Iter<T>,
+    ) -> Result<T, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        let malformed = || PrimitivesError::InvalidProof("Malformed range proof".to_string());
+        if !subtree_in_range::<I, ARITY>(lo, height, &self.start, &self.end) {
+            return siblings.next().copied().ok_or_else(malformed);
+        }
+        if height == 0 {
+            let elem = elems.next().ok_or_else(malformed)?;
+            return H::digest_leaf(&I::from(lo), elem);
+        }
+        let children = (0..ARITY)
+            .map(|i| match child_lo::<ARITY>(lo, i, height - 1) {
+                Some(child_lo) => self.compute_root::<H>(height - 1, child_lo, elems, siblings),
+                None => siblings.next().copied().ok_or_else(malformed),
+            })
+            .collect::<Result<Vec<_>, PrimitivesError>>()?;
+        H::digest(&children)
+    }
+}
+
+/// Sort and deduplicate the positions of a batch proof, along with their
+/// traversal paths in a tree of the given height.
+pub(crate) fn batch_traversal_paths<I, const ARITY: usize>(
+    positions: &[I],
+    height: usize,
+) -> Result<Vec<(I, Vec<usize>)>, PrimitivesError>
+where
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
+{
+    if positions.is_empty() {
+        return Err(PrimitivesError::ParameterError(
+            "Empty batch of leaves".to_string(),
+        ));
+    }
+    let capacity = I::from(
+        u32::try_from(height)
+            .ok()
+            .and_then(|height| (ARITY as u64).checked_pow(height))
+            .unwrap_or(u64::MAX),
+    );
+    if positions.iter().any(|pos| *pos >= capacity) {
+        return Err(PrimitivesError::IndexOutOfRange(
+            "Batch of leaves exceeds the tree capacity".to_string(),
+        ));
+    }
+    let mut paths = positions
+        .iter()
+        .map(|pos| (pos.clone(), pos.to_traversal_path(height)))
+        .collect::<Vec<_>>();
+    paths.sort_by(|(_, a), (_, b)| a.iter().rev().cmp(b.iter().rev()));
+    paths.dedup_by(|(_, a), (_, b)| a == b);
+    Ok(paths)
+}
+
+/// Split off the front of `paths` going through the child `branch` at the
+/// given level, `paths` being sorted as in [`batch_traversal_paths`].
+fn split_by_branch<'a, I>(
+    paths: &mut &'a [(I, Vec<usize>)],
+    level: usize,
+    branch: usize,
+) -> &'a [(I, Vec<usize>)] {
+    let all = *paths;
+    let len = all
+        .iter()
+        .take_while(|(_, path)| path[level] == branch)
+        .count();
+    let (group, rest) = all.split_at(len);
+    *paths = rest;
+    group
+}
+
+impl<E, I, T, const ARITY: usize> MerkleBatchProof<E, I, T, ARITY>
+where
+    E: Element,
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Verify a batch proof by recomputing the root from the proven elements
+    /// and the nodes off their paths, then comparing it to the committed one.
+    /// The height of the proof must be that of the commitment, so that an
+    /// untrusted proof cannot make the verifier recurse over more levels.
+    pub fn verify_batch_proof<H>(
+        &self,
+        expected_root: &MerkleTreeCommitment<T>,
+    ) -> Result<VerificationResult, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        if self.height != expected_root.height() {
+            return Err(PrimitivesError::InvalidProof(
+                "Batch proof does not match the commitment height".to_string(),
+            ));
+        }
+        let malformed = || PrimitivesError::InvalidProof("Malformed batch proof".to_string());
+        let paths = batch_traversal_paths::<I, ARITY>(&self.positions, self.height)?;
+        if !paths.iter().map(|(pos, _)| pos).eq(self.positions.iter()) {
+            return Err(malformed());
+        }
+        let mut elems = self.elems.iter();
+        let mut siblings = self.siblings.iter();
+        let computed_root =
+            Self::compute_root::<H>(self.height, &paths, &mut elems, &mut siblings)?;
+        if elems.next().is_some() || siblings.next().is_some() {
+            return Err(malformed());
+        }
+        if computed_root == expected_root.digest() {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(()))
+        }
+    }
+
+    // Mirrors `MerkleNode::batch_proof_internal`, consuming the proof in the
+    // same depth-first order.
+    fn compute_root<H>(
+        height: usize,
+        paths: &[(I, Vec<usize>)],
+        elems: &mut core::slice::Iter<E>,
+        siblings: &mut core::slice::Iter<T>,
+    ) -> Result<T, PrimitivesError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        let malformed = || PrimitivesError::InvalidProof("Malformed batch proof".to_string());
+        if height == 0 {
+            let elem = elems.next().ok_or_else(malformed)?;
+            return H::digest_leaf(&paths[0].0, elem);
+        }
+        let mut paths = paths;
+        let children = (0..ARITY)
+            .map(|i| {
+                let group = split_by_branch(&mut paths, height - 1, i);
+                if group.is_empty() {
+                    siblings.next().copied().ok_or_else(malformed)
+                } else {
+                    Self::compute_root::<H>(height - 1, group, elems, siblings)
+                }
+            })
+            .collect::<Result<Vec<_>, PrimitivesError>>()?;
+        H::digest(&children)
+    }
+}
+
+impl<T: NodeValue, const ARITY: usize> MerkleConsistencyProof<T, ARITY> {
+    /// Verify a consistency proof by recomputing both roots from the proof,
+    /// then comparing them to the expected ones.
+    /// * `returns` - Ok(Ok(())) if the proof is accepted, Ok(Err(())) if not.
+    ///   Err() if the proof is not well structured or does not match the
+    ///   commitments.
+    pub fn verify_consistency_proof<E, I, H>(
+        &self,
+        old_root: &MerkleTreeCommitment<T>,
+        new_root: &MerkleTreeCommitment<T>,
+    ) -> Result<VerificationResult, PrimitivesError>
+    where
+        E: Element,
+        I: Index,
+        H: DigestAlgorithm<E, I, T>,
+    {
+        if old_root.height() != self.height
+            || new_root.height() != self.height
+            || old_root.size() != self.old_size
+            || new_root.size() != self.new_size
+        {
+            return Err(PrimitivesError::InvalidProof(
+                "Consistency proof does not match the commitments".to_string(),
+            ));
+        }
+        if self.old_size > self.new_size {
+            return Err(PrimitivesError::ParameterError(
+                "Old tree is larger than the new one".to_string(),
+            ));
+        }
+        let mut proof = self.proof.iter();
+        let (old_digest, new_digest) = self.compute_roots::<E, I, H>(self.height, 0, &mut proof)?;
+        if proof.next().is_some() {
+            return Err(PrimitivesError::InvalidProof(
+                "Malformed consistency proof".to_string(),
+            ));
+        }
+        if old_digest == old_root.digest() && new_digest == new_root.digest() {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(()))
+        }
+    }
+
+    // Mirrors `MerkleNode::consistency_proof_internal`, consuming the proof in
+    // the same depth-first order. Returns the values of the subtree in the old
+    // and in the new tree.
+    fn compute_roots<E, I, H>(
+        &self,
+        height: usize,
+        lo: u64,
+        proof: &mut core::slice::Iter<T>,
+    ) -> Result<(T, T), PrimitivesError>
+    where
+        E: Element,
+        I: Index,
+        H: DigestAlgorithm<E, I, T>,
+    {
+        let malformed = || PrimitivesError::InvalidProof("Malformed consistency proof".to_string());
+        match prefix_coverage::<ARITY>(lo, height, self.old_size) {
+            PrefixCoverage::Full => {
+                let value = proof.next().copied().ok_or_else(malformed)?;
+                Ok((value, value))
+            },
+            PrefixCoverage::Empty => {
+                let value = proof.next().copied().ok_or_else(malformed)?;
+                Ok((T::default(), value))
+            },
+            PrefixCoverage::Partial => {
+                let (old_children, new_children): (Vec<_>, Vec<_>) = (0..ARITY)
+                    .map(|i| {
+                        self.compute_roots::<E, I, H>(
+                            height - 1,
+                            child_lo::<ARITY>(lo, i, height - 1).unwrap_or(u64::MAX),
+                            proof,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, PrimitivesError>>()?
+                    .into_iter()
+                    .unzip();
+                Ok((H::digest(&old_children)?, H::digest(&new_children)?))
+            },
+        }
+    }
+}
+
+impl<E: Element, I: Index, T: NodeValue> MerkleNode<E, I, T> {
+    /// Collect the positions of the leaves under this node, in index order,
+    /// only descending into the subtrees whose value satisfies `filter`.
+    pub(crate) fn search_internal(
+        &self,
+        filter: &impl Fn(&T) -> bool,
+        positions: &mut Vec<I>,
+    ) -> Result<(), PrimitivesError> {
+        if matches!(self, MerkleNode::Empty) || !filter(&self.value()) {
+            return Ok(());
+        }
+        match self {
+            MerkleNode::Leaf { pos, .. } => positions.push(pos.clone()),
+            MerkleNode::Branch { children, .. } => {
+                for child in children.iter() {
+                    child.search_internal(filter, positions)?;
+                }
+            },
+            _ => {
+                return Err(PrimitivesError::ParameterError(
+                    "Search needs forgotten leaves".to_string(),
+                ))
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the occupied leaves of a merkle tree, in index order. Empty
+/// and forgotten subtrees are skipped. Only the path to the current leaf is
+/// kept in memory.
+pub struct MerkleTreeIter<'a, E: Element, I: Index, T: NodeValue> {
+    stack: Vec<&'a MerkleNode<E, I, T>>,
+}
+
+impl<'a, E: Element, I: Index, T: NodeValue> MerkleTreeIter<'a, E, I, T> {
+    /// Initialize an iterator over all the leaves under `root`
+    pub(crate) fn new(root: &'a MerkleNode<E, I, T>) -> Self {
+        Self { stack: vec![root] }
+    }
+}
+
+impl<'a, E, I, T> Iterator for MerkleTreeIter<'a, E, I, T>
+where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+{
+    type Item = (&'a I, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            match node {
+                MerkleNode::Branch { value: _, children } => {
+                    // Push in reverse so that the leftmost child is visited first.
+                    self.stack.extend(
+                        children
+                            .iter()
+                            .rev()
+                            .map(|child| child.as_ref())
+                            .filter(|child| !matches!(child, MerkleNode::Empty)),
+                    );
+                },
+                MerkleNode::Leaf {
+                    value: _,
+                    pos,
+                    elem,
+                } => return Some((pos, elem)),
+                MerkleNode::Empty | MerkleNode::ForgettenSubtree { .. } => {},
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A keyed merkle tree hashes every leaf together with a secret salt of the
+//! tree, so that an observer who only knows the root cannot brute-force the
+//! leaves when they come from a small domain. Only holders of the salt can
+//! verify membership proofs.
+
+use super::{
+    append_only::MerkleTree,
+    internal::{MerkleNode, MerkleProof, MerkleTreeCommitment},
+    AppendableMerkleTreeScheme, DigestAlgorithm, LookupResult, MerkleTreeScheme,
+};
+use crate::{
+    errors::{PrimitivesError, VerificationResult},
+    rescue::{sponge::RescueCRHF, RescueParameter},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    borrow::Borrow,
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+
+/// Element of a [`KeyedMerkleTree`], paired with the salt of the tree. The
+/// salt is redacted from its [`Debug`] output.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KeyedElem<F: RescueParameter> {
+    salt: F,
+    elem: F,
+}
+
+impl<F: RescueParameter> Debug for KeyedElem<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedElem")
+            .field("salt", &"<redacted>")
+            .field("elem", &self.elem)
+            .finish()
+    }
+}
+
+/// Rescue hash function of a keyed merkle tree, whose leaf digest absorbs
+/// the salt along with the position and the element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyedRescueHash<F: RescueParameter> {
+    phantom_f: PhantomData<F>,
+}
+
+impl<F: RescueParameter> DigestAlgorithm<KeyedElem<F>, u64, F> for KeyedRescueHash<F> {
+    fn digest(data: &[F]) -> Result<F, PrimitivesError> {
+        Ok(RescueCRHF::<F>::sponge_no_padding(data, 1)?[0])
+    }
+
+    // Branch digests absorb exactly the 3 children, while the 3 leaf inputs
+    // are bit padded to 6 elements, so no leaf input is also a branch input.
+    fn digest_leaf(pos: &u64, elem: &KeyedElem<F>) -> Result<F, PrimitivesError> {
+        let data = [elem.salt, F::from(*pos), elem.elem];
+        Ok(RescueCRHF::<F>::sponge_with_bit_padding(&data, 1)[0])
+    }
+}
+
+/// Append only merkle tree of arity 3 over [`KeyedRescueHash`]
+pub type KeyedRescueMerkleTree<F> = MerkleTree<KeyedElem<F>, KeyedRescueHash<F>, u64, 3, F>;
+
+/// Membership proof of a [`KeyedMerkleTree`], whose leaf holds neither the
+/// salt nor the element.
+pub type KeyedMerkleProof<F> = MerkleProof<KeyedElem<F>, u64, F, 3>;
+
+/// Append only Rescue merkle tree of arity 3 whose leaf at position `pos`
+/// with element `elem` has the value of
+/// [`KeyedRescueHash::digest_leaf`] on the salt, `pos` and `elem`.
+///
+/// Membership proofs do not reveal the element, and only verify against the
+/// element with the same salt, see [`Self::verify`]. The salt is redacted
+/// from the [`Debug`] output.
+#[derive(Clone)]
+pub struct KeyedMerkleTree<F: RescueParameter> {
+    salt: F,
+    tree: KeyedRescueMerkleTree<F>,
+}
+
+impl<F: RescueParameter> Debug for KeyedMerkleTree<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedMerkleTree")
+            .field("salt", &"<redacted>")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+impl<F: RescueParameter> KeyedMerkleTree<F> {
+    /// Build a keyed merkle tree of the given height from a list of elements.
+    /// * `salt` - the secret salt absorbed in every leaf digest
+    /// * `returns` - Err() if the elements do not fit in the tree
+    pub fn keyed(
+        salt: F,
+        height: usize,
+        elems: impl IntoIterator<Item = impl Borrow<F>>,
+    ) -> Result<Self, PrimitivesError> {
+        let keyed_elems = elems.into_iter().map(|elem| KeyedElem {
+            salt,
+            elem: *elem.borrow(),
+        });
+        Ok(Self {
+            salt,
+            tree: KeyedRescueMerkleTree::<F>::from_elems(height, keyed_elems)?,
+        })
+    }
+
+    /// Height of the tree
+    pub fn height(&self) -> usize {
+        self.tree.height()
+    }
+
+    /// Number of leaves inserted so far
+    pub fn num_leaves(&self) -> u64 {
+        self.tree.num_leaves()
+    }
+
+    /// Commitment of the tree, which does not depend on anything but the
+    /// keyed leaves.
+    pub fn commitment(&self) -> MerkleTreeCommitment<F> {
+        self.tree.commitment()
+    }
+
+    /// Insert a new element at the next position.
+    /// * `returns` - Err() if the tree is full
+    pub fn push(&mut self, elem: impl Borrow<F>) -> Result<(), PrimitivesError> {
+        self.tree.push(KeyedElem {
+            salt: self.salt,
+            elem: *elem.borrow(),
+        })
+    }
+
+    /// Return a membership proof for the element at position `pos`. The salt
+    /// and the element are cleared from the proof, so the result carries no
+    /// element.
+    pub fn lookup(&self, pos: u64) -> LookupResult<(), KeyedMerkleProof<F>, ()> {
+        match self.tree.lookup(pos) {
+            LookupResult::Ok(_, mut proof) => {
+                set_leaf_elem(&mut proof, KeyedElem::default());
+                LookupResult::Ok((), proof)
+            },
+            LookupResult::NotInMemory => LookupResult::NotInMemory,
+            LookupResult::NotFound(_) => LookupResult::NotFound(()),
+        }
+    }
+
+    /// Verify that `elem` is at position `pos` of the keyed tree with root
+    /// `root` and salt `salt`.
+    /// * `returns` - Ok(Ok(())) if the proof is accepted, Ok(Err(())) if the
+    ///   proof, the element or the salt do not match, Err() if the proof is
+    ///   malformed
+    pub fn verify(
+        salt: impl Borrow<F>,
+        root: impl Borrow<F>,
+        pos: u64,
+        elem: impl Borrow<F>,
+        proof: impl Borrow<KeyedMerkleProof<F>>,
+    ) -> Result<VerificationResult, PrimitivesError> {
+        let mut proof = proof.borrow().clone();
+        set_leaf_elem(
+            &mut proof,
+            KeyedElem {
+                salt: *salt.borrow(),
+                elem: *elem.borrow(),
+            },
+        );
+        KeyedRescueMerkleTree::<F>::verify(root, pos, proof)
+    }
+}
+
+fn set_leaf_elem<F: RescueParameter>(proof: &mut KeyedMerkleProof<F>, keyed_elem: KeyedElem<F>) {
+    if let Some(MerkleNode::Leaf { elem, .. }) = proof.proof.first_mut() {
+        *elem = keyed_elem;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyedElem, KeyedMerkleTree};
+    use crate::{
+        merkle_tree::{prelude::RescueMerkleTree, MerkleCommitment, MerkleTreeScheme},
+        rescue::RescueParameter,
+    };
+    use ark_ed_on_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as Fq254;
+    use ark_std::{vec::Vec, UniformRand};
+
+    #[test]
+    fn test_keyed_mt() {
+        test_keyed_mt_helper::<Fq254>();
+        test_keyed_mt_helper::<Fq377>();
+        test_keyed_mt_helper::<Fq381>();
+    }
+
+    fn test_keyed_mt_helper<F: RescueParameter>() {
+        let mut rng = jf_utils::test_rng();
+        // A small domain, which could be enumerated in an unkeyed tree.
+        let elems = (0..10u64).map(|i| F::from(i % 2)).collect::<Vec<_>>();
+        let salt = F::rand(&mut rng);
+        let mut mt = KeyedMerkleTree::keyed(salt, 3, &elems[..9]).unwrap();
+        mt.push(elems[9]).unwrap();
+        assert_eq!(mt.num_leaves(), 10);
+        assert_eq!(
+            mt.commitment(),
+            KeyedMerkleTree::keyed(salt, 3, &elems)
+                .unwrap()
+                .commitment()
+        );
+        // The root differs from the unkeyed one, and from another salt.
+        let root = mt.commitment().digest();
+        let unkeyed = RescueMerkleTree::<F>::from_elems(3, &elems).unwrap();
+        assert_ne!(root, unkeyed.commitment().digest());
+
+        let other_salt = F::rand(&mut rng);
+        let other = KeyedMerkleTree::keyed(other_salt, 3, &elems).unwrap();
+        let other_root = other.commitment().digest();
+        assert_ne!(root, other_root);
+
+        for (pos, elem) in elems.iter().enumerate() {
+            let pos = pos as u64;
+            let (_, proof) = mt.lookup(pos).expect_ok().unwrap();
+            // The proof holds neither the salt nor the element.
+            assert_eq!(proof.elem(), Some(&KeyedElem::default()));
+            assert!(KeyedMerkleTree::verify(salt, root, pos, elem, &proof)
+                .unwrap()
+                .is_ok());
+            // Wrong element, position or root
+            assert!(
+                KeyedMerkleTree::verify(salt, root, pos, *elem + F::one(), &proof)
+                    .unwrap()
+                    .is_err()
+            );
+            assert!(KeyedMerkleTree::verify(salt, root, pos + 1, elem, &proof)
+                .unwrap()
+                .is_err());
+            assert!(KeyedMerkleTree::verify(salt, other_root, pos, elem, &proof)
+                .unwrap()
+                .is_err());
+
+            // Proofs of a differently salted tree fail with this salt, and
+            // the other way round.
+            let (_, other_proof) = other.lookup(pos).expect_ok().unwrap();
+            assert!(
+                KeyedMerkleTree::verify(salt, other_root, pos, elem, &other_proof)
+                    .unwrap()
+                    .is_err()
+            );
+            assert!(KeyedMerkleTree::verify(other_salt, root, pos, elem, &proof)
+                .unwrap()
+                .is_err());
+            assert!(
+                KeyedMerkleTree::verify(other_salt, other_root, pos, elem, &other_proof)
+                    .unwrap()
+                    .is_ok()
+            );
+        }
+        assert!(mt.lookup(10).expect_not_found().is_ok());
+
+        let debug = ark_std::format!("{:?}", mt);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&ark_std::format!("{:?}", salt)));
+    }
+}
@@ -10,9 +10,13 @@
 use core::ops::AddAssign;
 
 use super::{
-    internal::{build_light_weight_tree_internal, MerkleNode, MerkleProof, MerkleTreeCommitment},
+    internal::{
+        batch_traversal_paths, build_light_weight_tree_internal, check_range, MerkleBatchProof,
+        MerkleNode, MerkleProof, MerkleRangeProof, MerkleTreeCommitment,
+    },
+    store::{NodeId, NodeStore},
     AppendableMerkleTreeScheme, DigestAlgorithm, Element, ForgetableMerkleTreeScheme, Index,
-    LookupResult, MerkleCommitment, MerkleTreeScheme, NodeValue, ToTraversalPath,
+    LookupResult, MerkleCommitment, MerkleTreeIter, MerkleTreeScheme, NodeValue, ToTraversalPath,
 };
 use crate::{
     errors::{PrimitivesError, VerificationResult},
@@ -24,17 +28,16 @@ use ark_std::{
 use num_bigint::BigUint;
 use num_traits::pow::pow;
 use serde::{Deserialize, Serialize};
-use typenum::Unsigned;
 
 impl_merkle_tree_scheme!(LightWeightMerkleTree, build_light_weight_tree_internal);
 impl_forgetable_merkle_tree_scheme!(LightWeightMerkleTree);
 
-impl<E, H, I, Arity, T> AppendableMerkleTreeScheme for LightWeightMerkleTree<E, H, I, Arity, T>
+impl<E, H, I, const ARITY: usize, T> AppendableMerkleTreeScheme
+    for LightWeightMerkleTree<E, H, I, ARITY, T>
 where
     E: Element,
     H: DigestAlgorithm<E, I, T>,
-    I: Index + From<u64> + AddAssign + ToTraversalPath<Arity>,
-    Arity: Unsigned,
+    I: Index + From<u64> + AddAssign + ToTraversalPath<ARITY>,
     T: NodeValue,
 {
     fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), PrimitivesError> {
@@ -48,7 +51,7 @@ where
         let mut iter = elems.into_iter().peekable();
 
         let traversal_path = I::from(self.num_leaves).to_traversal_path(self.height);
-        self.num_leaves += self.root.extend_and_forget_internal::<H, Arity>(
+        self.num_leaves += self.root.extend_and_forget_internal::<H, ARITY>(
             self.height,
             &I::from(self.num_leaves),
             &traversal_path,
@@ -56,7 +59,7 @@ where
             &mut iter,
         )?;
         if iter.peek().is_some() {
-            return Err(PrimitivesError::ParameterError(
+            return Err(PrimitivesError::IndexOutOfRange(
                 "Exceed merkle tree capacity".to_string(),
             ));
         }
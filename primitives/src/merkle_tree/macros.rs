@@ -16,12 +16,11 @@ macro_rules! impl_merkle_tree_scheme {
             bound = "E: ark_serialize::CanonicalSerialize + ark_serialize::CanonicalDeserialize,
                      I: ark_serialize::CanonicalSerialize + ark_serialize::CanonicalDeserialize,"
         )]
-        pub struct $name<E, H, I, Arity, T>
+        pub struct $name<E, H, I, const ARITY: usize, T>
         where
             E: Element,
             H: DigestAlgorithm<E, I, T>,
             I: Index,
-            Arity: Unsigned,
             T: NodeValue,
         {
             root: Box<MerkleNode<E, I, T>>,
@@ -29,38 +28,35 @@ macro_rules! impl_merkle_tree_scheme {
             num_leaves: u64,
 
             _phantom_h: PhantomData<H>,
-            _phantom_ta: PhantomData<Arity>,
         }
 
-        impl<E, H, I, Arity, T> MerkleTreeScheme for $name<E, H, I, Arity, T>
+        impl<E, H, I, const ARITY: usize, T> MerkleTreeScheme for $name<E, H, I, ARITY, T>
         where
             E: Element,
             H: DigestAlgorithm<E, I, T>,
-            I: Index + From<u64> + ToTraversalPath<Arity>,
-            Arity: Unsigned,
+            I: Index + From<u64> + ToTraversalPath<ARITY>,
             T: NodeValue,
         {
             type Element = E;
             type Index = I;
             type NodeValue = T;
-            type MembershipProof = MerkleProof<E, I, T, Arity>;
+            type MembershipProof = MerkleProof<E, I, T, ARITY>;
             // TODO(Chengyu): implement batch membership proof
             type BatchMembershipProof = ();
             type Commitment = MerkleTreeCommitment<T>;
 
-            const ARITY: usize = Arity::USIZE;
+            const ARITY: usize = ARITY;
 
             fn from_elems(
                 height: usize,
                 elems: impl IntoIterator<Item = impl Borrow<Self::Element>>,
             ) -> Result<Self, PrimitivesError> {
-                let (root, num_leaves) = $builder::<E, H, I, Arity, T>(height, elems)?;
+                let (root, num_leaves) = $builder::<E, H, I, ARITY, T>(height, elems)?;
                 Ok($name {
                     root,
                     height,
                     num_leaves,
                     _phantom_h: PhantomData,
-                    _phantom_ta: PhantomData,
                 })
             }
 
@@ -88,7 +84,17 @@ macro_rules! impl_merkle_tree_scheme {
                 let traversal_path = pos.to_traversal_path(self.height);
                 match self.root.lookup_internal(self.height, &traversal_path) {
                     LookupResult::Ok(value, proof) => {
-                        LookupResult::Ok(&value, MerkleProof::new(pos.clone(), proof))
+                        // A position beyond the capacity of the tree wraps around to
+                        // some other leaf, which must not be reported as a hit.
+                        let is_hit = matches!(
+                            &proof[0],
+                            MerkleNode::Leaf { pos: leaf_pos, .. } if leaf_pos == pos
+                        );
+                        if is_hit {
+                            LookupResult::Ok(&value, MerkleProof::new(pos.clone(), proof))
+                        } else {
+                            LookupResult::NotFound(())
+                        }
                     },
                     LookupResult::NotInMemory => LookupResult::NotInMemory,
                     LookupResult::NotFound(_) => LookupResult::NotFound(()),
@@ -105,6 +111,366 @@ macro_rules! impl_merkle_tree_scheme {
                 }
                 proof.borrow().verify_membership_proof::<H>(root.borrow())
             }
+
+            fn iter(&self) -> MerkleTreeIter<E, I, T> {
+                MerkleTreeIter::new(&self.root)
+            }
+        }
+
+        impl<E, H, I, const ARITY: usize, T> $name<E, H, I, ARITY, T>
+        where
+            E: Element,
+            H: DigestAlgorithm<E, I, T>,
+            I: Index + From<u64> + ToTraversalPath<ARITY>,
+            T: NodeValue,
+        {
+            /// Compute the root from a membership proof, without comparing it
+            /// to an expected one. [`MerkleTreeScheme::verify`] accepts a proof
+            /// iff this is the root and the position is the proven one.
+            /// * `proof` - a membership proof
+            /// * `returns` - the computed root, Err() if the proof is not well
+            ///   structured
+            pub fn compute_root_from_proof(
+                proof: impl Borrow<MerkleProof<E, I, T, ARITY>>,
+            ) -> Result<T, PrimitivesError> {
+                proof.borrow().compute_root::<H>()
+            }
+
+            /// Verify a list of membership proofs against the same root at once.
+            /// Internal nodes shared between the proofs are only hashed once.
+            /// * `root` - a merkle tree root
+            /// * `proofs` - membership proofs, the same position may appear
+            ///   several times
+            /// * `returns` - Ok(Ok(())) if all the proofs are accepted,
+            ///   Ok(Err(())) if any of them is not. Err() if some proof is not
+            ///   well structured.
+            pub fn verify_batch(
+                root: impl Borrow<T>,
+                proofs: &[MerkleProof<E, I, T, ARITY>],
+            ) -> Result<VerificationResult, PrimitivesError> {
+                MerkleProof::verify_batch::<H>(root.borrow(), proofs)
+            }
+
+            /// Returns a single proof for all the leaves in `[start, end)`,
+            /// which is much smaller than one membership proof per leaf.
+            /// * `start` - position of the first leaf in the range
+            /// * `end` - position right after the last leaf in the range
+            /// * `returns` - Err() if the range is empty, or if some position in
+            ///   it is not occupied or has been forgotten.
+            pub fn range_proof(
+                &self,
+                start: impl Borrow<I>,
+                end: impl Borrow<I>,
+            ) -> Result<MerkleRangeProof<E, I, T, ARITY>, PrimitivesError> {
+                let (start, end) = (start.borrow(), end.borrow());
+                check_range::<I, ARITY>(start, end, self.height)?;
+                let mut elems = vec![];
+                let mut siblings = vec![];
+                self.root.range_proof_internal::<ARITY>(
+                    self.height,
+                    0,
+                    start,
+                    end,
+                    &mut elems,
+                    &mut siblings,
+                )?;
+                Ok(MerkleRangeProof {
+                    start: start.clone(),
+                    end: end.clone(),
+                    height: self.height,
+                    elems,
+                    siblings,
+                })
+            }
+
+            /// Verify a range proof against a merkle tree root
+            /// * `root` - a merkle tree root
+            /// * `proof` - a range proof from [`Self::range_proof`]
+            /// * `returns` - Ok(Ok(())) if the proof is accepted, Ok(Err(())) if
+            ///   not. Err() if the proof is not well structured.
+            pub fn verify_range_proof(
+                root: impl Borrow<T>,
+                proof: impl Borrow<MerkleRangeProof<E, I, T, ARITY>>,
+            ) -> Result<VerificationResult, PrimitivesError> {
+                proof.borrow().verify_range_proof::<H>(root.borrow())
+            }
+
+            /// Returns a single proof for the leaves at `positions`, in which
+            /// the nodes shared by several of them are only included once.
+            /// * `positions` - positions of the leaves, in any order and
+            ///   possibly repeated
+            /// * `returns` - Err() if `positions` is empty, or if some position
+            ///   in it is not occupied or has been forgotten.
+            pub fn batch_proof(
+                &self,
+                positions: &[I],
+            ) -> Result<MerkleBatchProof<E, I, T, ARITY>, PrimitivesError> {
+                let paths = batch_traversal_paths::<I, ARITY>(positions, self.height)?;
+                let mut elems = vec![];
+                let mut siblings = vec![];
+                self.root
+                    .batch_proof_internal(self.height, &paths, &mut elems, &mut siblings)?;
+                Ok(MerkleBatchProof {
+                    positions: paths.into_iter().map(|(pos, _)| pos).collect(),
+                    height: self.height,
+                    elems,
+                    siblings,
+                })
+            }
+
+            /// Verify a batch proof against a merkle tree commitment
+            /// * `root` - a merkle tree commitment
+            /// * `proof` - a batch proof from [`Self::batch_proof`]
+            /// * `returns` - Ok(Ok(())) if the proof is accepted, Ok(Err(())) if
+            ///   not. Err() if the proof is not well structured, or not for a
+            ///   tree of the committed height.
+            pub fn verify_batch_proof(
+                root: impl Borrow<MerkleTreeCommitment<T>>,
+                proof: impl Borrow<MerkleBatchProof<E, I, T, ARITY>>,
+            ) -> Result<VerificationResult, PrimitivesError> {
+                proof.borrow().verify_batch_proof::<H>(root.borrow())
+            }
+
+            /// Returns the authentication path of the leaf at `pos`, one level
+            /// at a time from the leaf to the root, so that proofs can be
+            /// encoded without going through [`MerkleProof`]. Each level is
+            /// the branch taken by the path in the node, along with the values
+            /// of the other children of the node, in order.
+            /// * `returns` - Err() if the position is not occupied or has been
+            ///   forgotten.
+            pub fn authentication_path(
+                &self,
+                pos: impl Borrow<I>,
+            ) -> Result<impl Iterator<Item = (usize, Vec<T>)>, PrimitivesError> {
+                let pos = pos.borrow();
+                let (_, proof) = self.lookup(pos).expect_ok()?;
+                let traversal_path = pos.to_traversal_path(self.height);
+                Ok(traversal_path
+                    .into_iter()
+                    .zip(proof.proof.into_iter().skip(1))
+                    .map(|(branch, node)| {
+                        let siblings = match node {
+                            MerkleNode::Branch { value: _, children } => children
+                                .iter()
+                                .enumerate()
+                                .filter(|(i, _)| *i != branch)
+                                .map(|(_, child)| child.value())
+                                .collect(),
+                            _ => vec![],
+                        };
+                        (branch, siblings)
+                    }))
+            }
+
+            /// Returns the value of the internal node at the end of `prefix`,
+            /// i.e. the commitment of the subtree rooted there.
+            /// * `prefix` - branches to follow from the root, at most `height`
+            ///   of them and each smaller than the arity. An empty prefix
+            ///   designates the root.
+            /// * `returns` - Err() if the prefix is invalid, or if it goes
+            ///   through a forgotten subtree.
+            pub fn subtree_commitment(&self, prefix: &[usize]) -> Result<T, PrimitivesError> {
+                if prefix.len() > self.height || prefix.iter().any(|branch| *branch >= ARITY) {
+                    return Err(PrimitivesError::IndexOutOfRange(
+                        "Invalid prefix for this merkle tree".to_string(),
+                    ));
+                }
+                let mut node: &MerkleNode<E, I, T> = &self.root;
+                for branch in prefix {
+                    node = match node {
+                        MerkleNode::Branch { children, .. } => &*children[*branch],
+                        MerkleNode::Empty => return Ok(T::default()),
+                        _ => {
+                            return Err(PrimitivesError::ParameterError(
+                                "Subtree has been forgotten".to_string(),
+                            ))
+                        },
+                    };
+                }
+                Ok(node.value())
+            }
+
+            /// Returns the positions, in index order, of the leaves which differ
+            /// between this tree and `other`. Only the subtrees whose values
+            /// differ are visited, so the cost is proportional to the number
+            /// of changes times the height.
+            /// * `returns` - Err() if the trees have different heights, or if
+            ///   some differing leaves have been forgotten.
+            pub fn diff(&self, other: &Self) -> Result<Vec<I>, PrimitivesError> {
+                if self.height != other.height {
+                    return Err(PrimitivesError::ParameterError(
+                        "Cannot diff merkle trees of different heights".to_string(),
+                    ));
+                }
+                let mut positions = vec![];
+                self.root.diff_internal(&other.root, &mut positions)?;
+                positions.sort();
+                Ok(positions)
+            }
+
+            /// Returns the positions, in index order, of the leaves reached by
+            /// only descending into the subtrees whose value satisfies
+            /// `filter`. Node values which aggregate some property of their
+            /// leaves, e.g. the bounds of an
+            /// [`IntervalNode`](crate::merkle_tree::examples::IntervalNode),
+            /// can thus prune the search.
+            /// * `returns` - Err() if some subtree satisfying `filter` has been
+            ///   forgotten.
+            pub fn search(&self, filter: impl Fn(&T) -> bool) -> Result<Vec<I>, PrimitivesError> {
+                let mut positions = vec![];
+                self.root.search_internal(&filter, &mut positions)?;
+                Ok(positions)
+            }
+
+            /// Serialize the whole tree into a single blob, from which it can
+            /// be restored with [`Self::deserialize_tree`]. The layout is
+            /// `arity: u32`, `height: u32` and `num_leaves: u64` in
+            /// little-endian, followed by the compressed canonical
+            /// serialization of the root node. Forgotten subtrees only keep
+            /// their value.
+            pub fn serialize_tree(&self) -> Result<Vec<u8>, PrimitivesError>
+            where
+                E: ark_serialize::CanonicalSerialize,
+                I: ark_serialize::CanonicalSerialize,
+            {
+                let mut bytes = vec![];
+                bytes.extend_from_slice(&(ARITY as u32).to_le_bytes());
+                bytes.extend_from_slice(&(self.height as u32).to_le_bytes());
+                bytes.extend_from_slice(&self.num_leaves.to_le_bytes());
+                ark_serialize::CanonicalSerialize::serialize_compressed(&*self.root, &mut bytes)?;
+                Ok(bytes)
+            }
+
+            /// Restore a tree from a blob written by [`Self::serialize_tree`].
+            /// Every node is checked against its children, so that the
+            /// restored tree is the serialized one.
+            /// * `returns` - Err() if the blob is corrupted, or if it is for
+            ///   another arity.
+            pub fn deserialize_tree(bytes: &[u8]) -> Result<Self, PrimitivesError>
+            where
+                E: ark_serialize::CanonicalDeserialize,
+                I: ark_serialize::CanonicalDeserialize,
+            {
+                let corrupted =
+                    || PrimitivesError::InvalidEncoding("Corrupted merkle tree".to_string());
+                if bytes.len() < 16 {
+                    return Err(corrupted());
+                }
+                let (header, mut reader) = bytes.split_at(16);
+                let word = |range: core::ops::Range<usize>| {
+                    let mut le_bytes = [0u8; 8];
+                    let len = range.len();
+                    le_bytes[..len].copy_from_slice(&header[range]);
+                    u64::from_le_bytes(le_bytes)
+                };
+                if word(0..4) != ARITY as u64 {
+                    return Err(PrimitivesError::InvalidEncoding(
+                        "Merkle tree is for another arity".to_string(),
+                    ));
+                }
+                let height = word(4..8) as usize;
+                let num_leaves = word(8..16);
+                let root: MerkleNode<E, I, T> =
+                    ark_serialize::CanonicalDeserialize::deserialize_compressed(&mut reader)?;
+                if !reader.is_empty() {
+                    return Err(corrupted());
+                }
+                let (leaves_in_memory, forgotten) =
+                    root.check_internal::<H, ARITY>(height, &mut vec![])?;
+                if leaves_in_memory > num_leaves || (!forgotten && leaves_in_memory != num_leaves)
+                {
+                    return Err(corrupted());
+                }
+                Ok($name {
+                    root: Box::new(root),
+                    height,
+                    num_leaves,
+                    _phantom_h: PhantomData,
+                })
+            }
+
+            /// Write all the nodes of this tree to `store`. The tree can be
+            /// rebuilt later from the store and its commitment with
+            /// [`Self::from_store`].
+            pub fn save_to_store<S>(&self, store: &mut S) -> Result<(), PrimitivesError>
+            where
+                S: NodeStore<MerkleNode<E, I, T>>,
+            {
+                self.root.store_internal(&mut vec![], store)
+            }
+
+            /// Open a tree whose nodes were written to `store` by
+            /// [`Self::save_to_store`]. Only the root is read: the tree starts
+            /// out as if everything had been forgotten, and the paths to the
+            /// leaves are brought back in memory with
+            /// [`Self::load_from_store`] when they are needed.
+            /// * `com` - commitment of the stored tree
+            /// * `returns` - Err() if the stored root does not match `com`, or
+            ///   if the store cannot be read
+            pub fn from_store<S>(
+                store: &S,
+                com: impl Borrow<MerkleTreeCommitment<T>>,
+            ) -> Result<Self, PrimitivesError>
+            where
+                S: NodeStore<MerkleNode<E, I, T>>,
+            {
+                let com = com.borrow();
+                let root = match store.get(&NodeId::default())? {
+                    None => MerkleNode::Empty,
+                    Some(node) => MerkleNode::ForgettenSubtree {
+                        value: node.value(),
+                    },
+                };
+                if root.value() != com.digest() {
+                    return Err(PrimitivesError::ParameterError(
+                        "Stored merkle tree does not match the commitment".to_string(),
+                    ));
+                }
+                Ok($name {
+                    root: Box::new(root),
+                    height: com.height(),
+                    num_leaves: com.size(),
+                    _phantom_h: PhantomData,
+                })
+            }
+
+            /// Read the path to the leaf at `pos` from `store`, so that it can
+            /// be looked up or updated. Every node read is checked against the
+            /// digest of its parent, which is already in memory. Leaves which
+            /// were forgotten before the tree was stored remain forgotten.
+            /// * `returns` - Err() if the store does not hold this tree, or if
+            ///   it cannot be read
+            pub fn load_from_store<S>(
+                &mut self,
+                pos: impl Borrow<I>,
+                store: &S,
+            ) -> Result<(), PrimitivesError>
+            where
+                S: NodeStore<MerkleNode<E, I, T>>,
+            {
+                let traversal_path = pos.borrow().to_traversal_path(self.height);
+                self.root.fetch_internal::<H, S, ARITY>(
+                    self.height,
+                    &traversal_path,
+                    &mut vec![],
+                    store,
+                )
+            }
+        }
+
+        impl<'a, E, H, I, const ARITY: usize, T> IntoIterator for &'a $name<E, H, I, ARITY, T>
+        where
+            E: Element,
+            H: DigestAlgorithm<E, I, T>,
+            I: Index + From<u64> + ToTraversalPath<ARITY>,
+            T: NodeValue,
+        {
+            type Item = (&'a I, &'a E);
+            type IntoIter = MerkleTreeIter<'a, E, I, T>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                MerkleTreeIter::new(&self.root)
+            }
         }
     };
 }
@@ -113,24 +479,31 @@ macro_rules! impl_merkle_tree_scheme {
 #[macro_export]
 macro_rules! impl_forgetable_merkle_tree_scheme {
     ($name: ident) => {
-        impl<E, H, I, Arity, T> ForgetableMerkleTreeScheme for $name<E, H, I, Arity, T>
+        impl<E, H, I, const ARITY: usize, T> ForgetableMerkleTreeScheme for $name<E, H, I, ARITY, T>
         where
             E: Element,
             H: DigestAlgorithm<E, I, T>,
-            I: Index + From<u64> + ToTraversalPath<Arity>,
-            Arity: Unsigned,
+            I: Index + From<u64> + ToTraversalPath<ARITY>,
             T: NodeValue,
         {
             fn from_commitment(com: impl Borrow<Self::Commitment>) -> Self {
                 let com = com.borrow();
-                $name {
-                    root: Box::new(MerkleNode::ForgettenSubtree {
+                // An empty tree has nothing to forget, so that it can be
+                // appended to right away. A commitment to no leaf with another
+                // digest than the empty one is inconsistent: it is kept
+                // forgotten, so that nothing can be inserted under it.
+                let root = if com.size() == 0 && com.digest() == T::default() {
+                    MerkleNode::Empty
+                } else {
+                    MerkleNode::ForgettenSubtree {
                         value: com.digest(),
-                    }),
+                    }
+                };
+                $name {
+                    root: Box::new(root),
                     height: com.height(),
                     num_leaves: com.size(),
                     _phantom_h: PhantomData,
-                    _phantom_ta: PhantomData,
                 }
             }
 
@@ -156,18 +529,23 @@ macro_rules! impl_forgetable_merkle_tree_scheme {
             ) -> Result<(), PrimitivesError> {
                 let proof = proof.borrow();
                 let traversal_path = pos.to_traversal_path(self.height);
-                if let MerkleNode::<E, I, T>::Leaf {
+                if let Some(MerkleNode::<E, I, T>::Leaf {
                     value: _,
-                    pos,
+                    pos: leaf_pos,
                     elem,
-                } = &proof.proof[0]
+                }) = proof.proof.first()
                 {
+                    if *leaf_pos != pos {
+                        return Err(PrimitivesError::InvalidProof(
+                            "Position does not match the proof.".to_string(),
+                        ));
+                    }
                     if !elem.eq(element.borrow()) {
-                        return Err(PrimitivesError::ParameterError(
+                        return Err(PrimitivesError::InvalidProof(
                             "Element does not match the proof.".to_string(),
                         ));
                     }
-                    let proof_leaf_value = H::digest_leaf(pos, elem)?;
+                    let proof_leaf_value = H::digest_leaf(leaf_pos, elem)?;
                     let mut path_values = vec![proof_leaf_value];
                     traversal_path.iter().zip(proof.proof.iter().skip(1)).fold(
                         Ok(proof_leaf_value),
@@ -182,7 +560,7 @@ macro_rules! impl_forgetable_merkle_tree_scheme {
                                         path_values.push(digest);
                                         Ok(digest)
                                     },
-                                    _ => Err(PrimitivesError::ParameterError(
+                                    _ => Err(PrimitivesError::InvalidProof(
                                         "Incompatible proof for this merkle tree".to_string(),
                                     )),
                                 },
@@ -190,14 +568,14 @@ macro_rules! impl_forgetable_merkle_tree_scheme {
                             }
                         },
                     )?;
-                    self.root.remember_internal::<H, Arity>(
+                    self.root.remember_internal::<H, ARITY>(
                         self.height,
                         &traversal_path,
                         &path_values,
                         &proof.proof,
                     )
                 } else {
-                    Err(PrimitivesError::ParameterError(
+                    Err(PrimitivesError::InvalidProof(
                         "Invalid proof type".to_string(),
                     ))
                 }
@@ -207,16 +585,27 @@ macro_rules! impl_forgetable_merkle_tree_scheme {
 }
 
 /// Macros for implementing ToTreversalPath for primitive types
+///
+/// Every item the expansion needs is referred to by its `$crate` path, so the
+/// macro also expands in `no_std` crates and without any import.
 #[macro_export]
 macro_rules! impl_to_traversal_path_primitives {
     ($t: ty) => {
-        impl<Arity: Unsigned> ToTraversalPath<Arity> for $t {
-            fn to_traversal_path(&self, height: usize) -> Vec<usize> {
+        impl<const ARITY: usize> $crate::merkle_tree::ToTraversalPath<ARITY> for $t {
+            fn to_traversal_path(
+                &self,
+                height: usize,
+            ) -> $crate::__reexports::ark_std::vec::Vec<usize> {
                 let mut pos = *self as u64;
-                let mut ret = vec![];
+                let mut ret = $crate::__reexports::ark_std::vec![];
                 for _i in 0..height {
-                    ret.push((pos % (Arity::to_u64())).to_usize().unwrap());
-                    pos /= Arity::to_u64();
+                    ret.push(
+                        $crate::__reexports::num_traits::ToPrimitive::to_usize(
+                            &(pos % (ARITY as u64)),
+                        )
+                        .unwrap(),
+                    );
+                    pos /= ARITY as u64;
                 }
                 ret
             }
@@ -225,19 +614,45 @@ macro_rules! impl_to_traversal_path_primitives {
 }
 
 /// Macros for implementing ToTreversalPath for BigUint types
+///
+/// Same as [`impl_to_traversal_path_primitives`], for types converting into
+/// `BigUint`.
 #[macro_export]
 macro_rules! impl_to_traversal_path_biguint {
     ($t: ty) => {
-        impl<Arity: Unsigned> ToTraversalPath<Arity> for $t {
-            fn to_traversal_path(&self, height: usize) -> Vec<usize> {
-                let mut pos: BigUint = <Self as Into<BigUint>>::into(self.clone());
-                let mut ret = vec![];
+        impl<const ARITY: usize> $crate::merkle_tree::ToTraversalPath<ARITY> for $t {
+            fn to_traversal_path(
+                &self,
+                height: usize,
+            ) -> $crate::__reexports::ark_std::vec::Vec<usize> {
+                let mut pos: $crate::__reexports::num_bigint::BigUint =
+                    ::core::convert::Into::into(::core::clone::Clone::clone(self));
+                let mut ret = $crate::__reexports::ark_std::vec![];
                 for _i in 0..height {
-                    ret.push((&pos % (Arity::to_u64())).to_usize().unwrap());
-                    pos /= Arity::to_u64();
+                    ret.push(
+                        $crate::__reexports::num_traits::ToPrimitive::to_usize(
+                            &(&pos % (ARITY as u64)),
+                        )
+                        .unwrap(),
+                    );
+                    pos /= ARITY as u64;
                 }
                 ret
             }
         }
     };
 }
+
+#[cfg(test)]
+mod test {
+    // Nothing is imported for the macro.
+    impl_to_traversal_path_primitives!(i64);
+
+    #[test]
+    fn test_to_traversal_path_without_alloc_imports() {
+        assert_eq!(
+            <i64 as crate::merkle_tree::ToTraversalPath<3>>::to_traversal_path(&5, 4),
+            ark_std::vec![2, 1, 0, 0]
+        );
+    }
+}
@@ -7,7 +7,9 @@
 //! Merkle Tree traits and implementations
 pub mod append_only;
 pub mod examples;
+pub mod frontier;
 pub mod hasher;
+pub mod keyed;
 pub mod light_weight;
 pub mod macros;
 pub mod namespaced_merkle_tree;
@@ -15,18 +17,21 @@ pub mod universal_merkle_tree;
 
 pub(crate) mod internal;
 
+pub use internal::{MerkleBatchProof, MerkleConsistencyProof, MerkleRangeProof, MerkleTreeIter};
+
 pub mod prelude;
+#[cfg(feature = "std")]
+pub mod shared;
+pub mod store;
 
 use crate::{
     errors::{PrimitivesError, VerificationResult},
     impl_to_traversal_path_biguint, impl_to_traversal_path_primitives,
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{borrow::Borrow, fmt::Debug, hash::Hash, string::ToString, vec, vec::Vec};
+use ark_std::{borrow::Borrow, fmt::Debug, hash::Hash, string::ToString, vec::Vec};
 use num_bigint::BigUint;
-use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
-use typenum::Unsigned;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 /// The result of querying at an index in the tree
@@ -93,6 +98,13 @@ pub trait Index: Debug + Eq + PartialEq + Hash + Ord + PartialOrd + Clone {}
 impl<T: Debug + Eq + PartialEq + Hash + Ord + PartialOrd + Clone> Index for T {}
 
 /// An internal node value type in a Merkle tree.
+///
+/// The [`Default`] value is the value of empty subtrees, at any height, in
+/// root computations as well as in membership and non-membership proofs. A
+/// tree with another empty value, e.g. an application specific sentinel, is
+/// obtained with a node type whose [`Default`] is that sentinel. It cannot be
+/// a runtime setting of the tree, since proofs are verified against a root
+/// alone.
 pub trait NodeValue:
     Default
     + Eq
@@ -137,7 +149,7 @@ where
 }
 
 /// An trait for Merkle tree index type.
-pub trait ToTraversalPath<Arity: Unsigned> {
+pub trait ToTraversalPath<const ARITY: usize> {
     /// Convert the given index to a vector of branch indices given tree height
     /// and arity.
     fn to_traversal_path(&self, height: usize) -> Vec<usize>;
@@ -215,7 +227,7 @@ pub trait MerkleTreeScheme: Sized {
     /// Returns the leaf value given a position
     /// * `pos` - zero-based index of the leaf in the tree
     /// * `returns` - Leaf value at the position along with a proof.
-    ///   LookupResult::EmptyLeaf if the leaf position is empty or invalid,
+    ///   LookupResult::NotFound if the leaf position is empty or invalid,
     ///   LookupResult::NotInMemory if the leaf position has been forgotten.
     fn lookup(
         &self,
@@ -235,6 +247,11 @@ pub trait MerkleTreeScheme: Sized {
         proof: impl Borrow<Self::MembershipProof>,
     ) -> Result<VerificationResult, PrimitivesError>;
 
+    /// Return an iterator over all the occupied leaves, in index order. Each
+    /// item is a pair of the leaf position and the element. Forgotten leaves
+    /// are skipped.
+    fn iter(&self) -> MerkleTreeIter<Self::Element, Self::Index, Self::NodeValue>;
+
     // fn batch_lookup(&self, pos: impl Iterator<Item = usize>) -> LookupResult<(),
     // Self::BatchProof>; fn batch_verify(
     //     &self,
@@ -299,10 +316,21 @@ pub trait UniversalMerkleTreeScheme: MerkleTreeScheme {
         elem: impl Borrow<Self::Element>,
     ) -> Result<LookupResult<Self::Element, (), ()>, PrimitivesError>;
 
+    /// Remove the leaf at a given position, turning it back into an empty leaf
+    /// * `pos` - zero-based index of the leaf in the tree
+    /// * `returns` - Ok(LookupResult::Ok(elem, ())) if the removal is success,
+    ///   and `elem` is the removed element. Ok(LookupResult::NotFound(())) if
+    ///   the position is already empty, Ok(LookupResult::NotInMemory) if it has
+    ///   been forgotten. Err() if the removal fails.
+    fn remove(
+        &mut self,
+        pos: impl Borrow<Self::Index>,
+    ) -> Result<LookupResult<Self::Element, (), ()>, PrimitivesError>;
+
     /// Returns the leaf value given a position
     /// * `pos` - zero-based index of the leaf in the tree
     /// * `returns` - Leaf value at the position along with a proof.
-    ///   LookupResult::EmptyLeaf(p) if the leaf position is empty along with a
+    ///   LookupResult::NotFound(p) if the leaf position is empty along with a
     ///   proof p. LookupResult::NotInMemory if the leaf position has been
     ///   forgotten.
     fn universal_lookup(
@@ -345,7 +373,12 @@ pub trait ForgetableMerkleTreeScheme: MerkleTreeScheme {
     ) -> Result<(), PrimitivesError>;
 
     /// Rebuild a merkle tree from a commitment.
-    /// Return a tree which is entirely forgotten.
+    /// Return a tree which is entirely forgotten. It can check proofs against
+    /// the commitment, but lookups return `LookupResult::NotInMemory` until
+    /// leaves are remembered. Insertions fail, unless the tree is empty or
+    /// the path they update has been remembered. A commitment of size 0 whose
+    /// digest is not the one of the empty tree is not taken for an empty tree,
+    /// and insertions fail.
     fn from_commitment(commitment: impl Borrow<Self::Commitment>) -> Self;
 }
 
@@ -120,7 +120,7 @@ where
 impl<E, I, N> BindNamespace<E, I, Sha3Node, N> for Sha3Digest
 where
     E: Element + CanonicalSerialize,
-    I: Index,
+    I: Index + CanonicalSerialize,
     N: Namespace,
 {
     // TODO ensure the hashing of (min,max,hash) is collision resistant
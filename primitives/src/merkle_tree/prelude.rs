@@ -20,9 +20,13 @@ use crate::{
     errors::PrimitivesError,
     rescue::{sponge::RescueCRHF, RescueParameter},
 };
-use ark_std::marker::PhantomData;
+use ark_crypto_primitives::sponge::{
+    poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge, FieldBasedCryptographicSponge,
+};
+use ark_std::{boxed::Box, marker::PhantomData};
 use num_bigint::BigUint;
-use typenum::U3;
+use once_cell::race::OnceBox;
 
 use super::light_weight::LightWeightMerkleTree;
 
@@ -44,10 +48,10 @@ impl<F: RescueParameter> DigestAlgorithm<F, u64, F> for RescueHash<F> {
 }
 
 /// A standard merkle tree using RATE-3 rescue hash function
-pub type RescueMerkleTree<F> = MerkleTree<F, RescueHash<F>, u64, U3, F>;
+pub type RescueMerkleTree<F> = MerkleTree<F, RescueHash<F>, u64, 3, F>;
 
 /// A standard light merkle tree using RATE-3 rescue hash function
-pub type RescueLightWeightMerkleTree<F> = LightWeightMerkleTree<F, RescueHash<F>, u64, U3, F>;
+pub type RescueLightWeightMerkleTree<F> = LightWeightMerkleTree<F, RescueHash<F>, u64, 3, F>;
 
 impl<F: RescueParameter> DigestAlgorithm<F, BigUint, F> for RescueHash<F> {
     fn digest(data: &[F]) -> Result<F, PrimitivesError> {
@@ -72,4 +76,182 @@ impl<F: RescueParameter> DigestAlgorithm<F, F, F> for RescueHash<F> {
 }
 
 /// Example instantiation of a SparseMerkleTree indexed by I
-pub type RescueSparseMerkleTree<I, F> = UniversalMerkleTree<F, RescueHash<F>, I, U3, F>;
+pub type RescueSparseMerkleTree<I, F> = UniversalMerkleTree<F, RescueHash<F>, I, 3, F>;
+
+/// Wrapper for the Poseidon hash function, over a sponge of rate 3 and
+/// capacity 1.
+///
+/// The S-box is the power map with the exponent of [`RescueParameter::A`],
+/// and the round constants and MDS matrix are generated with the Grain LFSR
+/// of the Poseidon paper. They are generated once per field, on the first
+/// digest, and cached by [`PoseidonParameter::poseidon_config`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoseidonHash<F: PoseidonParameter> {
+    phantom_f: PhantomData<F>,
+}
+
+impl<F: PoseidonParameter> PoseidonHash<F> {
+    /// Number of full rounds of the permutation
+    pub const FULL_ROUNDS: usize = 8;
+    /// Number of partial rounds of the permutation. Together with
+    /// [`Self::FULL_ROUNDS`], these are the round numbers given by the
+    /// reference script of the Poseidon paper for a width of 4 and 128-bit
+    /// security, including its security margin: 56 for `x^5`, as in
+    /// circomlib, and 37 for `x^11`.
+    pub const PARTIAL_ROUNDS: usize = match F::A {
+        5 => 56,
+        11 => 37,
+        _ => panic!("No Poseidon round numbers for this S-box"),
+    };
+    /// Number of elements absorbed per permutation
+    pub const RATE: usize = 3;
+
+    /// Parameters of the Poseidon sponge.
+    pub fn config() -> &'static PoseidonConfig<F> {
+        F::poseidon_config()
+    }
+
+    // Runs the Grain LFSR, which is too slow to do for every digest.
+    fn generate_config() -> PoseidonConfig<F> {
+        let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
+            Self::RATE,
+            Self::FULL_ROUNDS as u64,
+            Self::PARTIAL_ROUNDS as u64,
+            0,
+        );
+        PoseidonConfig::new(
+            Self::FULL_ROUNDS,
+            Self::PARTIAL_ROUNDS,
+            F::A,
+            mds,
+            ark,
+            Self::RATE,
+            1,
+        )
+    }
+
+    // Leaves and internal nodes start from different capacity elements, so
+    // that a leaf digest can never be mistaken for an internal node one.
+    fn hash(domain: u64, data: &[F]) -> F {
+        let mut sponge = PoseidonSponge::new(Self::config());
+        sponge.state[0] = F::from(domain);
+        sponge.absorb(&data);
+        sponge.squeeze_native_field_elements(1)[0]
+    }
+}
+
+/// Fields over which [`PoseidonHash`] is defined, each caching its Poseidon
+/// parameters.
+pub trait PoseidonParameter: RescueParameter {
+    /// Parameters of the Poseidon sponge of [`PoseidonHash`], generated on the
+    /// first call.
+    fn poseidon_config() -> &'static PoseidonConfig<Self>;
+}
+
+macro_rules! impl_poseidon_parameter {
+    ($($field:ty),*) => {
+        $(
+            impl PoseidonParameter for $field {
+                fn poseidon_config() -> &'static PoseidonConfig<Self> {
+                    static CONFIG: OnceBox<PoseidonConfig<$field>> = OnceBox::new();
+                    CONFIG.get_or_init(|| Box::new(PoseidonHash::<Self>::generate_config()))
+                }
+            }
+        )*
+    };
+}
+
+impl_poseidon_parameter!(
+    ark_bls12_377::Fq,
+    ark_bls12_381::Fq,
+    ark_bn254::Fq,
+    ark_bw6_761::Fq,
+    ark_ed_on_bls12_377::Fq,
+    ark_ed_on_bls12_381::Fq,
+    ark_ed_on_bn254::Fq
+);
+
+impl<F: PoseidonParameter> DigestAlgorithm<F, u64, F> for PoseidonHash<F> {
+    fn digest(data: &[F]) -> Result<F, PrimitivesError> {
+        Ok(Self::hash(0, data))
+    }
+
+    fn digest_leaf(pos: &u64, elem: &F) -> Result<F, PrimitivesError> {
+        Ok(Self::hash(1, &[F::from(*pos), *elem]))
+    }
+}
+
+/// A standard merkle tree using RATE-3 Poseidon hash function
+pub type PoseidonMerkleTree<F> = MerkleTree<F, PoseidonHash<F>, u64, 3, F>;
+
+#[cfg(test)]
+mod tests {
+    use super::{PoseidonHash, PoseidonMerkleTree, PoseidonParameter};
+    use crate::merkle_tree::{DigestAlgorithm, MerkleCommitment, MerkleTreeScheme};
+    use ark_ed_on_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as Fq254;
+    use ark_std::vec::Vec;
+
+    #[test]
+    fn test_poseidon_hash() {
+        test_poseidon_hash_helper::<Fq254>();
+        test_poseidon_hash_helper::<Fq377>();
+        test_poseidon_hash_helper::<Fq381>();
+    }
+
+    fn test_poseidon_hash_helper<F: PoseidonParameter>() {
+        let data = [F::from(1u64), F::from(2u64), F::from(3u64)];
+        let digest = PoseidonHash::<F>::digest(&data).unwrap();
+        assert_eq!(digest, PoseidonHash::<F>::digest(&data).unwrap());
+        assert_ne!(digest, PoseidonHash::<F>::digest(&data[..2]).unwrap());
+
+        let leaf = PoseidonHash::<F>::digest_leaf(&1, &F::from(2u64)).unwrap();
+        assert_eq!(
+            leaf,
+            PoseidonHash::<F>::digest_leaf(&1, &F::from(2u64)).unwrap()
+        );
+        assert_ne!(
+            leaf,
+            PoseidonHash::<F>::digest_leaf(&2, &F::from(2u64)).unwrap()
+        );
+        // Same inputs as the leaf, without the domain separation.
+        assert_ne!(leaf, PoseidonHash::<F>::digest(&data[..2]).unwrap());
+
+        // The parameters are generated once.
+        assert!(core::ptr::eq(
+            PoseidonHash::<F>::config(),
+            PoseidonHash::<F>::config()
+        ));
+        assert_eq!(
+            PoseidonHash::<F>::config().partial_rounds,
+            PoseidonHash::<F>::PARTIAL_ROUNDS
+        );
+    }
+
+    #[test]
+    fn test_poseidon_mt_membership() {
+        test_poseidon_mt_membership_helper::<Fq254>();
+        test_poseidon_mt_membership_helper::<Fq377>();
+        test_poseidon_mt_membership_helper::<Fq381>();
+    }
+
+    fn test_poseidon_mt_membership_helper<F: PoseidonParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mt = PoseidonMerkleTree::<F>::from_elems(3, &elems).unwrap();
+        let root = mt.commitment().digest();
+        for (pos, elem) in elems.iter().enumerate() {
+            let (found, proof) = mt.lookup(pos as u64).expect_ok().unwrap();
+            assert_eq!(found, elem);
+            assert!(PoseidonMerkleTree::<F>::verify(root, pos as u64, &proof)
+                .unwrap()
+                .is_ok());
+            assert!(
+                PoseidonMerkleTree::<F>::verify(root, pos as u64 + 1, &proof)
+                    .unwrap()
+                    .is_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A merkle tree wrapper which can be shared across threads, for concurrent
+//! reads and occasional appends.
+
+use super::{AppendableMerkleTreeScheme, LookupResult, MerkleTreeScheme};
+use crate::errors::PrimitivesError;
+use ark_std::{borrow::Borrow, string::ToString};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A merkle tree behind a reference-counted read-write lock. Clones are cheap
+/// and all refer to the same tree. Reads take the lock in shared mode and
+/// appends in exclusive mode, so callers never handle the lock themselves.
+#[derive(Debug, Default)]
+pub struct SharedMerkleTree<M>(Arc<RwLock<M>>);
+
+impl<M> Clone for SharedMerkleTree<M> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<M: MerkleTreeScheme> SharedMerkleTree<M> {
+    /// Share `tree`
+    pub fn new(tree: M) -> Self {
+        Self(Arc::new(RwLock::new(tree)))
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<M>, PrimitivesError> {
+        self.0.read().map_err(|_| {
+            PrimitivesError::InternalError("A writer panicked on a shared merkle tree".to_string())
+        })
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<M>, PrimitivesError> {
+        self.0.write().map_err(|_| {
+            PrimitivesError::InternalError("A writer panicked on a shared merkle tree".to_string())
+        })
+    }
+
+    /// Return the current commitment of the tree
+    pub fn commitment(&self) -> Result<M::Commitment, PrimitivesError> {
+        Ok(self.read()?.commitment())
+    }
+
+    /// Returns the leaf value given a position, along with the commitment of
+    /// the tree at the time of the lookup. The proof is valid against this
+    /// commitment, even if leaves have been appended since.
+    /// * `pos` - zero-based index of the leaf in the tree
+    /// * `returns` - Same as [`MerkleTreeScheme::lookup`] with the element
+    ///   copied out of the tree. Err() if the lock is poisoned.
+    #[allow(clippy::type_complexity)]
+    pub fn lookup(
+        &self,
+        pos: impl Borrow<M::Index>,
+    ) -> Result<
+        (
+            M::Commitment,
+            LookupResult<M::Element, M::MembershipProof, ()>,
+        ),
+        PrimitivesError,
+    > {
+        let tree = self.read()?;
+        let res = match tree.lookup(pos) {
+            LookupResult::Ok(elem, proof) => LookupResult::Ok(elem.clone(), proof),
+            LookupResult::NotInMemory => LookupResult::NotInMemory,
+            LookupResult::NotFound(_) => LookupResult::NotFound(()),
+        };
+        Ok((tree.commitment(), res))
+    }
+
+    /// Run `f` on the tree, holding the lock in shared mode
+    pub fn with_read<R>(&self, f: impl FnOnce(&M) -> R) -> Result<R, PrimitivesError> {
+        Ok(f(&*self.read()?))
+    }
+}
+
+impl<M: AppendableMerkleTreeScheme> SharedMerkleTree<M> {
+    /// Insert a new value at the leftmost available slot
+    pub fn push(&self, elem: impl Borrow<M::Element>) -> Result<(), PrimitivesError> {
+        self.write()?.push(elem)
+    }
+
+    /// Insert a list of new values at the leftmost available slots, all under
+    /// a single lock so that readers never observe a partial insertion.
+    pub fn extend(
+        &self,
+        elems: impl IntoIterator<Item = impl Borrow<M::Element>>,
+    ) -> Result<(), PrimitivesError> {
+        self.write()?.extend(elems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        merkle_tree::{prelude::RescueMerkleTree, MerkleCommitment},
+        rescue::RescueParameter,
+    };
+    use ark_ed_on_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as Fq254;
+    use ark_std::vec::Vec;
+    use std::thread;
+
+    #[test]
+    fn test_shared_mt_concurrent_reads() {
+        test_shared_mt_concurrent_reads_helper::<Fq254>();
+        test_shared_mt_concurrent_reads_helper::<Fq377>();
+        test_shared_mt_concurrent_reads_helper::<Fq381>();
+    }
+
+    fn test_shared_mt_concurrent_reads_helper<F: RescueParameter>() {
+        let elems = (0..100u64).map(F::from).collect::<Vec<_>>();
+        let mt = SharedMerkleTree::new(RescueMerkleTree::<F>::from_elems(5, &elems[..1]).unwrap());
+
+        let writer = {
+            let mt = mt.clone();
+            let elems = elems.clone();
+            thread::spawn(move || {
+                for elem in &elems[1..] {
+                    mt.push(elem).unwrap();
+                }
+            })
+        };
+        let readers = (0..4u64)
+            .map(|i| {
+                let mt = mt.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        let (com, res) = mt.lookup(i).unwrap();
+                        if i >= com.size() {
+                            res.expect_not_found().unwrap();
+                            continue;
+                        }
+                        let (elem, proof) = res.expect_ok().unwrap();
+                        assert_eq!(elem, F::from(i));
+                        assert!(RescueMerkleTree::<F>::verify(com.digest(), i, &proof)
+                            .unwrap()
+                            .is_ok());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(mt.commitment().unwrap().size(), 100);
+        assert_eq!(
+            mt.with_read(|tree| tree.commitment()).unwrap(),
+            RescueMerkleTree::<F>::from_elems(5, &elems)
+                .unwrap()
+                .commitment()
+        );
+    }
+}
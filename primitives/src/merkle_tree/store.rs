@@ -0,0 +1,218 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Storage backends to persist the nodes of a merkle tree. A tree can be
+//! written to a [`NodeStore`] with `save_to_store()`, reopened from it with
+//! `from_store()`, and its paths read back on demand with
+//! `load_from_store()`.
+
+use crate::errors::PrimitivesError;
+use ark_std::vec::Vec;
+use hashbrown::HashMap;
+
+/// Identifier of a node in a merkle tree: the traversal path from the root to
+/// this node, the root being the empty path.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub Vec<usize>);
+
+/// A key-value store for the nodes of a merkle tree.
+pub trait NodeStore<T> {
+    /// Return the node stored under `id`, or None if there is none.
+    /// * `returns` - Err() if the node cannot be read or is corrupted
+    fn get(&self, id: &NodeId) -> Result<Option<T>, PrimitivesError>;
+    /// Store `node` under `id`, replacing any previous value.
+    fn put(&mut self, id: NodeId, node: T) -> Result<(), PrimitivesError>;
+}
+
+/// A node store keeping all the nodes in memory.
+#[derive(Clone, Debug)]
+pub struct MemoryNodeStore<T>(HashMap<NodeId, T>);
+
+impl<T> Default for MemoryNodeStore<T> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<T: Clone> NodeStore<T> for MemoryNodeStore<T> {
+    fn get(&self, id: &NodeId) -> Result<Option<T>, PrimitivesError> {
+        Ok(self.0.get(id).cloned())
+    }
+
+    fn put(&mut self, id: NodeId, node: T) -> Result<(), PrimitivesError> {
+        self.0.insert(id, node);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+pub use file::FileNodeStore;
+
+#[cfg(feature = "std")]
+mod file {
+    use super::{NodeId, NodeStore};
+    use crate::errors::PrimitivesError;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{format, marker::PhantomData, string::ToString, vec::Vec};
+    use std::{
+        fs,
+        io::ErrorKind,
+        path::{Path, PathBuf},
+    };
+
+    /// A node store keeping one file per node in a directory, so that the
+    /// nodes survive the process.
+    #[derive(Clone, Debug)]
+    pub struct FileNodeStore<T> {
+        dir: PathBuf,
+        _phantom: PhantomData<T>,
+    }
+
+    impl<T> FileNodeStore<T> {
+        /// Open the store in `dir`, creating the directory if it does not
+        /// exist. Nodes written by a previous store in the same directory
+        /// are visible.
+        pub fn open(dir: impl AsRef<Path>) -> Result<Self, PrimitivesError> {
+            let dir = dir.as_ref().to_path_buf();
+            fs::create_dir_all(&dir).map_err(|e| {
+                PrimitivesError::InternalError(format!("Cannot open node store: {e}"))
+            })?;
+            Ok(Self {
+                dir,
+                _phantom: PhantomData,
+            })
+        }
+
+        fn path(&self, id: &NodeId) -> PathBuf {
+            let name = ["node".to_string()]
+                .into_iter()
+                .chain(id.0.iter().map(|branch| branch.to_string()))
+                .collect::<Vec<_>>()
+                .join("_");
+            self.dir.join(name)
+        }
+    }
+
+    impl<T: CanonicalSerialize + CanonicalDeserialize> NodeStore<T> for FileNodeStore<T> {
+        fn get(&self, id: &NodeId) -> Result<Option<T>, PrimitivesError> {
+            let bytes = match fs::read(self.path(id)) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(e) => {
+                    return Err(PrimitivesError::InternalError(format!(
+                        "Cannot read from node store: {e}"
+                    )))
+                },
+            };
+            Ok(Some(T::deserialize_compressed(&bytes[..])?))
+        }
+
+        fn put(&mut self, id: NodeId, node: T) -> Result<(), PrimitivesError> {
+            let mut bytes = Vec::new();
+            node.serialize_compressed(&mut bytes)?;
+            fs::write(self.path(&id), bytes).map_err(|e| {
+                PrimitivesError::InternalError(format!("Cannot write to node store: {e}"))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        merkle_tree::{
+            internal::MerkleNode,
+            prelude::{RescueMerkleTree, RescueSparseMerkleTree},
+            LookupResult, MerkleTreeScheme, UniversalMerkleTreeScheme,
+        },
+        rescue::RescueParameter,
+    };
+    use ark_ed_on_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as Fq254;
+    use ark_std::{vec, One};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_memory_store() {
+        test_memory_store_helper::<Fq254>();
+        test_memory_store_helper::<Fq377>();
+        test_memory_store_helper::<Fq381>();
+    }
+
+    fn test_memory_store_helper<F: RescueParameter>() {
+        let elems = (0..100u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(5, &elems).unwrap();
+        let mut store = MemoryNodeStore::default();
+        mt.save_to_store(&mut store).unwrap();
+        let mut loaded = RescueMerkleTree::<F>::from_store(&store, mt.commitment()).unwrap();
+        assert_eq!(loaded.commitment(), mt.commitment());
+        // Nothing but the root is read until a path is loaded.
+        assert!(matches!(loaded.lookup(42), LookupResult::NotInMemory));
+        loaded.load_from_store(42, &store).unwrap();
+        assert_eq!(
+            loaded.lookup(42).expect_ok().unwrap(),
+            mt.lookup(42).expect_ok().unwrap()
+        );
+        assert!(matches!(loaded.lookup(43), LookupResult::NotInMemory));
+        for pos in 0..100 {
+            loaded.load_from_store(pos, &store).unwrap();
+        }
+        assert_eq!(loaded, mt);
+
+        // The store has to match the commitment.
+        let other = RescueMerkleTree::<F>::from_elems(5, &elems[1..]).unwrap();
+        assert!(RescueMerkleTree::<F>::from_store(&store, other.commitment()).is_err());
+
+        // Every node read has to match its parent.
+        let mut corrupted = store.clone();
+        let (id, node) = store
+            .0
+            .iter()
+            .find(|(_, node)| matches!(node, MerkleNode::Leaf { .. }))
+            .unwrap();
+        if let MerkleNode::Leaf { value, pos, elem } = node.clone() {
+            corrupted.0.insert(
+                id.clone(),
+                MerkleNode::Leaf {
+                    value,
+                    pos,
+                    elem: elem + F::one(),
+                },
+            );
+            let mut loaded =
+                RescueMerkleTree::<F>::from_store(&corrupted, mt.commitment()).unwrap();
+            assert!(loaded.load_from_store(pos, &corrupted).is_err());
+        }
+        let mut corrupted = store.clone();
+        corrupted.0.remove(&NodeId(vec![0, 0]));
+        let mut loaded = RescueMerkleTree::<F>::from_store(&corrupted, mt.commitment()).unwrap();
+        assert!(loaded.load_from_store(0, &corrupted).is_err());
+
+        // A sparse tree, with empty subtrees left out of the store.
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            10,
+            [
+                (BigUint::from(7u64), F::from(1u64)),
+                (BigUint::from(30000u64), F::from(2u64)),
+            ],
+        )
+        .unwrap();
+        let mut store = MemoryNodeStore::default();
+        mt.save_to_store(&mut store).unwrap();
+        let mut loaded =
+            RescueSparseMerkleTree::<BigUint, F>::from_store(&store, mt.commitment()).unwrap();
+        for pos in [7u64, 8, 30000] {
+            loaded.load_from_store(BigUint::from(pos), &store).unwrap();
+        }
+        assert!(loaded
+            .universal_lookup(BigUint::from(8u64))
+            .expect_not_found()
+            .is_ok());
+        assert_eq!(loaded, mt);
+    }
+}
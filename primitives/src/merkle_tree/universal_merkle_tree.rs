@@ -6,10 +6,14 @@
 
 //! Implementation of a typical Sparse Merkle Tree.
 use super::{
-    internal::{build_tree_internal, MerkleNode, MerkleProof, MerkleTreeCommitment},
-    DigestAlgorithm, Element, ForgetableMerkleTreeScheme, ForgetableUniversalMerkleTreeScheme,
-    Index, LookupResult, MerkleCommitment, MerkleTreeScheme, NodeValue, ToTraversalPath,
-    UniversalMerkleTreeScheme,
+    internal::{
+        batch_traversal_paths, build_tree_internal, check_range, MerkleBatchProof, MerkleNode,
+        MerkleProof, MerkleRangeProof, MerkleTreeCommitment,
+    },
+    store::{NodeId, NodeStore},
+    AppendableMerkleTreeScheme, DigestAlgorithm, Element, ForgetableMerkleTreeScheme,
+    ForgetableUniversalMerkleTreeScheme, Index, LookupResult, MerkleCommitment, MerkleTreeIter,
+    MerkleTreeScheme, NodeValue, ToTraversalPath, UniversalMerkleTreeScheme,
 };
 use crate::{
     errors::{PrimitivesError, VerificationResult},
@@ -18,24 +22,36 @@ use crate::{
 use ark_std::{
     borrow::Borrow, boxed::Box, fmt::Debug, marker::PhantomData, string::ToString, vec, vec::Vec,
 };
+use core::ops::AddAssign;
 use num_bigint::BigUint;
 use num_traits::pow::pow;
 use serde::{Deserialize, Serialize};
-use typenum::Unsigned;
+
+/// An entry of [`UniversalMerkleTree::occupied_positions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OccupiedPosition<I> {
+    /// The position of a leaf in memory
+    InMemory(I),
+    /// A forgotten subtree, given by the branches taken to reach it from the
+    /// root. They are listed from the bottom up, as in
+    /// [`ToTraversalPath::to_traversal_path`], so the traversal path of every
+    /// position in the subtree ends with them.
+    Forgotten(Vec<usize>),
+}
 
 // A standard Universal Merkle tree implementation
 impl_merkle_tree_scheme!(UniversalMerkleTree, build_tree_internal);
 impl_forgetable_merkle_tree_scheme!(UniversalMerkleTree);
 
-impl<E, H, I, Arity, T> UniversalMerkleTreeScheme for UniversalMerkleTree<E, H, I, Arity, T>
+impl<E, H, I, const ARITY: usize, T> UniversalMerkleTreeScheme
+    for UniversalMerkleTree<E, H, I, ARITY, T>
 where
     E: Element,
     H: DigestAlgorithm<E, I, T>,
-    I: Index + From<u64> + ToTraversalPath<Arity>,
-    Arity: Unsigned,
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
     T: NodeValue,
 {
-    type NonMembershipProof = MerkleProof<E, I, T, Arity>;
+    type NonMembershipProof = MerkleProof<E, I, T, ARITY>;
     type BatchNonMembershipProof = ();
 
     fn update(
@@ -48,13 +64,25 @@ where
         let traversal_path = pos.to_traversal_path(self.height);
         let ret = self
             .root
-            .update_internal::<H, Arity>(self.height, pos, &traversal_path, elem)?;
+            .update_internal::<H, ARITY>(self.height, pos, &traversal_path, elem)?;
         if let LookupResult::NotFound(_) = ret {
             self.num_leaves += 1;
         }
         Ok(ret)
     }
 
+    fn remove(&mut self, pos: impl Borrow<I>) -> Result<LookupResult<E, (), ()>, PrimitivesError> {
+        let pos = pos.borrow();
+        let traversal_path = pos.to_traversal_path(self.height);
+        let ret = self
+            .root
+            .remove_internal::<H, ARITY>(self.height, pos, &traversal_path)?;
+        if let LookupResult::Ok(..) = ret {
+            self.num_leaves -= 1;
+        }
+        Ok(ret)
+    }
+
     fn from_kv_set<BI, BE>(
         height: usize,
         data: impl IntoIterator<Item = impl Borrow<(BI, BE)>>,
@@ -79,12 +107,12 @@ where
         let pos = pos.borrow();
         let proof = proof.borrow();
         if self.height != proof.tree_height() - 1 {
-            return Err(PrimitivesError::ParameterError(
+            return Err(PrimitivesError::InvalidProof(
                 "Incompatible membership proof for this merkle tree".to_string(),
             ));
         }
         if *pos != proof.pos {
-            return Err(PrimitivesError::ParameterError(
+            return Err(PrimitivesError::InvalidProof(
                 "Inconsistent proof index".to_string(),
             ));
         }
@@ -99,7 +127,18 @@ where
         let traversal_path = pos.to_traversal_path(self.height);
         match self.root.lookup_internal(self.height, &traversal_path) {
             LookupResult::Ok(value, proof) => {
-                LookupResult::Ok(value, MerkleProof::new(pos.clone(), proof))
+                // A position beyond the capacity of the tree wraps around to some other
+                // leaf. It is not found, and the path to that leaf, which is not a valid
+                // non-membership proof, is rejected by `non_membership_verify`.
+                let is_hit = matches!(
+                    &proof[0],
+                    MerkleNode::Leaf { pos: leaf_pos, .. } if leaf_pos == pos
+                );
+                if is_hit {
+                    LookupResult::Ok(value, MerkleProof::new(pos.clone(), proof))
+                } else {
+                    LookupResult::NotFound(MerkleProof::new(pos.clone(), proof))
+                }
             },
             LookupResult::NotInMemory => LookupResult::NotInMemory,
             LookupResult::NotFound(non_membership_proof) => {
@@ -109,13 +148,110 @@ where
     }
 }
 
-impl<E, H, I, Arity, T> ForgetableUniversalMerkleTreeScheme
-    for UniversalMerkleTree<E, H, I, Arity, T>
+impl<E, H, I, const ARITY: usize, T> UniversalMerkleTree<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Return the largest occupied position, found in O(height) by following
+    /// the rightmost non-empty subtrees.
+    /// * `returns` - LookupResult::NotFound if the tree is empty,
+    ///   LookupResult::NotInMemory if the rightmost leaf has been forgotten.
+    pub fn max_index(&self) -> LookupResult<&I, (), ()> {
+        self.root.max_index_internal()
+    }
+
+    /// Return the occupied positions, in index order, so that a client can
+    /// tell which leaves to sync. Only the occupied subtrees are visited, so
+    /// the cost does not depend on the capacity of the tree.
+    ///
+    /// The positions of forgotten leaves are not known to the tree, so each
+    /// forgotten subtree is returned as an [`OccupiedPosition::Forgotten`]
+    /// marker, in the place its leaves would take. It holds at least one of
+    /// the `self.num_leaves()` leaves.
+    pub fn occupied_positions(&self) -> Vec<OccupiedPosition<I>> {
+        let mut positions = vec![];
+        occupied_positions_internal(&self.root, &mut vec![], &mut positions);
+        positions
+    }
+
+    /// Apply a list of updates, with the same outcome as calling
+    /// [`UniversalMerkleTreeScheme::update`] on each of them in order, but
+    /// hashing every node on the updated paths only once. When several
+    /// updates share a position, the last one wins.
+    /// * `returns` - the result of each update, as returned by
+    ///   [`UniversalMerkleTreeScheme::update`]
+    pub fn update_batch(
+        &mut self,
+        updates: &[(I, E)],
+    ) -> Result<Vec<LookupResult<E, (), ()>>, PrimitivesError> {
+        let traversal_paths = updates
+            .iter()
+            .map(|(pos, _)| pos.to_traversal_path(self.height))
+            .collect::<Vec<_>>();
+        let items = updates
+            .iter()
+            .zip(traversal_paths.iter())
+            .enumerate()
+            .map(|(i, ((pos, elem), path))| (i, pos, path.as_slice(), elem))
+            .collect::<Vec<_>>();
+        let mut results: Vec<LookupResult<E, (), ()>> = (0..updates.len())
+            .map(|_| LookupResult::NotInMemory)
+            .collect();
+        self.root
+            .update_batch_internal::<H, ARITY>(self.height, &items, &mut results)?;
+        self.num_leaves += results
+            .iter()
+            .filter(|res| matches!(res, LookupResult::NotFound(_)))
+            .count() as u64;
+        Ok(results)
+    }
+}
+
+impl<E, H, I, const ARITY: usize, T> AppendableMerkleTreeScheme
+    for UniversalMerkleTree<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + From<u64> + AddAssign + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Insert a new value right after the largest occupied position, or at 0
+    /// if the tree is empty.
+    fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), PrimitivesError> {
+        let pos = match self.max_index() {
+            LookupResult::Ok(max, _) => {
+                let mut pos = max.clone();
+                pos += I::from(1u64);
+                pos
+            },
+            LookupResult::NotFound(_) => I::from(0u64),
+            LookupResult::NotInMemory => {
+                return Err(PrimitivesError::ParameterError(
+                    "The largest occupied position has been forgotten".to_string(),
+                ))
+            },
+        };
+        if let Ok(capacity) = u64::try_from(self.capacity()) {
+            if pos >= I::from(capacity) {
+                return Err(PrimitivesError::IndexOutOfRange(
+                    "Exceed merkle tree capacity".to_string(),
+                ));
+            }
+        }
+        UniversalMerkleTreeScheme::update(self, pos, elem)?;
+        Ok(())
+    }
+}
+
+impl<E, H, I, const ARITY: usize, T> ForgetableUniversalMerkleTreeScheme
+    for UniversalMerkleTree<E, H, I, ARITY, T>
 where
     E: Element,
     H: DigestAlgorithm<E, I, T>,
-    I: Index + From<u64> + ToTraversalPath<Arity>,
-    Arity: Unsigned,
+    I: Index + From<u64> + ToTraversalPath<ARITY>,
     T: NodeValue,
 {
     fn universal_forget(
@@ -159,44 +295,75 @@ where
                                 path_values.push(empty_value);
                                 Ok(empty_value)
                             },
-                            _ => Err(PrimitivesError::ParameterError(
+                            _ => Err(PrimitivesError::InvalidProof(
                                 "Incompatible proof for this merkle tree".to_string(),
                             )),
                         }
                     },
                 )?;
-            self.root.remember_internal::<H, Arity>(
+            self.root.remember_internal::<H, ARITY>(
                 self.height,
                 &traversal_path,
                 &path_values,
                 &proof.proof,
             )
         } else {
-            Err(PrimitivesError::ParameterError(
+            Err(PrimitivesError::InvalidProof(
                 "Invalid proof type".to_string(),
             ))
         }
     }
 }
 
+// `path` holds the branches taken from the root to `node`, from the top down.
+fn occupied_positions_internal<E, I, T>(
+    node: &MerkleNode<E, I, T>,
+    path: &mut Vec<usize>,
+    positions: &mut Vec<OccupiedPosition<I>>,
+) where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+{
+    match node {
+        MerkleNode::Leaf { pos, .. } => positions.push(OccupiedPosition::InMemory(pos.clone())),
+        MerkleNode::Branch { children, .. } => {
+            for (branch, child) in children.iter().enumerate() {
+                path.push(branch);
+                occupied_positions_internal(child, path, positions);
+                path.pop();
+            }
+        },
+        MerkleNode::ForgettenSubtree { .. } => {
+            positions.push(OccupiedPosition::Forgotten(
+                path.iter().rev().copied().collect(),
+            ));
+        },
+        MerkleNode::Empty => {},
+    }
+}
+
 #[cfg(test)]
 mod mt_tests {
     use crate::{
+        errors::PrimitivesError,
         merkle_tree::{
             internal::{MerkleNode, MerkleProof},
             prelude::{RescueHash, RescueSparseMerkleTree},
-            DigestAlgorithm, ForgetableMerkleTreeScheme, ForgetableUniversalMerkleTreeScheme,
-            Index, LookupResult, MerkleCommitment, MerkleTreeScheme, ToTraversalPath,
-            UniversalMerkleTreeScheme,
+            universal_merkle_tree::OccupiedPosition,
+            AppendableMerkleTreeScheme, DigestAlgorithm, ForgetableMerkleTreeScheme,
+            ForgetableUniversalMerkleTreeScheme, Index, LookupResult, MerkleCommitment,
+            MerkleTreeScheme, ToTraversalPath, UniversalMerkleTree, UniversalMerkleTreeScheme,
         },
         rescue::RescueParameter,
     };
     use ark_ed_on_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_381::Fq as Fq381;
     use ark_ed_on_bn254::Fq as Fq254;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{rand::Rng, vec::Vec};
     use hashbrown::HashMap;
     use num_bigint::BigUint;
-    use typenum::U3;
 
     #[test]
     fn test_universal_mt_builder() {
@@ -221,6 +388,124 @@ mod mt_tests {
         assert_eq!(mt.num_leaves(), hashmap.len() as u64);
     }
 
+    #[test]
+    fn test_universal_mt_getters() {
+        test_universal_mt_getters_helper::<Fq254>();
+        test_universal_mt_getters_helper::<Fq377>();
+        test_universal_mt_getters_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_getters_helper<F: RescueParameter>() {
+        let mut mt =
+            RescueSparseMerkleTree::<BigUint, F>::from_kv_set(10, [] as [&(BigUint, F); 0])
+                .unwrap();
+        assert_eq!(mt.height(), 10);
+        assert_eq!(mt.capacity(), BigUint::from(59049u64));
+        assert_eq!(mt.num_leaves(), 0);
+
+        // Only occupied slots are counted, wherever they are.
+        for pos in [59048u64, 7, 30000] {
+            mt.update(BigUint::from(pos), F::from(pos)).unwrap();
+        }
+        assert_eq!(mt.num_leaves(), 3);
+        mt.update(BigUint::from(7u64), F::from(8u64)).unwrap();
+        assert_eq!(mt.num_leaves(), 3);
+        mt.remove(BigUint::from(30000u64)).unwrap();
+        mt.remove(BigUint::from(30001u64)).unwrap();
+        assert_eq!(mt.num_leaves(), 2);
+        assert_eq!(mt.height(), 10);
+        assert_eq!(mt.capacity(), BigUint::from(59049u64));
+    }
+
+    #[test]
+    fn test_universal_mt_update_batch() {
+        test_universal_mt_update_batch_helper::<Fq254>();
+        test_universal_mt_update_batch_helper::<Fq377>();
+        test_universal_mt_update_batch_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_update_batch_helper<F: RescueParameter>() {
+        let mut rng = jf_utils::test_rng();
+        let initial = (0..50u64)
+            .map(|i| (BigUint::from(i * 1000), F::from(i)))
+            .collect::<Vec<_>>();
+        let mut expected = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(10, &initial).unwrap();
+        let mut mt = expected.clone();
+
+        // Scattered positions, some of them occupied or updated twice.
+        let updates = (0..500u64)
+            .map(|i| {
+                let pos = if i % 10 == 0 {
+                    (i % 50) * 1000
+                } else {
+                    rng.gen_range(0..59049u64)
+                };
+                (BigUint::from(pos), F::from(i + 100))
+            })
+            .collect::<Vec<_>>();
+        let sequential = updates
+            .iter()
+            .map(|(pos, elem)| expected.update(pos, elem).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(mt.update_batch(&updates).unwrap(), sequential);
+        assert_eq!(mt.commitment(), expected.commitment());
+        assert_eq!(mt.num_leaves(), expected.num_leaves());
+        for (pos, _) in updates.iter() {
+            assert_eq!(
+                mt.lookup(pos).expect_ok().unwrap(),
+                expected.lookup(pos).expect_ok().unwrap()
+            );
+        }
+
+        // Updates of a forgotten leaf fail without changing the root.
+        let root = mt.commitment().digest();
+        mt.forget(BigUint::from(0u64)).expect_ok().unwrap();
+        let results = mt
+            .update_batch(&[(BigUint::from(0u64), F::from(1u64))])
+            .unwrap();
+        assert_eq!(results, [LookupResult::NotInMemory]);
+        assert_eq!(mt.commitment().digest(), root);
+        assert!(mt.update_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_universal_lookup_wrapped_pos() {
+        test_universal_lookup_wrapped_pos_helper::<Fq254>();
+        test_universal_lookup_wrapped_pos_helper::<Fq377>();
+        test_universal_lookup_wrapped_pos_helper::<Fq381>();
+    }
+
+    fn test_universal_lookup_wrapped_pos_helper<F: RescueParameter>() {
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            2,
+            &[(BigUint::from(4u64), F::from(1u64))],
+        )
+        .unwrap();
+        let root = mt.commitment().digest();
+        // 13 = 9 + 4 has the traversal path of leaf 4 in a tree of capacity 9.
+        let wrapped = BigUint::from(13u64);
+        let proof = match mt.universal_lookup(&wrapped) {
+            LookupResult::NotFound(proof) => proof,
+            res => panic!("expected NotFound, got {:?}", res),
+        };
+        assert!(mt.non_membership_verify(&wrapped, &proof).is_err());
+        // The path does not pass as a membership proof of the wrapped
+        // position either.
+        assert!(
+            RescueSparseMerkleTree::<BigUint, F>::verify(root, &wrapped, &proof)
+                .unwrap()
+                .is_err()
+        );
+        // The wrapped position of an empty slot is not found, and proven so.
+        let proof = mt
+            .universal_lookup(BigUint::from(14u64))
+            .expect_not_found()
+            .unwrap();
+        assert!(mt
+            .non_membership_verify(BigUint::from(14u64), &proof)
+            .unwrap());
+    }
+
     #[test]
     fn test_non_membership_lookup_and_verify() {
         test_non_membership_lookup_and_verify_helper::<Fq254>();
@@ -248,7 +533,359 @@ mod mt_tests {
         assert!(verify_result.is_ok() && !verify_result.unwrap());
 
         let verify_result = mt.non_membership_verify(BigUint::from(4u64), proof);
-        assert!(verify_result.is_err());
+        assert!(matches!(
+            verify_result,
+            Err(PrimitivesError::InvalidProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_non_membership_edge_cases() {
+        test_non_membership_edge_cases_helper::<Fq254>();
+        test_non_membership_edge_cases_helper::<Fq377>();
+        test_non_membership_edge_cases_helper::<Fq381>();
+    }
+
+    fn test_non_membership_edge_cases_helper<F: RescueParameter>() {
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            10,
+            &[
+                (BigUint::from(1u64), F::from(2u64)),
+                (BigUint::from(2u64), F::from(3u64)),
+            ],
+        )
+        .unwrap();
+        let root = mt.commitment().digest();
+
+        // The first and the last index of the tree are both empty.
+        let max_index = mt.capacity() - 1u64;
+        for pos in [BigUint::from(0u64), max_index] {
+            let proof = mt.universal_lookup(&pos).expect_not_found().unwrap();
+            assert!(mt.non_membership_verify(&pos, &proof).unwrap());
+            assert!(proof
+                .verify_non_membership_proof::<RescueHash<F>>(&root)
+                .unwrap());
+        }
+
+        // There is no non-membership proof for an occupied index, and a proof
+        // for an empty neighbor cannot be reused for it.
+        assert!(mt
+            .universal_lookup(BigUint::from(1u64))
+            .expect_not_found()
+            .is_err());
+        let mut proof = mt
+            .universal_lookup(BigUint::from(0u64))
+            .expect_not_found()
+            .unwrap();
+        proof.pos = BigUint::from(1u64);
+        assert!(!mt
+            .non_membership_verify(BigUint::from(1u64), &proof)
+            .unwrap());
+        assert!(!proof
+            .verify_non_membership_proof::<RescueHash<F>>(&root)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_non_membership_deep_empty_regions() {
+        test_non_membership_deep_empty_regions_helper::<Fq254>();
+        test_non_membership_deep_empty_regions_helper::<Fq377>();
+        test_non_membership_deep_empty_regions_helper::<Fq381>();
+    }
+
+    fn test_non_membership_deep_empty_regions_helper<F: RescueParameter>() {
+        let height = 40;
+        let max_index = BigUint::from(3u64).pow(height as u32) - 1u64;
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            height,
+            [
+                (BigUint::from(0u64), F::from(1u64)),
+                (max_index.clone(), F::from(2u64)),
+            ],
+        )
+        .unwrap();
+        let root = mt.commitment().digest();
+
+        // The middle subtree below the root is entirely empty, so the proof is
+        // empty up to the root.
+        let pos = BigUint::from(3u64).pow(39) + 5u64;
+        let proof = mt.universal_lookup(&pos).expect_not_found().unwrap();
+        assert_eq!(proof.proof.len(), height + 1);
+        assert!(proof.proof[..height]
+            .iter()
+            .all(|node| matches!(node, MerkleNode::Empty)));
+
+        // Together with empty positions next to the occupied ones, whose
+        // proofs only become empty near the leaves.
+        for pos in [pos, BigUint::from(1u64), &max_index - 1u64] {
+            let proof = mt.universal_lookup(&pos).expect_not_found().unwrap();
+            assert!(proof
+                .verify_non_membership_proof::<RescueHash<F>>(&root)
+                .unwrap());
+        }
+        for pos in [BigUint::from(0u64), max_index] {
+            let (_, proof) = mt.universal_lookup(&pos).expect_ok().unwrap();
+            assert!(
+                RescueSparseMerkleTree::<BigUint, F>::verify(&root, &pos, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_universal_mt_remove() {
+        test_universal_mt_remove_helper::<Fq254>();
+        test_universal_mt_remove_helper::<Fq377>();
+        test_universal_mt_remove_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_remove_helper<F: RescueParameter>() {
+        let mut mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            10,
+            &[
+                (BigUint::from(1u64), F::from(2u64)),
+                (BigUint::from(2u64), F::from(3u64)),
+                (BigUint::from(100u64), F::from(4u64)),
+            ],
+        )
+        .unwrap();
+        let root = mt.commitment().digest();
+
+        // Removing an empty position is a no-op.
+        assert_eq!(
+            mt.remove(BigUint::from(3u64)).unwrap(),
+            LookupResult::NotFound(())
+        );
+        assert_eq!(mt.commitment().digest(), root);
+        assert_eq!(mt.num_leaves(), 3);
+
+        // Positions beyond the capacity wrap around to an occupied leaf, which
+        // is left in place.
+        let capacity = BigUint::from(3u64).pow(10);
+        assert_eq!(
+            mt.remove(capacity.clone() + 100u64).unwrap(),
+            LookupResult::NotFound(())
+        );
+        assert_eq!(
+            mt.remove(capacity * 2u64 + 1u64).unwrap(),
+            LookupResult::NotFound(())
+        );
+        assert_eq!(mt.commitment().digest(), root);
+        assert_eq!(mt.num_leaves(), 3);
+        assert_eq!(
+            mt.lookup(BigUint::from(100u64)).expect_ok().unwrap().0,
+            &F::from(4u64)
+        );
+
+        // Remove a leaf, then re-insert it.
+        assert_eq!(
+            mt.remove(BigUint::from(100u64)).unwrap(),
+            LookupResult::Ok(F::from(4u64), ())
+        );
+        assert_eq!(mt.num_leaves(), 2);
+        assert!(mt
+            .universal_lookup(BigUint::from(100u64))
+            .expect_not_found()
+            .is_ok());
+        let expected = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            10,
+            &[
+                (BigUint::from(1u64), F::from(2u64)),
+                (BigUint::from(2u64), F::from(3u64)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(mt.commitment(), expected.commitment());
+        mt.update(BigUint::from(100u64), F::from(4u64)).unwrap();
+        assert_eq!(mt.commitment().digest(), root);
+
+        // Removing every leaf yields an empty tree.
+        for pos in [1u64, 2, 100] {
+            mt.remove(BigUint::from(pos)).unwrap().expect_ok().unwrap();
+        }
+        let empty = RescueSparseMerkleTree::<BigUint, F>::from_elems(10, [] as [&F; 0]).unwrap();
+        assert_eq!(mt.commitment(), empty.commitment());
+    }
+
+    #[test]
+    fn test_universal_mt_sparse_and_append() {
+        test_universal_mt_sparse_and_append_helper::<Fq254>();
+        test_universal_mt_sparse_and_append_helper::<Fq377>();
+        test_universal_mt_sparse_and_append_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_sparse_and_append_helper<F: RescueParameter>() {
+        let mut mt =
+            RescueSparseMerkleTree::<BigUint, F>::from_kv_set(3, [] as [&(BigUint, F); 0]).unwrap();
+        assert_eq!(mt.max_index(), LookupResult::NotFound(()));
+        mt.push(F::from(1u64)).unwrap();
+        assert_eq!(mt.max_index(), LookupResult::Ok(&BigUint::from(0u64), ()));
+
+        // Sparse updates move the largest position.
+        mt.update(BigUint::from(10u64), F::from(2u64)).unwrap();
+        mt.update(BigUint::from(4u64), F::from(3u64)).unwrap();
+        assert_eq!(mt.max_index(), LookupResult::Ok(&BigUint::from(10u64), ()));
+        mt.push(F::from(4u64)).unwrap();
+        assert_eq!(
+            mt.universal_lookup(BigUint::from(11u64))
+                .expect_ok()
+                .unwrap()
+                .0,
+            &F::from(4u64)
+        );
+        assert_eq!(mt.num_leaves(), 4);
+
+        // Removing the largest leaf moves it back, and empty positions have a
+        // non-membership proof, including the removed one.
+        mt.remove(BigUint::from(11u64)).unwrap();
+        mt.remove(BigUint::from(10u64)).unwrap();
+        assert_eq!(mt.max_index(), LookupResult::Ok(&BigUint::from(4u64), ()));
+        for pos in [1u64, 10, 11, 26] {
+            let proof = mt
+                .universal_lookup(BigUint::from(pos))
+                .expect_not_found()
+                .unwrap();
+            assert!(mt
+                .non_membership_verify(BigUint::from(pos), &proof)
+                .unwrap());
+        }
+        mt.push(F::from(5u64)).unwrap();
+        assert_eq!(mt.max_index(), LookupResult::Ok(&BigUint::from(5u64), ()));
+
+        // The same leaves inserted out of order give the same tree.
+        let expected = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            3,
+            [(5u64, 5u64), (0, 1), (4, 3)].map(|(pos, elem)| (BigUint::from(pos), F::from(elem))),
+        )
+        .unwrap();
+        assert_eq!(mt.commitment(), expected.commitment());
+
+        // Appending past the last position fails.
+        mt.update(BigUint::from(26u64), F::from(6u64)).unwrap();
+        assert!(matches!(
+            mt.push(F::from(7u64)),
+            Err(PrimitivesError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_universal_mt_diff() {
+        test_universal_mt_diff_helper::<Fq254>();
+        test_universal_mt_diff_helper::<Fq377>();
+        test_universal_mt_diff_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_diff_helper<F: RescueParameter>() {
+        let kvs = (0..200u64)
+            .map(|i| (BigUint::from(i * 97), F::from(i)))
+            .collect::<Vec<_>>();
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(10, &kvs).unwrap();
+        assert!(mt.diff(&mt.clone()).unwrap().is_empty());
+
+        let mut other = mt.clone();
+        // An updated leaf, a removed leaf and two new leaves.
+        other
+            .update(BigUint::from(970u64), F::from(1000u64))
+            .unwrap();
+        other.remove(BigUint::from(97u64)).unwrap();
+        other.update(BigUint::from(5u64), F::from(5u64)).unwrap();
+        other
+            .update(BigUint::from(59048u64), F::from(6u64))
+            .unwrap();
+        // Setting a leaf to its current value is not a change.
+        other.update(BigUint::from(194u64), F::from(2u64)).unwrap();
+        let expected = [5u64, 97, 970, 59048]
+            .into_iter()
+            .map(BigUint::from)
+            .collect::<Vec<_>>();
+        assert_eq!(mt.diff(&other).unwrap(), expected);
+        assert_eq!(other.diff(&mt).unwrap(), expected);
+
+        let taller = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(11, &kvs).unwrap();
+        assert!(mt.diff(&taller).is_err());
+    }
+
+    #[test]
+    fn test_universal_mt_iter() {
+        test_universal_mt_iter_helper::<Fq254>();
+        test_universal_mt_iter_helper::<Fq377>();
+        test_universal_mt_iter_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_iter_helper<F: RescueParameter>() {
+        let kv = [(26u64, 1u64), (0, 2), (13, 3), (7, 4), (5, 5)]
+            .map(|(pos, elem)| (pos, F::from(elem)));
+        let mut mt = RescueSparseMerkleTree::<u64, F>::from_kv_set(3, kv).unwrap();
+
+        let mut expected = kv.to_vec();
+        expected.sort_by_key(|(pos, _)| *pos);
+        let leaves = mt
+            .iter()
+            .map(|(pos, elem)| (*pos, *elem))
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, expected);
+
+        // Removed and forgotten leaves are both skipped.
+        mt.remove(13).unwrap();
+        mt.forget(5).expect_ok().unwrap();
+        let leaves = mt.iter().map(|(pos, _)| *pos).collect::<Vec<_>>();
+        assert_eq!(leaves, [0, 7, 26]);
+    }
+
+    #[test]
+    fn test_universal_mt_occupied_positions() {
+        test_universal_mt_occupied_positions_helper::<Fq254>();
+        test_universal_mt_occupied_positions_helper::<Fq377>();
+        test_universal_mt_occupied_positions_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_occupied_positions_helper<F: RescueParameter>() {
+        // The capacity of 3^40 leaves cannot be enumerated.
+        let positions = [
+            BigUint::from(7u64),
+            BigUint::from(1u64) << 60,
+            BigUint::from(123_456_789u64),
+            BigUint::from(0u64),
+            BigUint::from(3u64).pow(40) - 1u64,
+        ];
+        let mut mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            40,
+            positions.iter().map(|pos| (pos.clone(), F::from(1u64))),
+        )
+        .unwrap();
+        let mut expected = positions.to_vec();
+        expected.sort();
+        let in_memory = |positions: &[BigUint]| {
+            positions
+                .iter()
+                .cloned()
+                .map(OccupiedPosition::InMemory)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(mt.occupied_positions(), in_memory(&expected));
+
+        mt.remove(BigUint::from(7u64)).unwrap();
+        mt.update(BigUint::from(8u64), F::from(2u64)).unwrap();
+        expected.retain(|pos| *pos != BigUint::from(7u64));
+        expected.insert(1, BigUint::from(8u64));
+        assert_eq!(mt.occupied_positions(), in_memory(&expected));
+
+        mt.forget(BigUint::from(123_456_789u64))
+            .expect_ok()
+            .unwrap();
+        // 123456789 is the only leaf in [2 * 3^16, 3^17), whose subtree is
+        // forgotten as a whole.
+        let path =
+            <BigUint as ToTraversalPath<3>>::to_traversal_path(&BigUint::from(123_456_789u64), 40);
+        let mut expected = in_memory(&expected);
+        expected[2] = OccupiedPosition::Forgotten(path[16..].to_vec());
+        assert_eq!(mt.occupied_positions(), expected);
+        assert_eq!(mt.num_leaves(), expected.len() as u64);
+
+        let empty =
+            RescueSparseMerkleTree::<BigUint, F>::from_kv_set(40, [] as [(BigUint, F); 0]).unwrap();
+        assert!(empty.occupied_positions().is_empty());
     }
 
     #[test]
@@ -264,8 +901,8 @@ mod mt_tests {
 
     fn test_update_and_lookup_helper<I, F>()
     where
-        I: Index + ToTraversalPath<U3> + From<u64>,
-        F: RescueParameter + ToTraversalPath<U3>,
+        I: Index + ToTraversalPath<3> + From<u64>,
+        F: RescueParameter + ToTraversalPath<3>,
         RescueHash<F>: DigestAlgorithm<F, I, F>,
     {
         let mut mt =
@@ -486,7 +1123,7 @@ mod mt_tests {
         test_universal_mt_serde_helper::<Fq381>();
     }
 
-    fn test_universal_mt_serde_helper<F: RescueParameter + ToTraversalPath<U3>>() {
+    fn test_universal_mt_serde_helper<F: RescueParameter + ToTraversalPath<3>>() {
         let mut hashmap = HashMap::new();
         hashmap.insert(F::from(1u64), F::from(2u64));
         hashmap.insert(F::from(10u64), F::from(3u64));
@@ -515,4 +1152,131 @@ mod mt_tests {
             bincode::deserialize(&bincode::serialize(node).unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn test_universal_mt_biguint_json_serde() {
+        test_universal_mt_biguint_json_serde_helper::<Fq254>();
+        test_universal_mt_biguint_json_serde_helper::<Fq377>();
+        test_universal_mt_biguint_json_serde_helper::<Fq381>();
+    }
+
+    fn test_universal_mt_biguint_json_serde_helper<F: RescueParameter>() {
+        // An index beyond 64 bits, as in a tree keyed by hashes.
+        let pos = BigUint::from(u64::MAX) * BigUint::from(3u64);
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            50,
+            &[
+                (pos.clone(), F::from(1u64)),
+                (BigUint::from(7u64), F::from(2u64)),
+            ],
+        )
+        .unwrap();
+        let root = mt.commitment().digest();
+        let mem_proof = mt.lookup(&pos).expect_ok().unwrap().1;
+        let non_mem_proof = match mt.universal_lookup(BigUint::from(8u64)) {
+            LookupResult::NotFound(proof) => proof,
+            res => panic!("expected NotFound, got {:?}", res),
+        };
+
+        let de_mt: RescueSparseMerkleTree<BigUint, F> =
+            serde_json::from_str(&serde_json::to_string(&mt).unwrap()).unwrap();
+        assert_eq!(mt, de_mt);
+        let de_mem_proof: MerkleProof<F, BigUint, F, 3> =
+            serde_json::from_str(&serde_json::to_string(&mem_proof).unwrap()).unwrap();
+        assert_eq!(mem_proof, de_mem_proof);
+        assert_eq!(*de_mem_proof.index(), pos);
+        assert!(
+            RescueSparseMerkleTree::<BigUint, F>::verify(root, &pos, &de_mem_proof)
+                .unwrap()
+                .is_ok()
+        );
+        let de_non_mem_proof: MerkleProof<F, BigUint, F, 3> =
+            serde_json::from_str(&serde_json::to_string(&non_mem_proof).unwrap()).unwrap();
+        assert_eq!(non_mem_proof, de_non_mem_proof);
+        assert!(de_mt
+            .non_membership_verify(BigUint::from(8u64), &de_non_mem_proof)
+            .unwrap());
+    }
+
+    /// Node value whose default, i.e. the value of the empty subtrees, is a
+    /// non-zero sentinel.
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        CanonicalSerialize,
+        CanonicalDeserialize,
+    )]
+    struct SentinelNode(Fq254);
+
+    impl Default for SentinelNode {
+        fn default() -> Self {
+            SentinelNode(Fq254::from(42u64))
+        }
+    }
+
+    struct SentinelHash;
+
+    impl DigestAlgorithm<Fq254, u64, SentinelNode> for SentinelHash {
+        fn digest(data: &[SentinelNode]) -> Result<SentinelNode, PrimitivesError> {
+            let data = data.iter().map(|node| node.0).collect::<Vec<_>>();
+            Ok(SentinelNode(<RescueHash<Fq254> as DigestAlgorithm<
+                Fq254,
+                u64,
+                Fq254,
+            >>::digest(&data)?))
+        }
+
+        fn digest_leaf(pos: &u64, elem: &Fq254) -> Result<SentinelNode, PrimitivesError> {
+            Ok(SentinelNode(<RescueHash<Fq254> as DigestAlgorithm<
+                Fq254,
+                u64,
+                Fq254,
+            >>::digest_leaf(pos, elem)?))
+        }
+    }
+
+    type SentinelMerkleTree = UniversalMerkleTree<Fq254, SentinelHash, u64, 3, SentinelNode>;
+
+    #[test]
+    fn test_universal_mt_custom_empty_value() {
+        let empty = SentinelNode::default();
+        let mt = SentinelMerkleTree::from_kv_set(2, [] as [(u64, Fq254); 0]).unwrap();
+        assert_eq!(mt.commitment().digest(), empty);
+
+        let elem = Fq254::from(5u64);
+        let mt = SentinelMerkleTree::from_kv_set(2, [(1u64, elem)]).unwrap();
+        // Position 1 is the middle leaf of the leftmost subtree, and every other
+        // subtree is empty.
+        let leaf = SentinelHash::digest_leaf(&1, &elem).unwrap();
+        let subtree = SentinelHash::digest(&[empty, leaf, empty]).unwrap();
+        let root = SentinelHash::digest(&[subtree, empty, empty]).unwrap();
+        assert_eq!(mt.commitment().digest(), root);
+        assert_ne!(
+            root.0,
+            RescueSparseMerkleTree::<u64, Fq254>::from_kv_set(2, [(1u64, elem)])
+                .unwrap()
+                .commitment()
+                .digest()
+        );
+
+        // Membership proof
+        let (_, proof) = mt.lookup(1).expect_ok().unwrap();
+        assert!(SentinelMerkleTree::verify(root, 1, &proof).unwrap().is_ok());
+
+        // Non-membership proofs, through an empty leaf of the same subtree and
+        // through an empty subtree.
+        for pos in [2u64, 5] {
+            let proof = mt.universal_lookup(pos).expect_not_found().unwrap();
+            assert!(mt.non_membership_verify(pos, &proof).unwrap());
+            assert!(proof
+                .verify_non_membership_proof::<SentinelHash>(&root)
+                .unwrap());
+        }
+    }
 }
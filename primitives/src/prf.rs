@@ -16,6 +16,7 @@ use ark_std::{
     fmt::Debug,
     marker::PhantomData,
     rand::{CryptoRng, RngCore},
+    vec::Vec,
     UniformRand,
 };
 
@@ -76,10 +77,48 @@ impl<F: RescueParameter, const INPUT_LEN: usize, const OUTPUT_LEN: usize> PRF
         Ok(output)
     }
 }
+
+/// A rescue-based PRF with variable-input size, built on the same Full State
+/// Keyed (FSK) sponge as [`RescuePRF`]: the key is placed in the capacity
+/// element, so that different keys index independent functions.
+///
+/// The input is bit padded, so inputs of different lengths do not collide.
+/// This is not a MAC: the same key should not be shared between protocols
+/// without prepending a domain separator to the input.
+#[derive(Debug, Clone)]
+pub struct VariableLengthRescuePRF<F: RescueParameter>(PhantomData<F>);
+
+impl<F: RescueParameter> VariableLengthRescuePRF<F> {
+    /// Evaluate the PRF indexed by `key` on `input`.
+    pub fn evaluate(key: &F, input: &[F]) -> F {
+        Self::evaluate_vec(key, input, 1)[0]
+    }
+
+    /// Same as [`Self::evaluate`] except that `num_outputs` elements are
+    /// returned, the first one being the output of [`Self::evaluate`].
+    pub fn evaluate_vec(key: &F, input: &[F], num_outputs: usize) -> Vec<F> {
+        RescuePRFCore::full_state_keyed_sponge_with_bit_padding(key, input, num_outputs)
+    }
+}
+
+impl<F: RescueParameter> PRF for VariableLengthRescuePRF<F> {
+    type Input = Vec<F>;
+    type Output = F;
+    type Seed = F;
+
+    fn evaluate<S: Borrow<Self::Seed>, I: Borrow<Self::Input>>(
+        seed: S,
+        input: I,
+    ) -> Result<Self::Output, PrimitivesError> {
+        let input: &Vec<F> = input.borrow();
+        Ok(Self::evaluate(seed.borrow(), input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        prf::{RescuePRF, PRF},
+        prf::{RescuePRF, VariableLengthRescuePRF, PRF},
         rescue::sponge::RescuePRFCore,
     };
     use ark_bls12_377::Fq as Fq377;
@@ -114,4 +153,58 @@ mod tests {
         test_prf!(Fq377);
         test_prf!(Fq254);
     }
+
+    macro_rules! test_variable_length_prf {
+        ($tr:tt) => {
+            let mut rng = jf_utils::test_rng();
+            let key = $tr::rand(&mut rng);
+            let other_key = $tr::rand(&mut rng);
+            let input = [$tr::from(1u8), $tr::from(2u8)];
+
+            let output = VariableLengthRescuePRF::evaluate(&key, &input);
+            assert_eq!(output, VariableLengthRescuePRF::evaluate(&key, &input));
+            assert_ne!(
+                output,
+                VariableLengthRescuePRF::evaluate(&other_key, &input)
+            );
+            assert_ne!(output, VariableLengthRescuePRF::evaluate(&key, &input[..1]));
+            assert_ne!(
+                output,
+                VariableLengthRescuePRF::evaluate(&key, &[input[0], input[1], $tr::from(0u8)])
+            );
+
+            let outputs = VariableLengthRescuePRF::evaluate_vec(&key, &input, 6);
+            assert_eq!(outputs.len(), 6);
+            assert_eq!(outputs[0], output);
+            assert_eq!(
+                outputs,
+                VariableLengthRescuePRF::evaluate_vec(&key, &input, 6)
+            );
+
+            // through the `PRF` trait
+            let input = input.to_vec();
+            assert_eq!(
+                <VariableLengthRescuePRF<$tr> as PRF>::evaluate(&key, &input).unwrap(),
+                output
+            );
+            let (seed, output) =
+                <VariableLengthRescuePRF<$tr> as PRF>::evaluate_with_rand_seed(&mut rng, &input)
+                    .unwrap();
+            assert_eq!(VariableLengthRescuePRF::evaluate(&seed, &input), output);
+            assert_ne!(seed, key);
+            assert_ne!(
+                output,
+                <VariableLengthRescuePRF<$tr> as PRF>::evaluate(&key, &input).unwrap()
+            );
+        };
+    }
+
+    #[test]
+    pub fn test_variable_length_prf() {
+        test_variable_length_prf!(FqEd254);
+        test_variable_length_prf!(FqEd377);
+        test_variable_length_prf!(FqEd381);
+        test_variable_length_prf!(Fq377);
+        test_variable_length_prf!(Fq254);
+    }
 }
@@ -0,0 +1,263 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Rescue permutation and sponge over an extension field, for wide rate
+//! hashing: each of the [`CRHF_RATE`] elements absorbed per permutation call
+//! carries `extension_degree` base field elements.
+//!
+//! The permutation has the Rescue-Prime round structure, see
+//! [`Permutation::rescue_prime`](super::Permutation::rescue_prime). The MDS
+//! matrix is the one of the base prime field: all its minors are non-zero
+//! base field elements, so it is still MDS over any extension. The S-box
+//! `x^alpha` must be a permutation of the extension, i.e. `alpha` must be
+//! coprime with the order of its multiplicative group.
+
+use super::{RescueMatrix, RescueParameter, RescueVector, CRHF_RATE, STATE_SIZE};
+use ark_bn254::Fr;
+use ark_ff::{Field, Fp2, Fp2Config, MontFp};
+use ark_std::{format, vec, vec::Vec};
+use jf_utils::pad_with_zeros;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+/// This trait defines the constants of Rescue over an extension field.
+pub trait RescueExtensionParameter: Field {
+    /// parameter A, a.k.a., alpha
+    const A: u64;
+    /// parameter A^-1, modulo the order of the multiplicative group of the
+    /// extension
+    const A_INV: &'static [u64];
+    /// Number of rounds of the permutation, each with two S-box layers
+    const ROUNDS: usize;
+
+    /// The MDS matrix, with coefficients in the base prime field
+    fn mds() -> RescueMatrix<Self>;
+}
+
+/// Configuration of the quadratic extension of the bn254 scalar field, which
+/// is also the ed_on_bn254 base field, by the square root of the quadratic
+/// non-residue 5.
+pub struct Fr254Ext2Config;
+
+impl Fp2Config for Fr254Ext2Config {
+    type Fp = Fr;
+
+    // 5 generates the multiplicative group of Fr.
+    const NONRESIDUE: Fr = MontFp!("5");
+
+    // NONRESIDUE^((r^i - 1) / 2) for i = 0, 1
+    const FROBENIUS_COEFF_FP2_C1: &'static [Fr] = &[
+        MontFp!("1"),
+        MontFp!("21888242871839275222246405745257275088548364400416034343698204186575808495616"),
+    ];
+}
+
+/// Quadratic extension of the bn254 scalar field, see [`Fr254Ext2Config`].
+pub type Fr254Ext2 = Fp2<Fr254Ext2Config>;
+
+impl RescueExtensionParameter for Fr254Ext2 {
+    // gcd(5, r^2 - 1) = 1, as r = 2 mod 5.
+    const A: u64 = 5;
+    // 1/5 % (r^2 - 1)
+    //   = 287457105609973705465192827130007244475665436462585017503803489935236405338641326855230285133046116971756008377154695142052596209725115717489227624926413
+    const A_INV: &'static [u64] = &[
+        0x38758d17eccccccd,
+        0x77f7d47bead1f333,
+        0x6658fd4dd8790a19,
+        0x974875ad24e11e9d,
+        0xe77acb16c4a08324,
+        0x83945703b215e585,
+        0xcf5ca94a685ee477,
+        0x057d0fa1e08ad95d,
+    ];
+    // The Rescue-Prime round number formula for alpha = 5, a state of size 4,
+    // capacity 1 and 128 bits of security does not depend on the field.
+    const ROUNDS: usize = 11;
+
+    fn mds() -> RescueMatrix<Self> {
+        let mds = <Fr as RescueParameter>::mds();
+        let rows = [0, 1, 2, 3].map(|i| RescueVector {
+            vec: mds.vec(i).vec.map(Self::from_base_prime_field),
+        });
+        RescueMatrix::from(&rows)
+    }
+}
+
+/// Security level, in bits, from which the round constants are derived.
+const SECURITY_LEVEL: usize = 128;
+
+/// Value of the capacity element at the start of
+/// [`ExtensionPermutation::sponge_with_padding`].
+const PADDED_SPONGE_DOMAIN_TAG: u64 = 1;
+
+/// Rescue permutation over an extension field
+#[derive(Debug, Clone)]
+pub struct ExtensionPermutation<F> {
+    mds: RescueMatrix<F>,
+    round_keys: Vec<RescueVector<F>>,
+}
+
+impl<F: RescueExtensionParameter> Default for ExtensionPermutation<F> {
+    fn default() -> Self {
+        Self {
+            mds: F::mds(),
+            round_keys: round_constants::<F>(),
+        }
+    }
+}
+
+impl<F: RescueExtensionParameter> ExtensionPermutation<F> {
+    /// Return a pointer to the round keys.
+    #[inline]
+    pub fn round_keys_ref(&self) -> &[RescueVector<F>] {
+        self.round_keys.as_slice()
+    }
+
+    /// Return a pointer to the mds matrix.
+    #[inline]
+    pub fn mds_matrix_ref(&self) -> &RescueMatrix<F> {
+        &self.mds
+    }
+
+    /// Compute the permutation on RescueVector `input`
+    pub fn eval(&self, input: &RescueVector<F>) -> RescueVector<F> {
+        let mut perm_state = *input;
+        self.round_keys.chunks_exact(2).for_each(|keys| {
+            perm_state.pow(&[F::A]);
+            perm_state.linear(&self.mds, &keys[0]);
+            perm_state.pow(F::A_INV);
+            perm_state.linear(&self.mds, &keys[1]);
+        });
+        perm_state
+    }
+
+    /// Sponge hashing for RATE 3 and CAPACITY 1 of inputs of any length, with
+    /// the same padding and domain separation as
+    /// [`Permutation::sponge_with_padding`](super::Permutation::sponge_with_padding).
+    pub fn sponge_with_padding(&self, input: &[F], num_outputs: usize) -> Vec<F> {
+        let mut padded = input.to_vec();
+        padded.push(F::one());
+        pad_with_zeros(&mut padded, CRHF_RATE);
+
+        // ABSORB PHASE
+        let mut state = RescueVector::zero();
+        state.vec[STATE_SIZE - 1] = F::from(PADDED_SPONGE_DOMAIN_TAG);
+        for chunk in padded.chunks_exact(CRHF_RATE) {
+            state.add_assign_elems(chunk);
+            state = self.eval(&state);
+        }
+
+        // SQUEEZE PHASE
+        let mut result = vec![];
+        loop {
+            let extract = (num_outputs - result.len()).min(CRHF_RATE);
+            result.extend_from_slice(&state.vec[..extract]);
+            if result.len() == num_outputs {
+                break;
+            }
+            state = self.eval(&state);
+        }
+        result
+    }
+}
+
+// The `2 * ROUNDS` round constants, read from SHAKE256 as in
+// `rescue_prime_round_constants`, with the extension degree appended to the
+// seed. Each extension element is made of `extension_degree` consecutive base
+// field elements.
+fn round_constants<F: RescueExtensionParameter>() -> Vec<RescueVector<F>> {
+    let degree = F::extension_degree() as usize;
+    let seed = format!(
+        "Rescue-XLIX({},{},{},{},{})",
+        F::BasePrimeField::MODULUS,
+        STATE_SIZE,
+        STATE_SIZE - CRHF_RATE,
+        SECURITY_LEVEL,
+        degree
+    );
+    let mut hasher = Shake256::default();
+    hasher.update(seed.as_bytes());
+    let mut reader = hasher.finalize_xof();
+
+    let bytes_per_elem = (F::BasePrimeField::MODULUS_BIT_SIZE as usize + 7) / 8 + 1;
+    let mut bytes = vec![0u8; bytes_per_elem];
+    let mut next_base_elem = || {
+        reader.read(&mut bytes);
+        F::BasePrimeField::from_le_bytes_mod_order(&bytes)
+    };
+    (0..2 * F::ROUNDS)
+        .map(|_| {
+            let mut vec = [F::zero(); STATE_SIZE];
+            for elem in vec.iter_mut() {
+                let coeffs = (0..degree).map(|_| next_base_elem()).collect::<Vec<_>>();
+                *elem = F::from_base_prime_field_elems(&coeffs)
+                    .expect("as many coefficients as the extension degree");
+            }
+            RescueVector { vec }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExtensionPermutation, Fr254Ext2, RescueExtensionParameter};
+    use crate::rescue::{RescueVector, STATE_SIZE};
+    use ark_ff::{Field, UniformRand};
+    use ark_std::vec::Vec;
+
+    #[test]
+    fn test_extension_sbox_is_invertible() {
+        let mut rng = jf_utils::test_rng();
+        for _ in 0..10 {
+            let x = Fr254Ext2::rand(&mut rng);
+            assert_eq!(x.pow([Fr254Ext2::A]).pow(Fr254Ext2::A_INV), x);
+        }
+    }
+
+    #[test]
+    fn test_extension_sponge_determinism() {
+        let perm = ExtensionPermutation::<Fr254Ext2>::default();
+        assert_eq!(perm.round_keys_ref().len(), 2 * Fr254Ext2::ROUNDS);
+        // The round constants do not fall in the base field.
+        assert!(perm
+            .round_keys_ref()
+            .iter()
+            .all(|key| key.vec.iter().all(|elem| elem.c1 != Default::default())));
+
+        let mut rng = jf_utils::test_rng();
+        let input = (0..7)
+            .map(|_| Fr254Ext2::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let output = perm.sponge_with_padding(&input, 5);
+        assert_eq!(output.len(), 5);
+        // Same input, same output, also with a fresh instance of the
+        // permutation.
+        assert_eq!(perm.sponge_with_padding(&input, 5), output);
+        assert_eq!(
+            ExtensionPermutation::<Fr254Ext2>::default().sponge_with_padding(&input, 5),
+            output
+        );
+        // The first outputs do not depend on how many are squeezed.
+        assert_eq!(perm.sponge_with_padding(&input, 2), output[..2]);
+
+        // Different inputs, including a trailing zero.
+        let mut other = input.clone();
+        other[3] += Fr254Ext2::ONE;
+        assert_ne!(perm.sponge_with_padding(&other, 5), output);
+        let mut extended = input;
+        extended.push(Fr254Ext2::ZERO);
+        assert_ne!(perm.sponge_with_padding(&extended, 5), output);
+
+        // The permutation is not the identity, and differs on close states.
+        let zero = RescueVector::<Fr254Ext2>::zero();
+        let mut one = zero;
+        one.vec[STATE_SIZE - 1] = Fr254Ext2::ONE;
+        assert_ne!(perm.eval(&zero), zero);
+        assert_ne!(perm.eval(&zero), perm.eval(&one));
+    }
+}
@@ -9,7 +9,8 @@
 //! - bls12_377 base field
 //! - ed_on_bls12_377 base field
 //! - ed_on_bls12_381 base field
-//! - ed_on_bn254 base field
+//! - ed_on_bn254 base field, which is also the bn254 scalar field
+//!   `ark_bn254::Fr`
 //!
 //! It also has place holders for
 //! - bls12_381 base field
@@ -17,15 +18,33 @@
 //! - bw6_761 base field
 //!
 //! Those three place holders should never be used.
+//!
+//! The Rescue-Prime variant of the permutation,
+//! [`Permutation::rescue_prime`], is available over the ed_on_bn254 base
+//! field.
+//!
+//! The [`extension`] module runs the permutation and the sponge over an
+//! extension field, for a wider rate.
+//!
+//! The [`prime`] module has Rescue-Prime permutations and sponges over states
+//! of other widths, for rates which do not fit [`STATE_SIZE`].
 
 #![deny(warnings)]
 pub mod errors;
+pub mod extension;
+pub mod prime;
 mod rescue_constants;
 pub mod sponge;
+pub mod transcript;
 
 use ark_crypto_primitives::sponge::Absorb;
-use ark_ff::{PrimeField, Zero};
-use ark_std::{vec, vec::Vec};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_std::{format, vec, vec::Vec};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+use zeroize::Zeroize;
 
 /// The state size of rescue hash.
 pub const STATE_SIZE: usize = 4;
@@ -89,6 +108,51 @@ pub trait RescueParameter: PrimeField + Absorb {
     const KEY_INJECTION_LE: [[&'static [u8]; 4]; 2 * ROUNDS];
     /// Permutation keys.
     const PERMUTATION_ROUND_KEYS: [[&'static [u8]; 4]; 25];
+
+    /// Number of rounds of the permutation. Each round applies two S-box
+    /// layers, so [`Permutation`] uses `2 * num_rounds() + 1` round
+    /// constants.
+    fn num_rounds() -> usize {
+        ROUNDS
+    }
+
+    /// The round constants of [`Permutation`], parsed from
+    /// [`Self::PERMUTATION_ROUND_KEYS`]. The first one is added to the input,
+    /// the `i`-th other one is added after the `i`-th S-box layer and MDS
+    /// multiplication.
+    fn round_constants() -> Vec<RescueVector<Self>> {
+        Self::PERMUTATION_ROUND_KEYS
+            .iter()
+            .map(|key| RescueVector::from_elems_le_bytes(key[0], key[1], key[2], key[3]))
+            .collect()
+    }
+
+    /// The MDS matrix, parsed from [`Self::MDS_LE`].
+    fn mds() -> RescueMatrix<Self> {
+        let rows = Self::MDS_LE
+            .map(|row| RescueVector::from_elems_le_bytes(row[0], row[1], row[2], row[3]));
+        RescueMatrix::from(&rows)
+    }
+
+    /// The smallest primitive root of the field, which the Rescue-Prime
+    /// reference implementation takes for its MDS matrices, see
+    /// [`prime::RescuePrimePermutation`]. Defaults to
+    /// [`ark_ff::FftField::GENERATOR`], which is the smallest one for the base
+    /// and scalar fields of BN254, BLS12-381 and BLS12-377, and should be
+    /// overridden for a field where it is not.
+    fn primitive_root() -> Self {
+        Self::GENERATOR
+    }
+}
+
+/// Parameters of the Rescue-Prime variant of the permutation, see
+/// [`Permutation::rescue_prime`]. The S-box and the MDS matrix are the ones of
+/// [`RescueParameter`], the round constants are derived from the field.
+pub trait RescuePrimeParameter: RescueParameter {
+    /// Number of Rescue-Prime rounds for a state of size [`STATE_SIZE`],
+    /// capacity 1 and 128 bits of security, following the round number
+    /// formula of the Rescue-Prime specification.
+    const PRIME_ROUNDS: usize;
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Copy, Default)]
@@ -98,7 +162,7 @@ pub struct RescueVector<F> {
 }
 
 // Public functions
-impl<F: PrimeField> RescueVector<F> {
+impl<F: Field> RescueVector<F> {
     /// zero vector
     pub fn zero() -> RescueVector<F> {
         RescueVector {
@@ -117,7 +181,8 @@ impl<F: PrimeField> RescueVector<F> {
     pub fn linear(&mut self, matrix: &RescueMatrix<F>, vector: &RescueVector<F>) {
         let mut aux = matrix.mul_vec(self);
         aux.add_assign(vector);
-        *self = aux
+        *self = aux;
+        aux.wipe();
     }
 }
 
@@ -133,13 +198,25 @@ impl<F: PrimeField> RescueVector<F> {
             ],
         }
     }
+}
 
+// The arithmetic only needs a field, so that extension fields can be used, see
+// the `extension` module.
+impl<F: Field> RescueVector<F> {
     fn pow(&mut self, exp: &[u64]) {
         self.vec.iter_mut().for_each(|elem| {
             *elem = elem.pow(exp);
         });
     }
 
+    // Wipe a copy of an intermediate state, which may depend on secret
+    // inputs, when the `rescue-zeroize` feature is enabled.
+    #[inline]
+    pub(crate) fn wipe(&mut self) {
+        #[cfg(feature = "rescue-zeroize")]
+        self.zeroize();
+    }
+
     fn add_assign(&mut self, vector: &RescueVector<F>) {
         for (a, b) in self.vec.iter_mut().zip(vector.vec.iter()) {
             a.add_assign(b);
@@ -175,8 +252,16 @@ impl<F: RescueParameter> RescueVector<F> {
         let mut self_aux = *self;
         self_aux.pow(&[F::A]);
         let mut aux = matrix.mul_vec(&self_aux);
+        self_aux.wipe();
         aux.add_assign(vector);
         *self = aux;
+        aux.wipe();
+    }
+}
+
+impl<F: Field> Zeroize for RescueVector<F> {
+    fn zeroize(&mut self) {
+        self.vec.zeroize();
     }
 }
 
@@ -206,13 +291,13 @@ pub struct RescueMatrix<F> {
     matrix: [RescueVector<F>; STATE_SIZE],
 }
 
-impl<F: PrimeField> From<&[RescueVector<F>; STATE_SIZE]> for RescueMatrix<F> {
+impl<F: Field> From<&[RescueVector<F>; STATE_SIZE]> for RescueMatrix<F> {
     fn from(vectors: &[RescueVector<F>; STATE_SIZE]) -> Self {
         Self { matrix: *vectors }
     }
 }
 
-impl<F: PrimeField> RescueMatrix<F> {
+impl<F: Field> RescueMatrix<F> {
     fn mul_vec(&self, vector: &RescueVector<F>) -> RescueVector<F> {
         let mut result = [F::zero(); STATE_SIZE];
         self.matrix
@@ -267,32 +352,7 @@ impl<F: RescueParameter> Default for PRP<F> {
             ));
         }
         PRP {
-            mds: RescueMatrix::from(&[
-                RescueVector::from_elems_le_bytes(
-                    F::MDS_LE[0][0],
-                    F::MDS_LE[0][1],
-                    F::MDS_LE[0][2],
-                    F::MDS_LE[0][3],
-                ),
-                RescueVector::from_elems_le_bytes(
-                    F::MDS_LE[1][0],
-                    F::MDS_LE[1][1],
-                    F::MDS_LE[1][2],
-                    F::MDS_LE[1][3],
-                ),
-                RescueVector::from_elems_le_bytes(
-                    F::MDS_LE[2][0],
-                    F::MDS_LE[2][1],
-                    F::MDS_LE[2][2],
-                    F::MDS_LE[2][3],
-                ),
-                RescueVector::from_elems_le_bytes(
-                    F::MDS_LE[3][0],
-                    F::MDS_LE[3][1],
-                    F::MDS_LE[3][2],
-                    F::MDS_LE[3][3],
-                ),
-            ]),
+            mds: F::mds(),
             init_vec: RescueVector::from_elems_le_bytes(
                 F::INIT_VEC_LE[0],
                 F::INIT_VEC_LE[1],
@@ -308,8 +368,10 @@ impl<F: RescueParameter> PRP<F> {
     /// Rescue pseudorandom permutation for Bls12381 scalars vectors of size 4
     /// without key scheduled keys (scheduling occurs online)
     pub fn prp(&self, key: &RescueVector<F>, input: &RescueVector<F>) -> RescueVector<F> {
-        let round_keys = self.key_schedule(key);
-        self.prp_with_round_keys(round_keys.as_slice(), input)
+        let mut round_keys = self.key_schedule(key);
+        let output = self.prp_with_round_keys(round_keys.as_slice(), input);
+        round_keys.iter_mut().for_each(RescueVector::wipe);
+        output
     }
 
     /// Rescue pseudorandom permutation for Bls12381 scalars vectors of size 4
@@ -329,11 +391,17 @@ impl<F: RescueParameter> PRP<F> {
             }
             perm_state.linear(&self.mds, key)
         });
-        perm_state
+        let output = perm_state;
+        perm_state.wipe();
+        output
     }
 
     /// Key scheduling for rescue based PRP for Bls12_381 scalars vector of size
     /// 4
+    ///
+    /// The round keys are derived from `key`: callers holding a secret key
+    /// should zeroize them once done, as [`Self::prp`] does with the
+    /// `rescue-zeroize` feature.
     pub fn key_schedule(&self, key: &RescueVector<F>) -> Vec<RescueVector<F>> {
         let mut aux = key.add(&self.init_vec);
         let mut round_keys = vec![aux];
@@ -343,6 +411,7 @@ impl<F: RescueParameter> PRP<F> {
             aux.linear(&self.mds, &self.key_injection[i]);
             round_keys.push(aux);
         });
+        aux.wipe();
         round_keys
     }
 
@@ -369,29 +438,107 @@ impl<F: RescueParameter> PRP<F> {
     }
 }
 
+// Security level, in bits, from which the Rescue-Prime round constants are
+// derived.
+pub(crate) const RESCUE_PRIME_SECURITY_LEVEL: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RoundStructure {
+    // Initial key addition, then S-box layers alternating between x^(1/alpha)
+    // and x^alpha, each followed by the MDS matrix and a round key.
+    Rescue,
+    // Rounds of x^alpha, MDS and constants, then x^(1/alpha), MDS and
+    // constants, without initial key addition.
+    RescuePrime,
+}
+
 /// Instance of a unkeyed cryptographic permutation to be used for instantiation
 /// hashing, pseudo-random function, and other cryptographic primitives
 #[derive(Debug, Clone)]
 pub struct Permutation<F> {
     rescue_prp: PRP<F>,
     round_keys: Vec<RescueVector<F>>,
+    structure: RoundStructure,
 }
 
 impl<F: RescueParameter> From<PRP<F>> for Permutation<F> {
     fn from(rescue: PRP<F>) -> Self {
-        let mut keys: Vec<RescueVector<F>> = Vec::with_capacity(2 * ROUNDS + 1);
-        for key in F::PERMUTATION_ROUND_KEYS.iter() {
-            keys.push(RescueVector::from_elems_le_bytes(
-                key[0], key[1], key[2], key[3],
-            ))
-        }
         Permutation {
             rescue_prp: rescue,
-            round_keys: keys,
+            round_keys: F::round_constants(),
+            structure: RoundStructure::Rescue,
+        }
+    }
+}
+
+impl<F: RescuePrimeParameter> Permutation<F> {
+    /// The Rescue-Prime permutation. It can be used everywhere a
+    /// [`Permutation`] is expected, e.g. with
+    /// [`RescueSponge`](sponge::RescueSponge), but not in circuits, which only
+    /// implement the Rescue round structure.
+    ///
+    /// The MDS matrix is the transpose of the Rescue one, as the
+    /// specification takes the transpose of the right half of the
+    /// systematic Vandermonde matrix. The round constants are read from
+    /// SHAKE256 on the `Rescue-XLIX(p,m,capacity,security_level)` seed.
+    pub fn rescue_prime() -> Self {
+        let mds = F::mds();
+        let mut columns = [RescueVector::zero(); STATE_SIZE];
+        for (j, column) in columns.iter_mut().enumerate() {
+            for (i, elem) in column.vec.iter_mut().enumerate() {
+                *elem = mds.vec(i).vec[j];
+            }
+        }
+        Permutation {
+            rescue_prp: PRP {
+                mds: RescueMatrix::from(&columns),
+                ..PRP::default()
+            },
+            round_keys: rescue_prime_round_constants::<F>(),
+            structure: RoundStructure::RescuePrime,
         }
     }
 }
 
+// The `2 * PRIME_ROUNDS` round constants of Rescue-Prime, two per round.
+fn rescue_prime_round_constants<F: RescuePrimeParameter>() -> Vec<RescueVector<F>> {
+    rescue_prime_constants::<F>(STATE_SIZE, STATE_SIZE - CRHF_RATE, F::PRIME_ROUNDS)
+        .chunks_exact(STATE_SIZE)
+        .map(RescueVector::from)
+        .collect()
+}
+
+// The `2 * num_rounds * width` round constants of Rescue-Prime over `width`
+// elements with capacity `capacity`, read from SHAKE256 on the
+// `Rescue-XLIX(p,m,capacity,security_level)` seed. Each field element is read,
+// little endian, from one more byte than the modulus needs and reduced modulo
+// p, which makes the bias negligible.
+pub(crate) fn rescue_prime_constants<F: PrimeField>(
+    width: usize,
+    capacity: usize,
+    num_rounds: usize,
+) -> Vec<F> {
+    let seed = format!(
+        "Rescue-XLIX({},{},{},{})",
+        F::MODULUS,
+        width,
+        capacity,
+        RESCUE_PRIME_SECURITY_LEVEL
+    );
+    let mut hasher = Shake256::default();
+    hasher.update(seed.as_bytes());
+    let mut reader = hasher.finalize_xof();
+
+    let bytes_per_elem = (F::MODULUS_BIT_SIZE as usize + 7) / 8 + 1;
+    let mut bytes = vec![0u8; bytes_per_elem];
+    (0..2 * num_rounds * width)
+        .map(|_| {
+            reader.read(&mut bytes);
+            F::from_le_bytes_mod_order(&bytes)
+        })
+        .collect()
+}
+
 impl<F: RescueParameter> Default for Permutation<F> {
     fn default() -> Self {
         Permutation::from(PRP::default())
@@ -414,8 +561,24 @@ impl<F: RescueParameter> Permutation<F> {
     }
     /// Compute the permutation on RescueVector `input`
     pub fn eval(&self, input: &RescueVector<F>) -> RescueVector<F> {
-        self.rescue_prp
-            .prp_with_round_keys(self.round_keys.as_slice(), input)
+        match self.structure {
+            RoundStructure::Rescue => self
+                .rescue_prp
+                .prp_with_round_keys(self.round_keys.as_slice(), input),
+            RoundStructure::RescuePrime => {
+                let mds = self.mds_matrix_ref();
+                let mut perm_state = *input;
+                self.round_keys.chunks_exact(2).for_each(|keys| {
+                    perm_state.pow(&[F::A]);
+                    perm_state.linear(mds, &keys[0]);
+                    perm_state.pow(F::A_INV);
+                    perm_state.linear(mds, &keys[1]);
+                });
+                let output = perm_state;
+                perm_state.wipe();
+                output
+            },
+        }
     }
 }
 
@@ -680,16 +843,20 @@ mod test_prp {
 
 #[cfg(test)]
 mod test_permutation {
-    use crate::rescue::{
-        sponge::{RescueCRHF, RescuePRFCore},
-        Permutation, RescueParameter, RescueVector, PRP,
+    use crate::{
+        rescue::{
+            sponge::{hash_bytes_to_field, RescueCRHF, RescuePRFCore, RescueSponge},
+            Permutation, RescueParameter, RescueVector, CRHF_RATE, PRP,
+        },
+        test_vectors::{self, RescueVectors},
     };
     use ark_bls12_377::Fq as Fq377;
     use ark_bn254::Fq as Fq254;
+    use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
     use ark_ed_on_bls12_377::Fq as Fr377;
     use ark_ed_on_bls12_381::Fq as Fr381;
     use ark_ed_on_bn254::Fq as Fr254;
-    use ark_ff::PrimeField;
+    use ark_ff::{MontFp, PrimeField};
     use ark_std::{vec, Zero};
 
     #[test]
@@ -863,6 +1030,266 @@ mod test_permutation {
         assert_eq!(output, state.vec[0]);
     }
 
+    #[test]
+    fn test_parameter_getters() {
+        test_parameter_getters_helper::<Fq254>();
+        test_parameter_getters_helper::<Fr254>();
+        test_parameter_getters_helper::<Fr377>();
+        test_parameter_getters_helper::<Fr381>();
+        test_parameter_getters_helper::<Fq377>();
+    }
+
+    fn test_parameter_getters_helper<F: RescueParameter>() {
+        let perm = Permutation::<F>::default();
+        let round_constants = F::round_constants();
+        let mds = F::mds();
+        assert_eq!(F::num_rounds(), 12);
+        assert_eq!(round_constants.len(), 2 * F::num_rounds() + 1);
+        assert_eq!(round_constants, perm.round_keys_ref());
+        for i in 0..mds.len() {
+            assert_eq!(mds.vec(i), perm.mds_matrix_ref().vec(i));
+        }
+
+        // Re-derive the sponge output from the constants alone.
+        let mut prng = jf_utils::test_rng();
+        let input = [F::rand(&mut prng), F::rand(&mut prng), F::rand(&mut prng)];
+        let add = |state: &[F], key: &RescueVector<F>| -> Vec<F> {
+            state.iter().zip(key.elems()).map(|(a, b)| *a + b).collect()
+        };
+        let mut state = add(
+            &[input[0], input[1], input[2], F::zero()],
+            &round_constants[0],
+        );
+        for (round, key) in round_constants[1..].iter().enumerate() {
+            let exp: &[u64] = if round % 2 == 0 { F::A_INV } else { &[F::A] };
+            let powered = state.iter().map(|elem| elem.pow(exp)).collect::<Vec<_>>();
+            let mixed = (0..mds.len())
+                .map(|i| {
+                    mds.vec(i)
+                        .elems()
+                        .iter()
+                        .zip(powered.iter())
+                        .map(|(a, b)| *a * b)
+                        .sum()
+                })
+                .collect::<Vec<F>>();
+            state = add(&mixed, key);
+        }
+        assert_eq!(
+            state[0],
+            RescueCRHF::sponge_no_padding(&input, 1).unwrap()[0]
+        );
+    }
+
+    #[test]
+    fn test_crhf_evaluate() {
+        test_crhf_evaluate_helper::<Fq254>();
+        test_crhf_evaluate_helper::<Fr254>();
+        test_crhf_evaluate_helper::<Fr377>();
+        test_crhf_evaluate_helper::<Fr381>();
+        test_crhf_evaluate_helper::<Fq377>();
+    }
+
+    fn test_crhf_evaluate_helper<F: RescueParameter>() {
+        let mut prng = jf_utils::test_rng();
+        let input = (0..5).map(|_| F::rand(&mut prng)).collect::<Vec<_>>();
+        let digest = RescueCRHF::evaluate(&input);
+        assert_eq!(digest, RescueCRHF::evaluate(&input));
+        assert_eq!(digest, RescueCRHF::sponge_with_bit_padding(&input, 1)[0]);
+        assert_ne!(digest, RescueCRHF::evaluate(&input[..4]));
+
+        // The empty input is hashed as the single padding block [1, 0, 0].
+        let empty = RescueCRHF::<F>::evaluate(&[]);
+        assert_eq!(empty, RescueCRHF::evaluate(&[]));
+        assert_eq!(
+            empty,
+            RescueCRHF::sponge_no_padding(&[F::one(), F::zero(), F::zero()], 1).unwrap()[0]
+        );
+        assert_ne!(empty, RescueCRHF::evaluate(&[F::zero()]));
+    }
+
+    #[test]
+    fn test_variable_length_squeeze() {
+        test_variable_length_squeeze_helper::<Fq254>();
+        test_variable_length_squeeze_helper::<Fr254>();
+        test_variable_length_squeeze_helper::<Fr377>();
+        test_variable_length_squeeze_helper::<Fr381>();
+        test_variable_length_squeeze_helper::<Fq377>();
+    }
+
+    fn test_variable_length_squeeze_helper<F: RescueParameter>() {
+        let mut prng = jf_utils::test_rng();
+        let input = (0..6).map(|_| F::rand(&mut prng)).collect::<Vec<_>>();
+        let squeeze = |n| {
+            let mut sponge = RescueSponge::<F, CRHF_RATE>::new(&Permutation::default());
+            sponge.absorb(&input);
+            sponge.squeeze(n)
+        };
+
+        let output = squeeze(2 * CRHF_RATE);
+        assert_eq!(output.len(), 2 * CRHF_RATE);
+        assert_eq!(
+            output[..CRHF_RATE],
+            RescueCRHF::sponge_no_padding(&input, CRHF_RATE).unwrap()[..]
+        );
+        assert_eq!(output, squeeze(2 * CRHF_RATE));
+        assert_eq!(output[..CRHF_RATE + 1], squeeze(CRHF_RATE + 1)[..]);
+        // The elements beyond the rate come from one more permutation.
+        assert_ne!(output[..CRHF_RATE], output[CRHF_RATE..]);
+    }
+
+    #[test]
+    fn test_sponge_rates() {
+        test_sponge_rates_helper::<Fq254>();
+        test_sponge_rates_helper::<Fr254>();
+        test_sponge_rates_helper::<Fr377>();
+        test_sponge_rates_helper::<Fr381>();
+        test_sponge_rates_helper::<Fq377>();
+    }
+
+    fn test_sponge_rates_helper<F: RescueParameter>() {
+        let mut prng = jf_utils::test_rng();
+        let perm = Permutation::default();
+        let input = (0..6).map(|_| F::rand(&mut prng)).collect::<Vec<_>>();
+
+        // Rate 2, capacity 2.
+        let mut sponge = RescueSponge::<F, 2>::new(&perm);
+        sponge.absorb(&input);
+        let mut state = RescueVector::zero();
+        for chunk in input.chunks(2) {
+            state = perm.eval(&RescueVector::from(&[
+                state.vec[0] + chunk[0],
+                state.vec[1] + chunk[1],
+                state.vec[2],
+                state.vec[3],
+            ]));
+        }
+        let output = sponge.squeeze(3);
+        assert_eq!(output[..2], state.vec[..2]);
+        assert_eq!(output[2], perm.eval(&state).vec[0]);
+
+        // Rate 3 is the CRHF sponge.
+        let mut sponge = RescueSponge::<F, CRHF_RATE>::new(&perm);
+        sponge.absorb(&input);
+        assert_eq!(
+            sponge.squeeze(3),
+            RescueCRHF::sponge_no_padding(&input, 3).unwrap()
+        );
+        assert_ne!(output, RescueCRHF::sponge_no_padding(&input, 3).unwrap());
+    }
+
+    #[test]
+    fn test_batch_sponge() {
+        test_batch_sponge_helper::<Fq254>();
+        test_batch_sponge_helper::<Fr254>();
+        test_batch_sponge_helper::<Fr377>();
+        test_batch_sponge_helper::<Fr381>();
+        test_batch_sponge_helper::<Fq377>();
+    }
+
+    fn test_batch_sponge_helper<F: RescueParameter>() {
+        let mut prng = jf_utils::test_rng();
+        let perm = Permutation::default();
+        let inputs = (0..20)
+            .map(|len| (0..len).map(|_| F::rand(&mut prng)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let expected = inputs
+            .iter()
+            .map(|input| perm.sponge_with_padding(input, 2))
+            .collect::<Vec<_>>();
+        assert_eq!(perm.batch_sponge(&inputs, 2), expected);
+        assert!(perm.batch_sponge(&[], 2).is_empty());
+    }
+
+    #[test]
+    fn test_hash_bytes_to_field() {
+        test_hash_bytes_to_field_helper::<Fq254>();
+        test_hash_bytes_to_field_helper::<Fr254>();
+        test_hash_bytes_to_field_helper::<Fr377>();
+        test_hash_bytes_to_field_helper::<Fr381>();
+        test_hash_bytes_to_field_helper::<Fq377>();
+
+        // this value is cross checked with a python implementation of
+        // rescue254 and of `bytes_to_field_elements`
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let expected = [
+            [
+                0x8B, 0xC8, 0x9C, 0x76, 0x37, 0x60, 0x0C, 0xEB, 0x66, 0x21, 0x57, 0xFD, 0x6E, 0xC2,
+                0xAF, 0x7D, 0x1E, 0x95, 0x73, 0x30, 0x3B, 0xD1, 0xCD, 0x2E, 0xFE, 0xC7, 0x2E, 0xB5,
+                0xA6, 0xEE, 0xDF, 0x20,
+            ],
+            [
+                0x72, 0xE7, 0x45, 0x90, 0x6A, 0xB5, 0x30, 0x92, 0xE2, 0x37, 0xEE, 0x22, 0xFC, 0xF3,
+                0xB5, 0x2D, 0x2B, 0xA4, 0x79, 0x28, 0xD9, 0x7E, 0x80, 0x4E, 0xB2, 0xD3, 0x18, 0x85,
+                0xF9, 0x77, 0x5F, 0x03,
+            ],
+        ]
+        .map(|bytes| Fr254::from_le_bytes_mod_order(&bytes));
+        assert_eq!(hash_bytes_to_field::<Fr254>(data, 2), expected);
+    }
+
+    fn test_hash_bytes_to_field_helper<F: RescueParameter>() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let output = hash_bytes_to_field::<F>(data, 5);
+        assert_eq!(output.len(), 5);
+        assert_eq!(output[..2], hash_bytes_to_field::<F>(data, 2)[..]);
+        assert_eq!(
+            output,
+            Permutation::default()
+                .sponge_with_padding(&jf_utils::bytes_to_field_elements::<_, F>(&data[..]), 5)
+        );
+        // Inputs which only differ by trailing zero bytes do not collide.
+        let mut longer = data.to_vec();
+        longer.push(0);
+        assert_ne!(output, hash_bytes_to_field::<F>(&longer, 5));
+        assert_ne!(
+            hash_bytes_to_field::<F>(&[], 1),
+            hash_bytes_to_field::<F>(&[0], 1)
+        );
+    }
+
+    #[test]
+    fn test_sponge_with_padding() {
+        test_sponge_with_padding_helper::<Fq254>();
+        test_sponge_with_padding_helper::<Fr254>();
+        test_sponge_with_padding_helper::<Fr377>();
+        test_sponge_with_padding_helper::<Fr381>();
+        test_sponge_with_padding_helper::<Fq377>();
+    }
+
+    fn test_sponge_with_padding_helper<F: RescueParameter>() {
+        let perm = Permutation::default();
+        let a = F::rand(&mut jf_utils::test_rng());
+
+        // Zero padding to a multiple of the rate cannot tell `[a]` and `[a, 0]`
+        // apart, while padding with a domain tag can.
+        for (short, long) in [
+            (vec![a], vec![a, F::zero()]),
+            (vec![a, a], vec![a, a, F::zero()]),
+        ] {
+            assert_eq!(
+                RescueCRHF::sponge_with_zero_padding(&short, 1),
+                RescueCRHF::sponge_with_zero_padding(&long, 1)
+            );
+            assert_ne!(
+                perm.sponge_with_padding(&short, 1),
+                perm.sponge_with_padding(&long, 1)
+            );
+        }
+
+        // The domain tag also separates it from the unpadded sponge on the
+        // padded input.
+        assert_ne!(
+            perm.sponge_with_padding(&[a], 1),
+            RescueCRHF::sponge_no_padding(&[a, F::one(), F::zero()], 1).unwrap()
+        );
+        assert_eq!(perm.sponge_with_padding(&[a], 5).len(), 5);
+        assert_eq!(
+            perm.sponge_with_padding(&[a], 5)[..1],
+            perm.sponge_with_padding(&[a], 1)[..]
+        );
+    }
+
     #[test]
     fn test_rescue_hash_on_0_vec() {
         test_rescue_hash_on_0_vec_fq254();
@@ -892,6 +1319,12 @@ mod test_permutation {
         ];
         let real_output = RescueCRHF::sponge_no_padding(&input, 3).unwrap();
         assert_eq!(real_output, expected);
+
+        // ed_on_bn254 base field is the bn254 scalar field, so the same
+        // parameters are used for `ark_bn254::Fr`.
+        let input = [ark_bn254::Fr::zero(); 3];
+        let real_output = RescueCRHF::<ark_bn254::Fr>::sponge_no_padding(&input, 3).unwrap();
+        assert_eq!(real_output, expected);
     }
 
     fn test_rescue_hash_on_0_vec_377() {
@@ -1047,4 +1480,65 @@ mod test_permutation {
             10
         );
     }
+
+    // Test vectors of the reference implementation `rescue_prime.sage`,
+    // published with "Rescue-Prime: a Standard Specification (SoK)",
+    // <https://eprint.iacr.org/2020/1143>, for p = q254, m = 4, capacity 1,
+    // 128 bits of security and alpha = 5. They are reproduced by
+    // `scripts/rescue_prime_test_vectors.py`, a port of it to plain Python.
+    #[test]
+    fn test_rescue_prime_fr254() {
+        let perm = Permutation::<Fr254>::rescue_prime();
+        assert_eq!(perm.round_keys_ref().len(), 2 * 11);
+        assert_eq!(
+            perm.round_keys_ref()[0].vec[..2],
+            [
+                MontFp!(
+                    "19372445876017403532017686159715766973255657576148543734955949270184900210002"
+                ),
+                MontFp!(
+                    "7426763599231497886592559462516288340908653525034938070104560966990732678923"
+                ),
+            ]
+        );
+
+        let input = RescueVector::from(&[0u64, 1, 2, 3].map(Fr254::from));
+        let expected: [Fr254; 4] = [
+            MontFp!(
+                "17333178955977042199479936369437270786487137858152022215081994148672921158408"
+            ),
+            MontFp!("8006726097758931559973272907884845846453113410286106509125997875515397162743"),
+            MontFp!("9434084398625838466297011603701866751011358678150517537600748135805911517609"),
+            MontFp!("7622214221431152665281586291361980203350090867725753540724535009239019393868"),
+        ];
+        assert_eq!(perm.eval(&input).vec, expected);
+        // Different from the Rescue permutation
+        assert_ne!(Permutation::default().eval(&input).vec, expected);
+
+        // The hash of the reference code, i.e. padding with a one then zeros
+        // up to the rate, through the sponge API.
+        let mut sponge = RescueSponge::<Fr254, CRHF_RATE>::new(&perm);
+        sponge.absorb(&[1u64, 2, 3, 1, 0, 0].map(Fr254::from).to_vec());
+        let expected: [Fr254; 3] = [
+            MontFp!("7278894510556073631276897146229257982932324091738355420827670126116324394248"),
+            MontFp!("7288381507884621461154064641858416681764116810896108551337160792061429667259"),
+            MontFp!("6182740191078794403731094054075174055659298314452891281839567122981480945029"),
+        ];
+        assert_eq!(sponge.squeeze_native_field_elements(3), expected);
+    }
+
+    #[test]
+    fn test_rescue_vector_file() {
+        let vectors: RescueVectors = test_vectors::load("rescue_ed_on_bn254.json");
+        assert!(!vectors.vectors.is_empty());
+        for vector in &vectors.vectors {
+            let perm = match vector.permutation.as_str() {
+                "Rescue" => Permutation::<Fr254>::default(),
+                "Rescue-Prime" => Permutation::rescue_prime(),
+                other => panic!("unknown permutation {}", other),
+            };
+            let input = RescueVector::from(&vector.input::<Fr254>()[..]);
+            assert_eq!(perm.eval(&input).vec[..], vector.output::<Fr254>()[..]);
+        }
+    }
 }
@@ -0,0 +1,499 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Rescue-Prime permutation and sponge over a state of `WIDTH` field elements,
+//! for rates which do not fit the [`STATE_SIZE`](super::STATE_SIZE) elements
+//! of [`Permutation`](super::Permutation), e.g. rate 2 over a width of 3 or
+//! rate 4 over a width of 5.
+//!
+//! The parameters are derived from the field as in the Rescue-Prime
+//! specification, for 128 bits of security:
+//! * the number of rounds is the one of `get_number_of_rounds` of the
+//!   reference implementation
+//! * the MDS matrix is the transpose of the right half of the systematic form
+//!   of the `WIDTH x 2 * WIDTH` Vandermonde matrix of the smallest primitive
+//!   root of the field, [`RescueParameter::primitive_root`]
+//! * the round constants are read from SHAKE256 on the
+//!   `Rescue-XLIX(p,m,capacity,security_level)` seed
+//!
+//! The S-box is the `x^alpha` of [`RescueParameter`]. The permutations over
+//! the BN254 scalar field match the reference implementation, see
+//! `scripts/rescue_prime_test_vectors.py`.
+
+use super::{rescue_prime_constants, RescueParameter, RESCUE_PRIME_SECURITY_LEVEL};
+use ark_crypto_primitives::sponge::{
+    Absorb, CryptographicSponge, FieldBasedCryptographicSponge, FieldElementSize,
+};
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec, One};
+use num_bigint::BigUint;
+
+/// Rescue-Prime permutation over `WIDTH` field elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RescuePrimePermutation<F, const WIDTH: usize> {
+    mds: [[F; WIDTH]; WIDTH],
+    round_keys: Vec<[F; WIDTH]>,
+}
+
+impl<F: RescueParameter, const WIDTH: usize> RescuePrimePermutation<F, WIDTH> {
+    // Evaluated when a permutation of this width is built, so that a state
+    // without room for a capacity element does not compile.
+    const WIDTH_CHECK: () = assert!(WIDTH >= 2, "Rescue-Prime Error : WIDTH must be at least 2.");
+
+    /// Derive the permutation for sponges of capacity `capacity`, which is
+    /// part of the seed of the round constants.
+    /// * `capacity` - between 1 and `WIDTH - 1`
+    pub fn new(capacity: usize) -> Self {
+        let () = Self::WIDTH_CHECK;
+        assert!(
+            capacity > 0 && capacity < WIDTH,
+            "Rescue-Prime Error : capacity must be between 1 and WIDTH - 1."
+        );
+        let round_keys = rescue_prime_constants::<F>(
+            WIDTH,
+            capacity,
+            rescue_prime_num_rounds::<F>(WIDTH, capacity),
+        )
+        .chunks_exact(WIDTH)
+        .map(|chunk| {
+            let mut key = [F::zero(); WIDTH];
+            key.copy_from_slice(chunk);
+            key
+        })
+        .collect();
+        Self {
+            mds: rescue_prime_mds(),
+            round_keys,
+        }
+    }
+
+    /// Number of rounds, each with two S-box layers.
+    pub fn num_rounds(&self) -> usize {
+        self.round_keys.len() / 2
+    }
+
+    /// The MDS matrix, row by row.
+    pub fn mds_matrix_ref(&self) -> &[[F; WIDTH]; WIDTH] {
+        &self.mds
+    }
+
+    /// Compute the permutation on `input`.
+    pub fn eval(&self, input: &[F; WIDTH]) -> [F; WIDTH] {
+        let mut state = *input;
+        for keys in self.round_keys.chunks_exact(2) {
+            state.iter_mut().for_each(|elem| *elem = elem.pow([F::A]));
+            state = self.linear(&state, &keys[0]);
+            state.iter_mut().for_each(|elem| *elem = elem.pow(F::A_INV));
+            state = self.linear(&state, &keys[1]);
+        }
+        state
+    }
+
+    fn linear(&self, state: &[F; WIDTH], key: &[F; WIDTH]) -> [F; WIDTH] {
+        let mut output = *key;
+        for (out, row) in output.iter_mut().zip(self.mds.iter()) {
+            for (coeff, elem) in row.iter().zip(state.iter()) {
+                *out += *coeff * elem;
+            }
+        }
+        output
+    }
+}
+
+// `get_number_of_rounds(p, width, capacity, 128, alpha)` of the Rescue-Prime
+// reference implementation: the smallest number of rounds `l1` for which the
+// Groebner basis attack exceeds the security level, with a minimum of 5 and a
+// 50% margin.
+fn rescue_prime_num_rounds<F: RescueParameter>(width: usize, capacity: usize) -> usize {
+    let rate = width - capacity;
+    let alpha = F::A as usize;
+    let target = BigUint::one() << RESCUE_PRIME_SECURITY_LEVEL;
+    let l1 = (1..25)
+        .find(|&n| {
+            let dcon = (alpha - 1) * width * (n - 1) / 2 + 2;
+            let v = width * (n - 1) + rate;
+            binomial(v + dcon, v).pow(2) > target
+        })
+        .unwrap_or(24);
+    (3 * l1.max(5) + 1) / 2
+}
+
+fn binomial(n: usize, k: usize) -> BigUint {
+    // After the i-th step, `acc` is binomial(n - k + i, i).
+    (1..=k).fold(BigUint::one(), |acc, i| acc * (n - k + i) / i)
+}
+
+// Transpose of the right half of the reduced row echelon form of the
+// Vandermonde matrix `V[i][j] = g^(i * j)` of the smallest primitive root `g`,
+// `0 <= i < WIDTH`,
+// `0 <= j < 2 * WIDTH`. Its left half is an invertible Vandermonde matrix, so
+// that the echelon form is `[I | A]`.
+fn rescue_prime_mds<F: RescueParameter, const WIDTH: usize>() -> [[F; WIDTH]; WIDTH] {
+    let g = F::primitive_root();
+    let mut rows = (0..WIDTH)
+        .map(|i| {
+            let g_i = g.pow([i as u64]);
+            let mut power = F::one();
+            (0..2 * WIDTH)
+                .map(|_| {
+                    let elem = power;
+                    power *= g_i;
+                    elem
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Gauss-Jordan elimination. The pivots are on the diagonal of the
+    // Vandermonde left half, whose leading minors are all non-zero.
+    for col in 0..WIDTH {
+        let pivot_inv = rows[col][col]
+            .inverse()
+            .expect("leading minors of a Vandermonde matrix are non-zero");
+        rows[col].iter_mut().for_each(|elem| *elem *= pivot_inv);
+        let pivot_row = rows[col].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != col && !row[col].is_zero() {
+                let factor = row[col];
+                for (elem, pivot) in row.iter_mut().zip(pivot_row.iter()) {
+                    *elem -= factor * pivot;
+                }
+            }
+        }
+    }
+
+    let mut mds = [[F::zero(); WIDTH]; WIDTH];
+    for (i, mds_row) in mds.iter_mut().enumerate() {
+        for (j, elem) in mds_row.iter_mut().enumerate() {
+            *elem = rows[j][WIDTH + i];
+        }
+    }
+    mds
+}
+
+/// Sponge over a [`RescuePrimePermutation`] of `WIDTH` elements, which absorbs
+/// and squeezes `RATE` elements per permutation, the other `WIDTH - RATE` ones
+/// being the capacity.
+///
+/// As for [`RescueSponge`](super::sponge::RescueSponge), absorbed elements are
+/// buffered until a full RATE chunk is available, and a trailing partial chunk
+/// is absorbed before squeezing. A rate which leaves no capacity does not
+/// compile:
+///
+/// ```compile_fail
+/// use ark_crypto_primitives::sponge::CryptographicSponge;
+/// use ark_ed_on_bn254::Fq;
+/// use jf_primitives::rescue::prime::{RescuePrimePermutation, RescuePrimeSponge};
+///
+/// let perm = RescuePrimePermutation::<Fq, 3>::new(1);
+/// let sponge = RescuePrimeSponge::<Fq, 3, 3>::new(&perm);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RescuePrimeSponge<F: RescueParameter, const WIDTH: usize, const RATE: usize> {
+    state: [F; WIDTH],
+    permutation: RescuePrimePermutation<F, WIDTH>,
+    buffer: Vec<F>,
+}
+
+impl<F: RescueParameter, const WIDTH: usize, const RATE: usize> RescuePrimeSponge<F, WIDTH, RATE> {
+    // Evaluated when a sponge of this rate is built.
+    const RATE_CHECK: () = assert!(
+        RATE > 0 && RATE < WIDTH,
+        "Rescue-Prime sponge Error : RATE must be between 1 and WIDTH - 1."
+    );
+
+    /// Squeeze `n` field elements from the sponge, permuting the state again
+    /// once the current rate is exhausted.
+    pub fn squeeze(&mut self, n: usize) -> Vec<F> {
+        self.squeeze_native_field_elements(n)
+    }
+
+    fn absorb_chunk(&mut self, chunk: &[F]) {
+        for (elem, input) in self.state.iter_mut().zip(chunk.iter()) {
+            *elem += input;
+        }
+        self.state = self.permutation.eval(&self.state);
+    }
+
+    /// Absorb the elements of `buffer` that form full RATE chunks, leaving
+    /// the remaining ones in it.
+    fn absorb_full_chunks(&mut self) {
+        let num_full = self.buffer.len() - self.buffer.len() % RATE;
+        let buffer = core::mem::take(&mut self.buffer);
+        for chunk in buffer[..num_full].chunks_exact(RATE) {
+            self.absorb_chunk(chunk);
+        }
+        self.buffer = buffer[num_full..].to_vec();
+    }
+
+    /// Absorb the elements left in `buffer` as a last, partial chunk.
+    fn absorb_partial_chunk(&mut self) {
+        if !self.buffer.is_empty() {
+            let buffer = core::mem::take(&mut self.buffer);
+            self.absorb_chunk(&buffer);
+        }
+    }
+}
+
+impl<T: RescueParameter, const WIDTH: usize, const RATE: usize> CryptographicSponge
+    for RescuePrimeSponge<T, WIDTH, RATE>
+{
+    /// Config used by the sponge.
+    type Config = RescuePrimePermutation<T, WIDTH>;
+
+    /// Initialize a new instance of the sponge.
+    fn new(permutation: &Self::Config) -> Self {
+        let () = Self::RATE_CHECK;
+        Self {
+            state: [T::zero(); WIDTH],
+            permutation: permutation.clone(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Absorb an input into the sponge, in chunks of `RATE`.
+    fn absorb(&mut self, input: &impl Absorb) {
+        input.to_sponge_field_elements(&mut self.buffer);
+        self.absorb_full_chunks();
+    }
+
+    /// WARNING! This trait method is unimplemented and should not be used.
+    /// Only use the `CryptographicSponge` for squeezing native field elements.
+    fn squeeze_bytes(&mut self, _num_bytes: usize) -> Vec<u8> {
+        unimplemented!("Currently we only support squeezing native field elements!")
+    }
+
+    /// WARNING! This trait method is unimplemented and should not be used.
+    /// Only use the `CryptographicSponge` for squeezing native field elements.
+    fn squeeze_bits(&mut self, _num_bits: usize) -> Vec<bool> {
+        unimplemented!("Currently we only support squeezing native field elements!")
+    }
+
+    /// WARNING! This trait method is unimplemented and should not be used.
+    /// Use `squeeze_native_field_elements` instead.
+    fn squeeze_field_elements_with_sizes<F: PrimeField>(
+        &mut self,
+        _sizes: &[FieldElementSize],
+    ) -> Vec<F> {
+        unimplemented!("Currently we only support squeezing native field elements!")
+    }
+
+    /// WARNING! This trait method is unimplemented and should not be used.
+    /// Use `squeeze_native_field_elements` instead.
+    fn squeeze_field_elements<F: PrimeField>(&mut self, _num_elements: usize) -> Vec<F> {
+        unimplemented!("Currently we only support squeezing native field elements!")
+    }
+}
+
+impl<T: RescueParameter, const WIDTH: usize, const RATE: usize> FieldBasedCryptographicSponge<T>
+    for RescuePrimeSponge<T, WIDTH, RATE>
+{
+    /// Squeeze `num_elements` field elements from the sponge.
+    fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Vec<T> {
+        self.absorb_partial_chunk();
+
+        let mut result = vec![];
+        let mut remaining = num_elements;
+        loop {
+            let extract = remaining.min(RATE);
+            result.extend_from_slice(&self.state[0..extract]);
+            remaining -= extract;
+            if remaining == 0 {
+                break;
+            }
+            self.state = self.permutation.eval(&self.state)
+        }
+        result
+    }
+
+    /// WARNING! This trait method is unimplemented and should not be used.
+    /// Use `squeeze_native_field_elements` instead.
+    fn squeeze_native_field_elements_with_sizes(&mut self, _sizes: &[FieldElementSize]) -> Vec<T> {
+        unimplemented!("Currently we only support squeezing native field elements!")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rescue::{RescuePrimeParameter, CRHF_RATE, STATE_SIZE};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as Fr377;
+    use ark_ed_on_bls12_381::Fq as Fr381;
+    use ark_ed_on_bn254::Fq as Fr254;
+    use ark_ff::MontFp;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_num_rounds() {
+        // The width of `Permutation::rescue_prime`.
+        assert_eq!(
+            rescue_prime_num_rounds::<Fr254>(STATE_SIZE, STATE_SIZE - CRHF_RATE),
+            Fr254::PRIME_ROUNDS
+        );
+        // alpha = 5
+        assert_eq!(rescue_prime_num_rounds::<Fr381>(2, 1), 20);
+        assert_eq!(rescue_prime_num_rounds::<Fr381>(3, 1), 14);
+        assert_eq!(rescue_prime_num_rounds::<Fr381>(5, 1), 9);
+        // alpha = 11
+        assert_eq!(rescue_prime_num_rounds::<Fr377>(3, 1), 11);
+        assert_eq!(rescue_prime_num_rounds::<Fr377>(4, 3), 9);
+        assert_eq!(binomial(10, 3), BigUint::from(120u64));
+        assert_eq!(binomial(10, 0), BigUint::one());
+    }
+
+    #[test]
+    fn test_primitive_root() {
+        // The smallest primitive roots, as found by the reference
+        // implementation from the factorization of `p - 1`.
+        assert_eq!(Fr254::primitive_root(), Fr254::from(5u64));
+        assert_eq!(ark_bn254::Fq::primitive_root(), ark_bn254::Fq::from(3u64));
+        assert_eq!(Fr381::primitive_root(), Fr381::from(7u64));
+        assert_eq!(
+            ark_bls12_381::Fq::primitive_root(),
+            ark_bls12_381::Fq::from(2u64)
+        );
+        assert_eq!(Fr377::primitive_root(), Fr377::from(22u64));
+        assert_eq!(Fq377::primitive_root(), Fq377::from(15u64));
+    }
+
+    // Known answers of the Rescue-Prime reference implementation over the BN254
+    // scalar field, for capacity 1, 128 bits of security and alpha = 5, given
+    // by `scripts/rescue_prime_test_vectors.py`. The hashes pad `[1, 2, 3]`
+    // with a one then zeros up to the rate.
+    #[test]
+    fn test_rescue_prime_kat() {
+        let perm = RescuePrimePermutation::<Fr254, 3>::new(1);
+        assert_eq!(perm.num_rounds(), 14);
+        let expected: [Fr254; 3] = [
+            MontFp!("6224690566795026170272976986384432621080028281436539532889157379570648910802"),
+            MontFp!(
+                "11125085147280074555337181371265636082619440214910773293161304065299707718600"
+            ),
+            MontFp!(
+                "12118779605307541175395572293313884052054477690855880723785138715937774904848"
+            ),
+        ];
+        assert_eq!(perm.eval(&[0u64, 1, 2].map(Fr254::from)), expected);
+        let mut sponge = RescuePrimeSponge::<Fr254, 3, 2>::new(&perm);
+        sponge.absorb(&[1u64, 2, 3, 1].map(Fr254::from).to_vec());
+        let expected: [Fr254; 2] = [
+            MontFp!(
+                "10426312538076513787842576207928300055993667092218520332880144400670314798844"
+            ),
+            MontFp!("4184464354440461145180986148039895060330502056651856767983333175045473850499"),
+        ];
+        assert_eq!(sponge.squeeze(2), expected);
+
+        let perm = RescuePrimePermutation::<Fr254, 5>::new(1);
+        assert_eq!(perm.num_rounds(), 9);
+        let expected: [Fr254; 5] = [
+            MontFp!(
+                "20285143679491630465414873272523871710272247847301674998201964149202140286382"
+            ),
+            MontFp!(
+                "18334168084722582820794594054007406971665638483254484179076056482949453281091"
+            ),
+            MontFp!(
+                "14402157272728489172725568351194374649902264293178868218946767754056129725442"
+            ),
+            MontFp!(
+                "21215028641990606058070153831358199279933836004524973419487418943825902828175"
+            ),
+            MontFp!("5523170601416163482252155505990026743763056993004561719598778309806652771645"),
+        ];
+        assert_eq!(perm.eval(&[0u64, 1, 2, 3, 4].map(Fr254::from)), expected);
+        let mut sponge = RescuePrimeSponge::<Fr254, 5, 4>::new(&perm);
+        sponge.absorb(&[1u64, 2, 3, 1].map(Fr254::from).to_vec());
+        let expected: [Fr254; 4] = [
+            MontFp!("9582446945978169707923203068246082890372969062999020734287122456249142731843"),
+            MontFp!("9560411635455843086244569404654723477371687576531066260537808348194903608018"),
+            MontFp!("5357354834751353653061457022085243684911440492269214025712762011166652688956"),
+            MontFp!("5424320789155657566178348696548084300894140645990857302625812880059067523101"),
+        ];
+        assert_eq!(sponge.squeeze(4), expected);
+    }
+
+    #[test]
+    fn test_rescue_prime_sponge() {
+        test_rescue_prime_sponge_helper::<Fr254>();
+        test_rescue_prime_sponge_helper::<Fr377>();
+        test_rescue_prime_sponge_helper::<Fr381>();
+        test_rescue_prime_sponge_helper::<Fq377>();
+    }
+
+    fn test_rescue_prime_sponge_helper<F: RescueParameter>() {
+        test_mds_helper::<F, 2>();
+        test_mds_helper::<F, 3>();
+        test_mds_helper::<F, 5>();
+
+        let mut rng = jf_utils::test_rng();
+        let input = (0..7).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+
+        // Rate 2 over a width of 3.
+        let perm = RescuePrimePermutation::<F, 3>::new(1);
+        let mut sponge = RescuePrimeSponge::<F, 3, 2>::new(&perm);
+        sponge.absorb(&input[..3].to_vec());
+        sponge.absorb(&input[3..].to_vec());
+        let mut state = [F::zero(); 3];
+        for chunk in input.chunks(2) {
+            for (elem, x) in state.iter_mut().zip(chunk) {
+                *elem += x;
+            }
+            state = perm.eval(&state);
+        }
+        let output = sponge.squeeze(3);
+        assert_eq!(output[..2], state[..2]);
+        assert_eq!(output[2], perm.eval(&state)[0]);
+
+        // Rate 4 over a width of 5.
+        let perm = RescuePrimePermutation::<F, 5>::new(1);
+        let mut sponge = RescuePrimeSponge::<F, 5, 4>::new(&perm);
+        sponge.absorb(&input);
+        let mut state = [F::zero(); 5];
+        for chunk in input.chunks(4) {
+            for (elem, x) in state.iter_mut().zip(chunk) {
+                *elem += x;
+            }
+            state = perm.eval(&state);
+        }
+        assert_eq!(sponge.squeeze(4), state[..4]);
+
+        // The permutation is a bijection: distinct inputs, distinct outputs.
+        let a = [input[0], input[1], input[2], input[3], input[4]];
+        let mut b = a;
+        b[4] += F::one();
+        assert_ne!(perm.eval(&a), perm.eval(&b));
+
+        // The capacity is part of the parameters.
+        assert_ne!(perm, RescuePrimePermutation::<F, 5>::new(2));
+        assert_eq!(perm.num_rounds(), rescue_prime_num_rounds::<F>(5, 1),);
+    }
+
+    // The MDS matrix of the `[I | A]` form of a Vandermonde matrix has no zero
+    // coefficient and an invertible 2x2 minor in every pair of rows and
+    // columns, which is checked exhaustively for these small widths.
+    fn test_mds_helper<F: RescueParameter, const WIDTH: usize>() {
+        let mds = rescue_prime_mds::<F, WIDTH>();
+        for (i, row) in mds.iter().enumerate() {
+            for (j, a) in row.iter().enumerate() {
+                assert!(!a.is_zero());
+                for other in mds[i + 1..].iter() {
+                    let c = other[j];
+                    for (b, d) in row[j + 1..].iter().zip(other[j + 1..].iter()) {
+                        assert!(!(*a * d - *b * c).is_zero());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_capacity_too_large() {
+        RescuePrimePermutation::<Fr254, 3>::new(3);
+    }
+}
@@ -4,7 +4,7 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-use crate::rescue::{RescueParameter, ROUNDS, STATE_SIZE};
+use crate::rescue::{RescueParameter, RescuePrimeParameter, ROUNDS, STATE_SIZE};
 use ark_ed_on_bn254::Fq;
 
 // the constants in this file are generated with
@@ -12,6 +12,10 @@ use ark_ed_on_bn254::Fq;
 
 // q254 = 21888242871839275222246405745257275088548364400416034343698204186575808495617
 // rescue254 = Rescue(128, q254, 4, 5)
+//
+// q254 is the order of the bn254 scalar field: `ark_ed_on_bn254::Fq` is the
+// same type as `ark_bn254::Fr`, so these parameters also instantiate Rescue
+// over the latter.
 
 impl RescueParameter for Fq {
     // rescue254.alpha
@@ -1306,3 +1310,10 @@ impl RescueParameter for Fq {
         ],
     ];
 }
+
+impl RescuePrimeParameter for Fq {
+    // get_number_of_rounds(q254, 4, 1, 128, 5) of the Rescue-Prime reference
+    // implementation: the Groebner basis bound is reached at 7 rounds, plus a
+    // 50% margin.
+    const PRIME_ROUNDS: usize = 11;
+}
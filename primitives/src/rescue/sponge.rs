@@ -11,7 +11,12 @@ use ark_crypto_primitives::sponge::{
 };
 use ark_ff::PrimeField;
 use ark_std::{string::ToString, vec, vec::Vec};
-use jf_utils::pad_with_zeros;
+use jf_utils::{bytes_to_field_elements, pad_with_zeros, par_utils::parallelizable_slice_iter};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use zeroize::Zeroize;
+#[cfg(feature = "rescue-zeroize")]
+use zeroize::ZeroizeOnDrop;
 
 use super::{
     errors::RescueError, Permutation, RescueParameter, RescueVector, CRHF_RATE, STATE_SIZE,
@@ -20,12 +25,45 @@ use super::{
 #[derive(Clone, Default, Debug)]
 /// A rescue hash function consists of a permutation function and
 /// an internal state.
-struct RescueSponge<F: RescueParameter, const RATE: usize> {
+///
+/// `RATE` is the number of state elements absorbed and squeezed per
+/// permutation, the other `STATE_SIZE - RATE` ones being the capacity. Any
+/// `RATE` from 1 to `STATE_SIZE` can be used with the same parameters: the
+/// CRHF uses [`CRHF_RATE`] and the PRF the full state, while a smaller rate
+/// trades throughput for a larger capacity. Other rates need a wider state,
+/// see [`RescuePrimeSponge`](super::prime::RescuePrimeSponge). A larger rate
+/// does not compile:
+///
+/// ```compile_fail
+/// use ark_crypto_primitives::sponge::CryptographicSponge;
+/// use ark_ed_on_bn254::Fq;
+/// use jf_primitives::rescue::{sponge::RescueSponge, Permutation};
+///
+/// let sponge = RescueSponge::<Fq, 5>::new(&Permutation::default());
+/// ```
+///
+/// The sponge can be fed incrementally: absorbed elements are buffered until
+/// a full RATE chunk is available, so absorbing an input in several pieces is
+/// the same as absorbing it at once. A trailing partial chunk is absorbed
+/// before squeezing.
+///
+/// The sponge always implements [`Zeroize`], which wipes the state and the
+/// buffered input. Only with the `rescue-zeroize` feature is it also wiped on
+/// drop, and [`ZeroizeOnDrop`](zeroize::ZeroizeOnDrop), as are the
+/// intermediate states of the permutation and the round keys scheduled from a
+/// PRP key, since they may hold a PRF key or secret inputs.
+pub struct RescueSponge<F: RescueParameter, const RATE: usize> {
     pub(crate) state: RescueVector<F>,
     pub(crate) permutation: Permutation<F>,
+    buffer: Vec<F>,
 }
 
-/// CRHF
+/// CRHF based on the rescue sponge with RATE 3 and CAPACITY 1.
+///
+/// [`RescueCRHF::evaluate`] is collision resistant over the domain of field
+/// element sequences of any length, including the empty one. The lower level
+/// `sponge_*` functions are only collision resistant for inputs of a fixed
+/// length, or whose length is a multiple of RATE.
 #[derive(Debug, Clone)]
 pub struct RescueCRHF<F: RescueParameter> {
     sponge: RescueSponge<F, CRHF_RATE>,
@@ -38,6 +76,13 @@ pub(crate) struct RescuePRFCore<F: RescueParameter> {
 }
 
 impl<F: RescueParameter> RescueCRHF<F> {
+    /// Hash an input of any length to a single field element. The input is
+    /// bit padded as in [`RescueCRHF::sponge_with_bit_padding`], so inputs
+    /// of different lengths never collide.
+    pub fn evaluate(input: &[F]) -> F {
+        Self::sponge_with_bit_padding(input, 1)[0]
+    }
+
     /// Sponge hashing based on rescue permutation for RATE 3. It allows
     /// unrestricted variable length input and returns a vector of
     /// `num_outputs` elements.
@@ -89,6 +134,27 @@ impl<F: RescueParameter> RescueCRHF<F> {
 }
 
 impl<F: RescueParameter> RescuePRFCore<F> {
+    /// Pseudorandom function based on rescue permutation for RATE 4. It allows
+    /// unrestricted variable length input and returns a vector of
+    /// `num_outputs` elements.
+    ///
+    /// we use ["bit padding"-style][padding] where "1" is always appended, then
+    /// as many "0" as required are added for the overall length to be a
+    /// multiple of `STATE_SIZE`
+    ///
+    /// [padding]: https://en.wikipedia.org/wiki/Padding_(cryptography)#Bit_padding
+    pub(crate) fn full_state_keyed_sponge_with_bit_padding(
+        key: &F,
+        input: &[F],
+        num_outputs: usize,
+    ) -> Vec<F> {
+        let mut padded = input.to_vec();
+        padded.push(F::one());
+        pad_with_zeros(&mut padded, STATE_SIZE);
+        Self::full_state_keyed_sponge_no_padding(key, padded.as_slice(), num_outputs)
+            .expect("Bug in JF Primitives : bad padding of input for FSKS construction")
+    }
+
     /// Similar to [`Self::full_state_keyed_sponge_with_bit_padding`] except the
     /// padding scheme are all "0" until the length of padded input is a
     /// multiple of `STATE_SIZE`
@@ -129,17 +195,126 @@ impl<F: RescueParameter> RescuePRFCore<F> {
     }
 }
 
+impl<F: RescueParameter, const RATE: usize> RescueSponge<F, RATE> {
+    /// Squeeze `n` field elements from the sponge. Once the current rate is
+    /// exhausted, the state is permuted again to produce more elements, so `n`
+    /// can be larger than RATE. The output only depends on the absorbed input
+    /// and on `n`, and the first elements do not depend on `n`, which makes it
+    /// usable as a field element PRG.
+    pub fn squeeze(&mut self, n: usize) -> Vec<F> {
+        self.squeeze_native_field_elements(n)
+    }
+
+    // Evaluated when a sponge of this RATE is built, so that an invalid rate
+    // does not compile.
+    const RATE_CHECK: () = assert!(
+        RATE > 0 && RATE <= STATE_SIZE,
+        "Rescue sponge Error : RATE must be between 1 and STATE_SIZE."
+    );
+
+    /// Absorb the elements of `buffer` that form full RATE chunks, leaving
+    /// the remaining ones in it.
+    fn absorb_full_chunks(&mut self) {
+        let num_full = self.buffer.len() - self.buffer.len() % RATE;
+        for chunk in self.buffer[..num_full].chunks_exact(RATE) {
+            self.state.add_assign_elems(chunk);
+            self.state = self.permutation.eval(&self.state)
+        }
+        self.buffer.drain(..num_full);
+    }
+
+    /// Absorb the elements left in `buffer` as a last, partial chunk.
+    fn absorb_partial_chunk(&mut self) {
+        if !self.buffer.is_empty() {
+            self.state.add_assign_elems(&self.buffer);
+            self.state = self.permutation.eval(&self.state);
+            self.buffer.clear();
+        }
+    }
+}
+
+/// Value of the capacity element at the start of
+/// [`Permutation::sponge_with_padding`], separating it from the unpadded
+/// sponges which start from an all-zero state.
+const PADDED_SPONGE_DOMAIN_TAG: u64 = 1;
+
+impl<F: RescueParameter> Permutation<F> {
+    /// Sponge hashing for RATE 3 and CAPACITY 1 of inputs of any length. The
+    /// input is "bit padded": a "1" is appended, then as many "0" as required
+    /// for the length to be a multiple of RATE, so that inputs of different
+    /// lengths such as `[a]` and `[a, 0]` do not collide. The capacity element
+    /// starts with a domain tag, so that the output also differs from
+    /// [`RescueCRHF::sponge_no_padding`] on the padded input.
+    ///
+    /// Fixed-length callers which do not need padding can keep using
+    /// [`RescueCRHF::sponge_no_padding`], which saves one permutation when the
+    /// length is already a multiple of RATE.
+    pub fn sponge_with_padding(&self, input: &[F], num_outputs: usize) -> Vec<F> {
+        let mut padded = input.to_vec();
+        padded.push(F::one());
+        pad_with_zeros(&mut padded, CRHF_RATE);
+
+        // ABSORB PHASE
+        let mut state = RescueVector::zero();
+        state.vec[STATE_SIZE - 1] = F::from(PADDED_SPONGE_DOMAIN_TAG);
+        let mut sponge = RescueSponge::<F, CRHF_RATE>::from_state(state, self);
+        sponge.absorb(&padded);
+
+        // SQUEEZE PHASE
+        sponge.squeeze_native_field_elements(num_outputs)
+    }
+
+    /// Evaluate [`Self::sponge_with_padding`] on each of `inputs`, returning
+    /// the outputs in the same order. The inputs are hashed in parallel when
+    /// the `parallel` feature is on.
+    pub fn batch_sponge(&self, inputs: &[Vec<F>], num_outputs: usize) -> Vec<Vec<F>> {
+        parallelizable_slice_iter(inputs)
+            .map(|input| self.sponge_with_padding(input, num_outputs))
+            .collect()
+    }
+}
+
+/// Deterministically map arbitrary bytes to `n` field elements, e.g. to
+/// derive Fiat-Shamir challenges. The bytes are packed into field elements
+/// with [`bytes_to_field_elements`], which prepends the byte length and
+/// handles inputs not aligned to the field element byte size, then hashed
+/// with [`Permutation::sponge_with_padding`].
+pub fn hash_bytes_to_field<F: RescueParameter>(data: &[u8], n: usize) -> Vec<F> {
+    let elems: Vec<F> = bytes_to_field_elements(data);
+    Permutation::default().sponge_with_padding(&elems, n)
+}
+
+impl<F: RescueParameter, const RATE: usize> Zeroize for RescueSponge<F, RATE> {
+    fn zeroize(&mut self) {
+        self.state.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+#[cfg(feature = "rescue-zeroize")]
+impl<F: RescueParameter, const RATE: usize> Drop for RescueSponge<F, RATE> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "rescue-zeroize")]
+impl<F: RescueParameter, const RATE: usize> ZeroizeOnDrop for RescueSponge<F, RATE> {}
+
 impl<F: RescueParameter, const RATE: usize> SpongeExt for RescueSponge<F, RATE> {
     type State = RescueVector<F>;
 
     fn from_state(state: Self::State, permutation: &Self::Config) -> Self {
+        let () = Self::RATE_CHECK;
         Self {
             state,
             permutation: permutation.clone(),
+            buffer: Vec::new(),
         }
     }
 
-    fn into_state(self) -> Self::State {
+    fn into_state(mut self) -> Self::State {
+        self.absorb_partial_chunk();
         self.state
     }
 }
@@ -152,23 +327,21 @@ impl<T: RescueParameter + PrimeField, const RATE: usize> CryptographicSponge
 
     /// Initialize a new instance of the sponge.
     fn new(permutation: &Self::Config) -> Self {
+        let () = Self::RATE_CHECK;
         Self {
             state: RescueVector::default(),
             permutation: permutation.clone(),
+            buffer: Vec::new(),
         }
     }
 
     /// Absorb an input into the sponge.
-    /// This function will absorb the entire input, in chunks of `RATE`,
-    /// even if the input lenght is not a multiple of `RATE`.
+    /// The input is absorbed in chunks of `RATE`; the elements which do not
+    /// fill a chunk are kept until the next call to `absorb`, or absorbed as
+    /// a partial chunk by the next squeeze.
     fn absorb(&mut self, input: &impl Absorb) {
-        let input_field_elements = input.to_sponge_field_elements_as_vec();
-
-        // Absorb input.
-        input_field_elements.chunks(RATE).for_each(|chunk| {
-            self.state.add_assign_elems(chunk);
-            self.state = self.permutation.eval(&self.state)
-        });
+        input.to_sponge_field_elements(&mut self.buffer);
+        self.absorb_full_chunks();
     }
 
     /// WARNING! This trait method is unimplemented and should not be used.
@@ -217,6 +390,8 @@ impl<T: RescueParameter, const RATE: usize> FieldBasedCryptographicSponge<T>
 {
     /// Squeeze `num_elements` field elements from the sponge.
     fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Vec<T> {
+        self.absorb_partial_chunk();
+
         // SQUEEZE PHASE
         let mut result = vec![];
         let mut remaining = num_elements;
@@ -313,6 +488,64 @@ mod test {
         assert_different_encodings::<Fr, _>(&lst1, &lst2);
     }
 
+    #[test]
+    fn test_streaming_absorb() {
+        let mut rng = test_rng();
+        let input: Vec<_> = (0..20).map(|_| Fr::rand(&mut rng)).collect();
+        let sponge_param = Permutation::default();
+
+        let mut sponge = RescueSponge::<Fr, 3>::new(&sponge_param);
+        sponge.absorb(&input);
+        let expected = sponge.squeeze(5);
+
+        for splits in [
+            vec![0],
+            vec![1],
+            vec![3],
+            vec![7, 7, 8],
+            vec![2, 4, 11, 20],
+            (0..20).collect(),
+        ] {
+            let mut sponge = RescueSponge::<Fr, 3>::new(&sponge_param);
+            let mut start = 0;
+            for end in splits.into_iter().chain([input.len()]) {
+                sponge.absorb(&input[start..end].to_vec());
+                start = end;
+            }
+            assert_eq!(sponge.squeeze(5), expected);
+        }
+
+        // Squeezing absorbs the trailing partial chunk as before.
+        let mut state = RescueVector::zero();
+        for chunk in input.chunks(3) {
+            state.add_assign_elems(chunk);
+            state = sponge_param.eval(&state);
+        }
+        assert_eq!(expected[..3], state.vec[..3]);
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let sentinel = Fr::from(0xdeadbeefu64);
+        let mut state = RescueVector::zero();
+        state.vec[3] = sentinel;
+        let mut sponge = RescueSponge::<Fr, 4>::from_state(state, &Permutation::default());
+        sponge.absorb(&vec![sentinel; 6]);
+        assert!(!sponge.buffer.is_empty());
+
+        // `Drop` wipes the sponge with this same call.
+        sponge.zeroize();
+        assert_eq!(sponge.state, RescueVector::zero());
+        assert!(sponge.buffer.is_empty());
+    }
+
+    #[cfg(feature = "rescue-zeroize")]
+    #[test]
+    fn test_zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<RescueSponge<Fr, 4>>();
+    }
+
     #[test]
     fn test_macros() {
         let sponge_param = Permutation::default();
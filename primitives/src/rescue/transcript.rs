@@ -0,0 +1,167 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A Fiat-Shamir transcript built on the Rescue sponge.
+
+use super::{sponge::RescueSponge, Permutation, RescueParameter, CRHF_RATE};
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ec::AffineRepr;
+use ark_std::{vec, vec::Vec};
+use jf_utils::bytes_to_field_elements;
+
+/// Tags separating the kinds of transcript entries, so that e.g. a byte
+/// string and a field element vector never have the same encoding.
+const LABEL_TAG: u64 = 0;
+const MESSAGE_TAG: u64 = 1;
+const FIELD_ELEMS_TAG: u64 = 2;
+const POINT_TAG: u64 = 3;
+const CHALLENGE_TAG: u64 = 4;
+
+/// Fiat-Shamir transcript over the field `F`, based on a duplex Rescue sponge
+/// with RATE 3 and CAPACITY 1.
+///
+/// Every entry is absorbed as a kind tag, a length-prefixed label and the
+/// length-prefixed message, so the encoding of a sequence of entries is
+/// injective. Challenges are squeezed from the sponge after absorbing their
+/// label, which also makes consecutive challenges distinct. Appending the same
+/// entries in the same order always gives the same challenges.
+#[derive(Debug, Clone)]
+pub struct Transcript<F: RescueParameter> {
+    sponge: RescueSponge<F, CRHF_RATE>,
+}
+
+impl<F: RescueParameter> Transcript<F> {
+    /// Create a new transcript for the protocol identified by `label`.
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Self {
+            sponge: RescueSponge::new(&Permutation::default()),
+        };
+        transcript.absorb_label(LABEL_TAG, label);
+        transcript
+    }
+
+    /// Append a byte string to the transcript.
+    pub fn append_message(&mut self, label: &'static [u8], msg: &[u8]) {
+        self.absorb_label(MESSAGE_TAG, label);
+        self.absorb_bytes(msg);
+    }
+
+    /// Append field elements to the transcript.
+    pub fn append_field_elems(&mut self, label: &'static [u8], elems: &[F]) {
+        self.absorb_label(FIELD_ELEMS_TAG, label);
+        self.sponge.absorb(&F::from(elems.len() as u64));
+        self.sponge.absorb(&elems.to_vec());
+    }
+
+    /// Append a curve point over `F` to the transcript, as its affine
+    /// coordinates. The point at infinity of a short Weierstrass curve is
+    /// appended as `(0, 0)`, which is on neither short Weierstrass curves with
+    /// a non-zero `b` nor twisted Edwards curves.
+    pub fn append_point<G: AffineRepr<BaseField = F>>(&mut self, label: &'static [u8], point: &G) {
+        let (x, y) = point.xy().map_or((F::zero(), F::zero()), |(x, y)| (*x, *y));
+        self.absorb_label(POINT_TAG, label);
+        self.sponge.absorb(&vec![x, y]);
+    }
+
+    /// Generate a challenge from the current transcript. The challenge is
+    /// bound to the subsequent challenges.
+    pub fn get_challenge(&mut self, label: &'static [u8]) -> F {
+        self.get_challenges(label, 1)[0]
+    }
+
+    /// Same as [`Self::get_challenge`] except that `n` challenges are
+    /// returned.
+    pub fn get_challenges(&mut self, label: &'static [u8], n: usize) -> Vec<F> {
+        self.absorb_label(CHALLENGE_TAG, label);
+        self.sponge.absorb(&F::from(n as u64));
+        self.sponge.squeeze(n)
+    }
+
+    fn absorb_label(&mut self, tag: u64, label: &[u8]) {
+        self.sponge.absorb(&F::from(tag));
+        self.absorb_bytes(label);
+    }
+
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        let elems: Vec<F> = bytes_to_field_elements(bytes);
+        self.sponge.absorb(&F::from(bytes.len() as u64));
+        self.sponge.absorb(&elems);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ec::twisted_edwards::{Affine, TECurveConfig};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_transcript() {
+        test_transcript_helper::<ark_ed_on_bn254::EdwardsConfig>();
+        test_transcript_helper::<ark_ed_on_bls12_377::EdwardsConfig>();
+        test_transcript_helper::<ark_ed_on_bls12_381::EdwardsConfig>();
+    }
+
+    fn test_transcript_helper<P>()
+    where
+        P: TECurveConfig,
+        P::BaseField: RescueParameter,
+    {
+        let mut rng = jf_utils::test_rng();
+        let elems = [P::BaseField::rand(&mut rng), P::BaseField::rand(&mut rng)];
+        let point = Affine::<P>::rand(&mut rng);
+
+        let run = |ordered: bool, label: &'static [u8]| {
+            let mut transcript = Transcript::<P::BaseField>::new(b"test");
+            if ordered {
+                transcript.append_message(label, b"message");
+                transcript.append_field_elems(b"elems", &elems);
+            } else {
+                transcript.append_field_elems(b"elems", &elems);
+                transcript.append_message(label, b"message");
+            }
+            transcript.append_point(b"point", &point);
+            transcript.get_challenges(b"challenge", 2)
+        };
+
+        // reproducible
+        let challenges = run(true, b"msg");
+        assert_eq!(challenges, run(true, b"msg"));
+        assert_ne!(challenges[0], challenges[1]);
+        // order and labels matter
+        assert_ne!(challenges, run(false, b"msg"));
+        assert_ne!(challenges, run(true, b"other msg"));
+
+        // the protocol label, the kind of entry and the points matter
+        let mut transcript = Transcript::<P::BaseField>::new(b"test");
+        transcript.append_field_elems(b"label", &[]);
+        let mut other_transcript = Transcript::<P::BaseField>::new(b"other test");
+        other_transcript.append_field_elems(b"label", &[]);
+        let mut msg_transcript = Transcript::<P::BaseField>::new(b"test");
+        msg_transcript.append_message(b"label", &[]);
+        let mut zero_transcript = Transcript::<P::BaseField>::new(b"test");
+        zero_transcript.append_field_elems(b"label", &[P::BaseField::from(0u8)]);
+        let mut point_transcript = Transcript::<P::BaseField>::new(b"test");
+        point_transcript.append_point(b"label", &Affine::<P>::zero());
+        let challenge = transcript.clone().get_challenge(b"challenge");
+        for mut t in [
+            other_transcript,
+            msg_transcript,
+            zero_transcript,
+            point_transcript,
+        ] {
+            assert_ne!(t.get_challenge(b"challenge"), challenge);
+        }
+
+        // consecutive challenges differ, even with the same label, and depend
+        // on their label
+        let mut other_transcript = transcript.clone();
+        let first = transcript.get_challenge(b"challenge");
+        assert_eq!(first, challenge);
+        assert_ne!(transcript.get_challenge(b"challenge"), first);
+        assert_ne!(other_transcript.get_challenge(b"other challenge"), first);
+    }
+}
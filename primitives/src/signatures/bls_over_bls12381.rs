@@ -12,6 +12,13 @@
 //! [irtf]: https://datatracker.ietf.org/doc/pdf/draft-irtf-cfrg-bls-signature-05
 //! [blst]: https://github.com/supranational/blst
 //!
+//! This is the "minimal-signature-size" variant: signatures and hashed
+//! messages are in G1, verification keys are in G2. Messages are hashed with
+//! the `BLS12381G1_XMD:SHA-256_SSWU_RO_` suite under the domain separation
+//! tag [`CS_ID_BLS_MIN_SIG`], which is the same map as
+//! [`SWHashToGroup::hash_to_group`](crate::hash_to_group::SWHashToGroup::hash_to_group)
+//! on BLS12-381 G1.
+//!
 //! # Examples
 //!
 //! ```
@@ -70,8 +77,22 @@
 //! ```
 //!
 //! [zeroize]: https://github.com/RustCrypto/utils/tree/master/zeroize
+//!
+//! ## Proofs of possession
+//!
+//! A multisignature on a common message is checked against the sum of the
+//! signers' verification keys, so an attacker could register a "rogue" key
+//! that cancels out honest keys. To rule this out, every key should come with
+//! a proof of possession of its signing key, checked before the key is
+//! aggregated with [`BLSSignatureScheme::aggregate_public_keys`].
+//!
+//! A proof of possession is a signature on the compressed verification key
+//! under the domain separation tag [`CS_ID_BLS_POP`], while messages are
+//! signed under [`CS_ID_BLS_MIN_SIG`]. The two tags hash to independent
+//! points, so a proof of possession is never a valid signature and a
+//! signature is never a valid proof of possession.
 
-use super::SignatureScheme;
+use super::{AggregateableSignatureSchemes, SignatureScheme};
 use crate::{
     constants::{
         BLS_SIG_COMPRESSED_PK_SIZE, BLS_SIG_COMPRESSED_SIGNATURE_SIZE, BLS_SIG_PK_SIZE,
@@ -80,15 +101,19 @@ use crate::{
     errors::PrimitivesError,
 };
 
-use crate::constants::CS_ID_BLS_MIN_SIG;
+use crate::constants::{CS_ID_BLS_MIN_SIG, CS_ID_BLS_POP};
 use ark_serialize::*;
 use ark_std::{
+    collections::BTreeSet,
     format,
     ops::{Deref, DerefMut},
     rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
 };
 use blst::{min_sig::*, BLST_ERROR};
 use espresso_systems_common::jellyfish::tag;
+use serde::{Deserialize, Serialize};
 use tagged_base64::tagged;
 use zeroize::{Zeroize, Zeroizing};
 
@@ -321,9 +346,13 @@ impl Valid for BLSSignature {
     }
 }
 
+/// A proof of possession of the signing key of a [`BLSVerKey`].
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+pub struct BLSPopProof(Signature);
+
 /// BLS signature scheme. Wrapping around structs from the `blst` crate.
 /// See [module-level documentation](self) for example usage.
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BLSSignatureScheme;
 
 impl SignatureScheme for BLSSignatureScheme {
@@ -394,6 +423,84 @@ impl SignatureScheme for BLSSignatureScheme {
     }
 }
 
+impl AggregateableSignatureSchemes for BLSSignatureScheme {
+    /// Aggregate multiple signatures into a single signature
+    /// Follow the instantiation from <https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-aggregate>
+    fn aggregate(
+        _pp: &Self::PublicParameter,
+        _vks: &[Self::VerificationKey],
+        sigs: &[Self::Signature],
+    ) -> Result<Self::Signature, PrimitivesError> {
+        if sigs.is_empty() {
+            return Err(PrimitivesError::ParameterError(
+                "no signatures to aggregate".to_string(),
+            ));
+        }
+        let sigs: Vec<&Signature> = sigs.iter().map(|sig| &sig.0).collect();
+        let agg_sig = AggregateSignature::aggregate(&sigs, true)?;
+        Ok(BLSSignature(agg_sig.to_signature()))
+    }
+
+    /// Verify an aggregate signature w.r.t. a list of distinct messages and
+    /// public keys. Each message is hashed on its own and paired with its own
+    /// public key. It is user's responsibility to ensure that the public keys
+    /// are validated.
+    /// Follow the instantiation from <https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-aggregateverify>
+    fn aggregate_verify<M: AsRef<[Self::MessageUnit]>>(
+        _pp: &Self::PublicParameter,
+        vks: &[Self::VerificationKey],
+        msgs: &[M],
+        sig: &Self::Signature,
+    ) -> Result<(), PrimitivesError> {
+        if vks.is_empty() {
+            return Err(PrimitivesError::ParameterError(
+                "no verification key for signature verification".to_string(),
+            ));
+        }
+        if vks.len() != msgs.len() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "vks.len = {}; msgs.len = {}",
+                vks.len(),
+                msgs.len(),
+            )));
+        }
+        let msgs: Vec<&[u8]> = msgs.iter().map(|msg| msg.as_ref()).collect();
+        // The basic scheme is only secure for distinct messages.
+        if msgs.iter().collect::<BTreeSet<_>>().len() != msgs.len() {
+            return Err(PrimitivesError::ParameterError(
+                "messages are not distinct".to_string(),
+            ));
+        }
+        let vks: Vec<&PublicKey> = vks.iter().map(|vk| &vk.0).collect();
+        match sig.aggregate_verify(true, &msgs, Self::CS_ID.as_bytes(), &vks, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            e => Err(PrimitivesError::VerificationError(format!("{e:?}"))),
+        }
+    }
+
+    /// Verify a multisignature w.r.t. a single message and a list of public
+    /// keys. It is user's responsibility to ensure that the public keys are
+    /// validated.
+    /// Follow the instantiation from <https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-fastaggregateverify>
+    fn multi_sig_verify(
+        _pp: &Self::PublicParameter,
+        vks: &[Self::VerificationKey],
+        msg: &[Self::MessageUnit],
+        sig: &Self::Signature,
+    ) -> Result<(), PrimitivesError> {
+        if vks.is_empty() {
+            return Err(PrimitivesError::ParameterError(
+                "no verification key for signature verification".to_string(),
+            ));
+        }
+        let vks: Vec<&PublicKey> = vks.iter().map(|vk| &vk.0).collect();
+        match sig.fast_aggregate_verify(true, msg, Self::CS_ID.as_bytes(), &vks) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            e => Err(PrimitivesError::VerificationError(format!("{e:?}"))),
+        }
+    }
+}
+
 impl BLSSignatureScheme {
     /// Alternative deterministic key_gen compatible with [IRTF draft v5][v5].
     ///
@@ -424,13 +531,76 @@ impl BLSSignatureScheme {
 
         Ok((BLSSignKey(sk), BLSVerKey(vk)))
     }
+
+    /// Prove possession of the signing key `sk`, following `PopProve` of the
+    /// [IRTF draft v5][pop].
+    ///
+    /// [pop]: https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-popprove
+    pub fn prove_possession(sk: &<Self as SignatureScheme>::SigningKey) -> BLSPopProof {
+        let vk = sk.sk_to_pk();
+        BLSPopProof(sk.sign(&vk.compress(), CS_ID_BLS_POP.as_bytes(), &[]))
+    }
+
+    /// Verify a proof of possession of the signing key of `vk`, following
+    /// `PopVerify` of the [IRTF draft v5][pop].
+    ///
+    /// [pop]: https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-popverify
+    pub fn verify_possession(
+        vk: &<Self as SignatureScheme>::VerificationKey,
+        proof: &BLSPopProof,
+    ) -> Result<(), PrimitivesError> {
+        match proof.0.verify(
+            true,
+            &vk.compress(),
+            CS_ID_BLS_POP.as_bytes(),
+            &[],
+            vk,
+            true,
+        ) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            e => Err(PrimitivesError::VerificationError(format!("{e:?}"))),
+        }
+    }
+
+    /// Aggregate verification keys into a single key that verifies
+    /// multisignatures on a common message. Every key must come with a valid
+    /// proof of possession, in the same order, otherwise an error is returned.
+    pub fn aggregate_public_keys(
+        vks: &[<Self as SignatureScheme>::VerificationKey],
+        proofs: &[BLSPopProof],
+    ) -> Result<<Self as SignatureScheme>::VerificationKey, PrimitivesError> {
+        if vks.is_empty() {
+            return Err(PrimitivesError::ParameterError(
+                "no verification keys to aggregate".to_string(),
+            ));
+        }
+        if vks.len() != proofs.len() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "vks.len = {}; proofs.len = {}",
+                vks.len(),
+                proofs.len(),
+            )));
+        }
+        for (vk, proof) in vks.iter().zip(proofs.iter()) {
+            Self::verify_possession(vk, proof)?;
+        }
+        let vks: Vec<&PublicKey> = vks.iter().map(|vk| &vk.0).collect();
+        let agg_vk = AggregatePublicKey::aggregate(&vks, false)?;
+        Ok(BLSVerKey(agg_vk.to_public_key()))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::signatures::tests::{failed_verification, sign_and_verify};
-    use ark_std::{fmt::Debug, vec};
+    use crate::{
+        hash_to_group::SWHashToGroup,
+        signatures::tests::{agg_sign_and_verify, failed_verification, sign_and_verify},
+    };
+    use ark_bls12_381::{g1::Config as G1Config, Fq, Fq2, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_std::{fmt::Debug, vec, vec::Vec};
 
     #[test]
     fn test_bls_sig() {
@@ -440,6 +610,119 @@ mod test {
         failed_verification::<BLSSignatureScheme>(message.as_ref(), message_bad.as_ref());
     }
 
+    #[test]
+    fn test_bls_sig_aggregation() {
+        let messages: [&[u8]; 3] = [b"message 1", b"message 2", b"message 3"];
+        agg_sign_and_verify::<BLSSignatureScheme>(&messages, b"wrong message");
+
+        // The same message can only be used through the multisignature path.
+        let mut rng = jf_utils::test_rng();
+        let keys: Vec<_> = (0..3)
+            .map(|_| BLSSignatureScheme::key_gen(&(), &mut rng).unwrap())
+            .collect();
+        let msg = b"the same message";
+        let sigs: Vec<_> = keys
+            .iter()
+            .map(|(sk, _)| BLSSignatureScheme::sign(&(), sk, msg, &mut rng).unwrap())
+            .collect();
+        let vks: Vec<_> = keys.iter().map(|(_, vk)| *vk).collect();
+        let agg_sig = BLSSignatureScheme::aggregate(&(), &vks, &sigs).unwrap();
+        assert!(BLSSignatureScheme::multi_sig_verify(&(), &vks, msg, &agg_sig).is_ok());
+        assert!(BLSSignatureScheme::aggregate_verify(&(), &vks, &[msg; 3], &agg_sig).is_err());
+        assert!(BLSSignatureScheme::aggregate(&(), &vks, &[]).is_err());
+    }
+
+    fn to_g2_affine(vk: &BLSVerKey) -> G2Affine {
+        let bytes = vk.serialize();
+        let fq = |i: usize| Fq::from_be_bytes_mod_order(&bytes[48 * i..48 * (i + 1)]);
+        G2Affine::new(Fq2::new(fq(1), fq(0)), Fq2::new(fq(3), fq(2)))
+    }
+
+    fn from_g2_affine(p: G2Affine) -> BLSVerKey {
+        let bytes: Vec<u8> = [p.x.c1, p.x.c0, p.y.c1, p.y.c0]
+            .iter()
+            .flat_map(|c| c.into_bigint().to_bytes_be())
+            .collect();
+        BLSVerKey(PublicKey::deserialize(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_proof_of_possession() {
+        let mut rng = jf_utils::test_rng();
+        let (sk, vk) = BLSSignatureScheme::key_gen(&(), &mut rng).unwrap();
+        let proof = BLSSignatureScheme::prove_possession(&sk);
+        assert!(BLSSignatureScheme::verify_possession(&vk, &proof).is_ok());
+
+        // A proof of possession is not a signature on the key, and vice versa.
+        let sig = BLSSignatureScheme::sign(&(), &sk, vk.compress(), &mut rng).unwrap();
+        assert!(
+            BLSSignatureScheme::verify(&(), &vk, vk.compress(), &BLSSignature(proof.0)).is_err()
+        );
+        assert!(BLSSignatureScheme::verify_possession(&vk, &BLSPopProof(sig.0)).is_err());
+
+        let (_, other_vk) = BLSSignatureScheme::key_gen(&(), &mut rng).unwrap();
+        assert!(BLSSignatureScheme::verify_possession(&other_vk, &proof).is_err());
+    }
+
+    #[test]
+    fn test_rogue_key_rejected() {
+        let mut rng = jf_utils::test_rng();
+        let msg = b"transfer everything to the attacker";
+        let (victim_sk, victim_vk) = BLSSignatureScheme::key_gen(&(), &mut rng).unwrap();
+        let victim_proof = BLSSignatureScheme::prove_possession(&victim_sk);
+
+        // The attacker publishes `g2^a / victim_vk`, so that the aggregate key
+        // is `g2^a` and the attacker alone can sign for both keys.
+        let (attacker_sk, attacker_vk) = BLSSignatureScheme::key_gen(&(), &mut rng).unwrap();
+        let rogue_vk = from_g2_affine(
+            (to_g2_affine(&attacker_vk).into_group() - to_g2_affine(&victim_vk)).into_affine(),
+        );
+        let forged_sig = BLSSignatureScheme::sign(&(), &attacker_sk, msg, &mut rng).unwrap();
+        let vks = [victim_vk, rogue_vk];
+        assert!(BLSSignatureScheme::multi_sig_verify(&(), &vks, msg, &forged_sig).is_ok());
+
+        // The attacker cannot prove possession of the rogue key.
+        let rogue_proof = BLSSignatureScheme::prove_possession(&attacker_sk);
+        assert!(BLSSignatureScheme::verify_possession(&rogue_vk, &rogue_proof).is_err());
+        assert!(
+            BLSSignatureScheme::aggregate_public_keys(&vks, &[victim_proof, rogue_proof]).is_err()
+        );
+
+        // Honest keys with valid proofs aggregate to a key verifying the
+        // multisignature.
+        let (sk, vk) = BLSSignatureScheme::key_gen(&(), &mut rng).unwrap();
+        let proof = BLSSignatureScheme::prove_possession(&sk);
+        let agg_vk =
+            BLSSignatureScheme::aggregate_public_keys(&[victim_vk, vk], &[victim_proof, proof])
+                .unwrap();
+        let sigs = [
+            BLSSignatureScheme::sign(&(), &victim_sk, msg, &mut rng).unwrap(),
+            BLSSignatureScheme::sign(&(), &sk, msg, &mut rng).unwrap(),
+        ];
+        let multi_sig = BLSSignatureScheme::aggregate(&(), &[], &sigs).unwrap();
+        assert!(BLSSignatureScheme::verify(&(), &agg_vk, msg, &multi_sig).is_ok());
+        assert!(BLSSignatureScheme::aggregate_public_keys(&[victim_vk], &[]).is_err());
+    }
+
+    #[test]
+    fn test_hash_to_group_compatibility() {
+        let mut rng = jf_utils::test_rng();
+        let (sk, _) = BLSSignatureScheme::key_gen(&(), &mut rng).unwrap();
+        let msg = "The quick brown fox jumps over the lazy dog";
+        let sig = BLSSignatureScheme::sign(&(), &sk, msg, &mut rng).unwrap();
+
+        // A signature is `sk * H(msg)`, with `H` the hash to G1 of this crate.
+        let sk_scalar = Fr::from_be_bytes_mod_order(&sk.to_bytes());
+        let sig_bytes = sig.serialize();
+        let sig_point = G1Affine::new(
+            Fq::from_be_bytes_mod_order(&sig_bytes[..48]),
+            Fq::from_be_bytes_mod_order(&sig_bytes[48..]),
+        );
+        let msg_point =
+            G1Config::hash_to_group::<&[u8]>(msg.as_ref(), CS_ID_BLS_MIN_SIG.as_ref()).unwrap();
+        assert_eq!((msg_point * sk_scalar).into_affine(), sig_point);
+    }
+
     #[test]
     fn test_canonical_serde() {
         let mut rng = jf_utils::test_rng();
@@ -28,13 +28,17 @@
 //!   [EIP-196][eip196], [EIP197][eip197]
 //! * `H` is implemented using the "hash-and-pray" approach. See function
 //!   [`hash_to_curve`]
+//! * the signing key can be split into shares with [`SignKey::split`], so
+//!   that any `t` of them sign together, see [`Signature::combine_shares`]
 //!
 //! [bls]: https://hovav.net/ucsd/dist/sigs.pdf
 //! [bn254]: https://eprint.iacr.org/2005/133.pdf
 //! [eip196]: https://eips.ethereum.org/EIPS/eip-196
 //! [eip197]: https://eips.ethereum.org/EIPS/eip-197
 
-use super::{AggregateableSignatureSchemes, SignatureScheme};
+use super::{
+    from_compressed_bytes, to_compressed_bytes, AggregateableSignatureSchemes, SignatureScheme,
+};
 use crate::{constants::CS_ID_BLS_BN254, errors::PrimitivesError};
 use ark_bn254::{
     Bn254, Fq as BaseField, Fr as ScalarField, G1Affine, G1Projective, G2Affine, G2Projective,
@@ -55,7 +59,7 @@ use ark_std::{
     rand::{CryptoRng, Rng, RngCore},
     string::ToString,
     vec::Vec,
-    One, UniformRand,
+    One, UniformRand, Zero,
 };
 use digest::DynDigest;
 use serde::{Deserialize, Serialize};
@@ -255,6 +259,18 @@ impl VerKey {
     pub fn to_affine(&self) -> G2Affine {
         self.0.into_affine()
     }
+
+    /// Compressed byte encoding of the verification key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_compressed_bytes(self)
+    }
+
+    /// Decode a verification key encoded by [`Self::to_bytes`]. Returns an
+    /// error if the point is not on the curve or not in the prime order
+    /// subgroup.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PrimitivesError> {
+        from_compressed_bytes(bytes)
+    }
 }
 
 // =====================================================
@@ -294,6 +310,151 @@ impl PartialEq for Signature {
     }
 }
 
+impl Signature {
+    /// Compressed byte encoding of the signature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_compressed_bytes(self)
+    }
+
+    /// Decode a signature encoded by [`Self::to_bytes`]. Returns an error if
+    /// the point is not on the curve.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PrimitivesError> {
+        from_compressed_bytes(bytes)
+    }
+}
+
+// =====================================================
+// Threshold signing
+// =====================================================
+
+/// Share of a signing key, produced by [`SignKey::split`]. It is the
+/// evaluation at `index` of a secret polynomial whose constant term is the
+/// signing key.
+#[derive(Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize, Debug)]
+pub struct SignKeyShare {
+    index: u64,
+    sk: SignKey,
+}
+
+/// Signature under a [`SignKeyShare`], see [`Signature::combine_shares`].
+#[derive(Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize, Debug)]
+pub struct SignatureShare {
+    index: u64,
+    sig: Signature,
+}
+
+impl SignKey {
+    /// Shamir secret sharing of the signing key: any `threshold` of the
+    /// `num_shares` returned shares can sign together with
+    /// [`Signature::combine_shares`], and fewer do not learn anything about
+    /// the key. The shares have indices `1..=num_shares`.
+    /// * `returns` - Err() if `threshold` is 0 or larger than `num_shares`
+    pub fn split<R: Rng>(
+        &self,
+        threshold: usize,
+        num_shares: usize,
+        prng: &mut R,
+    ) -> Result<Vec<SignKeyShare>, PrimitivesError> {
+        if threshold == 0 || threshold > num_shares {
+            return Err(ParameterError(format!(
+                "threshold {threshold} must be between 1 and the number of shares {num_shares}"
+            )));
+        }
+        let mut coeffs = Vec::with_capacity(threshold);
+        coeffs.push(self.0);
+        coeffs.extend((1..threshold).map(|_| ScalarField::rand(prng)));
+        let shares = (1..=num_shares as u64)
+            .map(|index| {
+                let x = ScalarField::from(index);
+                let y = coeffs
+                    .iter()
+                    .rev()
+                    .fold(ScalarField::zero(), |acc, coeff| acc * x + coeff);
+                SignKeyShare {
+                    index,
+                    sk: SignKey(y),
+                }
+            })
+            .collect();
+        coeffs.zeroize();
+        Ok(shares)
+    }
+}
+
+impl SignKeyShare {
+    /// Index of the share, i.e. the point at which the secret polynomial is
+    /// evaluated
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Verification key of this share, to check its signature shares
+    pub fn ver_key(&self) -> VerKey {
+        VerKey::from(&self.sk)
+    }
+
+    /// Sign `msg` with this share, see [`KeyPair::sign`]
+    pub fn sign_share<B: AsRef<[u8]>>(&self, msg: &[u8], csid: B) -> SignatureShare {
+        let sig = KeyPair::generate_with_sign_key(self.sk.0).sign(msg, csid);
+        SignatureShare {
+            index: self.index,
+            sig,
+        }
+    }
+}
+
+impl SignatureShare {
+    /// Index of the key share which produced this signature share
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// The signature under the key share alone, which verifies under
+    /// [`SignKeyShare::ver_key`]
+    pub fn signature(&self) -> &Signature {
+        &self.sig
+    }
+}
+
+impl Signature {
+    /// Combine signature shares into a signature under the key that was
+    /// split, by Lagrange interpolation at 0 in the exponent. With at least
+    /// `threshold` valid shares of the same message, the result verifies
+    /// under the verification key of the split [`SignKey`]. With fewer, it
+    /// does not.
+    /// * `returns` - Err() if there is no share, or if an index is 0 or
+    ///   repeated
+    pub fn combine_shares(shares: &[SignatureShare]) -> Result<Self, PrimitivesError> {
+        if shares.is_empty() {
+            return Err(ParameterError("no signature shares to combine".to_string()));
+        }
+        let xs = shares
+            .iter()
+            .map(|share| ScalarField::from(share.index))
+            .collect::<Vec<_>>();
+        let mut sigma = G1Projective::zero();
+        for (i, share) in shares.iter().enumerate() {
+            if xs[i].is_zero() {
+                return Err(ParameterError("signature share of index 0".to_string()));
+            }
+            // Lagrange coefficient of `xs[i]` at 0.
+            let mut num = ScalarField::one();
+            let mut denom = ScalarField::one();
+            for (j, x_j) in xs.iter().enumerate() {
+                if j != i {
+                    num *= x_j;
+                    denom *= *x_j - xs[i];
+                }
+            }
+            let denom_inv = denom.inverse().ok_or_else(|| {
+                ParameterError(format!("repeated signature share index {}", share.index))
+            })?;
+            sigma += share.sig.sigma * (num * denom_inv);
+        }
+        Ok(Signature { sigma })
+    }
+}
+
 // =====================================================
 // end of definitions
 // =====================================================
@@ -437,6 +598,7 @@ mod tests {
             tests::{agg_sign_and_verify, failed_verification, sign_and_verify},
         },
     };
+    use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
     use ark_ff::vec;
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_std::vec::Vec;
@@ -532,4 +694,104 @@ mod tests {
         let de: Signature = Signature::deserialize_compressed(&ser_bytes[..]).unwrap();
         assert_eq!(de, sig);
     }
+
+    #[test]
+    fn test_threshold_signature() {
+        let mut rng = jf_utils::test_rng();
+        let key_pair = KeyPair::generate(&mut rng);
+        let vk = key_pair.ver_key();
+        let msg = [1u8, 2, 3];
+        let (threshold, num_shares) = (3, 5);
+        let key_shares = key_pair
+            .sign_key_ref()
+            .split(threshold, num_shares, &mut rng)
+            .unwrap();
+        assert_eq!(key_shares.len(), num_shares);
+        let sig_shares = key_shares
+            .iter()
+            .map(|share| {
+                let sig_share = share.sign_share(&msg, CS_ID_BLS_BN254);
+                assert!(share
+                    .ver_key()
+                    .verify(&msg, sig_share.signature(), CS_ID_BLS_BN254)
+                    .is_ok());
+                sig_share
+            })
+            .collect::<Vec<_>>();
+
+        // Any `threshold` shares, in any order, give the signature of the key.
+        let expected = key_pair.sign(&msg, CS_ID_BLS_BN254);
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let shares = subset.map(|i| sig_shares[i].clone());
+            let sig = Signature::combine_shares(&shares).unwrap();
+            assert!(vk.verify(&msg, &sig, CS_ID_BLS_BN254).is_ok());
+            assert_eq!(sig, expected);
+        }
+        let sig = Signature::combine_shares(&sig_shares).unwrap();
+        assert!(vk.verify(&msg, &sig, CS_ID_BLS_BN254).is_ok());
+
+        // Fewer shares do not.
+        for subset in [&sig_shares[..2], &sig_shares[3..], &sig_shares[..1]] {
+            let sig = Signature::combine_shares(subset).unwrap();
+            assert!(vk.verify(&msg, &sig, CS_ID_BLS_BN254).is_err());
+        }
+
+        // Shares of another message, or repeated ones.
+        let mut mixed = sig_shares[..2].to_vec();
+        mixed.push(key_shares[2].sign_share(&[4u8], CS_ID_BLS_BN254));
+        let sig = Signature::combine_shares(&mixed).unwrap();
+        assert!(vk.verify(&msg, &sig, CS_ID_BLS_BN254).is_err());
+        let repeated = [0, 1, 1].map(|i| sig_shares[i].clone());
+        assert!(Signature::combine_shares(&repeated).is_err());
+        assert!(Signature::combine_shares(&[]).is_err());
+
+        // Invalid thresholds
+        let sk = key_pair.sign_key_ref();
+        assert!(sk.split(0, 5, &mut rng).is_err());
+        assert!(sk.split(6, 5, &mut rng).is_err());
+        // A threshold of 1 gives copies of the key.
+        for share in sk.split(1, 3, &mut rng).unwrap() {
+            assert_eq!(share.ver_key(), vk);
+        }
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let mut rng = jf_utils::test_rng();
+        let keypair = KeyPair::generate(&mut rng);
+        let vk = keypair.ver_key();
+        let sig = keypair.sign(&[87u8], CS_ID_BLS_BN254);
+        assert_eq!(VerKey::from_bytes(&vk.to_bytes()).unwrap(), vk);
+        assert_eq!(Signature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+
+        // truncated and oversized encodings
+        let bytes = sig.to_bytes();
+        assert!(Signature::from_bytes(&bytes[1..]).is_err());
+        assert!(Signature::from_bytes(&[&bytes[..], &[0u8]].concat()).is_err());
+
+        // a point on G2's curve but not in the prime order subgroup
+        let mut x = Fq2::from(1u64);
+        let bad_point = loop {
+            if let Some(p) = G2Affine::get_point_from_x_unchecked(x, false) {
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    break p;
+                }
+            }
+            x += Fq2::from(1u64);
+        };
+        let bad_vk = VerKey(bad_point.into());
+        assert!(VerKey::from_bytes(&bad_vk.to_bytes()).is_err());
+
+        // an encoding that is not on the curve
+        let mut x = Fq::from(1u64);
+        let off_curve = loop {
+            if G1Affine::get_point_from_x_unchecked(x, false).is_none() {
+                let mut bytes = vec![];
+                x.serialize_compressed(&mut bytes).unwrap();
+                break bytes;
+            }
+            x += Fq::from(1u64);
+        };
+        assert!(Signature::from_bytes(&off_curve).is_err());
+    }
 }
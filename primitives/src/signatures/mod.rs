@@ -1,7 +1,11 @@
 //! Module for signature primitives.
 
 use crate::errors::PrimitivesError;
-use ark_std::rand::{CryptoRng, RngCore};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    rand::{CryptoRng, RngCore},
+    vec::Vec,
+};
 
 pub mod bls_over_bls12381;
 pub mod bls_over_bn254;
@@ -127,6 +131,27 @@ pub trait AggregateableSignatureSchemes:
     ) -> Result<(), PrimitivesError>;
 }
 
+/// Compressed canonical serialization of `data`.
+pub(crate) fn to_compressed_bytes<T: CanonicalSerialize>(data: &T) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.compressed_size());
+    data.serialize_compressed(&mut bytes).unwrap(); // safe unwrap: writing to a Vec
+    bytes
+}
+
+/// Decode a `T` encoded by [`to_compressed_bytes`], checking that every curve
+/// point is on the curve and in the prime order subgroup. Trailing bytes are
+/// rejected.
+pub(crate) fn from_compressed_bytes<T: CanonicalDeserialize>(
+    bytes: &[u8],
+) -> Result<T, PrimitivesError> {
+    let mut reader = bytes;
+    let data = T::deserialize_compressed(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(SerializationError::InvalidData.into());
+    }
+    Ok(data)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,7 +7,7 @@
 //! This module implements the Schnorr signature over the various Edwards
 //! curves.
 
-use super::SignatureScheme;
+use super::{from_compressed_bytes, to_compressed_bytes, SignatureScheme};
 use crate::{
     constants::CS_ID_SCHNORR,
     crhf::{VariableLengthRescueCRHF, CRHF},
@@ -17,9 +17,9 @@ use crate::{
 };
 use ark_ec::{
     twisted_edwards::{Affine, Projective, TECurveConfig as Config},
-    AffineRepr, CurveConfig, CurveGroup, Group,
+    AffineRepr, CurveConfig, CurveGroup, Group, VariableBaseMSM,
 };
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, Zero};
 use ark_serialize::*;
 use ark_std::{
     hash::{Hash, Hasher},
@@ -100,6 +100,56 @@ where
     }
 }
 
+impl<F, P> SchnorrSignatureScheme<P>
+where
+    F: RescueParameter,
+    P: Config<BaseField = F> + Clone,
+{
+    /// Verify a batch of signatures, each item being a verification key, a
+    /// message and a signature of the message under the key.
+    ///
+    /// The verification equations are combined with random scalars drawn from
+    /// `prng` and checked with a single multi-scalar multiplication. The
+    /// scalars must be unpredictable to the signers, otherwise invalid
+    /// signatures could be crafted to cancel out, so `prng` must be a
+    /// cryptographically secure RNG. Returns `Ok(false)` if any signature is
+    /// invalid.
+    pub fn batch_verify<R: CryptoRng + RngCore, M: AsRef<[F]>>(
+        items: &[(VerKey<P>, M, Signature<P>)],
+        prng: &mut R,
+    ) -> Result<bool, PrimitivesError> {
+        let mut bases = vec![Projective::<P>::generator()];
+        let mut scalars = vec![P::ScalarField::zero()];
+        for (vk, msg, sig) in items {
+            // Unlike the individual check, the combined equation could be
+            // satisfied by components of small order, so both points must be
+            // in the prime order subgroup.
+            if !vk
+                .0
+                .into_affine()
+                .is_in_correct_subgroup_assuming_on_curve()
+                || vk.0.is_zero()
+                || !sig
+                    .R
+                    .into_affine()
+                    .is_in_correct_subgroup_assuming_on_curve()
+            {
+                return Ok(false);
+            }
+            // s * G = R + c * VK
+            let z = P::ScalarField::rand(prng);
+            let c = vk.challenge(&sig.R, msg.as_ref(), Self::CS_ID);
+            scalars[0] += z * sig.s;
+            bases.push(sig.R);
+            scalars.push(-z);
+            bases.push(vk.0);
+            scalars.push(-z * c);
+        }
+        let bases = Projective::<P>::normalize_batch(&bases);
+        Ok(Projective::<P>::msm_unchecked(&bases, &scalars).is_zero())
+    }
+}
+
 // =====================================================
 // Signing key
 // =====================================================
@@ -117,6 +167,16 @@ impl<F: PrimeField> Drop for SignKey<F> {
 }
 
 impl<F: PrimeField> SignKey<F> {
+    /// Compressed byte encoding of the signing key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_compressed_bytes(self)
+    }
+
+    /// Decode a signing key encoded by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PrimitivesError> {
+        from_compressed_bytes(bytes)
+    }
+
     // returns the randomized key
     fn randomize_with(&self, randomizer: &F) -> Self {
         Self(self.0 + randomizer)
@@ -185,6 +245,18 @@ impl<P: Config> VerKey<P> {
     pub fn to_affine(&self) -> Affine<P> {
         self.0.into_affine()
     }
+
+    /// Compressed byte encoding of the verification key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_compressed_bytes(self)
+    }
+
+    /// Decode a verification key encoded by [`Self::to_bytes`]. Returns an
+    /// error if the point is not on the curve or not in the prime order
+    /// subgroup.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PrimitivesError> {
+        from_compressed_bytes(bytes)
+    }
 }
 
 // =====================================================
@@ -249,6 +321,19 @@ where
         self.s == other.s && self.R.into_affine() == other.R.into_affine()
     }
 }
+
+impl<P: Config> Signature<P> {
+    /// Compressed byte encoding of the signature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_compressed_bytes(self)
+    }
+
+    /// Decode a signature encoded by [`Self::to_bytes`]. Returns an error if
+    /// `R` is not on the curve or not in the prime order subgroup.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PrimitivesError> {
+        from_compressed_bytes(bytes)
+    }
+}
 // =====================================================
 // end of definitions
 // =====================================================
@@ -466,17 +551,104 @@ mod tests {
         test_signature!(Param381b);
     }
 
+    macro_rules! test_deterministic_signature {
+        ($curve_param:tt) => {
+            let mut rng = jf_utils::test_rng();
+            let keypair: KeyPair<$curve_param> = KeyPair::generate(&mut rng);
+            let msg = vec![<$curve_param as CurveConfig>::BaseField::rand(&mut rng); 3];
+
+            // the nonce is derived from the signing key and the message
+            let sig = keypair.sign(&msg, CS_ID_SCHNORR);
+            assert_eq!(sig, keypair.sign(&msg, CS_ID_SCHNORR));
+            assert_ne!(sig.R, keypair.sign(&msg[1..], CS_ID_SCHNORR).R);
+
+            // tampered signatures are rejected
+            let vk = keypair.ver_key_ref();
+            let mut bad_sig = sig.clone();
+            bad_sig.s += <$curve_param as CurveConfig>::ScalarField::from(1u64);
+            assert!(vk.verify(&msg, &bad_sig, CS_ID_SCHNORR).is_err());
+            let mut bad_sig = sig.clone();
+            bad_sig.R += Projective::<$curve_param>::generator();
+            assert!(vk.verify(&msg, &bad_sig, CS_ID_SCHNORR).is_err());
+            assert!(vk.verify(&msg, &sig, "another cs id").is_err());
+        };
+    }
+
+    macro_rules! test_batch_verify {
+        ($curve_param:tt) => {
+            let mut rng = jf_utils::test_rng();
+            let mut items = vec![];
+            for i in 0..10u64 {
+                let keypair: KeyPair<$curve_param> = KeyPair::generate(&mut rng);
+                let msg = vec![<$curve_param as CurveConfig>::BaseField::from(i); 2];
+                let sig = keypair.sign(&msg, CS_ID_SCHNORR);
+                items.push((keypair.ver_key(), msg, sig));
+            }
+            assert!(
+                SchnorrSignatureScheme::<$curve_param>::batch_verify(&items, &mut rng).unwrap()
+            );
+            assert!(
+                SchnorrSignatureScheme::<$curve_param>::batch_verify(&items[..1], &mut rng)
+                    .unwrap()
+            );
+            assert!(SchnorrSignatureScheme::<$curve_param>::batch_verify(
+                &Vec::<(_, Vec<_>, _)>::new(),
+                &mut rng
+            )
+            .unwrap());
+
+            // one invalid signature
+            let mut bad_items = items.clone();
+            bad_items[3].2.s += <$curve_param as CurveConfig>::ScalarField::from(1u64);
+            assert!(
+                !SchnorrSignatureScheme::<$curve_param>::batch_verify(&bad_items, &mut rng)
+                    .unwrap()
+            );
+            // one wrong message
+            let mut bad_items = items.clone();
+            bad_items[7].1[0] += <$curve_param as CurveConfig>::BaseField::from(1u64);
+            assert!(
+                !SchnorrSignatureScheme::<$curve_param>::batch_verify(&bad_items, &mut rng)
+                    .unwrap()
+            );
+            // swapped signatures
+            let mut bad_items = items.clone();
+            bad_items[0].2 = items[1].2.clone();
+            bad_items[1].2 = items[0].2.clone();
+            assert!(
+                !SchnorrSignatureScheme::<$curve_param>::batch_verify(&bad_items, &mut rng)
+                    .unwrap()
+            );
+        };
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        test_batch_verify!(Param254);
+        test_batch_verify!(Param377);
+        test_batch_verify!(Param381);
+        test_batch_verify!(Param381b);
+    }
+
+    #[test]
+    fn test_deterministic_signature() {
+        test_deterministic_signature!(Param254);
+        test_deterministic_signature!(Param377);
+        test_deterministic_signature!(Param381);
+        test_deterministic_signature!(Param381b);
+    }
+
     mod serde {
         use super::super::{KeyPair, SignKey, Signature, VerKey};
         use crate::constants::CS_ID_SCHNORR;
-        use ark_ec::twisted_edwards::Projective;
+        use ark_ec::twisted_edwards::{Affine, Projective};
         use ark_ed_on_bls12_377::{EdwardsConfig as Param377, Fq as FqEd377, Fr as FrEd377};
         use ark_ed_on_bls12_381::{EdwardsConfig as Param381, Fq as FqEd381, Fr as FrEd381};
         use ark_ed_on_bls12_381_bandersnatch::{
             EdwardsConfig as Param381b, Fq as FqEd381b, Fr as FrEd381b,
         };
         use ark_ed_on_bn254::{EdwardsConfig as Param254, Fq as FqEd254, Fr as FrEd254};
-        use ark_ff::Zero;
+        use ark_ff::{One, Zero};
         use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
         use ark_std::{vec, vec::Vec, UniformRand};
 
@@ -592,6 +764,61 @@ mod tests {
             };
         }
 
+        macro_rules! test_to_bytes {
+            ($curve_param:tt, $scalar_field:tt, $base_field:tt) => {
+                let mut rng = jf_utils::test_rng();
+                let keypair: KeyPair<$curve_param> = KeyPair::generate(&mut rng);
+                let vk = keypair.ver_key();
+                let sig = keypair.sign(&[$base_field::rand(&mut rng)], CS_ID_SCHNORR);
+
+                // round trips
+                assert_eq!(
+                    SignKey::from_bytes(&keypair.sk.to_bytes()).unwrap(),
+                    keypair.sk
+                );
+                assert_eq!(VerKey::from_bytes(&vk.to_bytes()).unwrap(), vk);
+                assert_eq!(Signature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+
+                // truncated and oversized encodings
+                let bytes = vk.to_bytes();
+                assert!(VerKey::<$curve_param>::from_bytes(&bytes[1..]).is_err());
+                assert!(
+                    VerKey::<$curve_param>::from_bytes(&[&bytes[..], &[0u8]].concat()).is_err()
+                );
+
+                // a point of order 2, on the curve but not in the prime order subgroup
+                let small_order: Projective<$curve_param> =
+                    Affine::new_unchecked($base_field::zero(), -$base_field::one()).into();
+                let bad_vk = VerKey(small_order);
+                assert!(VerKey::<$curve_param>::from_bytes(&bad_vk.to_bytes()).is_err());
+                let bad_sig = Signature {
+                    s: sig.s,
+                    R: small_order,
+                };
+                assert!(Signature::<$curve_param>::from_bytes(&bad_sig.to_bytes()).is_err());
+
+                // an encoding that is not on the curve
+                let mut y = $base_field::from(2u64);
+                let off_curve = loop {
+                    let mut bytes = vec![];
+                    y.serialize_compressed(&mut bytes).unwrap();
+                    if Affine::<$curve_param>::get_point_from_y_unchecked(y, false).is_none() {
+                        break bytes;
+                    }
+                    y += $base_field::one();
+                };
+                assert!(VerKey::<$curve_param>::from_bytes(&off_curve).is_err());
+            };
+        }
+
+        #[test]
+        fn test_to_bytes() {
+            test_to_bytes!(Param254, FrEd254, FqEd254);
+            test_to_bytes!(Param377, FrEd377, FqEd377);
+            test_to_bytes!(Param381, FrEd381, FqEd381);
+            test_to_bytes!(Param381b, FrEd381b, FqEd381b);
+        }
+
         #[test]
         fn test_serde() {
             test_serde!(Param254, FrEd254, FqEd254);
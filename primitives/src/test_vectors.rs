@@ -0,0 +1,181 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Loader of the known-answer test vectors in the `test_vectors` directory of
+//! the crate.
+//!
+//! The files follow the JSON format of the vectors of the RFC 9380 reference
+//! implementation, so that standard vectors can be dropped in as is: field
+//! elements are big-endian hex strings with a `0x` prefix, and elements of an
+//! extension field are the comma separated list of their coefficients.
+//! Unknown fields are ignored.
+
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ff::{BigInteger, Field, PrimeField};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{fs, path::Path, string::String, vec::Vec};
+
+/// Parse the vector file `file_name` of the `test_vectors` directory.
+pub(crate) fn load<T: DeserializeOwned>(file_name: &str) -> T {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("test_vectors")
+        .join(file_name);
+    let json = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("cannot parse {}: {}", path.display(), e))
+}
+
+/// Parse a hex string, with or without the `0x` prefix.
+pub(crate) fn parse_bytes(hex: &str) -> Vec<u8> {
+    let hex = hex.trim().trim_start_matches("0x");
+    assert!(hex.len() % 2 == 0, "odd length hex string {}", hex);
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .unwrap_or_else(|_| panic!("invalid hex string {}", hex))
+        })
+        .collect()
+}
+
+/// Parse a field element, rejecting non canonical encodings.
+pub(crate) fn parse_field<F: Field>(s: &str) -> F {
+    let coeffs = s
+        .split(',')
+        .map(|coeff| {
+            let bytes = parse_bytes(coeff);
+            let elem = F::BasePrimeField::from_be_bytes_mod_order(&bytes);
+            let canonical = elem.into_bigint().to_bytes_be();
+            assert_eq!(
+                strip_leading_zeros(&canonical),
+                strip_leading_zeros(&bytes),
+                "{} is not a canonical field element",
+                coeff
+            );
+            elem
+        })
+        .collect::<Vec<_>>();
+    F::from_base_prime_field_elems(&coeffs)
+        .unwrap_or_else(|| panic!("wrong number of coefficients in {}", s))
+}
+
+fn parse_fields<F: Field>(elems: &[String]) -> Vec<F> {
+    elems.iter().map(|s| parse_field(s)).collect()
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Vectors of a hash-to-curve suite of RFC 9380, e.g.
+/// `BLS12381G1_XMD_SHA-256_SSWU_RO_.json`.
+#[derive(Deserialize)]
+pub(crate) struct HashToCurveSuite {
+    /// Domain separation tag
+    pub(crate) dst: String,
+    /// Whether the suite is `hash_to_curve`, or `encode_to_curve` otherwise
+    #[serde(rename = "randomOracle")]
+    pub(crate) random_oracle: bool,
+    pub(crate) vectors: Vec<HashToCurveVector>,
+}
+
+/// A message and its hash, with the intermediate values when given.
+#[derive(Deserialize)]
+pub(crate) struct HashToCurveVector {
+    pub(crate) msg: String,
+    #[serde(rename = "P")]
+    p: PointVector,
+    #[serde(default)]
+    u: Vec<String>,
+    #[serde(rename = "Q0", default)]
+    q0: Option<PointVector>,
+    #[serde(rename = "Q1", default)]
+    q1: Option<PointVector>,
+}
+
+#[derive(Deserialize)]
+struct PointVector {
+    x: String,
+    y: String,
+}
+
+impl PointVector {
+    fn to_affine<P: SWCurveConfig>(&self) -> Affine<P> {
+        Affine::new_unchecked(parse_field(&self.x), parse_field(&self.y))
+    }
+}
+
+impl HashToCurveVector {
+    /// The output point `P`.
+    pub(crate) fn output<P: SWCurveConfig>(&self) -> Affine<P> {
+        self.p.to_affine()
+    }
+
+    /// The field elements `u` hashed from the message.
+    pub(crate) fn field_elems<F: Field>(&self) -> Vec<F> {
+        parse_fields(&self.u)
+    }
+
+    /// The images `Q0` and `Q1` of the field elements by `map_to_curve`, if
+    /// given.
+    pub(crate) fn mapped_points<P: SWCurveConfig>(&self) -> Option<(Affine<P>, Affine<P>)> {
+        Some((self.q0.as_ref()?.to_affine(), self.q1.as_ref()?.to_affine()))
+    }
+}
+
+/// Vectors of the Rescue permutations over a field, e.g.
+/// `rescue_ed_on_bn254.json`.
+#[derive(Deserialize)]
+pub(crate) struct RescueVectors {
+    pub(crate) vectors: Vec<RescuePermutationVector>,
+}
+
+/// Input and output states of a permutation, either `Rescue` or
+/// `Rescue-Prime`.
+#[derive(Deserialize)]
+pub(crate) struct RescuePermutationVector {
+    pub(crate) permutation: String,
+    input: Vec<String>,
+    output: Vec<String>,
+}
+
+impl RescuePermutationVector {
+    pub(crate) fn input<F: Field>(&self) -> Vec<F> {
+        parse_fields(&self.input)
+    }
+
+    pub(crate) fn output<F: Field>(&self) -> Vec<F> {
+        parse_fields(&self.output)
+    }
+}
+
+/// Roots of merkle trees built from a list of field elements, e.g.
+/// `sha3_merkle_tree_ed_on_bn254.json`.
+#[derive(Deserialize)]
+pub(crate) struct MerkleRootVectors {
+    pub(crate) arity: usize,
+    pub(crate) vectors: Vec<MerkleRootVector>,
+}
+
+/// Elements of a merkle tree of a given height, and its root.
+#[derive(Deserialize)]
+pub(crate) struct MerkleRootVector {
+    pub(crate) height: usize,
+    elems: Vec<String>,
+    root: String,
+}
+
+impl MerkleRootVector {
+    pub(crate) fn elems<F: Field>(&self) -> Vec<F> {
+        parse_fields(&self.elems)
+    }
+
+    /// The root, for trees with a byte array as node value.
+    pub(crate) fn root_bytes(&self) -> Vec<u8> {
+        parse_bytes(&self.root)
+    }
+}
@@ -1 +1,522 @@
-//! Place holder for ECVRF
+//! Elliptic curve VRF over twisted Edwards curves
+//!
+//! Adapted from the `ECVRF-EDWARDS25519-SHA512-ELL2` ciphersuite of
+//! [RFC 9381](https://www.rfc-editor.org/rfc/rfc9381.html) to the embedded
+//! curves that implement [`TEHashToGroup`]:
+//! * The input is mapped to `H = hash_to_curve(PK || alpha)` with
+//!   [`TEHashToGroup::hash_to_group`] and [`CS_ID_ECVRF`] as the domain
+//!   separation tag.
+//! * The proof is `(Gamma, c, s)` with `Gamma = sk * H`, a 128-bit challenge
+//!   `c` and `s = k + c * sk`, where the nonce `k` is derived from `sk` and `H`
+//!   with SHA-512, so proving is deterministic.
+//! * The output is `beta = SHA-512(suite || 0x03 || cofactor * Gamma || 0x00)`.
+//!
+//! Points are encoded with the compressed arkworks serialization and scalars
+//! are decoded from SHA-512 outputs in little endian, as in RFC 8032.
+//!
+//! Proofs can also be produced in the longer `(Gamma, U, V, s)` form mentioned
+//! by RFC 9381, which allows [`ECVRFScheme::batch_verify`] to check many of
+//! them with a single multi-scalar multiplication.
+
+use super::Vrf;
+use crate::{
+    constants::CS_ID_ECVRF, errors::PrimitivesError, hash_to_group::TEHashToGroup,
+    signatures::to_compressed_bytes,
+};
+use ark_ec::{
+    twisted_edwards::{Affine, Projective, TECurveConfig},
+    AffineRepr, CurveGroup, Group, VariableBaseMSM,
+};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_serialize::*;
+use ark_std::{
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    vec,
+    vec::Vec,
+};
+use digest::Digest;
+use jf_utils::canonical;
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+/// Length in bytes of the challenge `c`.
+const CHALLENGE_LEN: usize = 16;
+
+/// Domain separators of the hashes of RFC 9381.
+const CHALLENGE_GENERATION_DOMAIN_SEPARATOR_FRONT: u8 = 0x02;
+const PROOF_TO_HASH_DOMAIN_SEPARATOR_FRONT: u8 = 0x03;
+const DOMAIN_SEPARATOR_BACK: u8 = 0x00;
+
+/// ECVRF scheme over the twisted Edwards curve `P`.
+///
+/// The [`Vrf::Proof`] of this scheme is the compact [`ECVRFProof`], which
+/// cannot be batch verified. Proofs meant for [`Self::batch_verify`] must be
+/// produced with [`Self::prove_batchable`] instead of [`Vrf::prove`], and
+/// converted with [`ECVRFBatchableProof::compact`] where an [`ECVRFProof`] is
+/// expected.
+#[derive(Derivative)]
+#[derivative(
+    Debug(bound = "P: TECurveConfig"),
+    Default(bound = "P: TECurveConfig"),
+    Clone(bound = "P: TECurveConfig")
+)]
+pub struct ECVRFScheme<P> {
+    curve_param: PhantomData<P>,
+}
+
+impl<P: TEHashToGroup> ECVRFScheme<P> {
+    /// Creates a new ECVRF instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the VRF proof associated with a VRF secret key, in the
+    /// batchable form verified by [`Self::batch_verify`].
+    pub fn prove_batchable(
+        &self,
+        secret_key: &ECVRFSecretKey<P>,
+        input: &[u8],
+    ) -> Result<ECVRFBatchableProof<P>, PrimitivesError> {
+        let (proof, u, v) = prove_internal(secret_key, input)?;
+        Ok(ECVRFBatchableProof {
+            gamma: proof.gamma,
+            u,
+            v,
+            s: proof.s,
+        })
+    }
+
+    /// Verifies a batch of VRF proofs, each under its own public key and
+    /// input.
+    ///
+    /// The verification equations are combined with random scalars drawn from
+    /// `prng` and checked with a single multi-scalar multiplication. The
+    /// scalars must be unpredictable to the provers, otherwise invalid proofs
+    /// could be crafted to cancel out, so `prng` must be a cryptographically
+    /// secure RNG. Returns `Ok(false)` if any proof is invalid.
+    ///
+    /// Only proofs from [`Self::prove_batchable`] can be checked here: the
+    /// [`ECVRFProof`]s returned by [`Vrf::prove`] lack the commitments `U` and
+    /// `V`, and must be verified one by one with [`Vrf::verify`].
+    pub fn batch_verify<R: CryptoRng + RngCore>(
+        items: &[(ECVRFPublicKey<P>, Vec<u8>, ECVRFBatchableProof<P>)],
+        prng: &mut R,
+    ) -> Result<bool, PrimitivesError> {
+        let mut bases = vec![Affine::<P>::generator()];
+        let mut scalars = vec![P::ScalarField::ZERO];
+        for (pk, alpha, proof) in items {
+            // Unlike the individual check, the combined equation could be
+            // satisfied by components of small order, so all the points must be
+            // in the prime order subgroup.
+            if !is_valid_point(&pk.0)
+                || pk.0.is_zero()
+                || !is_valid_point(&proof.gamma)
+                || !is_valid_point(&proof.u)
+                || !is_valid_point(&proof.v)
+            {
+                return Ok(false);
+            }
+            let h = encode_to_curve(pk, alpha)?;
+            let c = challenge_generation(&[pk.0, h, proof.gamma, proof.u, proof.v]);
+            // s * G = U + c * PK and s * H = V + c * Gamma
+            let z = P::ScalarField::rand(prng);
+            let w = P::ScalarField::rand(prng);
+            scalars[0] += z * proof.s;
+            bases.extend([proof.u, pk.0, h, proof.v, proof.gamma]);
+            scalars.extend([-z, -z * c, w * proof.s, -w, -w * c]);
+        }
+        Ok(Projective::<P>::msm_unchecked(&bases, &scalars)
+            .into_affine()
+            .is_zero())
+    }
+}
+
+/// ECVRF secret key.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative, Deserialize, Serialize)]
+#[derivative(
+    Debug(bound = "P: TECurveConfig"),
+    Clone(bound = "P: TECurveConfig"),
+    PartialEq(bound = "P: TECurveConfig"),
+    Eq(bound = "P: TECurveConfig")
+)]
+pub struct ECVRFSecretKey<P: TECurveConfig>(#[serde(with = "canonical")] pub(crate) P::ScalarField);
+
+impl<P: TECurveConfig> Zeroize for ECVRFSecretKey<P> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<P: TECurveConfig> Drop for ECVRFSecretKey<P> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// ECVRF public key.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative, Deserialize, Serialize)]
+#[derivative(
+    Debug(bound = "P: TECurveConfig"),
+    Clone(bound = "P: TECurveConfig"),
+    PartialEq(bound = "P: TECurveConfig"),
+    Eq(bound = "P: TECurveConfig")
+)]
+pub struct ECVRFPublicKey<P: TECurveConfig>(#[serde(with = "canonical")] pub(crate) Affine<P>);
+
+impl<P: TECurveConfig> From<&ECVRFSecretKey<P>> for ECVRFPublicKey<P> {
+    fn from(sk: &ECVRFSecretKey<P>) -> Self {
+        Self((Projective::<P>::generator() * sk.0).into_affine())
+    }
+}
+
+/// ECVRF proof `(Gamma, c, s)`.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative, Deserialize, Serialize)]
+#[derivative(
+    Debug(bound = "P: TECurveConfig"),
+    Clone(bound = "P: TECurveConfig"),
+    PartialEq(bound = "P: TECurveConfig"),
+    Eq(bound = "P: TECurveConfig")
+)]
+pub struct ECVRFProof<P: TECurveConfig> {
+    #[serde(with = "canonical")]
+    pub(crate) gamma: Affine<P>,
+    #[serde(with = "canonical")]
+    pub(crate) c: P::ScalarField,
+    #[serde(with = "canonical")]
+    pub(crate) s: P::ScalarField,
+}
+
+/// ECVRF proof in the batchable form `(Gamma, U, V, s)`, where `U` and `V` are
+/// the commitments from which the challenge `c` is derived.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative, Deserialize, Serialize)]
+#[derivative(
+    Debug(bound = "P: TECurveConfig"),
+    Clone(bound = "P: TECurveConfig"),
+    PartialEq(bound = "P: TECurveConfig"),
+    Eq(bound = "P: TECurveConfig")
+)]
+pub struct ECVRFBatchableProof<P: TECurveConfig> {
+    #[serde(with = "canonical")]
+    pub(crate) gamma: Affine<P>,
+    #[serde(with = "canonical")]
+    pub(crate) u: Affine<P>,
+    #[serde(with = "canonical")]
+    pub(crate) v: Affine<P>,
+    #[serde(with = "canonical")]
+    pub(crate) s: P::ScalarField,
+}
+
+impl<P: TEHashToGroup> ECVRFBatchableProof<P> {
+    /// The proof `(Gamma, c, s)` of the same evaluation, whose output is
+    /// given by [`Vrf::proof_to_hash`]. It is valid iff the batchable proof is
+    /// valid.
+    pub fn compact(
+        &self,
+        public_key: &ECVRFPublicKey<P>,
+        input: &[u8],
+    ) -> Result<ECVRFProof<P>, PrimitivesError> {
+        let h = encode_to_curve(public_key, input)?;
+        let c = challenge_generation(&[public_key.0, h, self.gamma, self.u, self.v]);
+        Ok(ECVRFProof {
+            gamma: self.gamma,
+            c,
+            s: self.s,
+        })
+    }
+}
+
+impl<P: TEHashToGroup> Vrf for ECVRFScheme<P> {
+    /// Public Parameter.
+    /// The default prime subgroup generator is used.
+    type PublicParameter = ();
+
+    /// VRF public key.
+    type PublicKey = ECVRFPublicKey<P>;
+
+    /// VRF secret key.
+    type SecretKey = ECVRFSecretKey<P>;
+
+    /// VRF signature, in the compact form which cannot be batch verified, see
+    /// [`ECVRFScheme::prove_batchable`].
+    type Proof = ECVRFProof<P>;
+
+    /// The input of VRF proof.
+    type Input = Vec<u8>;
+
+    /// The output of VRF evaluation.
+    type Output = Vec<u8>;
+
+    /// generate public parameters from RNG.
+    fn param_gen<R: CryptoRng + RngCore>(
+        &self,
+        _prng: Option<&mut R>,
+    ) -> Result<Self::PublicParameter, PrimitivesError> {
+        Ok(())
+    }
+
+    /// Creates a pair of VRF public and private keys.
+    fn key_gen<R: CryptoRng + RngCore>(
+        &self,
+        _pp: &Self::PublicParameter,
+        prng: &mut R,
+    ) -> Result<(Self::SecretKey, Self::PublicKey), PrimitivesError> {
+        let sk = ECVRFSecretKey(P::ScalarField::rand(prng));
+        let pk = ECVRFPublicKey::from(&sk);
+        Ok((sk, pk))
+    }
+
+    /// Creates the VRF proof associated with a VRF secret key. The proof is
+    /// deterministic and `prng` is not used.
+    ///
+    /// The proof cannot be passed to [`ECVRFScheme::batch_verify`], use
+    /// [`ECVRFScheme::prove_batchable`] to produce batchable proofs.
+    fn prove<R: CryptoRng + RngCore>(
+        &self,
+        _pp: &Self::PublicParameter,
+        secret_key: &Self::SecretKey,
+        input: &Self::Input,
+        _prng: &mut R,
+    ) -> Result<Self::Proof, PrimitivesError> {
+        Ok(prove_internal(secret_key, input)?.0)
+    }
+
+    /// Computes the VRF output associated with a VRF proof.
+    fn proof_to_hash(
+        &mut self,
+        _pp: &Self::PublicParameter,
+        proof: &Self::Proof,
+    ) -> Result<Self::Output, PrimitivesError> {
+        let gamma = proof.gamma.mul_by_cofactor();
+        Ok(Sha512::new()
+            .chain_update(CS_ID_ECVRF)
+            .chain_update([PROOF_TO_HASH_DOMAIN_SEPARATOR_FRONT])
+            .chain_update(to_compressed_bytes(&gamma))
+            .chain_update([DOMAIN_SEPARATOR_BACK])
+            .finalize()
+            .to_vec())
+    }
+
+    /// Verifies a VRF proof.
+    fn verify(
+        &mut self,
+        pp: &Self::PublicParameter,
+        proof: &Self::Proof,
+        public_key: &Self::PublicKey,
+        input: &Self::Input,
+    ) -> Result<(bool, Option<Self::Output>), PrimitivesError> {
+        // Keys and proofs may have been deserialized without validation, and a
+        // point of small order would make the output independent of the key.
+        if !is_valid_point(&public_key.0) || public_key.0.is_zero() || !is_valid_point(&proof.gamma)
+        {
+            return Ok((false, None));
+        }
+        let h = encode_to_curve(public_key, input)?;
+        let u = (Projective::<P>::generator() * proof.s - public_key.0 * proof.c).into_affine();
+        let v = (h * proof.s - proof.gamma * proof.c).into_affine();
+        if challenge_generation(&[public_key.0, h, proof.gamma, u, v]) == proof.c {
+            Ok((true, Some(self.proof_to_hash(pp, proof)?)))
+        } else {
+            Ok((false, None))
+        }
+    }
+}
+
+/// `ECVRF_prove` of RFC 9381, also returning the commitments `U` and `V`.
+fn prove_internal<P: TEHashToGroup>(
+    sk: &ECVRFSecretKey<P>,
+    alpha: &[u8],
+) -> Result<(ECVRFProof<P>, Affine<P>, Affine<P>), PrimitivesError> {
+    let pk = ECVRFPublicKey::from(sk);
+    let h = encode_to_curve(&pk, alpha)?;
+    let gamma = (h * sk.0).into_affine();
+    let k = nonce_generation(sk, &h);
+    let u = (Projective::<P>::generator() * k).into_affine();
+    let v = (h * k).into_affine();
+    let c = challenge_generation(&[pk.0, h, gamma, u, v]);
+    let s = k + c * sk.0;
+    Ok((ECVRFProof { gamma, c, s }, u, v))
+}
+
+/// `ECVRF_encode_to_curve` of RFC 9381 with the `h2c_suite` method, salted
+/// with the public key.
+fn encode_to_curve<P: TEHashToGroup>(
+    pk: &ECVRFPublicKey<P>,
+    alpha: &[u8],
+) -> Result<Affine<P>, PrimitivesError> {
+    let data = [to_compressed_bytes(&pk.0), alpha.to_vec()].concat();
+    Ok(P::hash_to_group(&data[..], CS_ID_ECVRF.as_bytes())?.into_affine())
+}
+
+/// `ECVRF_nonce_generation` of RFC 9381 following RFC 8032, with the secret
+/// scalar in place of the hashed secret key.
+fn nonce_generation<P: TECurveConfig>(sk: &ECVRFSecretKey<P>, h: &Affine<P>) -> P::ScalarField {
+    let digest = Sha512::new()
+        .chain_update(to_compressed_bytes(&sk.0))
+        .chain_update(to_compressed_bytes(h))
+        .finalize();
+    P::ScalarField::from_le_bytes_mod_order(&digest)
+}
+
+/// `ECVRF_challenge_generation` of RFC 9381, truncated to
+/// [`CHALLENGE_LEN`] bytes.
+fn challenge_generation<P: TECurveConfig>(points: &[Affine<P>; 5]) -> P::ScalarField {
+    let mut hasher = Sha512::new()
+        .chain_update(CS_ID_ECVRF)
+        .chain_update([CHALLENGE_GENERATION_DOMAIN_SEPARATOR_FRONT]);
+    for point in points {
+        hasher.update(to_compressed_bytes(point));
+    }
+    let digest = hasher.chain_update([DOMAIN_SEPARATOR_BACK]).finalize();
+    P::ScalarField::from_le_bytes_mod_order(&digest[..CHALLENGE_LEN])
+}
+
+/// Whether `point` is on the curve and in the prime order subgroup.
+fn is_valid_point<P: TECurveConfig>(point: &Affine<P>) -> bool {
+    point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::BigInteger;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_ecvrf() {
+        test_ecvrf_helper::<ark_ed_on_bls12_377::EdwardsConfig>();
+        test_ecvrf_helper::<ark_ed_on_bls12_381::EdwardsConfig>();
+    }
+
+    fn test_ecvrf_helper<P: TEHashToGroup>() {
+        let rng = &mut test_rng();
+        let mut vrf = ECVRFScheme::<P>::new();
+        let (sk, pk) = vrf.key_gen(&(), rng).unwrap();
+        let (other_sk, other_pk) = vrf.key_gen(&(), rng).unwrap();
+        let alpha = b"sample".to_vec();
+
+        let proof = vrf.prove(&(), &sk, &alpha, rng).unwrap();
+        let beta = vrf.proof_to_hash(&(), &proof).unwrap();
+        assert_eq!(beta.len(), 64);
+        assert_eq!(
+            vrf.verify(&(), &proof, &pk, &alpha).unwrap(),
+            (true, Some(beta.clone()))
+        );
+        assert_eq!(vrf.evaluate(&(), &sk, &alpha, rng).unwrap(), beta);
+
+        // proving is deterministic
+        assert_eq!(vrf.prove(&(), &sk, &alpha, rng).unwrap(), proof);
+
+        // the proof and output follow the construction
+        let h = P::hash_to_group(
+            &[to_compressed_bytes(&pk.0), alpha.clone()].concat()[..],
+            CS_ID_ECVRF.as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(proof.gamma, (h * sk.0).into_affine());
+        let expected_beta = Sha512::new()
+            .chain_update(CS_ID_ECVRF.as_bytes())
+            .chain_update([0x03])
+            .chain_update(to_compressed_bytes(&proof.gamma.mul_by_cofactor()))
+            .chain_update([0x00])
+            .finalize()
+            .to_vec();
+        assert_eq!(beta, expected_beta);
+        assert!(proof.c.into_bigint().num_bits() <= 8 * CHALLENGE_LEN as u32);
+
+        // distinct inputs and keys give distinct outputs
+        let other_alpha = b"test".to_vec();
+        let other_beta = vrf.evaluate(&(), &sk, &other_alpha, rng).unwrap();
+        assert_ne!(other_beta, beta);
+        assert_ne!(vrf.evaluate(&(), &other_sk, &alpha, rng).unwrap(), beta);
+
+        // wrong key, wrong input and tampered proofs are rejected
+        assert_eq!(
+            vrf.verify(&(), &proof, &other_pk, &alpha).unwrap(),
+            (false, None)
+        );
+        assert_eq!(
+            vrf.verify(&(), &proof, &pk, &other_alpha).unwrap(),
+            (false, None)
+        );
+        let mut bad_proof = proof.clone();
+        bad_proof.s += P::ScalarField::from(1u8);
+        assert!(!vrf.verify(&(), &bad_proof, &pk, &alpha).unwrap().0);
+        let mut bad_proof = proof.clone();
+        bad_proof.c += P::ScalarField::from(1u8);
+        assert!(!vrf.verify(&(), &bad_proof, &pk, &alpha).unwrap().0);
+        let mut bad_proof = proof.clone();
+        bad_proof.gamma = (Projective::<P>::generator() + proof.gamma).into_affine();
+        assert!(!vrf.verify(&(), &bad_proof, &pk, &alpha).unwrap().0);
+
+        // the identity is not a valid public key
+        let bad_pk = ECVRFPublicKey::<P>(Affine::zero());
+        assert_eq!(
+            vrf.verify(&(), &proof, &bad_pk, &alpha).unwrap(),
+            (false, None)
+        );
+
+        // serde round trip
+        let ser = serde_json::to_string(&proof).unwrap();
+        let de: ECVRFProof<P> = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, proof);
+        let ser = serde_json::to_string(&pk).unwrap();
+        let de: ECVRFPublicKey<P> = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, pk);
+    }
+
+    #[test]
+    fn test_ecvrf_batch_verify() {
+        test_ecvrf_batch_verify_helper::<ark_ed_on_bls12_377::EdwardsConfig>();
+        test_ecvrf_batch_verify_helper::<ark_ed_on_bls12_381::EdwardsConfig>();
+    }
+
+    fn test_ecvrf_batch_verify_helper<P: TEHashToGroup>() {
+        let rng = &mut test_rng();
+        let mut vrf = ECVRFScheme::<P>::new();
+        let mut items = vec![];
+        for i in 0..10u8 {
+            let (sk, pk) = vrf.key_gen(&(), rng).unwrap();
+            let alpha = vec![i; i as usize + 1];
+            let proof = vrf.prove_batchable(&sk, &alpha).unwrap();
+            // the compact form is the usual proof
+            let compact = proof.compact(&pk, &alpha).unwrap();
+            assert_eq!(compact, vrf.prove(&(), &sk, &alpha, rng).unwrap());
+            assert!(vrf.verify(&(), &compact, &pk, &alpha).unwrap().0);
+            items.push((pk, alpha, proof));
+        }
+        assert!(ECVRFScheme::<P>::batch_verify(&items, rng).unwrap());
+        assert!(ECVRFScheme::<P>::batch_verify(&items[..1], rng).unwrap());
+        assert!(ECVRFScheme::<P>::batch_verify(&[], rng).unwrap());
+
+        // a single invalid proof makes the whole batch invalid
+        let mut bad_items = items.clone();
+        bad_items[3].2.s += P::ScalarField::from(1u8);
+        assert!(!ECVRFScheme::<P>::batch_verify(&bad_items, rng).unwrap());
+        let mut bad_items = items.clone();
+        bad_items[5].1.push(0);
+        assert!(!ECVRFScheme::<P>::batch_verify(&bad_items, rng).unwrap());
+        let mut bad_items = items.clone();
+        bad_items[0].0 = items[1].0.clone();
+        assert!(!ECVRFScheme::<P>::batch_verify(&bad_items, rng).unwrap());
+        let mut bad_items = items.clone();
+        bad_items[9].2.gamma = (Projective::<P>::generator() + items[9].2.gamma).into_affine();
+        assert!(!ECVRFScheme::<P>::batch_verify(&bad_items, rng).unwrap());
+        // errors in distinct proofs do not cancel out
+        let mut bad_items = items.clone();
+        bad_items[1].2.u = (Projective::<P>::generator() + items[1].2.u).into_affine();
+        bad_items[2].2.u = (-Projective::<P>::generator() + items[2].2.u).into_affine();
+        assert!(!ECVRFScheme::<P>::batch_verify(&bad_items, rng).unwrap());
+
+        // the identity is not a valid public key
+        let mut bad_items = items.clone();
+        bad_items[4].0 = ECVRFPublicKey::<P>(Affine::zero());
+        assert!(!ECVRFScheme::<P>::batch_verify(&bad_items, rng).unwrap());
+
+        // serde round trip
+        let ser = serde_json::to_string(&items[0].2).unwrap();
+        let de: ECVRFBatchableProof<P> = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, items[0].2);
+    }
+}
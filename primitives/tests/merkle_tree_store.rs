@@ -0,0 +1,40 @@
+#![cfg(feature = "std")]
+use ark_ed_on_bn254::Fq as Fq254;
+use jf_primitives::{
+    errors::PrimitivesError,
+    merkle_tree::{
+        prelude::RescueMerkleTree, store::FileNodeStore, MerkleCommitment, MerkleTreeScheme,
+    },
+};
+
+#[test]
+fn file_store_round_trip() -> Result<(), PrimitivesError> {
+    let dir = std::env::temp_dir().join(format!("jf-merkle-store-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let elems = (0..100u64).map(Fq254::from).collect::<Vec<_>>();
+    let mt = RescueMerkleTree::<Fq254>::from_elems(5, &elems)?;
+    let commitment = mt.commitment();
+    let (_, expected_proof) = mt.lookup(42).expect_ok()?;
+    {
+        let mut store = FileNodeStore::open(&dir)?;
+        mt.save_to_store(&mut store)?;
+    }
+    drop(mt);
+
+    // Reopen the store, as a new process would.
+    let store = FileNodeStore::open(&dir)?;
+    let mut mt = RescueMerkleTree::<Fq254>::from_store(&store, &commitment)?;
+    mt.load_from_store(42, &store)?;
+    let (elem, proof) = mt.lookup(42).expect_ok()?;
+    assert_eq!(elem, &elems[42]);
+    assert_eq!(proof, expected_proof);
+    assert!(RescueMerkleTree::<Fq254>::verify(commitment.digest(), 42, proof)?.is_ok());
+
+    // A corrupted node is reported, rather than read as an empty subtree.
+    std::fs::write(dir.join("node"), b"garbage").unwrap();
+    assert!(RescueMerkleTree::<Fq254>::from_store(&store, &commitment).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    Ok(())
+}
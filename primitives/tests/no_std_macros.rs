@@ -0,0 +1,25 @@
+//! The exported macros expand in a `no_std` crate, where neither `Vec` nor
+//! `vec!` is in scope, and without importing anything they refer to.
+#![no_std]
+extern crate std;
+
+use jf_primitives::{impl_to_traversal_path_biguint, merkle_tree::ToTraversalPath};
+
+#[derive(Clone)]
+struct Position(u64);
+
+impl From<Position> for num_bigint::BigUint {
+    fn from(pos: Position) -> Self {
+        pos.0.into()
+    }
+}
+
+impl_to_traversal_path_biguint!(Position);
+
+#[test]
+fn traversal_path_macro_in_no_std() {
+    assert_eq!(
+        <Position as ToTraversalPath<3>>::to_traversal_path(&Position(5), 4),
+        [2, 1, 0, 0]
+    );
+}
@@ -8,6 +8,7 @@
 
 use crate::{errors::CircuitError, BoolVar, Circuit, PlonkCircuit, Variable};
 use ark_ff::{BigInteger, PrimeField};
+use ark_std::format;
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Constrain that `a` < `b`.
@@ -172,10 +173,66 @@ impl<F: PrimeField> PlonkCircuit<F> {
         let b = self.create_constant_variable(val)?;
         self.enforce_geq(a, b)
     }
+
+    /// Returns a `BoolVar` indicating whether `a` < `b`, where both `a` and `b`
+    /// are constrained to be in the range [0, 2^`n_bits`).
+    /// This is cheaper than [`Self::is_lt`] for short values.
+    /// Return error if the variables are invalid, or if `n_bits` is zero or
+    /// larger than the bit size of the field modulus minus 2.
+    pub fn is_less_than(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        n_bits: usize,
+    ) -> Result<BoolVar, CircuitError> {
+        self.check_cmp_bit_len(n_bits)?;
+        self.enforce_in_range(a, n_bits)?;
+        self.enforce_in_range(b, n_bits)?;
+        // 2^n_bits + a - b is in [1, 2^(n_bits+1)), and its most significant
+        // bit is set iff a >= b.
+        let c = self.sub(a, b)?;
+        let c = self.add_constant(c, &F::from(2u8).pow([n_bits as u64]))?;
+        let c_bits_le = self.range_gate_internal(c, n_bits + 1)?;
+        self.logic_neg(c_bits_le[n_bits])
+    }
+
+    /// Constrain that `a` < `b`, where both `a` and `b` are constrained to be
+    /// in the range [0, 2^`n_bits`).
+    /// This is cheaper than [`Self::enforce_lt`] for short values.
+    /// Return error if the variables are invalid, or if `n_bits` is zero or
+    /// larger than the bit size of the field modulus minus 2.
+    pub fn enforce_less_than(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        n_bits: usize,
+    ) -> Result<(), CircuitError> {
+        self.check_cmp_bit_len(n_bits)?;
+        self.enforce_in_range(a, n_bits)?;
+        self.enforce_in_range(b, n_bits)?;
+        // b - a - 1 is in [0, 2^n_bits) if a < b, and wraps around the field
+        // modulus otherwise.
+        let c = self.sub(b, a)?;
+        let c = self.add_constant(c, &-F::one())?;
+        self.enforce_in_range(c, n_bits)
+    }
 }
 
 /// Private helper functions for comparison gate
 impl<F: PrimeField> PlonkCircuit<F> {
+    /// Check that values of `n_bits` bits can be compared without wrapping
+    /// around the field modulus, i.e. that 2^(`n_bits` + 1) < q.
+    fn check_cmp_bit_len(&self, n_bits: usize) -> Result<(), CircuitError> {
+        if n_bits == 0 || n_bits + 2 > F::MODULUS_BIT_SIZE as usize {
+            return Err(CircuitError::ParameterError(format!(
+                "Maximum compared bit len: {}, requested bit len: {}",
+                F::MODULUS_BIT_SIZE - 2,
+                n_bits
+            )));
+        }
+        Ok(())
+    }
+
     /// Returns 2 `BoolVar`s.
     /// First indicates whether `a` <= (q-1)/2 and `b` > (q-1)/2.
     /// Second indicates whether `a` and `b` are both <= (q-1)/2
@@ -419,4 +476,78 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_bounded_cmp_gates() -> Result<(), CircuitError> {
+        test_bounded_cmp_helper::<FqEd254>()?;
+        test_bounded_cmp_helper::<FqEd377>()?;
+        test_bounded_cmp_helper::<FqEd381>()?;
+        test_bounded_cmp_helper::<Fq377>()
+    }
+
+    fn test_bounded_cmp_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let max_bit_len = F::MODULUS_BIT_SIZE as usize - 2;
+        let max = |n_bits: usize| F::from(2u8).pow([n_bits as u64]) - F::one();
+        for n_bits in [1, 8, 64, max_bit_len] {
+            let list = [
+                (F::zero(), F::zero()),
+                (F::zero(), F::one()),
+                (F::from(3u8), F::from(3u8)),
+                (F::one(), max(n_bits)),
+                (max(n_bits) - F::one(), max(n_bits)),
+                (F::zero(), max(n_bits)),
+                (max(n_bits), max(n_bits)),
+            ];
+            for (a, b) in list {
+                for (a, b) in [(a, b), (b, a)] {
+                    // skip the values out of range
+                    if a > max(n_bits) || b > max(n_bits) {
+                        continue;
+                    }
+                    let expected = a < b;
+                    for lookup in [false, true] {
+                        let mut circuit = if lookup {
+                            PlonkCircuit::<F>::new_ultra_plonk(8)
+                        } else {
+                            PlonkCircuit::<F>::new_turbo_plonk()
+                        };
+                        let a_var = circuit.create_variable(a)?;
+                        let b_var = circuit.create_variable(b)?;
+                        let lt = circuit.is_less_than(a_var, b_var, n_bits)?;
+                        let eq = circuit.is_equal(a_var, b_var)?;
+                        assert_eq!(circuit.witness(lt.into())?, F::from(expected));
+                        assert_eq!(circuit.witness(eq.into())?, F::from(a == b));
+                        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+                        circuit.enforce_less_than(a_var, b_var, n_bits)?;
+                        assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), expected);
+                    }
+                }
+            }
+        }
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(256u32))?;
+        let b = circuit.create_variable(F::from(3u32))?;
+        // out of range values
+        circuit.is_less_than(b, a, 8)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a = circuit.create_variable(-F::one())?;
+        let b = circuit.create_variable(F::from(3u32))?;
+        circuit.enforce_less_than(a, b, 8)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // invalid bit lengths
+        assert!(circuit.is_less_than(a, b, 0).is_err());
+        assert!(circuit.enforce_less_than(a, b, max_bit_len + 1).is_err());
+        // a tampered output is rejected
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(2u32))?;
+        let b = circuit.create_variable(F::from(3u32))?;
+        let lt = circuit.is_less_than(a, b, 8)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(lt.into()) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
 }
@@ -418,6 +418,9 @@ impl<F: PrimeField> PlonkCircuit<F> {
 
     /// Obtain the fixed-based scalar multiplication result of `scalar` * `Base`
     /// Currently only supports `Affine::<P>` scalar multiplication.
+    /// The multiples of `base` for every 2-bit window of the scalar are
+    /// precomputed and hardcoded in the selection gates, so this is cheaper
+    /// than [`Self::variable_base_scalar_mul`] on a constant base point.
     pub fn fixed_base_scalar_mul<P: Config<BaseField = F>>(
         &mut self,
         scalar: Variable,
@@ -1151,6 +1154,43 @@ mod test {
         Ok(circuit)
     }
 
+    #[test]
+    fn test_fixed_based_scalar_mul_num_gates() -> Result<(), CircuitError> {
+        test_fixed_based_scalar_mul_num_gates_helper::<FqEd354, Param254>()?;
+        test_fixed_based_scalar_mul_num_gates_helper::<FqEd377, Param377>()?;
+        test_fixed_based_scalar_mul_num_gates_helper::<FqEd381, Param381>()?;
+        test_fixed_based_scalar_mul_num_gates_helper::<FqEd381b, Param381b>()?;
+        test_fixed_based_scalar_mul_num_gates_helper::<Fq377, Param761>()
+    }
+
+    fn test_fixed_based_scalar_mul_num_gates_helper<F, P>() -> Result<(), CircuitError>
+    where
+        F: PrimeField,
+        P: Config<BaseField = F>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let base = Affine::<P>::rand(&mut rng);
+        let s = P::ScalarField::rand(&mut rng);
+        let expected = TEPoint::from((base * s).into_affine());
+
+        let mut fixed_circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let scalar = fixed_circuit.create_variable(fr_to_fq::<F, P>(&s))?;
+        let result = fixed_circuit.fixed_base_scalar_mul(scalar, &base)?;
+        assert_eq!(expected, fixed_circuit.point_witness(&result)?);
+        assert!(fixed_circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // The same multiplication with the base point as a constant variable.
+        let mut variable_circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let scalar = variable_circuit.create_variable(fr_to_fq::<F, P>(&s))?;
+        let base_var = variable_circuit.create_constant_point_variable(TEPoint::from(base))?;
+        let result = variable_circuit.variable_base_scalar_mul::<P>(scalar, &base_var)?;
+        assert_eq!(expected, variable_circuit.point_witness(&result)?);
+        assert!(variable_circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        assert!(fixed_circuit.num_gates() < variable_circuit.num_gates());
+        Ok(())
+    }
+
     #[test]
     fn test_binary_point_vars_select() -> Result<(), CircuitError> {
         test_binary_point_vars_select_helper::<FqEd354, Param254>()?;
@@ -1280,6 +1320,44 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_variable_base_scalar_mul_matches_native() -> Result<(), CircuitError> {
+        test_variable_base_scalar_mul_matches_native_helper::<FqEd354, Param254>()?;
+        test_variable_base_scalar_mul_matches_native_helper::<FqEd377, Param377>()?;
+        test_variable_base_scalar_mul_matches_native_helper::<FqEd381, Param381>()?;
+        test_variable_base_scalar_mul_matches_native_helper::<FqEd381b, Param381b>()?;
+        test_variable_base_scalar_mul_matches_native_helper::<Fq377, Param761>()
+    }
+    fn test_variable_base_scalar_mul_matches_native_helper<F, P>() -> Result<(), CircuitError>
+    where
+        F: PrimeField,
+        P: Config<BaseField = F>,
+    {
+        let mut rng = jf_utils::test_rng();
+        for mut circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            for _ in 0..4 {
+                let base = Affine::<P>::rand(&mut rng);
+                let s = P::ScalarField::rand(&mut rng);
+                let expected = TEPoint::from((base * s).into_affine());
+                let s_var = circuit.create_variable(fr_to_fq::<F, P>(&s))?;
+                let base_var = circuit.create_point_variable(TEPoint::from(base))?;
+
+                let result = circuit.variable_base_scalar_mul::<P>(s_var, &base_var)?;
+                assert_eq!(expected, circuit.point_witness(&result)?);
+
+                // Same result from the constrained bit decomposition of the scalar.
+                let s_bits_le = circuit.unpack(s_var, P::ScalarField::MODULUS_BIT_SIZE as usize)?;
+                let result = circuit.variable_base_binary_scalar_mul::<P>(&s_bits_le, &base_var)?;
+                assert_eq!(expected, circuit.point_witness(&result)?);
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+        Ok(())
+    }
+
     fn build_variable_base_scalar_mul_circuit<F, P>(
         scalar: F,
         base: TEPoint<F>,
@@ -139,6 +139,9 @@ impl<F: PrimeField> PlonkCircuit<F> {
 
     /// Obtain a variable that equals `x_0` if `b` is zero, or `x_1` if `b` is
     /// one. Return error if variables are invalid.
+    /// The gate does not constrain `b` to be boolean, this is the job of the
+    /// gadget creating the `BoolVar`, e.g.
+    /// [`Circuit::create_boolean_variable`].
     pub fn conditional_select(
         &mut self,
         b: BoolVar,
@@ -412,6 +415,61 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_conditional_select_non_boolean_selector() -> Result<(), CircuitError> {
+        test_conditional_select_non_boolean_selector_helper::<FqEd254>()?;
+        test_conditional_select_non_boolean_selector_helper::<FqEd377>()?;
+        test_conditional_select_non_boolean_selector_helper::<FqEd381>()?;
+        test_conditional_select_non_boolean_selector_helper::<Fq377>()
+    }
+
+    fn test_conditional_select_non_boolean_selector_helper<F: PrimeField>(
+    ) -> Result<(), CircuitError> {
+        let x_0_val = F::from(23u32);
+        let x_1_val = F::from(24u32);
+        for bit in [false, true] {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let b = circuit.create_boolean_variable(bit)?;
+            let x_0 = circuit.create_variable(x_0_val)?;
+            let x_1 = circuit.create_variable(x_1_val)?;
+            let y = circuit.conditional_select(b, x_0, x_1)?;
+            let expected = if bit { x_1_val } else { x_0_val };
+            assert_eq!(circuit.witness(y)?, expected);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // A selector of 2, with the output of the selection equation
+            // y = x_0 + b * (x_1 - x_0), is rejected by the boolean constraint.
+            let two = F::from(2u32);
+            *circuit.witness_mut(b.into()) = two;
+            *circuit.witness_mut(y) = x_0_val + two * (x_1_val - x_0_val);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // The selection also follows the native choice with logic gates
+        // computing the selector.
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+            let a_var = circuit.create_boolean_variable(a)?;
+            let b_var = circuit.create_boolean_variable(b)?;
+            let and = circuit.logic_and(a_var, b_var)?;
+            let or = circuit.logic_or(a_var, b_var)?;
+            let not_a = circuit.logic_neg(a_var)?;
+            assert_eq!(circuit.witness(and.into())?, F::from(a && b));
+            assert_eq!(circuit.witness(or.into())?, F::from(a || b));
+            assert_eq!(circuit.witness(not_a.into())?, F::from(!a));
+
+            let x_0 = circuit.create_variable(x_0_val)?;
+            let x_1 = circuit.create_variable(x_1_val)?;
+            for (selector, bit) in [(and, a && b), (or, a || b), (not_a, !a)] {
+                let y = circuit.conditional_select(selector, x_0, x_1)?;
+                let expected = if bit { x_1_val } else { x_0_val };
+                assert_eq!(circuit.witness(y)?, expected);
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+        Ok(())
+    }
+
     fn build_conditional_select_circuit<F: PrimeField>(
         bit: bool,
         x_0: F,
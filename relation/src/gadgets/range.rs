@@ -15,7 +15,8 @@ use ark_std::{format, string::ToString, vec::Vec};
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Constrain a variable to be within the [0, 2^`bit_len`) range
-    /// Return error if the variable is invalid.
+    /// Return error if the variable is invalid, or if `bit_len` is zero or
+    /// larger than the bit size of the field modulus.
     pub fn enforce_in_range(&mut self, a: Variable, bit_len: usize) -> Result<(), CircuitError> {
         if self.support_lookup() {
             self.range_gate_with_lookup(a, bit_len)?;
@@ -107,14 +108,15 @@ impl<F: PrimeField> PlonkCircuit<F> {
             ));
         }
 
-        let a_bits_le: Vec<bool> = self.witness(a)?.into_bigint().to_bits_le();
-        if bit_len > a_bits_le.len() {
+        if bit_len > F::MODULUS_BIT_SIZE as usize {
             return Err(CircuitError::ParameterError(format!(
                 "Maximum field bit size: {}, requested range upper bound bit len: {}",
-                a_bits_le.len(),
+                F::MODULUS_BIT_SIZE,
                 bit_len
             )));
         }
+
+        let a_bits_le: Vec<bool> = self.witness(a)?.into_bigint().to_bits_le();
         // convert to variable in the circuit from the vector of boolean as binary
         // representation
         let a_bits_le: Vec<BoolVar> = a_bits_le
@@ -151,7 +153,8 @@ mod test {
     use ark_ed_on_bls12_377::Fq as FqEd377;
     use ark_ed_on_bls12_381::Fq as FqEd381;
     use ark_ed_on_bn254::Fq as FqEd254;
-    use ark_ff::PrimeField;
+    use ark_ff::{Field, PrimeField};
+    use ark_std::rand::Rng;
 
     #[test]
     fn test_unpack() -> Result<(), CircuitError> {
@@ -224,6 +227,48 @@ mod test {
         Ok(circuit)
     }
 
+    #[test]
+    fn test_range_gate_bit_len() -> Result<(), CircuitError> {
+        test_range_gate_bit_len_helper::<FqEd254>(PlonkCircuit::new_turbo_plonk)?;
+        test_range_gate_bit_len_helper::<FqEd377>(PlonkCircuit::new_turbo_plonk)?;
+        test_range_gate_bit_len_helper::<FqEd381>(PlonkCircuit::new_turbo_plonk)?;
+        test_range_gate_bit_len_helper::<Fq377>(PlonkCircuit::new_turbo_plonk)?;
+        test_range_gate_bit_len_helper::<FqEd254>(|| PlonkCircuit::new_ultra_plonk(8))?;
+        test_range_gate_bit_len_helper::<FqEd377>(|| PlonkCircuit::new_ultra_plonk(8))?;
+        test_range_gate_bit_len_helper::<FqEd381>(|| PlonkCircuit::new_ultra_plonk(8))?;
+        test_range_gate_bit_len_helper::<Fq377>(|| PlonkCircuit::new_ultra_plonk(8))
+    }
+    fn test_range_gate_bit_len_helper<F: PrimeField>(
+        new_circuit: impl Fn() -> PlonkCircuit<F>,
+    ) -> Result<(), CircuitError> {
+        let max_bit_len = F::MODULUS_BIT_SIZE as usize;
+        let mut rng = jf_utils::test_rng();
+        for bit_len in [1, 7, 64, 65, 130, max_bit_len - 1, max_bit_len] {
+            // A random value with at most `bit_len` bits is in range.
+            let val = (0..bit_len).fold(F::zero(), |acc, _| {
+                acc.double() + F::from(rng.gen::<bool>())
+            });
+            let mut circuit = new_circuit();
+            let a = circuit.create_variable(val)?;
+            circuit.enforce_in_range(a, bit_len)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // 2^bit_len is out of range.
+            if bit_len < max_bit_len {
+                let mut circuit = new_circuit();
+                let a = circuit.create_variable(F::from(2u8).pow([bit_len as u64]))?;
+                circuit.enforce_in_range(a, bit_len)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+
+        // The bit length cannot exceed the capacity of the field.
+        let mut circuit = new_circuit();
+        let a = circuit.create_variable(F::one())?;
+        assert!(circuit.enforce_in_range(a, max_bit_len + 1).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_check_in_range() -> Result<(), CircuitError> {
         test_check_in_range_helper::<FqEd254>()?;
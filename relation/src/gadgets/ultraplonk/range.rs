@@ -10,14 +10,15 @@ use crate::{
     Circuit, PlonkCircuit, Variable,
 };
 use ark_ff::{BigInteger, PrimeField};
-use ark_std::{string::ToString, vec::Vec};
+use ark_std::{format, string::ToString, vec::Vec};
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Constrain a variable to be within the [0, 2^{bit_len}) range
     /// Return error if one of the following holds:
     /// 1. the variable is invalid;
     /// 2. `RANGE_BIT_LEN` equals zero;
-    /// 3. the circuit does not support lookup.
+    /// 3. `bit_len` is larger than the bit size of the field modulus;
+    /// 4. the circuit does not support lookup.
     pub(crate) fn range_gate_with_lookup(
         &mut self,
         a: Variable,
@@ -28,6 +29,13 @@ impl<F: PrimeField> PlonkCircuit<F> {
         if bit_len == 0 {
             return Err(ParameterError("bit_len cannot be zero".to_string()));
         }
+        if bit_len > F::MODULUS_BIT_SIZE as usize {
+            return Err(ParameterError(format!(
+                "Maximum field bit size: {}, requested range upper bound bit len: {}",
+                F::MODULUS_BIT_SIZE,
+                bit_len
+            )));
+        }
         self.check_var_bound(a)?;
         let leftover = bit_len % range_bit_len;
         let lookup_len = bit_len / range_bit_len;
@@ -0,0 +1,116 @@
+#!/usr/bin/env python3
+# Copyright (c) 2022 Espresso Systems (espressosys.com)
+# This file is part of the Jellyfish library.
+
+# You should have received a copy of the MIT License
+# along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+# Test vectors of Rescue-Prime over the BN254 scalar field, as used in
+# `test_rescue_prime_fr254` of primitives/src/rescue/mod.rs for m = 4, and in
+# `test_rescue_prime_kat` of primitives/src/rescue/prime.rs for m = 3 and 5.
+#
+# This is a line by line port to plain Python of the reference implementation
+# `rescue_prime.sage` published with "Rescue-Prime: a Standard Specification
+# (SoK)" by Szepieniec, Ashur and Dhooghe, https://eprint.iacr.org/2020/1143,
+# for p = q254, capacity 1 and 128 bits of security.
+#
+# Usage: python3 scripts/rescue_prime_test_vectors.py (requires sympy)
+
+import hashlib
+from math import ceil, comb, floor, gcd
+
+import sympy
+
+P = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+WIDTHS = [4, 3, 5]
+CAPACITY = 1
+SECURITY_LEVEL = 128
+
+
+def get_alphas(p):
+    alpha = 3
+    while gcd(alpha, p - 1) != 1:
+        alpha += 1
+    return alpha, pow(alpha, -1, p - 1)
+
+
+def get_number_of_rounds(p, m, capacity, security_level, alpha):
+    rate = m - capacity
+    dcon = lambda n: floor(0.5 * (alpha - 1) * m * (n - 1) + 2)
+    v = lambda n: m * (n - 1) + rate
+    target = 2**security_level
+    for l1 in range(1, 25):
+        if comb(v(l1) + dcon(l1), v(l1)) ** 2 > target:
+            break
+    return ceil(1.5 * max(5, l1))
+
+
+def get_round_constants(p, m, capacity, security_level, n):
+    bytes_per_int = ceil(len(bin(p)[2:]) / 8) + 1
+    num_bytes = bytes_per_int * 2 * m * n
+    seed_string = "Rescue-XLIX(%i,%i,%i,%i)" % (p, m, capacity, security_level)
+    byte_string = hashlib.shake_256(seed_string.encode("ascii")).digest(num_bytes)
+    return [
+        int.from_bytes(byte_string[bytes_per_int * i : bytes_per_int * (i + 1)], "little") % p
+        for i in range(2 * m * n)
+    ]
+
+
+def get_mds_matrix(p, m):
+    factors = sympy.factorint(p - 1)
+    g = 2
+    while any(pow(g, (p - 1) // q, p) == 1 for q in factors):
+        g += 1
+    # Reduced echelon form of the Vandermonde matrix [g^(i*j)]
+    v = [[pow(g, i * j, p) for j in range(2 * m)] for i in range(m)]
+    for c in range(m):
+        pivot = next(i for i in range(c, m) if v[i][c] != 0)
+        v[c], v[pivot] = v[pivot], v[c]
+        inv = pow(v[c][c], -1, p)
+        v[c] = [x * inv % p for x in v[c]]
+        for i in range(m):
+            if i != c and v[i][c] != 0:
+                f = v[i][c]
+                v[i] = [(a - f * b) % p for a, b in zip(v[i], v[c])]
+    # The MDS matrix is the transpose of the right half
+    return [[v[j][m + i] for j in range(m)] for i in range(m)]
+
+
+def rescue_prime_permutation(parameters, state):
+    p, m, alpha, alphainv, n, mds, round_constants = parameters
+    mul = lambda s: [sum(mds[i][j] * s[j] for j in range(m)) % p for i in range(m)]
+    for i in range(n):
+        state = mul([pow(x, alpha, p) for x in state])
+        state = [(state[j] + round_constants[i * 2 * m + j]) % p for j in range(m)]
+        state = mul([pow(x, alphainv, p) for x in state])
+        state = [(state[j] + round_constants[i * 2 * m + m + j]) % p for j in range(m)]
+    return state
+
+
+def rescue_prime_hash(parameters, input_sequence):
+    p, m = parameters[0], parameters[1]
+    rate = m - CAPACITY
+    padded_input = input_sequence + [1]
+    while len(padded_input) % rate != 0:
+        padded_input.append(0)
+    state = [0] * m
+    for k in range(0, len(padded_input), rate):
+        for i in range(rate):
+            state[i] = (state[i] + padded_input[k + i]) % p
+        state = rescue_prime_permutation(parameters, state)
+    return state[:rate]
+
+
+if __name__ == "__main__":
+    alpha, alphainv = get_alphas(P)
+    for m in WIDTHS:
+        n = get_number_of_rounds(P, m, CAPACITY, SECURITY_LEVEL, alpha)
+        round_constants = get_round_constants(P, m, CAPACITY, SECURITY_LEVEL, n)
+        parameters = (P, m, alpha, alphainv, n, get_mds_matrix(P, m), round_constants)
+        print("m:", m, "alpha:", alpha, "rounds:", n)
+        print("first round constants:", round_constants[:2])
+        print(
+            "permutation of %s:" % list(range(m)),
+            rescue_prime_permutation(parameters, list(range(m))),
+        )
+        print("hash of [1, 2, 3]:", rescue_prime_hash(parameters, [1, 2, 3]))